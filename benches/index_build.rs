@@ -0,0 +1,36 @@
+extern crate fdt_rs;
+
+use fdt_rs::base::DevTree;
+use fdt_rs::index::DevTreeIndex;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+#[repr(align(4))]
+struct _Wrapper<T>(T);
+pub const FDT: &[u8] = &_Wrapper(*include_bytes!("../tests/riscv64-virt.dtb")).0;
+
+/// Compare building an index with every token checked against building one from a device tree
+/// which was validated up front.
+fn index_build(c: &mut Criterion) {
+    let fdt = unsafe { DevTree::new(FDT) }.unwrap();
+    fdt.validate().unwrap();
+    let size = DevTreeIndex::required_buffer_size(&fdt).unwrap();
+    let mut buf = vec![0u8; size];
+
+    let mut group = c.benchmark_group("index-build");
+    group.bench_function("checked", |b| {
+        b.iter(|| {
+            DevTreeIndex::new(fdt, &mut buf).unwrap();
+        })
+    });
+    group.bench_function("unchecked", |b| {
+        // Unsafe OK. The device tree was validated above.
+        b.iter(|| unsafe {
+            DevTreeIndex::new_unchecked(fdt, &mut buf).unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, index_build);
+criterion_main!(benches);
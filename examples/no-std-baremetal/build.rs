@@ -0,0 +1,13 @@
+use std::env;
+
+fn main() {
+    // Only bare-metal targets need our linker script. Host builds of this example run as a normal
+    // program so the workspace can be built and tested as a whole.
+    let target = env::var("TARGET").unwrap();
+    if target.ends_with("-none-elf") {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        println!("cargo:rustc-link-arg=-T{}/link.x", dir);
+    }
+    println!("cargo:rerun-if-changed=link.x");
+    println!("cargo:rerun-if-changed=build.rs");
+}
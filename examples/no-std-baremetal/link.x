@@ -0,0 +1,39 @@
+/* Memory layout of a QEMU `virt` machine when loaded by OpenSBI. */
+OUTPUT_ARCH(riscv)
+ENTRY(_start)
+
+MEMORY
+{
+    RAM : ORIGIN = 0x80200000, LENGTH = 16M
+}
+
+SECTIONS
+{
+    .text : {
+        KEEP(*(.text.entry))
+        *(.text .text.*)
+    } > RAM
+
+    .rodata : ALIGN(8) {
+        *(.rodata .rodata.*)
+    } > RAM
+
+    .data : ALIGN(8) {
+        *(.data .data.* .sdata .sdata.*)
+    } > RAM
+
+    .bss (NOLOAD) : ALIGN(8) {
+        _sbss = .;
+        *(.bss .bss.* .sbss .sbss.*)
+        _ebss = .;
+    } > RAM
+
+    .stack (NOLOAD) : ALIGN(16) {
+        . += 64K;
+        _stack_top = .;
+    } > RAM
+
+    /DISCARD/ : {
+        *(.eh_frame .eh_frame_hdr)
+    }
+}
@@ -0,0 +1,221 @@
+//! A bare-metal example which uses `fdt-rs` without the standard library.
+//!
+//! Build for a QEMU `riscv64` `virt` machine with:
+//!
+//! ```sh
+//! rustup target add riscv64imac-unknown-none-elf
+//! cargo build -p no-std-baremetal --target riscv64imac-unknown-none-elf
+//! qemu-system-riscv64 -machine virt -nographic \
+//!     -kernel target/riscv64imac-unknown-none-elf/debug/no-std-baremetal
+//! ```
+//!
+//! The firmware passes the address of the device tree in `a1`. The example builds an index over
+//! it within a statically allocated buffer, locates the console UART through `/chosen` and prints
+//! a short summary of the device tree to it.
+//!
+//! When built for a hosted target the same code runs against the test device tree and prints to
+//! stdout instead, so the example is also exercised by `cargo test --workspace`.
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+use fdt_rs::base::DevTree;
+use fdt_rs::error::{DevTreeError, Result};
+use fdt_rs::index::{DevTreeIndex, DevTreeIndexNode};
+use fdt_rs::prelude::*;
+use fdt_rs::util::{read_reg, DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS};
+
+/// Size of the statically allocated buffer the index is built within.
+const INDEX_BUF_SIZE: usize = 32 * 1024;
+
+static mut INDEX_BUF: [u8; INDEX_BUF_SIZE] = [0; INDEX_BUF_SIZE];
+
+/// Build an index over `devtree` within [`INDEX_BUF`].
+///
+/// # Safety
+///
+/// Must be called at most once.
+unsafe fn build_index(devtree: DevTree<'static>) -> Result<DevTreeIndex<'static, 'static>> {
+    if DevTreeIndex::required_buffer_size(&devtree)? > INDEX_BUF_SIZE {
+        return Err(DevTreeError::NotEnoughMemory);
+    }
+    let buf = &mut *core::ptr::addr_of_mut!(INDEX_BUF);
+    DevTreeIndex::new(devtree, buf)
+}
+
+/// Returns the value of the named cell count property of `node`, or `default` if absent.
+fn cells(node: &DevTreeIndexNode, name: &str, default: u32) -> u32 {
+    node.props()
+        .find(|prop| prop.name() == Ok(name))
+        .and_then(|prop| prop.u32(0).ok())
+        .unwrap_or(default)
+}
+
+/// Returns the base address of the first `reg` entry of `node`.
+fn reg_base(node: &DevTreeIndexNode) -> Option<u64> {
+    let parent = node.parent()?;
+    let address_cells = cells(&parent, "#address-cells", DEFAULT_ADDRESS_CELLS);
+    let size_cells = cells(&parent, "#size-cells", DEFAULT_SIZE_CELLS);
+    let reg = node.props().find(|prop| prop.name() == Ok("reg"))?;
+    let (base, _size) = read_reg(&reg, address_cells, size_cells).next().ok()??;
+    Some(base)
+}
+
+/// Locate the console UART, returning its node and base address.
+///
+/// The `stdout-path` of `/chosen` is preferred (following an alias if required). When absent,
+/// the first `ns16550a` compatible node is used.
+fn find_uart<'a, 'i, 'dt>(
+    index: &'a DevTreeIndex<'i, 'dt>,
+) -> Option<(DevTreeIndexNode<'a, 'i, 'dt>, u64)> {
+    let stdout = index
+        .node_at_path("/chosen")
+        .and_then(|chosen| chosen.props().find(|prop| prop.name() == Ok("stdout-path")))
+        .and_then(|prop| prop.str().ok())
+        // Strip any options, e.g. "serial0:115200n8".
+        .and_then(|path| path.split(':').next());
+
+    let node = match stdout {
+        Some(path) if path.starts_with('/') => index.node_at_path(path),
+        Some(alias) => index
+            .node_at_path("/aliases")
+            .and_then(|aliases| aliases.props().find(|prop| prop.name() == Ok(alias)))
+            .and_then(|prop| prop.str().ok())
+            .and_then(|path| index.node_at_path(path)),
+        None => None,
+    };
+    let node = node.or_else(|| index.compatible_nodes("ns16550a").next())?;
+    Some((node, reg_base(&node)?))
+}
+
+/// Print a short summary of the device tree.
+fn report<W: Write>(index: &DevTreeIndex, uart: &DevTreeIndexNode, w: &mut W) -> Result<()> {
+    let model = index
+        .root()
+        .props()
+        .find(|prop| prop.name() == Ok("model"))
+        .and_then(|prop| prop.str().ok())
+        .unwrap_or("<unknown>");
+
+    writeln!(w, "fdt-rs no-std example")?;
+    writeln!(w, "model: {}", model)?;
+    writeln!(w, "nodes: {}", index.nodes().count())?;
+    writeln!(w, "console: {}", uart.name()?)?;
+    index.write_tree(w, 1, 16)
+}
+
+#[cfg(target_os = "none")]
+mod baremetal {
+    use core::fmt;
+    use core::panic::PanicInfo;
+
+    use super::*;
+
+    core::arch::global_asm!(
+        r#"
+        .section .text.entry
+        .globl _start
+    _start:
+        la sp, _stack_top
+
+        # Zero the .bss section, INDEX_BUF lives within it.
+        la t0, _sbss
+        la t1, _ebss
+    1:
+        bgeu t0, t1, 2f
+        sd zero, (t0)
+        addi t0, t0, 8
+        j 1b
+    2:
+        # a0 holds the hart id and a1 the address of the device tree.
+        call rust_main
+    3:
+        wfi
+        j 3b
+    "#
+    );
+
+    /// A minimal polled driver for the transmit side of a ns16550a UART.
+    struct Uart(*mut u8);
+
+    impl Uart {
+        /// Offset of the line status register.
+        const LSR: usize = 5;
+        /// Transmit holding register empty.
+        const LSR_THRE: u8 = 1 << 5;
+
+        fn putc(&mut self, c: u8) {
+            unsafe {
+                while core::ptr::read_volatile(self.0.add(Self::LSR)) & Self::LSR_THRE == 0 {}
+                core::ptr::write_volatile(self.0, c);
+            }
+        }
+    }
+
+    impl fmt::Write for Uart {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for b in s.bytes() {
+                if b == b'\n' {
+                    self.putc(b'\r');
+                }
+                self.putc(b);
+            }
+            Ok(())
+        }
+    }
+
+    #[no_mangle]
+    extern "C" fn rust_main(_hartid: usize, dtb: *const u8) {
+        unsafe {
+            // Read the header to discover the size of the device tree.
+            let header = core::slice::from_raw_parts(dtb, DevTree::MIN_HEADER_SIZE);
+            let size = match DevTree::read_totalsize(header) {
+                Ok(size) => size,
+                Err(_) => return,
+            };
+            let devtree = match DevTree::new(core::slice::from_raw_parts(dtb, size)) {
+                Ok(devtree) => devtree,
+                Err(_) => return,
+            };
+            let index = match build_index(devtree) {
+                Ok(index) => index,
+                Err(_) => return,
+            };
+            // Without a console there is nothing we can report.
+            if let Some((node, base)) = find_uart(&index) {
+                let mut uart = Uart(base as *mut u8);
+                if let Err(e) = report(&index, &node, &mut uart) {
+                    let _ = writeln!(uart, "error: {}", e);
+                }
+            }
+        }
+    }
+
+    #[panic_handler]
+    fn panic(_info: &PanicInfo) -> ! {
+        loop {}
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    #[repr(align(4))]
+    struct Wrapper<T>(T);
+    static FDT: &[u8] = &Wrapper(*include_bytes!("../../../tests/riscv64-virt.dtb")).0;
+
+    /// Adapts stdout to `fmt::Write`, like the UART driver above.
+    struct Stdout;
+    impl Write for Stdout {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            print!("{}", s);
+            Ok(())
+        }
+    }
+
+    let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    let index = unsafe { build_index(devtree) }.unwrap();
+    let (node, base) = find_uart(&index).expect("no console UART found");
+    assert_eq!(base, 0x1000_0000);
+    report(&index, &node, &mut Stdout).unwrap();
+}
@@ -0,0 +1,808 @@
+//! Iterative parsers of a [`DevTree`].
+use core::mem::size_of;
+use core::num::NonZeroUsize;
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::base::parse::ParsedTok;
+use crate::base::{DevTree, DevTreeItem, DevTreeNode, DevTreeProp};
+use crate::canonical::{next_prop_by_name, CanonicalItem, CanonicalPath, PropOrder};
+use crate::common::path::{component_matches_glob, path_components};
+use crate::error::{DevTreeError, Result};
+use crate::matcher::Matcher;
+use crate::priv_util::InvariantUnwrap;
+use crate::spec::{fdt_reserve_entry, StandardProp};
+
+// Re-export the basic parse iterator.
+pub use super::parse::DevTreeParseIter;
+
+use fallible_iterator::FallibleIterator;
+
+/// An iterator over [`fdt_reserve_entry`] objects within the FDT.
+#[derive(Clone)]
+pub struct DevTreeReserveEntryIter<'a, 'dt: 'a> {
+    offset: usize,
+    fdt: &'a DevTree<'dt>,
+}
+
+impl<'a, 'dt: 'a> DevTreeReserveEntryIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self {
+            offset: fdt.off_mem_rsvmap(),
+            fdt,
+        }
+    }
+
+    /// Return the current offset as a fdt_reserve_entry reference.
+    ///
+    /// # Safety
+    ///
+    /// The caller must verify that the current offset of this iterator is 32-bit aligned.
+    /// (Each field is 32-bit aligned and they may be read individually.)
+    unsafe fn read(&'a self) -> Result<&'dt fdt_reserve_entry> {
+        Ok(&*self.fdt.ptr_at(self.offset)?)
+    }
+}
+
+impl<'a, 'dt: 'a> Iterator for DevTreeReserveEntryIter<'a, 'dt> {
+    type Item = &'dt fdt_reserve_entry;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset > self.fdt.totalsize() {
+            None
+        } else {
+            // We guaruntee the read will be aligned to 32 bytes because:
+            // - We construct with guarunteed 32-bit aligned offset
+            // - We always increment by an aligned amount
+            let ret = unsafe { self.read().unwrap() };
+
+            if { ret.address } == 0.into() && { ret.size } == 0.into() {
+                return None;
+            }
+            self.offset += size_of::<fdt_reserve_entry>();
+            Some(ret)
+        }
+    }
+}
+
+/// An iterator over all [`DevTreeItem`] objects.
+#[derive(Clone)]
+pub struct DevTreeIter<'a, 'dt: 'a> {
+    /// Offset of the last opened Device Tree Node.
+    /// This is used to set properties' parent DevTreeNode.
+    ///
+    /// As defined by the spec, DevTreeProps must preceed Node definitions.
+    /// Therefore, once a node has been closed this offset is reset to None to indicate no
+    /// properties should follow.
+    current_prop_parent_off: Option<NonZeroUsize>,
+
+    /// Current offset into the flattened dt_struct section of the device tree.
+    offset: usize,
+
+    /// Number of nodes which have been opened but not yet closed.
+    depth: usize,
+    pub(crate) fdt: &'a DevTree<'dt>,
+}
+
+#[derive(Clone)]
+pub struct DevTreeNodeIter<'a, 'dt: 'a>(pub DevTreeIter<'a, 'dt>);
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeIter<'a, 'dt> {
+    type Item = DevTreeNode<'a, 'dt>;
+    type Error = DevTreeError;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.0.next_node()
+    }
+}
+
+#[derive(Clone)]
+pub struct DevTreePropIter<'a, 'dt: 'a>(pub DevTreeIter<'a, 'dt>);
+impl<'a, 'dt: 'a> FallibleIterator for DevTreePropIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeProp<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.0.next_prop()
+    }
+}
+
+#[derive(Clone)]
+pub struct DevTreeNodePropIter<'a, 'dt: 'a>(pub DevTreeIter<'a, 'dt>);
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodePropIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeProp<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.0.next_node_prop()
+    }
+}
+
+/// An iterator over a single [`DevTreeNode`]'s properties, classified by
+/// [`StandardProp`].
+#[derive(Clone)]
+pub struct DevTreeNodeStandardPropIter<'a, 'dt: 'a>(pub DevTreeNodePropIter<'a, 'dt>);
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeStandardPropIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = StandardProp<DevTreeProp<'a, 'dt>>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        Ok(self.0.next()?.map(StandardProp::new))
+    }
+}
+
+/// An iterator over the properties and direct children of a single [`DevTreeNode`].
+///
+/// Items are returned in document order; per the specification all properties of a node precede
+/// its children.
+#[derive(Clone)]
+pub struct DevTreeNodeItemIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    depth: usize,
+}
+
+impl<'a, 'dt: 'a> DevTreeNodeItemIter<'a, 'dt> {
+    pub(crate) fn new(node_iter: DevTreeIter<'a, 'dt>) -> Self {
+        Self {
+            depth: node_iter.depth(),
+            iter: node_iter,
+        }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeItemIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeItem<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        // Once the node has been closed, we're done.
+        if self.depth == 0 {
+            return Ok(None);
+        }
+        while let Some(item) = self.iter.next_item()? {
+            let depth = self.iter.depth();
+            match item {
+                DevTreeItem::Prop(_) if depth == self.depth => return Ok(Some(item)),
+                DevTreeItem::Node(_) if depth == self.depth + 1 => return Ok(Some(item)),
+                DevTreeItem::Node(_) if depth <= self.depth => break,
+                _ => continue,
+            }
+        }
+        self.depth = 0;
+        Ok(None)
+    }
+}
+
+/// An iterator over the direct child nodes of a single [`DevTreeNode`].
+///
+/// Created by [`DevTreeNode::children`].
+#[derive(Clone)]
+pub struct DevTreeNodeChildIter<'a, 'dt: 'a>(pub DevTreeNodeItemIter<'a, 'dt>);
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeChildIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeNode<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(item) = self.0.next()? {
+            if let DevTreeItem::Node(node) = item {
+                return Ok(Some(node));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// An iterator over the properties and descendants of a single [`DevTreeNode`], in document
+/// order.
+///
+/// Created by [`DevTreeNode::subtree`].
+#[derive(Clone)]
+pub struct DevTreeNodeSubtreeIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    depth: usize,
+}
+
+impl<'a, 'dt: 'a> DevTreeNodeSubtreeIter<'a, 'dt> {
+    pub(crate) fn new(node_iter: DevTreeIter<'a, 'dt>) -> Self {
+        Self {
+            depth: node_iter.depth(),
+            iter: node_iter,
+        }
+    }
+
+    /// Returns the depth of the most recently returned item. See [`DevTreeIter::depth`].
+    #[inline]
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.iter.depth()
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeSubtreeIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeItem<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        // Once the node has been closed, we're done.
+        if self.depth == 0 {
+            return Ok(None);
+        }
+        if let Some(item) = self.iter.next_item()? {
+            match item {
+                // A sibling (or a node following an ancestor) ends the subtree.
+                DevTreeItem::Node(_) if self.iter.depth() <= self.depth => {}
+                _ => return Ok(Some(item)),
+            }
+        }
+        self.depth = 0;
+        Ok(None)
+    }
+}
+
+/// An iterator over the descendant nodes of a single [`DevTreeNode`], in document order.
+///
+/// Created by [`DevTreeNode::descendants`].
+#[derive(Clone)]
+pub struct DevTreeNodeDescendantIter<'a, 'dt: 'a>(pub DevTreeNodeSubtreeIter<'a, 'dt>);
+
+impl<'a, 'dt: 'a> DevTreeNodeDescendantIter<'a, 'dt> {
+    /// Returns the depth of the most recently returned node. See [`DevTreeIter::depth`].
+    #[inline]
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.0.depth()
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeDescendantIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeNode<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(item) = self.0.next()? {
+            if let DevTreeItem::Node(node) = item {
+                return Ok(Some(node));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// An iterator over the canonical stream of a device tree. See [`crate::canonical`].
+///
+/// Created by [`DevTree::canonical_items`].
+#[derive(Clone)]
+pub struct DevTreeCanonicalIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    order: PropOrder,
+    path: CanonicalPath<'dt>,
+    // With `PropOrder::ByName`, an iterator positioned at the node whose properties are being
+    // returned, and the last property returned.
+    node: Option<DevTreeIter<'a, 'dt>>,
+    last: Option<(&'dt str, usize)>,
+}
+
+impl<'a, 'dt: 'a> DevTreeCanonicalIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>, order: PropOrder) -> Self {
+        Self {
+            iter: DevTreeIter::new(fdt),
+            order,
+            path: CanonicalPath::root(),
+            node: None,
+            last: None,
+        }
+    }
+
+    fn item(&self, name: Option<&'dt str>, value: &'dt [u8]) -> CanonicalItem<'dt> {
+        CanonicalItem {
+            path: self.path,
+            name,
+            value,
+        }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeCanonicalIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = CanonicalItem<'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if let Some(node) = &self.node {
+            let props = DevTreeNodePropIter(node.clone()).map(|p| Ok((p.name()?, p.propbuf())));
+            match next_prop_by_name(props, self.last)? {
+                Some((name, value, idx)) => {
+                    self.last = Some((name, idx));
+                    return Ok(Some(self.item(Some(name), value)));
+                }
+                None => self.node = None,
+            }
+        }
+        loop {
+            match self.iter.next_item()? {
+                Some(DevTreeItem::Node(node)) => {
+                    let depth = self.iter.depth();
+                    self.path.truncate(depth.saturating_sub(2));
+                    if depth > 1 {
+                        self.path.push(node.name()?)?;
+                    }
+                    if self.order == PropOrder::ByName {
+                        self.node = Some(node.parse_iter);
+                        self.last = None;
+                    }
+                    return Ok(Some(self.item(None, &[])));
+                }
+                // Properties have already been returned by name.
+                Some(DevTreeItem::Prop(_)) if self.order == PropOrder::ByName => continue,
+                Some(DevTreeItem::Prop(prop)) => {
+                    return Ok(Some(self.item(Some(prop.name()?), prop.propbuf())));
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Adapts a [`FallibleIterator`] into an [`Iterator`] which yields a [`Result`] per element.
+///
+/// Unlike [`FallibleIterator::iterator`], iteration ends after the first error has been returned.
+/// This allows `for` loops over untrusted device trees to distinguish the end of the tree from
+/// corruption without risking an endless stream of errors.
+#[derive(Clone)]
+pub struct DevTreeTryIter<I> {
+    iter: I,
+    done: bool,
+}
+
+impl<I> DevTreeTryIter<I> {
+    pub(crate) fn new(iter: I) -> Self {
+        Self { iter, done: false }
+    }
+}
+
+impl<I: FallibleIterator> Iterator for DevTreeTryIter<I> {
+    type Item = core::result::Result<I::Item, I::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.iter.next() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// An iterator over the [`DevTreeNode`] objects whose paths match a glob pattern.
+///
+/// Created by [`DevTree::nodes_matching_path`].
+#[derive(Clone)]
+pub struct DevTreePathMatchIter<'p, 'a, 'dt: 'a> {
+    pub(crate) iter: DevTreeIter<'a, 'dt>,
+    pub(crate) pattern: &'p str,
+    /// Number of path components matched by the ancestors of the current node.
+    pub(crate) matched: usize,
+}
+
+impl<'p, 'a, 'dt: 'a> FallibleIterator for DevTreePathMatchIter<'p, 'a, 'dt> {
+    type Item = DevTreeNode<'a, 'dt>;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let components = match path_components(self.pattern) {
+            Some(components) => components,
+            None => return Ok(None),
+        };
+        let count = match components {
+            "" => 0,
+            _ => components.split('/').count(),
+        };
+
+        while let Some(node) = self.iter.next_node()? {
+            // The number of path components leading to this node (none for the root).
+            let level = self.iter.depth() - 1;
+            self.matched = core::cmp::min(self.matched, level.saturating_sub(1));
+            if level == 0 {
+                if count == 0 {
+                    return Ok(Some(node));
+                }
+                continue;
+            }
+            if self.matched + 1 != level || level > count {
+                continue;
+            }
+            let component = components.split('/').nth(level - 1).unwrap_or("");
+            if component_matches_glob(node.name()?, component) {
+                self.matched = level;
+                if level == count {
+                    return Ok(Some(node));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// An iterator over [`DevTreeItem`] objects which bounds the traversal of a device tree.
+///
+/// Created by [`DevTreeIter::limit_depth`] or [`DevTreeIter::limit_nodes`]. When a limit is
+/// exceeded an [`Err`] containing [`DevTreeError::DepthLimitExceeded`] or
+/// [`DevTreeError::NodeLimitExceeded`] is returned and iteration ends.
+#[derive(Clone)]
+pub struct DevTreeLimitIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    max_depth: usize,
+    max_nodes: usize,
+    nodes: usize,
+    done: bool,
+}
+
+impl<'a, 'dt: 'a> DevTreeLimitIter<'a, 'dt> {
+    fn new(iter: DevTreeIter<'a, 'dt>) -> Self {
+        Self {
+            iter,
+            max_depth: usize::MAX,
+            max_nodes: usize::MAX,
+            nodes: 0,
+            done: false,
+        }
+    }
+
+    /// Fail once a node nested deeper than `max_depth` is found. The root node has a depth of 1.
+    #[must_use]
+    pub fn limit_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = core::cmp::min(self.max_depth, max_depth);
+        self
+    }
+
+    /// Fail once more than `max_nodes` nodes have been found.
+    #[must_use]
+    pub fn limit_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = core::cmp::min(self.max_nodes, max_nodes);
+        self
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeLimitIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeItem<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.done {
+            return Ok(None);
+        }
+        let res = match self.iter.next_item() {
+            Ok(Some(DevTreeItem::Node(node))) => {
+                self.nodes += 1;
+                if self.iter.depth() > self.max_depth {
+                    Err(DevTreeError::DepthLimitExceeded)
+                } else if self.nodes > self.max_nodes {
+                    Err(DevTreeError::NodeLimitExceeded)
+                } else {
+                    Ok(Some(DevTreeItem::Node(node)))
+                }
+            }
+            res => res,
+        };
+        if !matches!(res, Ok(Some(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+/// An iterator over [`DevTreeItem`] objects which maintains the path of the current node within
+/// a caller provided stack, for tools which need full paths without building an index.
+///
+/// Created by [`DevTreeIter::with_path_stack`]. After a node is returned, [`Self::path`] holds
+/// the names of the components of its path (the root node has none). After a property is
+/// returned it holds the path of the property's node. Nodes nested more deeply than the stack
+/// holds components return an [`Err`] containing [`DevTreeError::DepthLimitExceeded`], after
+/// which iteration ends.
+pub struct DevTreePathStackIter<'s, 'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    stack: &'s mut [&'dt str],
+    len: usize,
+    done: bool,
+}
+
+impl<'s, 'a, 'dt: 'a> DevTreePathStackIter<'s, 'a, 'dt> {
+    /// Returns the components of the path of the node most recently returned, or of the node
+    /// of the property most recently returned.
+    #[must_use]
+    pub fn path(&self) -> &[&'dt str] {
+        &self.stack[..self.len]
+    }
+
+    /// Write the path returned by [`Self::path`] into `w`, e.g. `/soc/uart@10000000`.
+    pub fn write_path<W: core::fmt::Write>(&self, w: &mut W) -> core::fmt::Result {
+        if self.len == 0 {
+            return w.write_char('/');
+        }
+        for name in self.path() {
+            w.write_char('/')?;
+            w.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'s, 'a, 'dt: 'a> FallibleIterator for DevTreePathStackIter<'s, 'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeItem<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.done {
+            return Ok(None);
+        }
+        let res = self.iter.next_item().and_then(|item| {
+            // The root node has a depth of 1 and no path components.
+            let len = self.iter.depth().saturating_sub(1);
+            if let Some(DevTreeItem::Node(node)) = &item {
+                if len > self.stack.len() {
+                    return Err(DevTreeError::DepthLimitExceeded);
+                }
+                if len != 0 {
+                    self.stack[len - 1] = node.name()?;
+                }
+            }
+            self.len = len;
+            Ok(item)
+        });
+        if !matches!(res, Ok(Some(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+pub struct DevTreeCompatibleNodeIter<'s, 'a, 'dt: 'a, M: Matcher + ?Sized = str> {
+    pub iter: DevTreeIter<'a, 'dt>,
+    pub matcher: &'s M,
+}
+impl<'s, 'a, 'dt: 'a, M: Matcher + ?Sized> Clone for DevTreeCompatibleNodeIter<'s, 'a, 'dt, M> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            matcher: self.matcher,
+        }
+    }
+}
+impl<'s, 'a, 'dt: 'a, M: Matcher + ?Sized> DevTreeCompatibleNodeIter<'s, 'a, 'dt, M> {
+    /// Restart the search from the root node.
+    pub fn rewind(&mut self) {
+        self.iter = DevTreeIter::new(self.iter.fdt);
+    }
+}
+
+impl<'s, 'a, 'dt: 'a, M: Matcher + ?Sized> FallibleIterator
+    for DevTreeCompatibleNodeIter<'s, 'a, 'dt, M>
+{
+    type Error = DevTreeError;
+    type Item = DevTreeNode<'a, 'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.iter.next_compatible_node(self.matcher)
+    }
+}
+
+impl<'a, 'dt: 'a> DevTreeIter<'a, 'dt> {
+    pub fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self {
+            offset: fdt.off_dt_struct(),
+            current_prop_parent_off: None,
+            depth: 0,
+            fdt,
+        }
+    }
+
+    /// Returns the node whose `FDT_BEGIN_NODE` token is at `node_off`, as if it had just been
+    /// parsed by an iterator with `depth - 1` open nodes.
+    ///
+    /// `node_off` must refer to a `FDT_BEGIN_NODE` token.
+    pub(crate) fn node_at(
+        fdt: &'a DevTree<'dt>,
+        node_off: usize,
+        depth: usize,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        let mut iter = Self {
+            offset: node_off,
+            current_prop_parent_off: None,
+            depth: depth - 1,
+            fdt,
+        };
+        match iter.next_item()? {
+            Some(DevTreeItem::Node(node)) => Ok(Some(node)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the number of nodes which are currently open.
+    ///
+    /// After a [`DevTreeNode`] has been returned this is the depth of that node (the root node
+    /// has a depth of 1). After a [`DevTreeProp`] has been returned it is the depth of the
+    /// property's node.
+    #[inline]
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Bound this traversal to nodes nested at most `max_depth` deep. See [`DevTreeLimitIter`].
+    #[must_use]
+    pub fn limit_depth(self, max_depth: usize) -> DevTreeLimitIter<'a, 'dt> {
+        DevTreeLimitIter::new(self).limit_depth(max_depth)
+    }
+
+    /// Bound this traversal to at most `max_nodes` nodes. See [`DevTreeLimitIter`].
+    #[must_use]
+    pub fn limit_nodes(self, max_nodes: usize) -> DevTreeLimitIter<'a, 'dt> {
+        DevTreeLimitIter::new(self).limit_nodes(max_nodes)
+    }
+
+    /// Track the path of each node of this traversal within `stack`, which bounds the depth of
+    /// the nodes which may be found. See [`DevTreePathStackIter`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::FDT;
+    /// use fdt_rs::base::DevTree;
+    /// use fdt_rs::prelude::*;
+    ///
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let mut stack = [""; 4];
+    /// let mut iter = devtree.items().with_path_stack(&mut stack);
+    /// while let Some(_) = iter.next().unwrap() {
+    ///     if iter.path().last() == Some(&"cpu@0") {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(iter.path(), &["cpus", "cpu@0"]);
+    ///
+    /// let mut path = String::new();
+    /// iter.write_path(&mut path).unwrap();
+    /// assert_eq!(path, "/cpus/cpu@0");
+    /// ```
+    #[must_use]
+    pub fn with_path_stack<'s>(
+        self,
+        stack: &'s mut [&'dt str],
+    ) -> DevTreePathStackIter<'s, 'a, 'dt> {
+        DevTreePathStackIter {
+            iter: self,
+            stack,
+            len: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the offset of the `FDT_BEGIN_NODE` token of the node most recently opened (if its
+    /// properties may still follow).
+    pub(crate) fn current_node_offset(&self) -> Option<usize> {
+        self.current_prop_parent_off.map(NonZeroUsize::get)
+    }
+
+    /// Returns the current offset into the device tree buffer.
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn current_node_itr(&self) -> Option<DevTreeIter<'a, 'dt>> {
+        match self.current_prop_parent_off {
+            Some(offset) => Some(DevTreeIter {
+                fdt: self.fdt,
+                current_prop_parent_off: self.current_prop_parent_off,
+                offset: offset.get(),
+                // The node will be re-opened when this iterator parses it.
+                depth: self.depth - 1,
+            }),
+            None => None,
+        }
+    }
+
+    pub fn next_item(&mut self) -> Result<Option<DevTreeItem<'a, 'dt>>> {
+        loop {
+            let old_offset = self.offset;
+            // Safe because we only pass offsets which are returned by next_devtree_token.
+            let res = unsafe { self.fdt.next_token(&mut self.offset)? };
+            #[cfg(feature = "profiling")]
+            crate::profiling::record_token();
+
+            match res {
+                Some(ParsedTok::BeginNode(node)) => {
+                    #[cfg(feature = "profiling")]
+                    crate::profiling::record_node();
+                    self.current_prop_parent_off =
+                        unsafe { Some(NonZeroUsize::new(old_offset).invariant_unwrap()) };
+                    self.depth += 1;
+                    return Ok(Some(DevTreeItem::Node(DevTreeNode {
+                        parse_iter: self.clone(),
+                        name: from_utf8(node.name).map_err(|e| e.into()),
+                    })));
+                }
+                Some(ParsedTok::Prop(prop)) => {
+                    // Prop must come after a node.
+                    let prev_node = match self.current_node_itr() {
+                        Some(n) => n,
+                        None => return Ok(None),
+                    };
+
+                    return Ok(Some(DevTreeItem::Prop(DevTreeProp::new(
+                        prev_node,
+                        prop.prop_buf,
+                        prop.name_offset,
+                    ))));
+                }
+                Some(ParsedTok::EndNode) => {
+                    // The current node has ended.
+                    // No properties may follow until the next node starts.
+                    self.current_prop_parent_off = None;
+                    self.depth = self.depth.saturating_sub(1);
+                }
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    pub fn next_prop(&mut self) -> Result<Option<DevTreeProp<'a, 'dt>>> {
+        loop {
+            match self.next() {
+                Ok(Some(DevTreeItem::Prop(p))) => return Ok(Some(p)),
+                Ok(Some(_p)) => continue,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn next_node(&mut self) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        loop {
+            match self.next() {
+                Ok(Some(DevTreeItem::Node(n))) => return Ok(Some(n)),
+                Ok(Some(_p)) => continue,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn next_node_prop(&mut self) -> Result<Option<DevTreeProp<'a, 'dt>>> {
+        match self.next() {
+            // Return if a new node or an EOF.
+            Ok(Some(item)) => Ok(item.prop()),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn next_compatible_node<M: Matcher + ?Sized>(
+        &mut self,
+        matcher: &M,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        #[cfg(feature = "profiling")]
+        let _search = crate::profiling::Search::begin();
+
+        // If there is another node, advance our iterator to that node.
+        self.next_node().and_then(|_| {
+            // Iterate through all remaining properties in the tree looking for the compatible
+            // string.
+            loop {
+                match self.next_prop() {
+                    Ok(Some(prop)) => {
+                        if prop.name()? == "compatible" && prop.strlist_matches(matcher) {
+                            return Ok(Some(prop.node()));
+                        }
+                        continue;
+                    }
+                    Ok(None) => return Ok(None),
+                    Err(e) => return Err(e),
+                }
+            }
+        })
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeItem<'a, 'dt>;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.next_item()
+    }
+}
@@ -7,6 +7,23 @@
 //!
 //! See the [`crate::index`] module for more advanced and performant utilites.
 //!
+//! # Iterator Costs
+//!
+//! Every iterator of this module stores its position within the structure block and a reference
+//! to the [`DevTree`]; none allocate. Most are four to eight machine words, so cloning one (e.g.
+//! to resume a search later) is a plain copy which does not re-parse the tree. [`DevTreeNode`]
+//! and [`DevTreeProp`] each embed the iterator position they were found at, which is what allows
+//! [`DevTreeNode::props`] and [`DevTreeProp::node`] to work without searching.
+//!
+//! Each call to `next` parses the tokens up to the following item, so iterating a whole tree (or
+//! searching it, e.g. by compatible string or path) is `O(n)` in the size of the structure block.
+//! Methods which must look backwards, such as [`DevTreeNode::parent`], re-parse the tree from its
+//! start.
+//!
+//! The exception to the size of iterators is [`iters::DevTreeCanonicalIter`], which stores the
+//! path of its current node inline (up to [`crate::canonical::MAX_CANONICAL_DEPTH`] components)
+//! and is several hundred bytes.
+//!
 //! # Examples
 //!
 //! ## Initialization
@@ -67,18 +84,29 @@ pub mod item;
 #[doc(hidden)]
 pub mod node;
 #[doc(hidden)]
+pub mod preconditions;
+#[doc(hidden)]
 pub mod prop;
 #[doc(hidden)]
 pub mod tree;
+#[cfg(feature = "writer")]
+#[doc(hidden)]
+pub mod tree_mut;
 
 pub mod iters;
 pub mod parse;
+pub mod storage;
 
 #[doc(inline)]
 pub use item::*;
 #[doc(inline)]
 pub use node::*;
 #[doc(inline)]
+pub use preconditions::*;
+#[doc(inline)]
 pub use prop::*;
 #[doc(inline)]
 pub use tree::*;
+#[cfg(feature = "writer")]
+#[doc(inline)]
+pub use tree_mut::*;
@@ -0,0 +1,261 @@
+#[cfg(doc)]
+use super::*;
+
+use core::ops::Range;
+
+use crate::prelude::*;
+
+use crate::base::iters::{
+    DevTreeIter, DevTreeNodeChildIter, DevTreeNodeDescendantIter, DevTreeNodeItemIter,
+    DevTreeNodePropIter, DevTreeNodeStandardPropIter, DevTreeNodeSubtreeIter,
+};
+use crate::base::parse::{DevTreeParseIter, ParsedTok};
+use crate::base::{DevTree, DevTreeProp};
+use crate::error::{DevTreeError, Result};
+use crate::matcher::Matcher;
+use crate::spec::{DuplicatePropPolicy, Status};
+use crate::util::{NodeRegIter, DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS};
+
+/// A handle to a Device Tree Node within the device tree.
+#[derive(Clone)]
+pub struct DevTreeNode<'a, 'dt: 'a> {
+    pub(super) name: Result<&'dt str>,
+    pub(super) parse_iter: DevTreeIter<'a, 'dt>,
+}
+
+impl<'a, 'dt: 'a> DevTreeNode<'a, 'dt> {
+    /// Returns the name of the `DevTreeNode` (including unit address tag)
+    #[inline]
+    pub fn name(&'a self) -> Result<&'dt str> {
+        self.name
+    }
+
+    /// Returns the [`DevTree`] this node belongs to.
+    #[inline]
+    #[must_use]
+    pub fn tree(&self) -> &'a DevTree<'dt> {
+        self.parse_iter.fdt
+    }
+
+    /// Returns an iterator over this node's children [`DevTreeProp`]
+    #[must_use]
+    pub fn props(&'a self) -> DevTreeNodePropIter<'a, 'dt> {
+        DevTreeNodePropIter(self.parse_iter.clone())
+    }
+
+    /// Returns an iterator over this node's properties, classified by
+    /// [`StandardProp`](crate::spec::StandardProp).
+    #[must_use]
+    pub fn standard_props(&'a self) -> DevTreeNodeStandardPropIter<'a, 'dt> {
+        DevTreeNodeStandardPropIter(self.props())
+    }
+
+    /// Returns the first property of this node named `name`.
+    ///
+    /// This is equivalent to [`prop_with_policy`](Self::prop_with_policy) with
+    /// [`DuplicatePropPolicy::FirstWins`].
+    pub fn prop(&'a self, name: &str) -> Result<Option<DevTreeProp<'a, 'dt>>> {
+        self.prop_with_policy(name, DuplicatePropPolicy::FirstWins)
+    }
+
+    /// Returns the property of this node named `name`, resolving duplicate properties of that
+    /// name according to `policy`.
+    pub fn prop_with_policy(
+        &'a self,
+        name: &str,
+        policy: DuplicatePropPolicy,
+    ) -> Result<Option<DevTreeProp<'a, 'dt>>> {
+        let mut props = self.props().filter(|prop| Ok(prop.name()? == name));
+        let first = match props.next()? {
+            Some(prop) => prop,
+            None => return Ok(None),
+        };
+        match policy {
+            DuplicatePropPolicy::FirstWins => Ok(Some(first)),
+            DuplicatePropPolicy::LastWins => Ok(Some(props.last()?.unwrap_or(first))),
+            DuplicatePropPolicy::Strict => match props.next()? {
+                Some(_) => Err(DevTreeError::DuplicateProperty),
+                None => Ok(Some(first)),
+            },
+        }
+    }
+
+    /// Returns the value of this node's `u32` property `name`, or `None` if it has no such
+    /// property.
+    pub fn prop_u32(&self, name: &str) -> Result<Option<u32>> {
+        match self.prop(name)? {
+            Some(prop) => prop.u32(0).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the `index`th 64-bit (two cell) value of this node's property `name`, or `None` if
+    /// it has no such property.
+    ///
+    /// A property too short to hold the value is reported as an error.
+    pub fn prop_u64_cells(&self, name: &str, index: usize) -> Result<Option<u64>> {
+        match self.prop(name)? {
+            Some(prop) => {
+                let offset = index.checked_mul(8).ok_or(DevTreeError::InvalidOffset)?;
+                prop.u64(offset).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the (first) string of this node's property `name`, or `None` if it has no such
+    /// property.
+    pub fn prop_str(&self, name: &str) -> Result<Option<&'dt str>> {
+        match self.prop(name)? {
+            Some(prop) => prop.str().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the `(address, size)` entries of this node's `reg` property,
+    /// decoded using its parent's `#address-cells` and `#size-cells`.
+    ///
+    /// See [`DevTreeIndexNode::reg`](crate::index::DevTreeIndexNode::reg). Finding the parent
+    /// re-parses the device tree from its start, as [`parent`](Self::parent) does.
+    pub fn reg(&'a self) -> Result<NodeRegIter<'dt, DevTreeProp<'a, 'dt>>> {
+        let (address_cells, size_cells) = match self.parent()? {
+            Some(parent) => (
+                parent.prop_u32("#address-cells")?.unwrap_or(DEFAULT_ADDRESS_CELLS),
+                parent.prop_u32("#size-cells")?.unwrap_or(DEFAULT_SIZE_CELLS),
+            ),
+            None => (DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS),
+        };
+        Ok(NodeRegIter::new(self.prop("reg")?, address_cells, size_cells))
+    }
+
+    /// Returns an iterator over this node's properties followed by its direct child nodes, in
+    /// document order.
+    #[must_use]
+    pub fn items(&self) -> DevTreeNodeItemIter<'a, 'dt> {
+        DevTreeNodeItemIter::new(self.parse_iter.clone())
+    }
+
+    /// Returns an iterator over this node's direct child nodes.
+    ///
+    /// The iterator parses the node's entire subtree, skipping over the contents of each child.
+    #[must_use]
+    pub fn children(&self) -> DevTreeNodeChildIter<'a, 'dt> {
+        DevTreeNodeChildIter(self.items())
+    }
+
+    /// Returns an iterator over this node's properties and all of its descendant nodes and
+    /// properties, in document order. The node itself is not returned.
+    ///
+    /// Iteration stops once the end of the node has been parsed.
+    #[must_use]
+    pub fn subtree(&self) -> DevTreeNodeSubtreeIter<'a, 'dt> {
+        DevTreeNodeSubtreeIter::new(self.parse_iter.clone())
+    }
+
+    /// Returns an iterator over this node's descendant nodes (children, grandchildren, etc.), in
+    /// document order. The node itself is not returned.
+    ///
+    /// Iteration stops once the end of the node has been parsed, so nodes following this node's
+    /// subtree are not visited.
+    #[must_use]
+    pub fn descendants(&self) -> DevTreeNodeDescendantIter<'a, 'dt> {
+        DevTreeNodeDescendantIter(self.subtree())
+    }
+
+    /// Returns this node's parent, or `None` for the root node.
+    ///
+    /// Nodes do not record their parent, so the device tree is re-parsed from its start up to
+    /// this node to find it. Prefer the [`crate::index`] API when parents are frequently needed.
+    pub fn parent(&self) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        let offset = self.node_offset().ok_or(DevTreeError::ParseError)?;
+        let depth = self.parse_iter.depth();
+        if depth <= 1 {
+            return Ok(None);
+        }
+
+        let mut iter = DevTreeIter::new(self.tree());
+        let mut parent = None;
+        while let Some(node) = iter.next_node()? {
+            if iter.current_node_offset() == Some(offset) {
+                return Ok(parent);
+            }
+            if iter.depth() == depth - 1 {
+                parent = Some(node);
+            }
+        }
+        // This node was not found within its own tree.
+        Err(DevTreeError::ParseError)
+    }
+
+    /// Returns the offset of this node's `FDT_BEGIN_NODE` token from the start of the device tree.
+    pub(crate) fn node_offset(&self) -> Option<usize> {
+        self.parse_iter.current_node_offset()
+    }
+
+    /// Returns the byte offsets (from the start of the device tree) spanned by this node within
+    /// the structure block.
+    ///
+    /// The range begins at the node's `FDT_BEGIN_NODE` token and ends just after its
+    /// `FDT_END_NODE` token. It therefore covers all of the node's properties and descendants.
+    ///
+    /// Determining the end of the range requires parsing the node's subtree.
+    pub fn subtree_byte_range(&self) -> Result<Range<usize>> {
+        let start = self
+            .parse_iter
+            .current_node_offset()
+            .ok_or(DevTreeError::ParseError)?;
+
+        let mut iter = DevTreeParseIter {
+            offset: self.parse_iter.offset(),
+            fdt: self.parse_iter.fdt,
+        };
+        let mut depth = 1usize;
+        while let Some(tok) = iter.next()? {
+            match tok {
+                ParsedTok::BeginNode(_) => depth += 1,
+                ParsedTok::EndNode => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(start..iter.offset);
+                    }
+                }
+                _ => continue,
+            }
+        }
+        // The structure block ended before this node did.
+        Err(DevTreeError::ParseError)
+    }
+
+    /// Returns true if this node's `status` property is `"okay"` (or the legacy `"ok"`), or if it
+    /// has no `status` property at all.
+    pub fn status_is_okay(&self) -> Result<bool> {
+        Ok(self.status()? == Status::Okay)
+    }
+
+    /// Returns the parsed value of this node's `status` property ([`Status::Okay`] if absent).
+    pub fn status(&self) -> Result<Status<'dt>> {
+        let mut props = self.props();
+        while let Some(prop) = props.next()? {
+            if prop.name()? == "status" {
+                return Ok(Status::from(prop.str()?));
+            }
+        }
+        Ok(Status::Okay)
+    }
+
+    /// Returns the next [`DevTreeNode`] object with a compatible device tree property accepted by
+    /// the provided [`Matcher`] or `None` if none exists.
+    ///
+    /// # Example
+    ///
+    /// The following example iterates through all nodes with compatible value "virtio,mmio"
+    /// and prints each node's name.
+    ///
+    /// TODO
+    pub fn find_next_compatible_node<M: Matcher + ?Sized>(
+        &self,
+        matcher: &M,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        self.parse_iter.clone().next_compatible_node(matcher)
+    }
+}
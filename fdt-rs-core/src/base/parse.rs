@@ -0,0 +1,426 @@
+//! Low level flattened device tree parsing.
+//!
+//! The structure block is tokenized by [`DevTreeParseIter`], or by
+//! [`DevTree::raw_tokens_unchecked()`] for device trees verified by [`DevTree::validate()`].
+//!
+//! # Safety
+//!
+//! The tokenizers behind these iterators read the structure block with unchecked aligned loads,
+//! so each is `unsafe` and requires that:
+//!
+//! 1. `buf` contains a device tree structure block, such as the buffer of a [`DevTree`].
+//!
+//! 2. `off` is the offset of a token within that block, and the address it refers to (i.e.
+//!    `buf.as_ptr() + off`) is u32 aligned. The structure block offset
+//!    ([`DevTree::off_dt_struct()`]) is such an offset.
+//!
+//! When a tokenizer returns a token, `off` is left at the start of the next token and remains
+//! aligned, so it may be called iteratively without checking the offset again. The unchecked
+//! tokenizer additionally requires that the device tree has been verified by
+//! [`DevTree::validate()`].
+//!
+//! [`DevTreeParseIter`] upholds these requirements itself. Its offset may only be chosen through
+//! [`DevTreeParseIter::at_offset()`], which checks it.
+
+use core::mem::size_of;
+
+use num_traits::FromPrimitive;
+
+use crate::base::DevTree;
+use crate::error::{DevTreeError, Result};
+#[cfg(any(feature = "checked", feature = "legacy-fdt"))]
+use crate::priv_util::InvariantUnwrap;
+use crate::priv_util::SliceRead;
+#[cfg(feature = "legacy-fdt")]
+use crate::spec::MAX_LEGACY_NODE_PATH_LEN;
+use crate::spec::{fdt_prop_header, FdtTok, MAX_FULL_NODE_NAME_LEN};
+
+use fallible_iterator::FallibleIterator;
+
+/// Tokenize the token at `off` within the device tree's structure block.
+///
+/// # Safety
+///
+/// See the [module documentation](self#safety).
+#[deprecated(note = "iterate the tokens with the safe `DevTreeParseIter` instead")]
+pub unsafe fn next_devtree_token<'a>(
+    buf: &'a [u8],
+    off: &mut usize,
+) -> Result<Option<ParsedTok<'a>>> {
+    next_modern_devtree_token(buf, off)
+}
+
+/// This function implements the logic to tokenize the device tree's main structure block.
+///
+/// This function will return the next [`ParsedTok`] if one exists. If it succeeds in parsing
+/// a token, `off` will be incremented to the start of the next token within `buf`.
+///
+/// # Safety
+///
+/// See the [module documentation](self#safety).
+pub(crate) unsafe fn next_modern_devtree_token<'a>(
+    buf: &'a [u8],
+    off: &mut usize,
+) -> Result<Option<ParsedTok<'a>>> {
+    next_devtree_token_with_name_len(buf, off, MAX_FULL_NODE_NAME_LEN)
+}
+
+/// Tokenize as [`next_modern_devtree_token`] does, accepting node names of up to `max_name_len`
+/// bytes (excluding the null byte).
+///
+/// # Safety
+///
+/// See [`next_modern_devtree_token`].
+unsafe fn next_devtree_token_with_name_len<'a>(
+    buf: &'a [u8],
+    off: &mut usize,
+    max_name_len: usize,
+) -> Result<Option<ParsedTok<'a>>> {
+    // This is guaranteed.
+    // We only produce associated offsets that are aligned to 32 bits. They may lie beyond the end
+    // of a malformed buffer, in which case the bounds checked read below fails.
+    debug_assert!(buf.as_ptr().add(*off) as usize % size_of::<u32>() == 0);
+
+    let tok_off = *off;
+    let malformed = |token| DevTreeError::MalformedToken {
+        offset: tok_off,
+        token,
+    };
+
+    let fdt_tok_val = buf
+        .unsafe_read_be_u32(*off)
+        .or(Err(malformed(None)))?;
+    *off += size_of::<u32>();
+
+    match FromPrimitive::from_u32(fdt_tok_val) {
+        Some(FdtTok::BeginNode) => {
+            // Read the name (or return an error if the device tree is incorrectly formatted).
+            // The window includes the null byte.
+            let name = buf
+                .nread_bstring0(*off, max_name_len + 1)
+                .or(Err(malformed(Some(FdtTok::BeginNode))))?;
+
+            // Move to the end of name (adding null byte).
+            *off += name.len() + 1;
+            // Per spec - align back to u32.
+            *off += buf.as_ptr().add(*off).align_offset(size_of::<u32>());
+
+            Ok(Some(ParsedTok::BeginNode(ParsedBeginNode { name })))
+        }
+        Some(FdtTok::Prop) => {
+            // Get the memory we'll use as the header
+            let header_slice = buf
+                .get(*off..*off + size_of::<fdt_prop_header>())
+                .ok_or(malformed(Some(FdtTok::Prop)))?;
+            // Re-interpret the data as a fdt_header.
+            //
+            // We already checked length.
+            // We statically verify alignment by ensuring pointer alignment matches known u32 alignment.
+            assert_eq_align!(fdt_prop_header, u32);
+            #[allow(clippy::cast_ptr_alignment)]
+            let header = &*(header_slice.as_ptr() as *const fdt_prop_header);
+            let prop_len = u32::from(header.len) as usize;
+
+            // Move offset past prop header
+            *off += size_of::<fdt_prop_header>();
+            // Create a slice using the offset
+            let prop_buf = buf
+                .get(*off..*off + prop_len)
+                .ok_or(malformed(Some(FdtTok::Prop)))?;
+
+            // Move the offset past the prop data.
+            *off += prop_buf.len();
+            // Align back to u32.
+            *off += buf.as_ptr().add(*off).align_offset(size_of::<u32>());
+
+            // The name offset is relative to the strings block, which is not known here. It is
+            // checked when the name is read, see `DevTree::string_at()`.
+            let name_offset = u32::from(header.nameoff) as usize;
+
+            Ok(Some(ParsedTok::Prop(ParsedProp {
+                name_offset,
+                prop_buf,
+            })))
+        }
+        Some(FdtTok::EndNode) => Ok(Some(ParsedTok::EndNode)),
+        Some(FdtTok::Nop) => Ok(Some(ParsedTok::Nop)),
+        Some(FdtTok::End) => Ok(None),
+        None => {
+            // Invalid token
+            Err(malformed(None))
+        }
+    }
+}
+
+/// The first version of the format which stores unit names (rather than full paths) in
+/// `FDT_BEGIN_NODE` tokens and aligns all property values to 4 bytes.
+#[cfg(feature = "legacy-fdt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "legacy-fdt")))]
+pub const FIRST_MODERN_VERSION: u32 = 16;
+
+/// Tokenize the structure block of a device tree older than [`FIRST_MODERN_VERSION`].
+///
+/// This behaves as [`next_modern_devtree_token`] does, but handles the layout of versions 1 to 3:
+///
+/// - Node names are full paths, of up to [`MAX_LEGACY_NODE_PATH_LEN`] bytes. The returned name
+///   is the final component of the path (and so is empty for the root node).
+/// - Property values of 8 or more bytes are aligned to 8 bytes relative to the start of the
+///   structure block, `struct_off`.
+///
+/// # Safety
+///
+/// See the [module documentation](self#safety).
+#[cfg(feature = "legacy-fdt")]
+pub(crate) unsafe fn next_legacy_devtree_token<'a>(
+    buf: &'a [u8],
+    off: &mut usize,
+    struct_off: usize,
+) -> Result<Option<ParsedTok<'a>>> {
+    let tok_off = *off;
+    match next_devtree_token_with_name_len(buf, off, MAX_LEGACY_NODE_PATH_LEN)? {
+        Some(ParsedTok::BeginNode(node)) => {
+            let name = match node.name.iter().rposition(|&b| b == b'/') {
+                Some(i) => &node.name[i + 1..],
+                None => node.name,
+            };
+            Ok(Some(ParsedTok::BeginNode(ParsedBeginNode { name })))
+        }
+        Some(ParsedTok::Prop(prop)) => {
+            let value_off = tok_off + size_of::<u32>() + size_of::<fdt_prop_header>();
+            let len = prop.prop_buf.len();
+            // Values were only realigned if they were not already 8 byte aligned.
+            if len < 8 || (value_off - struct_off) & 0x7 == 0 {
+                return Ok(Some(ParsedTok::Prop(prop)));
+            }
+            let value_off = value_off + size_of::<u32>();
+            let prop_buf = buf
+                .get(value_off..value_off + len)
+                .ok_or(DevTreeError::MalformedToken {
+                    offset: tok_off,
+                    token: Some(FdtTok::Prop),
+                })?;
+            *off = value_off + len;
+            *off += buf.as_ptr().add(*off).align_offset(size_of::<u32>());
+            Ok(Some(ParsedTok::Prop(ParsedProp {
+                name_offset: prop.name_offset,
+                prop_buf,
+            })))
+        }
+        tok => Ok(tok),
+    }
+}
+
+/// Tokenize the device tree's main structure block without any bounds or validity checks.
+///
+/// This is the equivalent of [`next_modern_devtree_token`] for device trees which have already been
+/// verified by [`DevTree::validate()`]. Returns `None` once the `FDT_END` token is reached.
+///
+/// # Safety
+///
+/// See the [module documentation](self#safety). The device tree must also have been verified by
+/// [`DevTree::validate()`], or the behavior is undefined.
+///
+/// With the `checked` feature enabled this performs the same checks as
+/// [`next_modern_devtree_token`] and panics if the requirements are violated.
+pub(crate) unsafe fn next_devtree_token_unchecked<'a>(
+    buf: &'a [u8],
+    off: &mut usize,
+) -> Option<ParsedTok<'a>> {
+    #[cfg(feature = "checked")]
+    return next_modern_devtree_token(buf, off).invariant_unwrap();
+    #[cfg(not(feature = "checked"))]
+    return next_devtree_token_fast(buf, off);
+}
+
+#[cfg(not(feature = "checked"))]
+#[inline]
+unsafe fn next_devtree_token_fast<'a>(buf: &'a [u8], off: &mut usize) -> Option<ParsedTok<'a>> {
+    debug_assert!(buf.len() >= (*off + size_of::<u32>()));
+
+    let read_u32 = |off: usize| u32::from_be(buf.as_ptr().add(off).cast::<u32>().read_unaligned());
+    let align = |off: usize| (off + size_of::<u32>() - 1) & !(size_of::<u32>() - 1);
+
+    let fdt_tok_val = read_u32(*off);
+    *off += size_of::<u32>();
+
+    match FromPrimitive::from_u32(fdt_tok_val) {
+        Some(FdtTok::BeginNode) => {
+            let mut len = 0;
+            while *buf.get_unchecked(*off + len) != 0 {
+                len += 1;
+            }
+            let name = buf.get_unchecked(*off..*off + len);
+            *off = align(*off + len + 1);
+            Some(ParsedTok::BeginNode(ParsedBeginNode { name }))
+        }
+        Some(FdtTok::Prop) => {
+            let prop_len = read_u32(*off) as usize;
+            let name_offset = read_u32(*off + size_of::<u32>()) as usize;
+            *off += size_of::<fdt_prop_header>();
+            let prop_buf = buf.get_unchecked(*off..*off + prop_len);
+            *off = align(*off + prop_len);
+            Some(ParsedTok::Prop(ParsedProp {
+                name_offset,
+                prop_buf,
+            }))
+        }
+        Some(FdtTok::EndNode) => Some(ParsedTok::EndNode),
+        Some(FdtTok::Nop) => Some(ParsedTok::Nop),
+        Some(FdtTok::End) => None,
+        None => {
+            debug_assert!(false, "invalid token within a validated device tree");
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ParsedBeginNode<'a> {
+    pub name: &'a [u8],
+}
+
+#[derive(Clone, Copy)]
+pub struct ParsedProp<'a> {
+    pub prop_buf: &'a [u8],
+    pub name_offset: usize,
+}
+
+/// Enumeration of all tokens within a device tree's structure block.
+#[derive(Clone, Copy)]
+pub enum ParsedTok<'a> {
+    BeginNode(ParsedBeginNode<'a>),
+    EndNode,
+    Prop(ParsedProp<'a>),
+    Nop,
+}
+
+/// An iterator over the tokens of a device tree's structure block.
+///
+/// Created by [`DevTree::parse_iter()`].
+#[derive(Clone, Copy)]
+pub struct DevTreeParseIter<'a, 'dt: 'a> {
+    // Crate private, as the tokenizer relies on it being the aligned offset of a token.
+    pub(crate) offset: usize,
+    pub(crate) fdt: &'a DevTree<'dt>,
+}
+
+impl<'a, 'dt: 'a> DevTreeParseIter<'a, 'dt> {
+    /// Returns an iterator over the tokens of `fdt`'s structure block.
+    pub fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self {
+            offset: fdt.off_dt_struct(),
+            fdt,
+        }
+    }
+
+    /// Returns an iterator over the tokens of `fdt`'s structure block, starting at the token at
+    /// `offset` (e.g. an offset returned by [`DevTreeParseIter::offset()`]).
+    ///
+    /// Returns [`DevTreeError::InvalidOffset`] if `offset` is not a u32 aligned offset within
+    /// the structure block. If it is aligned but does not begin a token, iteration returns an
+    /// error rather than tokens.
+    pub fn at_offset(fdt: &'a DevTree<'dt>, offset: usize) -> Result<Self> {
+        if offset < fdt.off_dt_struct()
+            || offset >= fdt.struct_end()
+            || fdt.buf().as_ptr().wrapping_add(offset).align_offset(size_of::<u32>()) != 0
+        {
+            return Err(DevTreeError::InvalidOffset);
+        }
+        Ok(Self { offset, fdt })
+    }
+
+    /// Returns the offset of the next token to be parsed.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the device tree being parsed.
+    #[inline]
+    #[must_use]
+    pub fn fdt(&self) -> &'a DevTree<'dt> {
+        self.fdt
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeParseIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = ParsedTok<'dt>;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        // Safe because we're passing an unmodified (by us) offset.
+        // next_devtree_token guaruntees alignment and out-of-bounds won't occur.
+        unsafe { self.fdt.next_token(&mut self.offset) }
+    }
+}
+
+/// An iterator over the tokens of a validated device tree which performs no bounds or validity
+/// checks.
+///
+/// Created by [`DevTree::raw_tokens_unchecked()`].
+#[derive(Clone)]
+pub struct DevTreeRawTokenIter<'dt> {
+    // Private, as the unchecked parser relies on it being an aligned offset produced by parsing.
+    offset: usize,
+    buf: &'dt [u8],
+    done: bool,
+    // The end of the structure block, which may lack an `FDT_END` token.
+    end: usize,
+    /// The structure block offset of a legacy device tree, which is parsed with checks.
+    #[cfg(feature = "legacy-fdt")]
+    legacy_struct_off: Option<usize>,
+}
+
+impl<'dt> DevTreeRawTokenIter<'dt> {
+    /// # Safety
+    ///
+    /// `fdt` must have been verified by [`DevTree::validate()`].
+    pub(crate) unsafe fn new(fdt: &DevTree<'dt>) -> Self {
+        Self {
+            offset: fdt.off_dt_struct(),
+            buf: fdt.buf(),
+            done: false,
+            end: fdt.struct_end(),
+            #[cfg(feature = "legacy-fdt")]
+            legacy_struct_off: if fdt.version() < FIRST_MODERN_VERSION {
+                Some(fdt.off_dt_struct())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Returns the offset of the next token from the start of the device tree.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'dt> Iterator for DevTreeRawTokenIter<'dt> {
+    type Item = ParsedTok<'dt>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Validation only succeeds without an `FDT_END` token if the end was permitted.
+        if self.done || self.offset >= self.end {
+            return None;
+        }
+        #[cfg(feature = "legacy-fdt")]
+        if let Some(struct_off) = self.legacy_struct_off {
+            // The tree was validated with the same parser, so this cannot fail.
+            let tok = unsafe {
+                next_legacy_devtree_token(self.buf, &mut self.offset, struct_off).invariant_unwrap()
+            };
+            self.done = tok.is_none();
+            return tok;
+        }
+        // Safe because our constructor requires a validated device tree and we only pass offsets
+        // produced by next_devtree_token_unchecked. We never read beyond the FDT_END token.
+        let tok = unsafe { next_devtree_token_unchecked(self.buf, &mut self.offset) };
+        self.done = tok.is_none();
+        tok
+    }
+}
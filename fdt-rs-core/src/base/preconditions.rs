@@ -0,0 +1,266 @@
+use core::fmt;
+use core::mem::size_of;
+
+use crate::base::DevTree;
+use crate::error::{DevTreeError, Result};
+use crate::priv_util::{InvariantUnwrap, SliceRead};
+use crate::spec::{fdt_reserve_entry, header_off, FDT_MAGIC};
+
+/// The outcome of a single check of a [`PreconditionReport`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// The precondition holds.
+    Passed,
+    /// The precondition does not hold, for the given reason.
+    Failed(DevTreeError),
+    /// The precondition could not be checked, as the buffer does not hold a device tree header.
+    Skipped,
+}
+
+impl Check {
+    fn from_bool(passed: bool, err: DevTreeError) -> Self {
+        if passed {
+            Check::Passed
+        } else {
+            Check::Failed(err)
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Check::Passed => write!(f, "ok"),
+            Check::Failed(err) => write!(f, "FAILED ({})", err),
+            Check::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// The outcome of each check of a buffer's device tree which [`DevTree::check_preconditions`]
+/// can make, e.g. for boot code to log before constructing a [`DevTree`].
+///
+/// A check which fails does not stop the remaining checks, except that nothing beyond the
+/// buffer's length and alignment is checked if it does not hold a device tree header with a valid
+/// magic number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreconditionReport {
+    /// The length of the buffer.
+    pub buf_len: usize,
+    /// The header's `totalsize`, if the header could be read.
+    pub totalsize: Option<usize>,
+    /// The header's `version` and `last_comp_version`, if the header could be read.
+    pub version: Option<(u32, u32)>,
+
+    /// The buffer is 32-bit aligned.
+    pub aligned: Check,
+    /// The buffer is large enough to hold a device tree header.
+    pub header: Check,
+    /// The header begins with the device tree magic number.
+    pub magic: Check,
+    /// The buffer is exactly `totalsize` bytes long.
+    pub length: Check,
+    /// The device tree's version may be read, and is compatible with the latest version of the
+    /// specification.
+    pub version_supported: Check,
+    /// The memory reservation block is 32-bit aligned, follows the header and is terminated
+    /// within the device tree.
+    pub mem_rsvmap: Check,
+    /// The structure block is 32-bit aligned, follows the header and lies within the device tree.
+    pub struct_block: Check,
+    /// The strings block follows the header and lies within the device tree.
+    pub strings_block: Check,
+    /// The memory reservation, structure and strings blocks appear in that order, as the
+    /// specification recommends.
+    pub blocks_ordered: Check,
+    /// The memory reservation, structure and strings blocks do not overlap.
+    pub blocks_disjoint: Check,
+}
+
+impl PreconditionReport {
+    /// Returns each check of the report along with its name.
+    #[must_use]
+    pub fn checks(&self) -> [(&'static str, Check); 10] {
+        [
+            ("aligned", self.aligned),
+            ("header", self.header),
+            ("magic", self.magic),
+            ("length", self.length),
+            ("version", self.version_supported),
+            ("mem_rsvmap", self.mem_rsvmap),
+            ("struct_block", self.struct_block),
+            ("strings_block", self.strings_block),
+            ("blocks_ordered", self.blocks_ordered),
+            ("blocks_disjoint", self.blocks_disjoint),
+        ]
+    }
+
+    /// Returns true if no check failed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.result().is_ok()
+    }
+
+    /// Returns the error of the first check which failed, in the order of [`Self::checks`].
+    pub fn result(&self) -> Result<()> {
+        for (_, check) in self.checks().iter() {
+            if let Check::Failed(err) = *check {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PreconditionReport {
+    /// Writes one line per check, e.g. `length: ok`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, check) in self.checks().iter() {
+            writeln!(f, "{}: {}", name, check)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if the non-empty ranges `a` and `b` overlap.
+fn overlaps(a: (usize, usize), b: (usize, usize)) -> bool {
+    a.0 < a.1 && b.0 < b.1 && a.0 < b.1 && b.0 < a.1
+}
+
+impl<'dt> DevTree<'dt> {
+    /// Check every precondition of [`DevTree::new()`], and of the specification's layout, which
+    /// may be verified from `buf`, reporting the outcome of each rather than only the first
+    /// failure.
+    ///
+    /// Unlike [`DevTree::new()`] this is safe, as the header is read without assuming `buf` is
+    /// aligned. The structure block's tokens are not parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::FDT;
+    /// use fdt_rs::base::{Check, DevTree};
+    ///
+    /// let report = DevTree::check_preconditions(FDT);
+    /// assert!(report.passed());
+    /// assert_eq!(report.totalsize, Some(FDT.len()));
+    ///
+    /// // A truncated device tree is reported, though its header is still checked.
+    /// let report = DevTree::check_preconditions(&FDT[..FDT.len() / 2]);
+    /// assert!(matches!(report.length, Check::Failed(_)));
+    /// assert_eq!(report.magic, Check::Passed);
+    /// assert!(report.to_string().contains("length: FAILED"));
+    /// ```
+    #[must_use]
+    pub fn check_preconditions(buf: &[u8]) -> PreconditionReport {
+        let mut report = PreconditionReport {
+            buf_len: buf.len(),
+            totalsize: None,
+            version: None,
+            aligned: Check::from_bool(
+                buf.as_ptr() as usize & (size_of::<u32>() - 1) == 0,
+                DevTreeError::InvalidParameter("Unaligned buffer provided"),
+            ),
+            header: Check::from_bool(
+                buf.len() >= Self::MIN_HEADER_SIZE,
+                DevTreeError::InvalidParameter("Buffer is too small to hold a device tree header"),
+            ),
+            magic: Check::Skipped,
+            length: Check::Skipped,
+            version_supported: Check::Skipped,
+            mem_rsvmap: Check::Skipped,
+            struct_block: Check::Skipped,
+            strings_block: Check::Skipped,
+            blocks_ordered: Check::Skipped,
+            blocks_disjoint: Check::Skipped,
+        };
+        if report.header != Check::Passed {
+            return report;
+        }
+
+        // Unsafe OK. The buffer holds a header, and fields are read without assuming alignment.
+        let field = |offset: usize| unsafe { buf.read_be_u32(offset).invariant_unwrap() };
+        report.magic = Check::from_bool(
+            field(header_off::MAGIC) == FDT_MAGIC,
+            DevTreeError::InvalidMagicNumber,
+        );
+        if report.magic != Check::Passed {
+            return report;
+        }
+
+        let totalsize = field(header_off::TOTALSIZE) as usize;
+        let version = field(header_off::VERSION);
+        let last_comp_version = field(header_off::LAST_COMP_VERSION);
+        report.totalsize = Some(totalsize);
+        report.version = Some((version, last_comp_version));
+
+        report.length = Check::from_bool(
+            buf.len() == totalsize,
+            DevTreeError::InvalidParameter("Buffer length does not match the totalsize"),
+        );
+        report.version_supported = Check::from_bool(
+            version >= DevTree::MIN_VERSION && last_comp_version <= DevTree::LATEST_VERSION,
+            DevTreeError::VersionNotSupported(version),
+        );
+
+        let header_end = Self::MIN_HEADER_SIZE;
+        let aligned = |off: usize| off & (size_of::<u32>() - 1) == 0;
+        // Each block is checked against the device tree's totalsize, and the buffer's length is
+        // checked separately, so that a truncated buffer does not hide problems of the header.
+
+        let rsv_start = field(header_off::OFF_MEM_RSVMAP) as usize;
+        let mut rsv_end = None;
+        let entry_len = size_of::<fdt_reserve_entry>();
+        let mut off = rsv_start;
+        while off.saturating_add(entry_len) <= core::cmp::min(totalsize, buf.len()) {
+            off += entry_len;
+            if buf[off - entry_len..off].iter().all(|&b| b == 0) {
+                rsv_end = Some(off);
+                break;
+            }
+        }
+        report.mem_rsvmap = Check::from_bool(
+            aligned(rsv_start) && rsv_start >= header_end && rsv_end.is_some(),
+            DevTreeError::InvalidParameter("Memory reservation block is invalid"),
+        );
+
+        let struct_start = field(header_off::OFF_DT_STRUCT) as usize;
+        // The size of the structure block is only recorded from version 17.
+        let struct_end = if version >= DevTree::LATEST_VERSION {
+            struct_start.checked_add(field(header_off::SIZE_DT_STRUCT) as usize)
+        } else {
+            struct_start.checked_add(size_of::<u32>())
+        };
+        report.struct_block = Check::from_bool(
+            aligned(struct_start)
+                && struct_start >= header_end
+                && matches!(struct_end, Some(end) if end <= totalsize),
+            DevTreeError::ParseError,
+        );
+
+        let strings_start = field(header_off::OFF_DT_STRINGS) as usize;
+        // Device trees older than version 3 do not record the size of the strings block.
+        let strings_end = if version >= 3 {
+            strings_start.checked_add(field(header_off::SIZE_DT_STRINGS) as usize)
+        } else {
+            Some(totalsize)
+        };
+        report.strings_block = Check::from_bool(
+            strings_start >= header_end && matches!(strings_end, Some(end) if end <= totalsize),
+            DevTreeError::InvalidStringsBlock,
+        );
+
+        report.blocks_ordered = Check::from_bool(
+            rsv_start <= struct_start && struct_start <= strings_start,
+            DevTreeError::InvalidParameter("Device tree blocks are out of order"),
+        );
+        let rsv = (rsv_start, rsv_end.unwrap_or(rsv_start));
+        let structure = (struct_start, struct_end.unwrap_or(usize::MAX));
+        let strings = (strings_start, strings_end.unwrap_or(usize::MAX));
+        report.blocks_disjoint = Check::from_bool(
+            !overlaps(rsv, structure) && !overlaps(rsv, strings) && !overlaps(structure, strings),
+            DevTreeError::InvalidParameter("Device tree blocks overlap"),
+        );
+        report
+    }
+}
@@ -0,0 +1,131 @@
+use crate::base::iters::DevTreeIter;
+use crate::base::{DevTree, DevTreeNode};
+use crate::error::DevTreeError;
+use crate::spec::Phandle;
+use crate::prelude::*;
+
+use crate::priv_util::InvariantUnwrap;
+
+/// A handle to a [`DevTreeNode`]'s Device Tree Property
+///
+/// The most common [`PropReader`] methods are also provided inherently, so the trait need only
+/// be imported for the remaining methods or by generic code.
+#[derive(Clone)]
+pub struct DevTreeProp<'a, 'dt: 'a> {
+    parent_iter: DevTreeIter<'a, 'dt>,
+    propbuf: &'dt [u8],
+    nameoff: usize,
+}
+
+impl<'r, 'dt: 'r> PropReader<'dt> for DevTreeProp<'r, 'dt> {
+    type NodeType = DevTreeNode<'r, 'dt>;
+
+    #[inline]
+    fn propbuf(&self) -> &'dt [u8] {
+        self.propbuf
+    }
+
+    #[inline]
+    fn nameoff(&self) -> usize {
+        self.nameoff
+    }
+
+    #[inline]
+    fn fdt(&self) -> &DevTree<'dt> {
+        self.parent_iter.fdt
+    }
+
+    /// Returns the node which this property is attached to
+    #[must_use]
+    fn node(&self) -> DevTreeNode<'r, 'dt> {
+        unsafe {
+            // Unsafe unwrap okay.
+            // We're look back in the tree - our parent node is behind us.
+            self.parent_iter
+                .clone()
+                .next_node()
+                .invariant_unwrap()
+                .invariant_unwrap()
+        }
+    }
+}
+
+impl<'a, 'dt: 'a> DevTreeProp<'a, 'dt> {
+    /// Returns the [`DevTree`] this property belongs to.
+    #[inline]
+    #[must_use]
+    pub fn tree(&self) -> &'a DevTree<'dt> {
+        self.parent_iter.fdt
+    }
+
+    /// Returns the name of the property. See [`PropReader::name`].
+    #[inline]
+    pub fn name(&self) -> Result<&'dt str, DevTreeError> {
+        PropReader::name(self)
+    }
+
+    /// Returns the length of the property value. See [`PropReader::length`].
+    #[inline]
+    #[must_use]
+    pub fn length(&self) -> usize {
+        PropReader::length(self)
+    }
+
+    /// Returns the node which this property is contained within. See [`PropReader::node`].
+    #[inline]
+    #[must_use]
+    pub fn node(&self) -> DevTreeNode<'a, 'dt> {
+        PropReader::node(self)
+    }
+
+    /// Reads a big-endian [`u32`] at `offset` within the value. See [`PropReader::u32`].
+    #[inline]
+    pub fn u32(&self, offset: usize) -> Result<u32, DevTreeError> {
+        PropReader::u32(self, offset)
+    }
+
+    /// Reads a big-endian [`u64`] at `offset` within the value. See [`PropReader::u64`].
+    #[inline]
+    pub fn u64(&self, offset: usize) -> Result<u64, DevTreeError> {
+        PropReader::u64(self, offset)
+    }
+
+    /// Reads the phandle at `offset` within the value. See [`PropReader::phandle`].
+    #[inline]
+    pub fn phandle(&self, offset: usize) -> Result<Phandle, DevTreeError> {
+        PropReader::phandle(self, offset)
+    }
+
+    /// Returns the value as a string. See [`PropReader::str`].
+    #[inline]
+    pub fn str(&self) -> Result<&'dt str, DevTreeError> {
+        PropReader::str(self)
+    }
+
+    /// Returns the raw value. See [`PropReader::raw`].
+    #[inline]
+    #[must_use]
+    pub fn raw(&self) -> &'dt [u8] {
+        PropReader::raw(self)
+    }
+
+    /// Returns true if the value's string list contains `s`. See
+    /// [`PropReader::strlist_contains`].
+    #[inline]
+    #[must_use]
+    pub fn strlist_contains(&self, s: &str) -> bool {
+        PropReader::strlist_contains(self, s)
+    }
+
+    pub(super) fn new(
+        parent_iter: DevTreeIter<'a, 'dt>,
+        propbuf: &'dt [u8],
+        nameoff: usize,
+    ) -> Self {
+        Self {
+            parent_iter,
+            propbuf,
+            nameoff,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Reading device trees from storage which is not directly addressable.
+//!
+//! [`DevTree`] is generic over the [`ReadableBuf`] holding the device tree. A [`DevTree`] over a
+//! byte slice (the default) borrows the bytes it parses, handing out names and property values
+//! which refer into the slice. A [`DevTree`] over other storage (e.g. SPI flash reached through
+//! a bounce buffer) reads its header through [`ReadableBuf::read_at()`], and may be copied into
+//! memory for parsing with [`DevTree::relocate_to()`] or [`DevTree::load_from()`].
+//!
+//! # Limitations
+//!
+//! Only the header of a device tree in other storage may be read in place. Its nodes,
+//! properties and memory reservations are only reachable once it has been copied into memory,
+//! as their accessors return names and values which borrow the buffer holding the device tree.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::FDT;
+//! use fdt_rs::base::DevTree;
+//!
+//! let mut storage = vec![0u32; FDT.len() / 4];
+//! let dst = unsafe {
+//!     core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len())
+//! };
+//!
+//! // A slice is the simplest ReadableBuf.
+//! let src: &[u8] = FDT;
+//! let devtree = DevTree::load_from(&src, dst).unwrap();
+//! assert_eq!(devtree.totalsize(), FDT.len());
+//! ```
+use crate::error::{DevTreeError, Result};
+
+#[cfg(doc)]
+use super::DevTree;
+
+/// Random read access to the bytes of a device tree.
+///
+/// Reads take `&self`, so storage which must be driven mutably (e.g. through a bounce buffer)
+/// should use interior mutability.
+pub trait ReadableBuf {
+    /// Returns the number of bytes which may be read.
+    fn len(&self) -> usize;
+
+    /// Returns true if there are no bytes to read.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fill `dst` with the bytes starting at `offset`.
+    ///
+    /// Implementations must return an error if any part of the range lies beyond
+    /// [`ReadableBuf::len()`].
+    ///
+    /// A [`DevTree`] reads its header once, when it is constructed.
+    fn read_at(&self, offset: usize, dst: &mut [u8]) -> Result<()>;
+
+    /// Returns the bytes of this buffer if they are directly addressable.
+    ///
+    /// [`DevTree::from_readable()`] verifies the alignment of addressable buffers, which may be
+    /// parsed in place.
+    #[inline]
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl ReadableBuf for [u8] {
+    #[inline]
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    #[inline]
+    fn read_at(&self, offset: usize, dst: &mut [u8]) -> Result<()> {
+        let end = offset
+            .checked_add(dst.len())
+            .ok_or(DevTreeError::InvalidOffset)?;
+        let src = self.get(offset..end).ok_or(DevTreeError::InvalidOffset)?;
+        dst.copy_from_slice(src);
+        Ok(())
+    }
+
+    #[inline]
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl<R: ReadableBuf + ?Sized> ReadableBuf for &R {
+    #[inline]
+    fn len(&self) -> usize {
+        R::len(self)
+    }
+
+    #[inline]
+    fn read_at(&self, offset: usize, dst: &mut [u8]) -> Result<()> {
+        R::read_at(self, offset, dst)
+    }
+
+    #[inline]
+    fn as_slice(&self) -> Option<&[u8]> {
+        R::as_slice(self)
+    }
+}
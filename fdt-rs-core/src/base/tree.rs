@@ -0,0 +1,993 @@
+#[cfg(doc)]
+use crate::base::*;
+
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::str::from_utf8;
+
+use crate::bounded::Collected;
+use crate::canonical::PropOrder;
+use crate::common::path::{component_matches, split_alias};
+use crate::error::{DevTreeError, Result};
+use crate::lint::DevTreeLintIter;
+use crate::matcher::Matcher;
+use crate::traits::{IterableDevTree, PropReader};
+
+use crate::priv_util::SliceRead;
+use crate::spec::{fdt_header, header_off, FdtTok, MissingEndPolicy, FDT_MAGIC};
+
+use fallible_iterator::FallibleIterator;
+
+use super::iters::{
+    DevTreeCanonicalIter, DevTreeCompatibleNodeIter, DevTreeIter, DevTreeNodeIter,
+    DevTreePropIter, DevTreePathMatchIter, DevTreeReserveEntryIter, DevTreeParseIter,
+    DevTreeTryIter,
+};
+#[cfg(feature = "legacy-fdt")]
+use super::parse::{next_legacy_devtree_token, FIRST_MODERN_VERSION};
+use super::parse::{next_modern_devtree_token, DevTreeRawTokenIter, ParsedTok};
+use super::storage::ReadableBuf;
+use super::{DevTreeItem, DevTreeNode};
+
+const fn is_aligned<T>(offset: usize) -> bool {
+    offset % size_of::<T>() == 0
+}
+
+const fn verify_offset_aligned<T>(offset: usize) -> Result<usize> {
+    let i: [Result<usize>; 2] = [Err(DevTreeError::ParseError), Ok(offset)];
+    i[is_aligned::<T>(offset) as usize]
+}
+
+// The number of fields of the header, each of which is a u32.
+const HEADER_FIELDS: usize = size_of::<fdt_header>() / size_of::<u32>();
+
+/// Read the fields of the header at the start of `buf`.
+fn read_header<B: ReadableBuf>(buf: &B) -> Result<[u32; HEADER_FIELDS]> {
+    let mut bytes = [0; size_of::<fdt_header>()];
+    buf.read_at(0, &mut bytes)?;
+    let mut header = [0; HEADER_FIELDS];
+    for (field, be) in header.iter_mut().zip(bytes.chunks_exact(size_of::<u32>())) {
+        let mut word = [0; size_of::<u32>()];
+        word.copy_from_slice(be);
+        *field = u32::from_be_bytes(word);
+    }
+    Ok(header)
+}
+
+macro_rules! get_be32_field {
+    ( $f:ident, $s:ident , $buf:expr ) => {
+        $buf.read_be_u32(offset_of!($s, $f))
+    };
+}
+
+/// A parseable Flattened Device Tree.
+///
+/// This parser was written according to the v0.3 specification provided at
+/// https://www.devicetree.org/
+///
+/// The device tree is held in a [`ReadableBuf`], by default a byte slice which is parsed in
+/// place. Device trees held in other storage provide their header fields, and may be copied into
+/// memory with [`DevTree::relocate_to()`] to be parsed. See the [`storage`](super::storage)
+/// module.
+#[derive(Copy, Clone, Debug)]
+pub struct DevTree<'dt, B = &'dt [u8]> {
+    buf: B,
+    // The header's fields, read when the device tree was constructed.
+    header: [u32; HEADER_FIELDS],
+    // The offset of the end of the structure block.
+    struct_end: usize,
+    missing_end: MissingEndPolicy,
+    _dt: PhantomData<&'dt [u8]>,
+}
+
+impl<'dt> DevTree<'dt> {
+    pub const MIN_HEADER_SIZE: usize = size_of::<fdt_header>();
+    /// The latest version of the format described by the specification.
+    pub const LATEST_VERSION: u32 = 17;
+    /// The earliest version of the format which may be read. Older layouts require the
+    /// `legacy-fdt` feature.
+    #[cfg(feature = "legacy-fdt")]
+    pub const MIN_VERSION: u32 = 1;
+    /// The earliest version of the format which may be read. Older layouts require the
+    /// `legacy-fdt` feature.
+    #[cfg(not(feature = "legacy-fdt"))]
+    pub const MIN_VERSION: u32 = 16;
+
+    /// Verify the magic header of a Device Tree buffer
+    ///
+    /// # Safety
+    ///
+    /// Callers of this method the must guarantee the following:
+    /// - The passed buffer is 32-bit aligned.
+    ///
+    /// The passed byte buffer will be interpreted as a Flattened Device Tree. For this reason this API
+    /// is marked unsafe.
+    #[inline]
+    pub unsafe fn verify_magic(buf: &[u8]) -> Result<()> {
+        if get_be32_field!(magic, fdt_header, buf)? != FDT_MAGIC {
+            Err(DevTreeError::InvalidMagicNumber)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Using the provided byte slice this method will:
+    ///
+    /// 1. Verify that the slice begins with the magic Device Tree header
+    /// 2. Return the reported `totalsize` field of the Device Tree header
+    ///
+    /// When parsing a FDT, it's possible that the actual size of the device tree may be unknown.
+    /// For that reason, this method can be called before constructing the [`DevTree`]. For this
+    /// read to take place, the provided buffer must be at least [`Self::MIN_HEADER_SIZE`] long.
+    ///
+    /// Once known, the user should resize the raw byte slice to this function's return value and
+    /// pass that slice to [`DevTree::new()`].
+    ///
+    /// # Example
+    ///
+    /// TODO
+    ///
+    /// # Safety
+    ///
+    /// Callers of this method the must guarantee the following:
+    /// - The passed buffer is 32-bit aligned.
+    /// - The passed buffer is of at least [`DevTree::MIN_HEADER_SIZE`] bytes in length
+    ///
+    /// The passed byte buffer will be interpreted as a Flattened Device Tree. For this reason this API
+    /// is marked unsafe.
+    #[inline]
+    pub unsafe fn read_totalsize(buf: &[u8]) -> Result<usize> {
+        // Verify provided buffer alignment
+        verify_offset_aligned::<u32>(buf.as_ptr() as usize)
+            .map_err(|_| DevTreeError::InvalidParameter("Unaligned buffer provided"))?;
+
+        // Verify provided buffer magic
+        Self::verify_magic(buf)?;
+        Ok(get_be32_field!(totalsize, fdt_header, buf)? as usize)
+    }
+
+    /// Construct the parseable DevTree object from the provided byte slice.
+    ///
+    /// # Safety
+    ///
+    /// Callers of this method the must guarantee the following:
+    ///
+    /// - The passed buffer is 32-bit aligned.
+    /// - The passed buffer is exactly the length returned by [`Self::read_totalsize()`]
+    ///
+    /// A [`DevTreeError::VersionNotSupported`] error is returned if the device tree's version may
+    /// not be read, see [`Self::check_version()`].
+    #[inline]
+    pub unsafe fn new(buf: &'dt [u8]) -> Result<Self> {
+        if buf.len() < Self::MIN_HEADER_SIZE || Self::read_totalsize(buf)? < buf.len() {
+            Err(DevTreeError::ParseError)
+        } else {
+            let header = read_header(&buf)?;
+            Self::from_parts(buf, header)
+        }
+    }
+
+    /// Copy a device tree out of `src` into `dst` and return a [`DevTree`] over the copy.
+    ///
+    /// This is [`DevTree::from_readable()`] followed by [`DevTree::relocate_to()`], so the
+    /// header is read and validated before the device tree is copied.
+    ///
+    /// `dst` must be 32-bit aligned and at least `totalsize` bytes long. Only the first
+    /// `totalsize` bytes of `dst` are used.
+    pub fn load_from<'d, R: ReadableBuf + ?Sized>(
+        src: &R,
+        dst: &'d mut [u8],
+    ) -> Result<DevTree<'d>> {
+        DevTree::from_readable(src)?.relocate_to(dst)
+    }
+}
+
+impl<'dt, B: ReadableBuf> DevTree<'dt, B> {
+    /// Construct a [`DevTree`] over the device tree held in `buf`, which need not be
+    /// addressable.
+    ///
+    /// The header is read and validated as performed by [`DevTree::new()`]. `buf` must hold at
+    /// least `totalsize` bytes, and must be 32-bit aligned if it is a byte slice.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::FDT;
+    /// use fdt_rs::base::DevTree;
+    ///
+    /// let devtree = DevTree::from_readable(FDT).unwrap();
+    /// assert_eq!(devtree.totalsize(), FDT.len());
+    /// assert_eq!(devtree.check_version(), Ok(17));
+    /// ```
+    pub fn from_readable(buf: B) -> Result<Self> {
+        if let Some(bytes) = buf.as_slice() {
+            verify_offset_aligned::<u32>(bytes.as_ptr() as usize)
+                .map_err(|_| DevTreeError::InvalidParameter("Unaligned buffer provided"))?;
+        }
+        if buf.len() < size_of::<fdt_header>() {
+            return Err(DevTreeError::ParseError);
+        }
+        let header = read_header(&buf)?;
+        if header[header_off::MAGIC / size_of::<u32>()] != FDT_MAGIC {
+            return Err(DevTreeError::InvalidMagicNumber);
+        }
+        if header[header_off::TOTALSIZE / size_of::<u32>()] as usize > buf.len() {
+            return Err(DevTreeError::ParseError);
+        }
+        Self::from_parts(buf, header)
+    }
+
+    /// Validate the header of a device tree whose magic number and `totalsize` have been
+    /// verified.
+    fn from_parts(buf: B, header: [u32; HEADER_FIELDS]) -> Result<Self> {
+        let mut ret = Self {
+            buf,
+            header,
+            struct_end: 0,
+            missing_end: MissingEndPolicy::Strict,
+            _dt: PhantomData,
+        };
+        ret.check_version()?;
+        ret.struct_end = ret.off_dt_struct().saturating_add(ret.size_dt_struct());
+        // Verify required alignment before returning.
+        verify_offset_aligned::<u32>(ret.off_mem_rsvmap())?;
+        verify_offset_aligned::<u32>(ret.off_dt_struct())?;
+        ret.verify_strings_block()?;
+        Ok(ret)
+    }
+
+    /// Returns the header field at `offset`, one of the [`header_off`] offsets.
+    fn header_field(&self, offset: usize) -> u32 {
+        self.header[offset / size_of::<u32>()]
+    }
+
+    /// Returns the totalsize field of the Device Tree. This is the number of bytes of the device
+    /// tree structure.
+    #[inline]
+    #[must_use]
+    pub fn totalsize(&self) -> usize {
+        self.header_field(header_off::TOTALSIZE) as usize
+    }
+
+    /// Returns the of rsvmap offset field of the Device Tree
+    #[inline]
+    #[must_use]
+    pub fn off_mem_rsvmap(&self) -> usize {
+        self.header_field(header_off::OFF_MEM_RSVMAP) as usize
+    }
+
+    /// Returns the of dt_struct offset field of the Device Tree
+    #[inline]
+    #[must_use]
+    pub fn off_dt_struct(&self) -> usize {
+        self.header_field(header_off::OFF_DT_STRUCT) as usize
+    }
+
+    /// Returns the of dt_strings offset field of the Device Tree
+    #[inline]
+    #[must_use]
+    pub fn off_dt_strings(&self) -> usize {
+        self.header_field(header_off::OFF_DT_STRINGS) as usize
+    }
+
+    /// Returns the size_dt_strings field of the Device Tree
+    ///
+    /// With the `legacy-fdt` feature enabled, device trees older than version 3 (which do not
+    /// have this field) report the size of the remainder of the device tree.
+    #[inline]
+    #[must_use]
+    pub fn size_dt_strings(&self) -> usize {
+        #[cfg(feature = "legacy-fdt")]
+        if self.version() < 3 {
+            return self.totalsize().saturating_sub(self.off_dt_strings());
+        }
+        self.header_field(header_off::SIZE_DT_STRINGS) as usize
+    }
+
+    /// Returns the size_dt_struct field of the Device Tree.
+    ///
+    /// This field is only present from version 17 of the format. Earlier device trees report the
+    /// space between the start of the structure block and the start of the strings block, or
+    /// the end of the device tree if the strings block precedes the structure block.
+    ///
+    /// The size reported never extends the structure block beyond `totalsize`.
+    #[inline]
+    #[must_use]
+    pub fn size_dt_struct(&self) -> usize {
+        let start = self.off_dt_struct();
+        let limit = self.totalsize().saturating_sub(start);
+        if self.version() < <DevTree>::LATEST_VERSION {
+            let strings = self.off_dt_strings();
+            return if strings > start {
+                (strings - start).min(limit)
+            } else {
+                limit
+            };
+        }
+        let size = self.header_field(header_off::SIZE_DT_STRUCT);
+        (size as usize).min(limit)
+    }
+
+    /// Returns this device tree with the given handling of a structure block which is missing
+    /// its `FDT_END` token. [`MissingEndPolicy::Strict`] is used by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::FDT;
+    /// use fdt_rs::base::DevTree;
+    /// use fdt_rs::spec::MissingEndPolicy;
+    ///
+    /// let devtree = unsafe { DevTree::new(FDT) }
+    ///     .unwrap()
+    ///     .with_missing_end_policy(MissingEndPolicy::Permissive);
+    /// assert_eq!(devtree.missing_end_policy(), MissingEndPolicy::Permissive);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_missing_end_policy(mut self, policy: MissingEndPolicy) -> Self {
+        self.missing_end = policy;
+        self
+    }
+
+    /// Returns the handling of a structure block which is missing its `FDT_END` token.
+    #[inline]
+    #[must_use]
+    pub fn missing_end_policy(&self) -> MissingEndPolicy {
+        self.missing_end
+    }
+
+    /// Returns the offset of the end of the structure block, see [`Self::size_dt_struct()`].
+    #[inline]
+    pub(crate) fn struct_end(&self) -> usize {
+        self.struct_end
+    }
+
+    /// Returns the version field of the Device Tree.
+    #[inline]
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.header_field(header_off::VERSION)
+    }
+
+    /// Returns the last_comp_version field of the Device Tree. This is the lowest version of the
+    /// format with which the device tree is backwards compatible.
+    #[inline]
+    #[must_use]
+    pub fn last_comp_version(&self) -> u32 {
+        self.header_field(header_off::LAST_COMP_VERSION)
+    }
+
+    /// Returns the version field of the Device Tree if this crate can read it.
+    ///
+    /// A [`DevTreeError::VersionNotSupported`] error holding the version is returned if the
+    /// version is older than [`Self::MIN_VERSION`], or if the device tree is not backwards
+    /// compatible with [`Self::LATEST_VERSION`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::FDT;
+    /// use fdt_rs::base::DevTree;
+    ///
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// assert_eq!(devtree.check_version(), Ok(17));
+    /// ```
+    #[inline]
+    pub fn check_version(&self) -> Result<u32> {
+        let version = self.version();
+        if version < <DevTree>::MIN_VERSION
+            || self.last_comp_version() > <DevTree>::LATEST_VERSION
+        {
+            return Err(DevTreeError::VersionNotSupported(version));
+        }
+        Ok(version)
+    }
+
+    /// Returns the boot_cpuid_phys field of the Device Tree: the physical ID of the boot CPU,
+    /// as given by the `reg` property of its CPU node.
+    ///
+    /// This field is only present from version 2 of the format.
+    #[inline]
+    #[must_use]
+    pub fn boot_cpuid_phys(&self) -> u32 {
+        self.header_field(header_off::BOOT_CPUID_PHYS)
+    }
+
+    /// Verify that the strings block lies within both the reported `totalsize` and the buffer.
+    fn verify_strings_block(&self) -> Result<()> {
+        let limit = core::cmp::min(self.totalsize(), self.buf.len());
+        match self.off_dt_strings().checked_add(self.size_dt_strings()) {
+            Some(end) if self.off_dt_strings() <= limit && end <= limit => Ok(()),
+            _ => Err(DevTreeError::InvalidStringsBlock),
+        }
+    }
+
+    /// Copy the device tree into `dst` and return a [`DevTree`] over the copy.
+    ///
+    /// This is useful to move a device tree out of a memory region which is about to be
+    /// reclaimed. The copy's header, block offsets, and sizes are re-validated (as performed by
+    /// [`DevTree::new()`]) before it is returned.
+    ///
+    /// Device trees held in storage other than a byte slice are read in a single
+    /// [`ReadableBuf::read_at()`] call.
+    ///
+    /// `dst` must be 32-bit aligned and at least [`DevTree::totalsize()`] bytes long. Only the
+    /// first `totalsize` bytes of `dst` are used.
+    pub fn relocate_to<'d>(&self, dst: &'d mut [u8]) -> Result<DevTree<'d>> {
+        let size = self.totalsize();
+        if size > self.buf.len() {
+            return Err(DevTreeError::ParseError);
+        }
+        verify_offset_aligned::<u32>(dst.as_ptr() as usize)
+            .map_err(|_| DevTreeError::InvalidParameter("Unaligned buffer provided"))?;
+        let dst = dst.get_mut(..size).ok_or(DevTreeError::NotEnoughMemory)?;
+        self.buf.read_at(0, dst)?;
+
+        let dst: &'d [u8] = dst;
+        // Safe because we verified alignment and sized the buffer to exactly totalsize bytes.
+        unsafe { DevTree::new(dst) }
+    }
+}
+
+impl<'dt> DevTree<'dt> {
+    /// Parse the token at `off` within the structure block, advancing `off` to the next token.
+    ///
+    /// With the `legacy-fdt` feature enabled, device trees older than version 16 are parsed with
+    /// [`next_legacy_devtree_token`].
+    ///
+    /// Reaching the end of the structure block is handled according to the device tree's
+    /// [`MissingEndPolicy`].
+    ///
+    /// # Safety
+    ///
+    /// See [`next_modern_devtree_token`].
+    #[inline]
+    pub(crate) unsafe fn next_token(&self, off: &mut usize) -> Result<Option<ParsedTok<'dt>>> {
+        if *off >= self.struct_end {
+            return match self.missing_end {
+                MissingEndPolicy::Permissive => Ok(None),
+                MissingEndPolicy::Strict => Err(DevTreeError::MissingEndToken),
+            };
+        }
+        #[cfg(feature = "legacy-fdt")]
+        if self.version() < FIRST_MODERN_VERSION {
+            return next_legacy_devtree_token(self.buf, off, self.off_dt_struct());
+        }
+        next_modern_devtree_token(self.buf, off)
+    }
+
+    /// Returns the strings block of the Device Tree.
+    ///
+    /// The block's bounds are verified by [`DevTree::new()`]. The returned slice will be empty if
+    /// the device tree does not contain any strings.
+    #[inline]
+    #[must_use]
+    pub fn strings_block(&self) -> &'dt [u8] {
+        let start = self.off_dt_strings();
+        &self.buf[start..start + self.size_dt_strings()]
+    }
+
+    /// Returns the string found at `offset` within the strings block of the Device Tree.
+    ///
+    /// `offset` need not be the start of a string. Tools which share the storage of names that
+    /// are suffixes of other names (as `dtc` does) refer to the middle of the longer string, so
+    /// the string read is the remainder of that string from `offset`. Names must therefore be
+    /// compared by their contents rather than their offsets.
+    ///
+    /// An [`Err`] containing [`DevTreeError::EmptyStringsBlock`] is returned if the device tree has
+    /// no strings and [`DevTreeError::InvalidOffset`] if the offset lies outside of the block.
+    pub fn string_at(&self, offset: usize) -> Result<&'dt str> {
+        let strings = self.strings_block();
+        if strings.is_empty() {
+            return Err(DevTreeError::EmptyStringsBlock);
+        }
+        if offset >= strings.len() {
+            return Err(DevTreeError::InvalidOffset);
+        }
+        unsafe { Ok(from_utf8(strings.read_bstring0(offset)?)?) }
+    }
+
+    /// Returns a typed `*const T` to the given offset in the Device Tree buffer.
+    ///
+    /// # Safety
+    ///
+    /// Due to the unsafe nature of re-interpretation casts this method is unsafe.  This method
+    /// will verify that enough space to fit type T remains within the buffer.
+    ///
+    /// The caller must verify that the pointer is not misaligned before it is dereferenced.
+    pub(crate) unsafe fn ptr_at<T>(&self, offset: usize) -> Result<*const T> {
+        if offset + size_of::<T>() > self.buf.len() {
+            Err(DevTreeError::InvalidOffset)
+        } else {
+            Ok(self.buf.as_ptr().add(offset) as *const T)
+        }
+    }
+
+    /// Returns an iterator over the Dev Tree "5.3 Memory Reservation Blocks"
+    #[must_use]
+    pub fn reserved_entries(&self) -> DevTreeReserveEntryIter {
+        DevTreeReserveEntryIter::new(self)
+    }
+
+    /// Returns an iterator over [`DevTreeNode`] objects
+    pub fn nodes(&self) -> DevTreeNodeIter<'_, 'dt> {
+        DevTreeNodeIter(DevTreeIter::new(self))
+    }
+
+    #[must_use]
+    pub fn props(&self) -> DevTreePropIter<'_, 'dt> {
+        DevTreePropIter(DevTreeIter::new(self))
+    }
+
+    /// Returns an iterator over objects within the [`DevTreeItem`] enum
+    pub fn items(&self) -> DevTreeIter<'_, 'dt> {
+        DevTreeIter::new(self)
+    }
+
+    /// Returns an [`Iterator`] over [`DevTreeItem`] objects which yields a [`Result`] per element.
+    ///
+    /// A parse error is returned as the final element of the iteration.
+    pub fn try_items(&self) -> DevTreeTryIter<DevTreeIter<'_, 'dt>> {
+        DevTreeTryIter::new(self.items())
+    }
+
+    /// Returns an [`Iterator`] over [`DevTreeNode`] objects which yields a [`Result`] per element.
+    ///
+    /// A parse error is returned as the final element of the iteration.
+    pub fn try_nodes(&self) -> DevTreeTryIter<DevTreeNodeIter<'_, 'dt>> {
+        DevTreeTryIter::new(self.nodes())
+    }
+
+    /// Returns an [`Iterator`] over [`DevTreeProp`] objects which yields a [`Result`] per element.
+    ///
+    /// A parse error is returned as the final element of the iteration.
+    pub fn try_props(&self) -> DevTreeTryIter<DevTreePropIter<'_, 'dt>> {
+        DevTreeTryIter::new(self.props())
+    }
+
+    /// Returns an iterator over low level parsing tokens, [`ParsedTok`].
+    #[must_use]
+    pub fn parse_iter(&self) -> DevTreeParseIter<'_, 'dt> {
+        DevTreeParseIter::new(self)
+    }
+
+    /// Verify the entire structure block of the device tree.
+    ///
+    /// Every token is parsed with full bounds checking, nodes must be correctly nested within a
+    /// single root node, the block must be terminated by an `FDT_END` token, and every property
+    /// name must lie within the strings block.
+    ///
+    /// A device tree which passes validation may be traversed with
+    /// [`DevTree::raw_tokens_unchecked()`].
+    pub fn validate(&self) -> Result<()> {
+        let mut iter = self.parse_iter();
+        let mut depth = 0usize;
+        let mut roots = 0usize;
+        let mut offset = iter.offset;
+        let misplaced = |offset, token| DevTreeError::MalformedToken {
+            offset,
+            token: Some(token),
+        };
+        while let Some(tok) = iter.next()? {
+            match tok {
+                ParsedTok::BeginNode(_) => {
+                    if depth == 0 {
+                        roots += 1;
+                        if roots > 1 {
+                            return Err(misplaced(offset, FdtTok::BeginNode));
+                        }
+                    }
+                    depth += 1;
+                }
+                ParsedTok::EndNode => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or(misplaced(offset, FdtTok::EndNode))?;
+                }
+                ParsedTok::Prop(prop) => {
+                    if depth == 0 {
+                        return Err(misplaced(offset, FdtTok::Prop));
+                    }
+                    self.string_at(prop.name_offset)?;
+                }
+                ParsedTok::Nop => {}
+            }
+            offset = iter.offset;
+        }
+        if depth != 0 || roots != 1 {
+            return Err(misplaced(offset, FdtTok::End));
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the violations of the specification within the device tree which
+    /// do not prevent it from being parsed, such as duplicate properties or dangling phandles.
+    ///
+    /// See [`crate::lint`] for the violations reported.
+    #[must_use]
+    pub fn lint(&self) -> DevTreeLintIter<'_, 'dt> {
+        DevTreeLintIter::new(self)
+    }
+
+    /// Returns an iterator over the tokens of the structure block which performs no bounds or
+    /// validity checks, for use by performance sensitive full tree traversals.
+    ///
+    /// # Safety
+    ///
+    /// [`DevTree::validate()`] must have returned `Ok` for this device tree. Iterating a device
+    /// tree which has not been validated results in undefined behavior.
+    #[inline]
+    pub unsafe fn raw_tokens_unchecked(&self) -> DevTreeRawTokenIter<'dt> {
+        DevTreeRawTokenIter::new(self)
+    }
+
+    /// Returns the first [`DevTreeNode`] object with the provided compatible device tree property
+    /// or `None` if none exists.
+    pub fn compatible_nodes<'s, 'a: 's>(
+        &'a self,
+        string: &'s str,
+    ) -> DevTreeCompatibleNodeIter<'s, 'a, 'dt> {
+        self.compatible_nodes_matching(string)
+    }
+
+    /// Returns an iterator over the [`DevTreeNode`] objects following `node` (in document order)
+    /// with the provided compatible device tree property.
+    ///
+    /// The search begins immediately after `node`, so it includes `node`'s descendants but not
+    /// `node` itself. This allows a search to be resumed from a previously found node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let first = devtree.compatible_nodes("virtio,mmio").next().unwrap().unwrap();
+    ///
+    /// let mut rest = devtree.compatible_nodes_from(&first, "virtio,mmio");
+    /// assert_eq!(rest.clone().count().unwrap(), 7);
+    ///
+    /// // Rewinding restarts the search from the root node.
+    /// rest.rewind();
+    /// assert_eq!(rest.count().unwrap(), 8);
+    /// ```
+    pub fn compatible_nodes_from<'s, 'a: 's>(
+        &'a self,
+        node: &DevTreeNode<'a, 'dt>,
+        string: &'s str,
+    ) -> DevTreeCompatibleNodeIter<'s, 'a, 'dt> {
+        DevTreeCompatibleNodeIter {
+            iter: node.parse_iter.clone(),
+            matcher: string,
+        }
+    }
+
+    /// Returns an iterator over the [`DevTreeNode`] objects following the node at `offset` with
+    /// the provided compatible device tree property, as
+    /// [`compatible_nodes_from`](Self::compatible_nodes_from) does.
+    ///
+    /// `offset` is the offset of the node's `FDT_BEGIN_NODE` token from the start of the device
+    /// tree (the start of [`DevTreeNode::subtree_byte_range()`]). The tree is parsed up to
+    /// `offset` to find the node, and [`DevTreeError::InvalidOffset`] is returned if no node
+    /// begins there.
+    pub fn compatible_nodes_from_offset<'s, 'a: 's>(
+        &'a self,
+        offset: usize,
+        string: &'s str,
+    ) -> Result<DevTreeCompatibleNodeIter<'s, 'a, 'dt>> {
+        let mut iter = self.items();
+        while iter.next_node()?.is_some() {
+            if iter.current_node_offset() == Some(offset) {
+                return Ok(DevTreeCompatibleNodeIter {
+                    iter,
+                    matcher: string,
+                });
+            }
+        }
+        Err(DevTreeError::InvalidOffset)
+    }
+
+    /// Returns an iterator over the [`DevTreeNode`] objects whose compatible device tree property
+    /// contains any of the strings of `compatibles`.
+    ///
+    /// Each node is returned once, however many of its entries match, and the tree is only
+    /// parsed once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let nodes = devtree.compatible_nodes_any(&["virtio,mmio", "ns16550a"]);
+    /// assert_eq!(nodes.count().unwrap(), 9);
+    /// ```
+    pub fn compatible_nodes_any<'s, 'a: 's>(
+        &'a self,
+        compatibles: &'s [&'s str],
+    ) -> DevTreeCompatibleNodeIter<'s, 'a, 'dt, [&'s str]> {
+        self.compatible_nodes_matching(compatibles)
+    }
+
+    /// Returns an iterator over [`DevTreeNode`] objects with a compatible device tree property
+    /// accepted by the provided [`Matcher`].
+    pub fn compatible_nodes_matching<'s, 'a: 's, M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &'s M,
+    ) -> DevTreeCompatibleNodeIter<'s, 'a, 'dt, M> {
+        DevTreeCompatibleNodeIter {
+            iter: self.items(),
+            matcher,
+        }
+    }
+
+    /// Returns the first node with a `compatible` property accepted by `matcher`. See
+    /// [`IterableDevTree::find_first_compatible_node`].
+    #[inline]
+    pub fn find_first_compatible_node<'a, M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &M,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        IterableDevTree::find_first_compatible_node(self, matcher)
+    }
+
+    /// Returns the node which best matches the prioritized list of `compatibles`, along with the
+    /// index of the string it matched, or `None` if no node matches any of them.
+    ///
+    /// A node matching an earlier string of `compatibles` is preferred over one matching a later
+    /// string. Of the nodes matching the same string, the first in document order is returned.
+    /// Unlike searching for each string in turn, the tree is only parsed once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let (node, index) = devtree
+    ///     .first_matching(&["vendor,uart-v3", "ns16550a", "virtio,mmio"])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(node.name().unwrap(), "uart@10000000");
+    /// ```
+    pub fn first_matching<'a>(
+        &'a self,
+        compatibles: &[&str],
+    ) -> Result<Option<(DevTreeNode<'a, 'dt>, usize)>> {
+        #[cfg(feature = "profiling")]
+        let _search = crate::profiling::Search::begin();
+
+        let mut best = None;
+        // Only strings preferred over the best match so far need to be checked.
+        let mut limit = compatibles.len();
+        let mut iter = self.items();
+        while let Some(prop) = iter.next_prop()? {
+            if prop.name()? != "compatible" {
+                continue;
+            }
+            let found = compatibles[..limit]
+                .iter()
+                .position(|&compatible| prop.strlist_matches(compatible));
+            if let Some(index) = found {
+                best = Some((prop.node(), index));
+                if index == 0 {
+                    break;
+                }
+                limit = index;
+            }
+        }
+        Ok(best)
+    }
+
+    /// Returns an iterator over the [`DevTreeNode`] objects whose absolute paths match `pattern`.
+    ///
+    /// Each component of `pattern` is matched against a node name as a
+    /// [`crate::matcher::Glob`]. As with paths, a component without a unit address also matches
+    /// nodes which have one. A pattern which is not absolute matches no nodes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// # let fdt = index.fdt();
+    /// let virtio = fdt.nodes_matching_path("/virtio_mmio@*");
+    /// assert_eq!(virtio.count()?, 8);
+    /// # Ok::<(), fdt_rs::error::DevTreeError>(())
+    /// ```
+    pub fn nodes_matching_path<'p, 'a>(
+        &'a self,
+        pattern: &'p str,
+    ) -> DevTreePathMatchIter<'p, 'a, 'dt> {
+        DevTreePathMatchIter {
+            iter: self.items(),
+            pattern,
+            matched: 0,
+        }
+    }
+
+    /// Returns the canonical stream of this device tree, with each node's properties returned in
+    /// the given `order`. See [`crate::canonical`].
+    #[must_use]
+    pub fn canonical_items(&self, order: PropOrder) -> DevTreeCanonicalIter<'_, 'dt> {
+        DevTreeCanonicalIter::new(self, order)
+    }
+
+    /// Collects up to `N` nodes with a compatible property accepted by `matcher`. See
+    /// [`crate::bounded`].
+    pub fn collect_nodes<'a, M: Matcher + ?Sized, const N: usize>(
+        &'a self,
+        matcher: &M,
+    ) -> Result<Collected<DevTreeNode<'a, 'dt>, N>> {
+        let mut collected = Collected::new();
+        let mut nodes = self.compatible_nodes_matching(matcher);
+        while let Some(node) = nodes.next()? {
+            collected.push(node);
+        }
+        Ok(collected)
+    }
+
+    pub fn buf(&self) -> &'dt [u8] {
+        self.buf
+    }
+
+    /// Returns a fingerprint (a 32-bit FNV-1a hash) of the device tree's `totalsize` bytes.
+    ///
+    /// The fingerprint is intended to detect corruption when copying a device tree. It is not a
+    /// cryptographic hash.
+    #[must_use]
+    pub fn fingerprint(&self) -> u32 {
+        const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+        const FNV_PRIME: u32 = 0x0100_0193;
+
+        let len = core::cmp::min(self.totalsize(), self.buf.len());
+        self.buf[..len].iter().fold(FNV_OFFSET_BASIS, |hash, b| {
+            (hash ^ u32::from(*b)).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    /// Perform [`DevTree::relocate_to()`] and additionally verify that the fingerprint of the copy
+    /// matches the fingerprint of this device tree.
+    ///
+    /// An [`Err`] containing [`DevTreeError::FingerprintMismatch`] is returned if they differ.
+    pub fn relocate_to_verified<'d>(&self, dst: &'d mut [u8]) -> Result<DevTree<'d>> {
+        let expected = self.fingerprint();
+        let relocated = self.relocate_to(dst)?;
+        if relocated.fingerprint() != expected {
+            return Err(DevTreeError::FingerprintMismatch);
+        }
+        Ok(relocated)
+    }
+
+    /// Write an indented dump of the device tree into `w` without allocating.
+    ///
+    /// See [`crate::print::write_tree`] for details on the output format.
+    pub fn write_tree<W: core::fmt::Write>(
+        &self,
+        w: &mut W,
+        max_depth: usize,
+        max_value_bytes: usize,
+    ) -> Result<()> {
+        crate::print::write_tree(self, w, max_depth, max_value_bytes)
+    }
+
+    /// Write the device tree into `w` as device tree source without allocating.
+    ///
+    /// See [`crate::print::write_dts`] for details on the output format.
+    pub fn write_dts<W: core::fmt::Write>(&self, w: &mut W) -> Result<()> {
+        crate::print::write_dts(self, w)
+    }
+
+    /// Returns the root [`DevTreeNode`] object of the device tree (if it exists).
+    pub fn root(&self) -> Result<Option<DevTreeNode<'_, 'dt>>> {
+        self.nodes().next()
+    }
+
+    /// Returns the node at the provided path, as [`DevTreeIndex::node_at_path`] does, or `None`
+    /// if no such node exists.
+    ///
+    /// A path which does not begin with `/` begins with an alias instead (e.g. `"serial0"`),
+    /// which is resolved using [`resolve_alias`](Self::resolve_alias).
+    ///
+    /// [`DevTreeIndex::node_at_path`]: crate::index::DevTreeIndex::node_at_path
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let uart = devtree.node_at_path("/uart").unwrap().unwrap();
+    /// assert_eq!(uart.name().unwrap(), "uart@10000000");
+    /// assert!(devtree.node_at_path("/missing").unwrap().is_none());
+    /// ```
+    pub fn node_at_path(&self, path: &str) -> Result<Option<DevTreeNode<'_, 'dt>>> {
+        let root = match self.root()? {
+            Some(root) => root,
+            None => return Ok(None),
+        };
+        if path.starts_with('/') {
+            return Self::node_below(root, path);
+        }
+        let (alias, rest) = split_alias(path);
+        let target = match self.resolve_alias(alias)? {
+            Some(target) if target.starts_with('/') => target,
+            _ => return Ok(None),
+        };
+        match Self::node_below(root, target)? {
+            Some(node) => Self::node_below(node, rest),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the path an alias refers to, as given by the property of that name within the
+    /// `/aliases` node, or `None` if there is no such alias.
+    pub fn resolve_alias(&self, alias: &str) -> Result<Option<&'dt str>> {
+        let aliases = match self.node_at_path("/aliases")? {
+            Some(aliases) => aliases,
+            None => return Ok(None),
+        };
+        let mut items = aliases.items();
+        while let Some(item) = items.next()? {
+            if let DevTreeItem::Prop(prop) = item {
+                if prop.name()? == alias {
+                    return prop.str().map(Some);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the node at `path` relative to `node`.
+    fn node_below<'a>(
+        mut node: DevTreeNode<'a, 'dt>,
+        path: &str,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            let mut children = node.children();
+            node = loop {
+                match children.next()? {
+                    Some(child) if component_matches(child.name?, component) => break child,
+                    Some(_) => {}
+                    None => return Ok(None),
+                }
+            };
+        }
+        Ok(Some(node))
+    }
+}
+
+impl<'a, 'dt: 'a> IterableDevTree<'a, 'dt> for DevTree<'dt> {
+    type Node = DevTreeNode<'a, 'dt>;
+
+    fn find_first_compatible_node<M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &M,
+    ) -> Result<Option<Self::Node>> {
+        self.compatible_nodes_matching(matcher).next()
+    }
+}
+
+/// Iterates over the items of the device tree as [`DevTree::try_items()`] does.
+///
+/// # Example
+///
+/// ```
+/// # use fdt_rs::doctest::*;
+/// # let (index, _) = doctest_index();
+/// # let fdt = *index.fdt();
+/// let mut nodes = 0;
+/// for item in &fdt {
+///     if let DevTreeItem::Node(_) = item? {
+///         nodes += 1;
+///     }
+/// }
+/// assert_eq!(nodes, fdt.nodes().count()?);
+/// # Ok::<(), fdt_rs::error::DevTreeError>(())
+/// ```
+impl<'a, 'dt: 'a> IntoIterator for &'a DevTree<'dt> {
+    type Item = Result<DevTreeItem<'a, 'dt>>;
+    type IntoIter = DevTreeTryIter<DevTreeIter<'a, 'dt>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.try_items()
+    }
+}
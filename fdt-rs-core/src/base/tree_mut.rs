@@ -0,0 +1,134 @@
+use core::mem::size_of;
+
+use crate::prelude::*;
+
+use crate::base::parse::{DevTreeParseIter, ParsedTok};
+use crate::base::DevTree;
+use crate::error::{DevTreeError, Result};
+use crate::priv_util::InvariantUnwrap;
+use crate::spec::FdtTok;
+
+/// A device tree within a mutable buffer, whose property values may be overwritten in place.
+///
+/// The layout of the device tree is never changed, so a property's value may only be replaced by
+/// one which fits within the space of its current value. This suffices for patching values such
+/// as `/chosen/bootargs` or `/memory/reg` before handing the device tree to the next boot stage.
+#[cfg_attr(docsrs, doc(cfg(feature = "writer")))]
+pub struct DevTreeMut<'dt> {
+    buf: &'dt mut [u8],
+}
+
+impl<'dt> DevTreeMut<'dt> {
+    /// Construct a mutable device tree from the provided byte slice.
+    ///
+    /// # Safety
+    ///
+    /// The requirements of [`DevTree::new()`] apply.
+    pub unsafe fn new(buf: &'dt mut [u8]) -> Result<Self> {
+        DevTree::new(buf)?;
+        Ok(Self { buf })
+    }
+
+    /// Returns a [`DevTree`] which reads the current contents of the device tree.
+    #[must_use]
+    pub fn devtree(&self) -> DevTree<'_> {
+        // Unsafe OK. The buffer was verified by new() and its header is never modified.
+        unsafe { DevTree::new(self.buf).invariant_unwrap() }
+    }
+
+    /// Overwrite the value of the property `name` of the node at `path` (as given to
+    /// [`DevTree::node_at_path()`]) with `value`.
+    ///
+    /// Returns `Ok(false)` if there is no such node or property. `value` may be shorter than the
+    /// current value, in which case the space it no longer uses is filled with `FDT_NOP` tokens,
+    /// but a longer value is rejected with a [`DevTreeError::InvalidParameter`]. Values which a
+    /// device tree older than version 16 aligned to 8 bytes must keep their length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::FDT;
+    /// use fdt_rs::base::DevTreeMut;
+    ///
+    /// let mut buf = vec![0u32; FDT.len() / 4];
+    /// let bytes = unsafe {
+    ///     core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, FDT.len())
+    /// };
+    /// bytes.copy_from_slice(FDT);
+    ///
+    /// // Halve the size of the memory node's region.
+    /// let mut fdt = unsafe { DevTreeMut::new(bytes) }.unwrap();
+    /// let reg = [0, 0x8000_0000u32, 0, 0x400_0000];
+    /// let value: Vec<u8> = reg.iter().flat_map(|cell| cell.to_be_bytes()).collect();
+    /// assert!(fdt.set_prop("/memory", "reg", &value).unwrap());
+    ///
+    /// let devtree = fdt.devtree();
+    /// let memory = devtree.node_at_path("/memory").unwrap().unwrap();
+    /// assert_eq!(memory.prop_u64_cells("reg", 1).unwrap(), Some(0x400_0000));
+    /// ```
+    #[must_use = "the property is not written if it does not exist"]
+    pub fn set_prop(&mut self, path: &str, name: &str, value: &[u8]) -> Result<bool> {
+        let (tok_off, value_off, old_len) = match self.find_prop(path, name)? {
+            Some(found) => found,
+            None => return Ok(false),
+        };
+        if value.len() > old_len {
+            return Err(DevTreeError::InvalidParameter(
+                "Value is longer than the property's current value",
+            ));
+        }
+        let len_off = tok_off + size_of::<u32>();
+        let header_end = len_off + 2 * size_of::<u32>();
+        if value_off != header_end && value.len() != old_len {
+            return Err(DevTreeError::InvalidParameter(
+                "Aligned legacy property values may not change length",
+            ));
+        }
+
+        let align = |len: usize| (len + 3) & !3;
+        let new_end = value_off + value.len();
+        self.buf[value_off..new_end].copy_from_slice(value);
+        for b in &mut self.buf[new_end..value_off + align(value.len())] {
+            *b = 0;
+        }
+        let nop = (FdtTok::Nop as u32).to_be_bytes();
+        for tok in self.buf[value_off + align(value.len())..value_off + align(old_len)]
+            .chunks_exact_mut(size_of::<u32>())
+        {
+            tok.copy_from_slice(&nop);
+        }
+        self.buf[len_off..len_off + size_of::<u32>()]
+            .copy_from_slice(&(value.len() as u32).to_be_bytes());
+        Ok(true)
+    }
+
+    /// Returns the offsets of the `FDT_PROP` token and of the value of the property `name` of
+    /// the node at `path`, along with the length of its value.
+    fn find_prop(&self, path: &str, name: &str) -> Result<Option<(usize, usize, usize)>> {
+        let fdt = self.devtree();
+        let node_off = match fdt.node_at_path(path)?.and_then(|node| node.node_offset()) {
+            Some(off) => off,
+            None => return Ok(None),
+        };
+        let mut iter = DevTreeParseIter {
+            offset: node_off,
+            fdt: &fdt,
+        };
+        // Skip the node's FDT_BEGIN_NODE token.
+        iter.next()?;
+        loop {
+            let tok_off = iter.offset;
+            match iter.next()? {
+                Some(ParsedTok::Prop(prop)) => {
+                    if fdt.string_at(prop.name_offset)? == name {
+                        let value_off =
+                            prop.prop_buf.as_ptr() as usize - fdt.buf().as_ptr() as usize;
+                        return Ok(Some((tok_off, value_off, prop.prop_buf.len())));
+                    }
+                }
+                Some(ParsedTok::Nop) => {}
+                _ => return Ok(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Fixed capacity collections for gathering results without allocating.
+//!
+//! [`BoundedVec`] stores up to `N` items inline. [`Collected`] wraps a [`BoundedVec`] and also
+//! counts the items which did not fit, so callers can tell whether a result was truncated.
+//! [`Collected`] implements [`FromIterator`], allowing any iterator to be collected into it.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::bounded::Collected;
+//!
+//! // Collect up to 4 of the virtio devices.
+//! let virtio: Collected<_, 4> = index.collect_nodes("virtio,mmio");
+//! assert_eq!(virtio.stored(), 4);
+//! assert_eq!(virtio.found(), 8);
+//! assert!(!virtio.is_complete());
+//!
+//! let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+//! let compatible = cpu.prop("compatible").unwrap();
+//! let strs: Collected<_, 2> = compatible.iter_str().collect::<Result<_, _>>().unwrap();
+//! assert_eq!(strs.items().as_slice(), &["riscv"]);
+//! ```
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::slice;
+
+/// A vector with a fixed capacity of `N` items, stored inline.
+pub struct BoundedVec<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> BoundedVec<T, N> {
+    /// Creates an empty `BoundedVec`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            // An array of MaybeUninit does not require initialization.
+            items: unsafe { MaybeUninit::uninit().assume_init() },
+            len: 0,
+        }
+    }
+
+    /// Appends `item`, or returns it within an [`Err`] if the vector is full.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        match self.items.get_mut(self.len) {
+            Some(slot) => {
+                *slot = MaybeUninit::new(item);
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(item),
+        }
+    }
+
+    /// Returns the number of items which may be stored.
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns true if no more items may be stored.
+    #[inline]
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the stored items.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        // The first `len` items are initialized.
+        unsafe { slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+
+    /// Returns the stored items.
+    #[inline]
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // The first `len` items are initialized.
+        unsafe { slice::from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T, const N: usize> Default for BoundedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for BoundedVec<T, N> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize> Deref for BoundedVec<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for BoundedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for BoundedVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut vec = Self::new();
+        for item in self.iter() {
+            // Unable to fail, the clone has the same capacity.
+            let _ = vec.push(item.clone());
+        }
+        vec
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for BoundedVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a BoundedVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Up to `N` collected items, along with the total number of items which were found.
+#[derive(Clone, Debug, Default)]
+pub struct Collected<T, const N: usize> {
+    items: BoundedVec<T, N>,
+    found: usize,
+}
+
+impl<T, const N: usize> Collected<T, N> {
+    /// Creates an empty collection.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            items: BoundedVec::new(),
+            found: 0,
+        }
+    }
+
+    /// Records a found item, storing it if there is room.
+    pub fn push(&mut self, item: T) {
+        self.found += 1;
+        let _ = self.items.push(item);
+    }
+
+    /// Returns the stored items.
+    #[inline]
+    #[must_use]
+    pub fn items(&self) -> &BoundedVec<T, N> {
+        &self.items
+    }
+
+    /// Returns the stored items, discarding the count of found items.
+    #[inline]
+    #[must_use]
+    pub fn into_items(self) -> BoundedVec<T, N> {
+        self.items
+    }
+
+    /// Returns the number of items which were found.
+    #[inline]
+    #[must_use]
+    pub fn found(&self) -> usize {
+        self.found
+    }
+
+    /// Returns the number of items which were stored.
+    #[inline]
+    #[must_use]
+    pub fn stored(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if every found item was stored.
+    #[inline]
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.found == self.stored()
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for Collected<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut collected = Self::new();
+        for item in iter {
+            collected.push(item);
+        }
+        collected
+    }
+}
@@ -0,0 +1,155 @@
+//! A canonical stream of a device tree's contents, for comparing trees semantically.
+//!
+//! [`DevTree::canonical_items`] and [`DevTreeIndex::canonical_items`] return identical streams of
+//! [`CanonicalItem`] objects: one per node (in document order) followed by one per property of
+//! that node. Each item carries the full path of its node, so two trees may be compared (or
+//! hashed) item by item without regard to how they were encoded. `FDT_NOP` tokens, padding and
+//! the layout of the strings block do not affect the stream, and [`PropOrder::ByName`] also
+//! ignores the order in which properties were written.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::canonical::PropOrder;
+//! use fdt_rs::prelude::*;
+//!
+//! // Both backends produce the same stream.
+//! let base = index.fdt().canonical_items(PropOrder::ByName);
+//! assert!(index.canonical_items(PropOrder::ByName).eq(base).unwrap());
+//!
+//! let mut items = index.canonical_items(PropOrder::Document);
+//! let root = items.next().unwrap().unwrap();
+//! assert_eq!(root.path.components(), &[] as &[&str]);
+//! assert_eq!(root.name, None);
+//! let first = items.next().unwrap().unwrap();
+//! assert_eq!(first.name, Some("#address-cells"));
+//! ```
+use core::fmt;
+
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+
+#[cfg(doc)]
+use crate::base::DevTree;
+#[cfg(doc)]
+use crate::index::DevTreeIndex;
+
+/// The maximum number of components of a [`CanonicalPath`].
+///
+/// Canonical streams of trees with nodes nested more deeply return
+/// [`DevTreeError::DepthLimitExceeded`].
+pub const MAX_CANONICAL_DEPTH: usize = 16;
+
+/// The order in which the properties of each node are returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropOrder {
+    /// The order the properties appear within the device tree.
+    Document,
+    /// Sorted by name. Properties of the same name remain in document order.
+    ///
+    /// Each node's properties are re-read to find the next one, so this takes time quadratic in
+    /// the number of properties of a node.
+    ByName,
+}
+
+/// The path of a node, stored without allocating.
+///
+/// The root node has no components.
+#[derive(Clone, Copy)]
+pub struct CanonicalPath<'dt> {
+    components: [&'dt str; MAX_CANONICAL_DEPTH],
+    len: usize,
+}
+
+impl<'dt> CanonicalPath<'dt> {
+    pub(crate) fn root() -> Self {
+        Self {
+            components: [""; MAX_CANONICAL_DEPTH],
+            len: 0,
+        }
+    }
+
+    /// Returns the names of the nodes along the path, starting below the root node.
+    #[must_use]
+    pub fn components(&self) -> &[&'dt str] {
+        &self.components[..self.len]
+    }
+
+    pub(crate) fn push(&mut self, name: &'dt str) -> Result<()> {
+        let slot = self
+            .components
+            .get_mut(self.len)
+            .ok_or(DevTreeError::DepthLimitExceeded)?;
+        *slot = name;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = core::cmp::min(self.len, len);
+    }
+}
+
+impl<'dt> PartialEq for CanonicalPath<'dt> {
+    fn eq(&self, other: &Self) -> bool {
+        self.components() == other.components()
+    }
+}
+
+impl<'dt> Eq for CanonicalPath<'dt> {}
+
+impl<'dt> fmt::Display for CanonicalPath<'dt> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.len == 0 {
+            return f.write_str("/");
+        }
+        for component in self.components() {
+            write!(f, "/{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'dt> fmt::Debug for CanonicalPath<'dt> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\"", self)
+    }
+}
+
+/// An entry of a canonical stream: either the start of a node or one of its properties.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CanonicalItem<'dt> {
+    /// The path of the node, or of the node the property belongs to.
+    pub path: CanonicalPath<'dt>,
+    /// The name of the property, or `None` for the item which starts a node.
+    pub name: Option<&'dt str>,
+    /// The value of the property. Empty for the item which starts a node.
+    pub value: &'dt [u8],
+}
+
+/// Returns the property which follows `last` when sorted by `(name, document index)`, along with
+/// its document index.
+pub(crate) fn next_prop_by_name<'dt, I>(
+    mut props: I,
+    last: Option<(&'dt str, usize)>,
+) -> Result<Option<(&'dt str, &'dt [u8], usize)>>
+where
+    I: FallibleIterator<Item = (&'dt str, &'dt [u8]), Error = DevTreeError>,
+{
+    let mut best: Option<(&'dt str, &'dt [u8], usize)> = None;
+    let mut idx = 0;
+    while let Some((name, value)) = props.next()? {
+        let key = (name, idx);
+        idx += 1;
+        if matches!(last, Some(last) if key <= last) {
+            continue;
+        }
+        if !matches!(best, Some((best_name, _, best_idx)) if (best_name, best_idx) <= key) {
+            best = Some((name, value, key.1));
+        }
+    }
+    Ok(best)
+}
@@ -0,0 +1,13 @@
+pub mod path;
+
+// The traits previously defined here are now exported by `crate::traits`. These paths remain for
+// one release so that code naming them continues to build.
+pub mod item {
+    pub use crate::traits::UnwrappableDevTreeItem;
+}
+pub mod prop {
+    pub use crate::traits::PropReader;
+}
+pub mod tree {
+    pub use crate::traits::IterableDevTree;
+}
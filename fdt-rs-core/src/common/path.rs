@@ -0,0 +1,44 @@
+use crate::matcher::{Glob, Matcher};
+
+/// Returns true if the node `name` is matched by the path `component`.
+///
+/// Per the specification, the unit address of a node may be omitted from a path if the node name
+/// alone is unambiguous. A component without a unit address therefore also matches nodes which
+/// have one.
+pub fn component_matches(name: &str, component: &str) -> bool {
+    if name == component {
+        return true;
+    }
+    !component.contains('@') && name.split('@').next() == Some(component)
+}
+
+/// Returns true if the node `name` is matched by the glob pattern `component` (see [`Glob`]).
+///
+/// As with [`component_matches`], a component without a unit address also matches nodes which
+/// have one.
+pub fn component_matches_glob(name: &str, component: &str) -> bool {
+    let glob = Glob(component);
+    if glob.matches(name) {
+        return true;
+    }
+    !component.contains('@') && matches!(name.split('@').next(), Some(n) if glob.matches(n))
+}
+
+/// Returns the components of the absolute path `pattern`, or `None` if it is not absolute.
+///
+/// The root path (`"/"`) has no components.
+pub fn path_components(pattern: &str) -> Option<&str> {
+    if !pattern.starts_with('/') {
+        return None;
+    }
+    Some(&pattern[1..])
+}
+
+/// Splits a path which begins with an alias (e.g. `"serial0/child"`) into the alias and the
+/// remainder of the path, which is relative to the aliased node.
+pub fn split_alias(path: &str) -> (&str, &str) {
+    match path.find('/') {
+        Some(i) => path.split_at(i),
+        None => (path, ""),
+    }
+}
@@ -0,0 +1,103 @@
+//! Recording of which nodes and properties of an index are accessed.
+//!
+//! When the `coverage` feature is enabled and a bitmap has been registered with [`record_into`],
+//! accesses through a [`DevTreeIndex`] set the bit of the node or property accessed, keyed by its
+//! id ([`DevTreeIndexNode::id`] and [`DevTreeIndexProp::id`]). Once the system has booted, the
+//! bits which remain clear identify content which no driver used, which may then be trimmed from
+//! the device tree.
+//!
+//! The following are recorded as accesses:
+//!
+//! * Reading the value of a property (through any [`PropReader`] method which does so).
+//! * Listing the properties of a node (and therefore looking up a property by name).
+//!
+//! Searches read the values of the properties they match against, e.g. the `compatible` property
+//! of every node visited by a compatible node search.
+//!
+//! The bitmap is stored globally and ids are only unique within a single index, so only one
+//! index should be in use while recording. It is registered through a single pointer, so it may
+//! be replaced or removed while other threads (or interrupt handlers) access the index.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use core::sync::atomic::AtomicU32;
+//! use fdt_rs::coverage::{bitmap_words, is_recorded, record_into, stop_recording};
+//!
+//! const CLEAR: AtomicU32 = AtomicU32::new(0);
+//! static WORDS: [AtomicU32; 256] = [CLEAR; 256];
+//! static BITMAP: &[AtomicU32] = &WORDS;
+//! assert!(bitmap_words(&index) <= BITMAP.len());
+//!
+//! record_into(&BITMAP);
+//! let memory = index.node_at_path("/memory").unwrap();
+//! let reg = memory.prop("reg").unwrap();
+//! reg.u64(0).unwrap();
+//! stop_recording();
+//!
+//! assert!(is_recorded(BITMAP, memory.id()));
+//! assert!(is_recorded(BITMAP, reg.id()));
+//! ```
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
+
+use crate::index::DevTreeIndex;
+#[cfg(doc)]
+use crate::index::{DevTreeIndexNode, DevTreeIndexProp};
+#[cfg(doc)]
+use crate::prelude::PropReader;
+
+// The registered bitmap. The slice (and so its length) is published by a single store, so a
+// concurrent `record()` never pairs a bitmap with the length of another.
+static BITMAP: AtomicPtr<&'static [AtomicU32]> = AtomicPtr::new(null_mut());
+
+/// Returns the number of 32-bit words a bitmap needs to record every node and property of
+/// `index`.
+#[must_use]
+pub fn bitmap_words(index: &DevTreeIndex) -> usize {
+    index.id_limit().div_ceil(32)
+}
+
+/// Start recording accesses into `bitmap`, replacing any bitmap previously registered.
+///
+/// Bits are only ever set, so `bitmap` should be cleared beforehand. Ids beyond the end of
+/// `bitmap` are not recorded.
+///
+/// The bitmap is registered by reference to a static slice (rather than by the slice itself) so
+/// that it may be published atomically.
+pub fn record_into(bitmap: &'static &'static [AtomicU32]) {
+    BITMAP.store(bitmap as *const _ as *mut _, Ordering::Release);
+}
+
+/// Stop recording accesses.
+pub fn stop_recording() {
+    BITMAP.store(null_mut(), Ordering::Release);
+}
+
+/// Returns true if the access of the node or property `id` was recorded within `bitmap`.
+#[must_use]
+pub fn is_recorded(bitmap: &[AtomicU32], id: usize) -> bool {
+    match bitmap.get(id / 32) {
+        Some(word) => word.load(Ordering::Relaxed) & (1 << (id % 32)) != 0,
+        None => false,
+    }
+}
+
+/// Records that the node or property `id` was accessed.
+#[inline]
+pub(crate) fn record(id: usize) {
+    // Unsafe OK. A non-null pointer was created from a `&'static &'static [AtomicU32]`.
+    let bitmap: &'static [AtomicU32] = match unsafe { BITMAP.load(Ordering::Acquire).as_ref() } {
+        Some(bitmap) => bitmap,
+        None => return,
+    };
+    let word = match bitmap.get(id / 32) {
+        Some(word) => word,
+        None => return,
+    };
+    // Only loads and stores are used so that targets without atomic read-modify-write
+    // instructions are supported. Concurrent accesses may therefore lose a bit.
+    word.store(word.load(Ordering::Relaxed) | 1 << (id % 32), Ordering::Relaxed);
+}
@@ -0,0 +1,242 @@
+//! Errors reported by this library
+
+#[cfg(doc)]
+use crate::index::DevTreeIndex;
+
+use crate::canonical::CanonicalPath;
+use crate::index::{DevTreeIndexNode, DevTreeIndexProp};
+use crate::priv_util::SliceReadError;
+use crate::spec::FdtTok;
+use core::str::Utf8Error;
+use core::fmt;
+use core::result;
+
+/// An error describe parsing problems when creating device trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevTreeError {
+    InvalidParameter(&'static str),
+
+    /// The magic number FDT_MAGIC was not found at the start of the
+    /// structure.
+    InvalidMagicNumber,
+
+    /// Unable to safely read data from the given device tree using the supplied offset
+    InvalidOffset,
+
+    /// The data was not formatted as expected.  This likely indicates an error in the Device Tree
+    /// we're parsing.
+    ParseError,
+
+    /// A token of the structure block was malformed, or did not belong where it was found.
+    ///
+    /// `offset` is the offset of the token from the start of the device tree. `token` is the type
+    /// of the token being parsed, or `None` if the token's value is not that of any token (or
+    /// lies beyond the end of the device tree).
+    MalformedToken { offset: usize, token: Option<FdtTok> },
+
+    /// While trying to convert a string that was supposed to be ASCII, invalid
+    /// `str` sequences were encounter.
+    StrError(Utf8Error),
+
+    /// There wasn't enough memory to create a [`DevTreeIndex`].
+    NotEnoughMemory,
+
+    /// The strings block described by the header (`off_dt_strings` and `size_dt_strings`) does
+    /// not lie within the device tree.
+    InvalidStringsBlock,
+
+    /// A property name was requested, but the device tree's strings block is empty.
+    EmptyStringsBlock,
+
+    /// An error was returned by a [`core::fmt::Write`] implementation while printing.
+    FmtError,
+
+    /// A relocated device tree's fingerprint did not match the fingerprint of the original.
+    FingerprintMismatch,
+
+    /// A traversal encountered a node nested deeper than its configured limit.
+    DepthLimitExceeded,
+
+    /// A traversal encountered more nodes than its configured limit.
+    NodeLimitExceeded,
+
+    /// A node contains multiple properties of the requested name.
+    DuplicateProperty,
+
+    /// The structure block ended without an `FDT_END` token. See
+    /// [`crate::spec::MissingEndPolicy`].
+    MissingEndToken,
+
+    /// The device tree's version (the value given) is older than the earliest version which may
+    /// be read, or is not backwards compatible with the latest version of the specification.
+    VersionNotSupported(u32),
+
+    /// More device tree fragments were given than are supported, such as by
+    /// [`DevTreeIndex::new_stitched()`].
+    TooManyFragments,
+}
+
+impl From<SliceReadError> for DevTreeError {
+    fn from(_: SliceReadError) -> DevTreeError {
+        DevTreeError::ParseError
+    }
+}
+
+impl From<Utf8Error> for DevTreeError {
+    fn from(e: Utf8Error) -> DevTreeError {
+        DevTreeError::StrError(e)
+    }
+}
+
+impl From<fmt::Error> for DevTreeError {
+    fn from(_: fmt::Error) -> DevTreeError {
+        DevTreeError::FmtError
+    }
+}
+
+/// The result of a parse.
+pub type Result<T> = core::result::Result<T, DevTreeError>;
+
+impl fmt::Display for DevTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        match *self {
+            DevTreeError::InvalidParameter(err) => write!(f, "Invalid parameter supplied: {}", err),
+            DevTreeError::InvalidOffset => write!(f, "Invalid offset provided."),
+
+            DevTreeError::InvalidMagicNumber => write!(f, "Device tree contains invalid magic number."),
+            DevTreeError::ParseError => write!(f, "Failed to parse device tree. It is invalid."),
+            DevTreeError::MalformedToken { offset, token: Some(token) } => write!(f, "Malformed {:?} token at offset {:#x}.", token, offset),
+            DevTreeError::MalformedToken { offset, token: None } => write!(f, "Invalid token at offset {:#x}.", offset),
+            DevTreeError::StrError(utf_err) => write!(f, "Failed to parse device tree string: {}", utf_err),
+
+            DevTreeError::NotEnoughMemory => write!(f, "Unable to fit device tree index into the provided buffer."),
+            DevTreeError::InvalidStringsBlock => write!(f, "Device tree strings block lies outside of the device tree."),
+            DevTreeError::EmptyStringsBlock => write!(f, "Device tree strings block is empty."),
+            DevTreeError::FmtError => write!(f, "Failed to write formatted output."),
+            DevTreeError::FingerprintMismatch => write!(f, "Relocated device tree does not match the original."),
+            DevTreeError::DepthLimitExceeded => write!(f, "Device tree nesting exceeds the traversal depth limit."),
+            DevTreeError::NodeLimitExceeded => write!(f, "Device tree contains more nodes than the traversal limit."),
+            DevTreeError::DuplicateProperty => write!(f, "Node contains multiple properties of the same name."),
+            DevTreeError::MissingEndToken => write!(f, "Device tree structure block is missing its end token."),
+            DevTreeError::VersionNotSupported(version) => write!(f, "Device tree version {} is not supported.", version),
+            DevTreeError::TooManyFragments => write!(f, "More device tree fragments were given than are supported."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for DevTreeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DevTreeError::StrError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A [`DevTreeError`] along with the node, and optionally the property, it concerns.
+///
+/// Errors returned by the high-level helpers (such as those of [`crate::util`]) may be annotated
+/// with their node using the [`ErrorContext`] trait, so diagnostics identify the offending part
+/// of a malformed tree. The path is stored without allocating.
+///
+/// # Example
+///
+/// ```
+/// # use fdt_rs::doctest::*;
+/// # let (index, _) = doctest_index();
+/// use fdt_rs::error::ErrorContext;
+///
+/// let memory = index.node_at_path("/memory").unwrap();
+/// let err = memory.prop_u64_cells("reg", 2).node_context(&memory).unwrap_err();
+/// assert_eq!(err.to_string(), "/memory@80000000: Invalid offset provided.");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextError<'dt> {
+    /// The error which occurred.
+    pub error: DevTreeError,
+    /// The path of the node, or `None` if it could not be determined (e.g. because the node is
+    /// nested too deeply to be stored).
+    pub path: Option<CanonicalPath<'dt>>,
+    /// The name of the property, if the error concerns a single property.
+    pub prop: Option<&'dt str>,
+}
+
+impl<'dt> ContextError<'dt> {
+    /// Returns `error` annotated with `node` and, optionally, the name of one of its properties.
+    pub fn new(
+        error: DevTreeError,
+        node: &DevTreeIndexNode<'_, '_, 'dt>,
+        prop: Option<&'dt str>,
+    ) -> Self {
+        Self {
+            error,
+            path: node.path().ok(),
+            prop,
+        }
+    }
+}
+
+impl<'dt> From<ContextError<'dt>> for DevTreeError {
+    fn from(err: ContextError<'dt>) -> DevTreeError {
+        err.error
+    }
+}
+
+impl<'dt> fmt::Display for ContextError<'dt> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        match self.path {
+            Some(path) => write!(f, "{}", path)?,
+            None => write!(f, "<unknown node>")?,
+        }
+        if let Some(prop) = self.prop {
+            write!(f, ": {}", prop)?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+/// Annotates the errors of results with the node or property they concern. See
+/// [`ContextError`].
+// The path is stored inline to avoid allocating, which makes the error large. Annotated results
+// are expected to be returned rarely and only while reporting a failure.
+#[allow(clippy::result_large_err)]
+pub trait ErrorContext<'dt, T> {
+    /// Annotates an error with `node`.
+    fn node_context(
+        self,
+        node: &DevTreeIndexNode<'_, '_, 'dt>,
+    ) -> result::Result<T, ContextError<'dt>>;
+
+    /// Annotates an error with `prop` and the node it belongs to.
+    fn prop_context(
+        self,
+        prop: &DevTreeIndexProp<'_, '_, 'dt>,
+    ) -> result::Result<T, ContextError<'dt>>;
+}
+
+#[allow(clippy::result_large_err)]
+impl<'dt, T> ErrorContext<'dt, T> for Result<T> {
+    fn node_context(
+        self,
+        node: &DevTreeIndexNode<'_, '_, 'dt>,
+    ) -> result::Result<T, ContextError<'dt>> {
+        self.map_err(|error| ContextError::new(error, node, None))
+    }
+
+    fn prop_context(
+        self,
+        prop: &DevTreeIndexProp<'_, '_, 'dt>,
+    ) -> result::Result<T, ContextError<'dt>> {
+        self.map_err(|error| ContextError::new(error, &prop.node(), prop.name().ok()))
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<'dt> std::error::Error for ContextError<'dt> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,131 @@
+//! Compile time sizing of [`DevTreeIndex`] buffers.
+//!
+//! Systems which embed their device tree with `include_bytes!` know its contents at compile
+//! time. [`index_size`] may be evaluated in a `const` context to compute the exact number of
+//! bytes required to index such a device tree, and [`DevTreeIndexBuffer`] provides a suitably
+//! aligned buffer of that size which may be placed in a `static`.
+//!
+//! # Example
+//!
+//! ```
+//! use fdt_rs::base::DevTree;
+//! use fdt_rs::index::DevTreeIndex;
+//! use fdt_rs::index::buffer::{index_size, DevTreeIndexBuffer};
+//!
+//! #[repr(align(4))]
+//! struct _Wrapper<T>(T);
+//! const FDT: &[u8] = &_Wrapper(*include_bytes!("../../../tests/riscv64-virt.dtb")).0;
+//!
+//! const INDEX_SIZE: usize = index_size(FDT);
+//! static mut INDEX_BUF: DevTreeIndexBuffer<INDEX_SIZE> = DevTreeIndexBuffer::new();
+//!
+//! let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+//! let buf = unsafe { &mut *core::ptr::addr_of_mut!(INDEX_BUF) };
+//! let index = DevTreeIndex::new(devtree, buf.as_mut_slice()).unwrap();
+//! assert_eq!(index.root().name().unwrap(), "");
+//! ```
+use core::mem::{align_of, size_of};
+
+use super::tree::{DTINode, DTIProp};
+use crate::spec::{fdt_header, fdt_prop_header, FdtTok, FDT_MAGIC};
+
+#[cfg(doc)]
+use super::DevTreeIndex;
+
+const TOK_BEGIN_NODE: u32 = FdtTok::BeginNode as u32;
+const TOK_END_NODE: u32 = FdtTok::EndNode as u32;
+const TOK_PROP: u32 = FdtTok::Prop as u32;
+const TOK_NOP: u32 = FdtTok::Nop as u32;
+const TOK_END: u32 = FdtTok::End as u32;
+
+/// Byte offset of the `off_dt_struct` field within the [`fdt_header`].
+const OFF_DT_STRUCT_FIELD: usize = 8;
+
+const fn read_be_u32(buf: &[u8], off: usize) -> u32 {
+    if off + size_of::<u32>() > buf.len() {
+        panic!("device tree is truncated");
+    }
+    u32::from_be_bytes([buf[off], buf[off + 1], buf[off + 2], buf[off + 3]])
+}
+
+const fn align_u32(off: usize) -> usize {
+    (off + size_of::<u32>() - 1) & !(size_of::<u32>() - 1)
+}
+
+/// Returns the number of bytes required to build a [`DevTreeIndex`] over `fdt`.
+///
+/// This is equivalent to `DevTreeIndex::get_layout(..).size()` but may be evaluated at compile
+/// time. A buffer of this size is sufficient provided it is aligned as a
+/// [`DevTreeIndexBuffer`] is.
+///
+/// # Panics
+///
+/// Panics if `fdt` does not contain a valid device tree. When evaluated in a `const` context
+/// this results in a compile time error.
+pub const fn index_size(fdt: &[u8]) -> usize {
+    if fdt.len() < size_of::<fdt_header>() || read_be_u32(fdt, 0) != FDT_MAGIC {
+        panic!("buffer does not contain a device tree");
+    }
+
+    let mut nodes = 0;
+    let mut props = 0;
+    let mut off = read_be_u32(fdt, OFF_DT_STRUCT_FIELD) as usize;
+    loop {
+        let tok = read_be_u32(fdt, off);
+        off += size_of::<u32>();
+        match tok {
+            TOK_BEGIN_NODE => {
+                nodes += 1;
+                // Skip the name and its null terminator.
+                while off < fdt.len() && fdt[off] != 0 {
+                    off += 1;
+                }
+                off = align_u32(off + 1);
+            }
+            TOK_PROP => {
+                props += 1;
+                let len = read_be_u32(fdt, off) as usize;
+                off = align_u32(off + size_of::<fdt_prop_header>() + len);
+            }
+            TOK_END_NODE | TOK_NOP => {}
+            TOK_END => break,
+            _ => panic!("invalid device tree token"),
+        }
+    }
+
+    nodes * size_of::<DTINode>() + props * size_of::<DTIProp>()
+}
+
+/// A buffer of `N` bytes aligned for use as [`DevTreeIndex`] storage.
+///
+/// Combined with [`index_size`] this allows the storage for an index to be reserved statically.
+#[repr(C)]
+pub struct DevTreeIndexBuffer<const N: usize> {
+    _align: [usize; 0],
+    buf: [u8; N],
+}
+
+// The alignment of the buffer is provided by the zero length usize array.
+const_assert_eq!(align_of::<DTINode<'static, 'static>>(), align_of::<usize>());
+
+impl<const N: usize> DevTreeIndexBuffer<N> {
+    /// Create a new zeroed buffer.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            _align: [],
+            buf: [0; N],
+        }
+    }
+
+    /// Returns the buffer as a byte slice which may be passed to [`DevTreeIndex::new`].
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.buf
+    }
+}
+
+impl<const N: usize> Default for DevTreeIndexBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
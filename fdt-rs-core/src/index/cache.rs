@@ -0,0 +1,162 @@
+//! A small query cache for repeated [`DevTreeIndex`] lookups.
+//!
+//! Operating systems frequently query the same few nodes while initializing drivers. A
+//! [`QueryCache`] remembers the results of recent path lookups and compatible searches in
+//! caller-provided storage, evicting the least recently used entry when full.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::index::cache::{CacheEntry, QueryCache};
+//!
+//! let mut storage: [Option<CacheEntry>; 4] = [None; 4];
+//! let mut cache = QueryCache::new(&index, &mut storage);
+//!
+//! let uart = cache.first_compatible("ns16550a").unwrap();
+//! let again = cache.first_compatible("ns16550a").unwrap();
+//! assert_eq!(uart.name(), again.name());
+//! assert_eq!(cache.stats().hits, 1);
+//! assert_eq!(cache.stats().misses, 1);
+//! ```
+use super::{DevTreeIndex, DevTreeIndexNode};
+
+/// The kind of query whose result is cached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryKind {
+    /// A lookup performed by [`DevTreeIndex::node_at_path`].
+    Path,
+    /// A search for the first node with a matching `compatible` property.
+    Compatible,
+}
+
+/// A single cached query result.
+#[derive(Clone, Copy)]
+pub struct CacheEntry<'k, 'a, 'i: 'a, 'dt: 'i> {
+    kind: QueryKind,
+    key: &'k str,
+    result: Option<DevTreeIndexNode<'a, 'i, 'dt>>,
+    last_used: u32,
+}
+
+/// Hit and miss counters of a [`QueryCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of queries answered from the cache.
+    pub hits: usize,
+    /// Number of queries which required searching the index.
+    pub misses: usize,
+}
+
+/// A fixed-size least recently used cache of [`DevTreeIndex`] query results.
+///
+/// The number of cached results is bounded by the length of the storage slice provided to
+/// [`QueryCache::new`].
+pub struct QueryCache<'c, 'k, 'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    entries: &'c mut [Option<CacheEntry<'k, 'a, 'i, 'dt>>],
+    clock: u32,
+    stats: CacheStats,
+}
+
+impl<'c, 'k, 'a, 'i: 'a, 'dt: 'i> QueryCache<'c, 'k, 'a, 'i, 'dt> {
+    /// Create a cache over `index` which stores its entries in `storage`.
+    ///
+    /// Any entries already within `storage` are discarded.
+    pub fn new(
+        index: &'a DevTreeIndex<'i, 'dt>,
+        storage: &'c mut [Option<CacheEntry<'k, 'a, 'i, 'dt>>],
+    ) -> Self {
+        let mut this = Self {
+            index,
+            entries: storage,
+            clock: 0,
+            stats: CacheStats::default(),
+        };
+        this.clear();
+        this
+    }
+
+    /// Returns the index this cache queries.
+    pub fn index(&self) -> &'a DevTreeIndex<'i, 'dt> {
+        self.index
+    }
+
+    /// Cached equivalent of [`DevTreeIndex::node_at_path`].
+    pub fn node_at_path(&mut self, path: &'k str) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        let index = self.index;
+        self.query(QueryKind::Path, path, || index.node_at_path(path))
+    }
+
+    /// Returns the first node whose `compatible` property matches `compatible`, caching the
+    /// result.
+    pub fn first_compatible(
+        &mut self,
+        compatible: &'k str,
+    ) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        let index = self.index;
+        self.query(QueryKind::Compatible, compatible, || {
+            index.compatible_nodes(compatible).next()
+        })
+    }
+
+    /// Returns the hit and miss counters of this cache.
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Discard all cached results and reset the statistics.
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+        self.clock = 0;
+        self.stats = CacheStats::default();
+    }
+
+    fn query<F>(
+        &mut self,
+        kind: QueryKind,
+        key: &'k str,
+        search: F,
+    ) -> Option<DevTreeIndexNode<'a, 'i, 'dt>>
+    where
+        F: FnOnce() -> Option<DevTreeIndexNode<'a, 'i, 'dt>>,
+    {
+        self.clock = self.clock.wrapping_add(1);
+        let now = self.clock;
+
+        for entry in self.entries.iter_mut().flatten() {
+            if entry.kind == kind && entry.key == key {
+                entry.last_used = now;
+                self.stats.hits += 1;
+                return entry.result;
+            }
+        }
+
+        self.stats.misses += 1;
+        let result = search();
+
+        // Prefer an empty slot, otherwise evict the least recently used entry.
+        let slot = match self.entries.iter().position(Option::is_none) {
+            Some(slot) => Some(slot),
+            None => self
+                .entries
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, entry)| entry.map_or(0, |e| now.wrapping_sub(e.last_used)))
+                .map(|(slot, _)| slot),
+        };
+        if let Some(slot) = slot {
+            self.entries[slot] = Some(CacheEntry {
+                kind,
+                key,
+                result,
+                last_used: now,
+            });
+        }
+        result
+    }
+}
@@ -0,0 +1,715 @@
+use core::ptr;
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::canonical::{next_prop_by_name, CanonicalItem, CanonicalPath, PropOrder};
+use crate::common::path::{component_matches_glob, path_components};
+use crate::error::DevTreeError;
+use crate::matcher::Matcher;
+use crate::priv_util::find_subslice;
+use crate::spec::StandardProp;
+use crate::view::PropStrIter;
+
+use super::tree::{CompatibleEntry, DTINode};
+use super::{DevTreeIndex, DevTreeIndexItem, DevTreeIndexNode, DevTreeIndexProp};
+//use crate::error::{Result};
+
+/***********************************/
+/***********  Node Siblings  *******/
+/***********************************/
+
+#[derive(Clone)]
+pub struct DevTreeIndexNodeSiblingIter<'a, 'i: 'a, 'dt: 'i>(DevTreeIndexIter<'a, 'i, 'dt>);
+
+impl<'a, 'i: 'a, 'dt: 'i> From<DevTreeIndexIter<'a, 'i, 'dt>>
+    for DevTreeIndexNodeSiblingIter<'a, 'i, 'dt>
+{
+    fn from(iter: DevTreeIndexIter<'a, 'i, 'dt>) -> Self {
+        Self(iter)
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodeSiblingIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_sibling()
+    }
+}
+
+/// An iterator over the descendant nodes of a single [`DevTreeIndexNode`], in depth-first
+/// (document) order.
+///
+/// Created by [`DevTreeIndexNode::descendants`].
+#[derive(Clone)]
+pub struct DevTreeIndexNodeDescendantIter<'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    node: Option<&'a DTINode<'i, 'dt>>,
+    root: &'a DTINode<'i, 'dt>,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNodeDescendantIter<'a, 'i, 'dt> {
+    pub(super) fn new(node: &DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
+        Self {
+            index: node.index(),
+            node: node.node.first_child(),
+            root: node.node,
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodeDescendantIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node?;
+        // Visit the node's first child, or otherwise the next sibling of the node or of its
+        // closest ancestor which has one, stopping at the subtree's root. Each node is climbed
+        // out of once, so the whole subtree is visited in linear time.
+        self.node = node.first_child().or_else(|| {
+            let mut cur = node;
+            while !ptr::eq(cur, self.root) {
+                if let Some(sibling) = cur.next_sibling() {
+                    return Some(sibling);
+                }
+                cur = cur.parent()?;
+            }
+            None
+        });
+        Some(DevTreeIndexNode::new(self.index, node))
+    }
+}
+
+/***********************************/
+/***********  Items      ***********/
+/***********************************/
+
+#[derive(Clone)]
+pub struct DevTreeIndexIter<'a, 'i: 'a, 'dt: 'i> {
+    pub index: &'a DevTreeIndex<'i, 'dt>,
+    node: Option<&'a DTINode<'i, 'dt>>,
+    prop_idx: usize,
+    initial_node_returned: bool,
+}
+
+/// An iterator over the properties and direct children of a single [`DevTreeIndexNode`].
+///
+/// Items are returned in document order; per the specification all properties of a node precede
+/// its children.
+#[derive(Clone)]
+pub struct DevTreeIndexNodeItemIter<'a, 'i: 'a, 'dt: 'i> {
+    props: DevTreeIndexNodePropIter<'a, 'i, 'dt>,
+    children: DevTreeIndexNodeSiblingIter<'a, 'i, 'dt>,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNodeItemIter<'a, 'i, 'dt> {
+    pub(super) fn new(node: &DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
+        Self {
+            props: node.props(),
+            children: node.children(),
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodeItemIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexItem<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.props.next() {
+            Some(prop) => Some(DevTreeIndexItem::Prop(prop)),
+            None => self.children.next().map(DevTreeIndexItem::Node),
+        }
+    }
+}
+
+/// An iterator over [`DevTreeIndexItem`] objects which bounds the traversal of an index.
+///
+/// Created by [`DevTreeIndexIter::limit_depth`] or [`DevTreeIndexIter::limit_nodes`]. When a limit
+/// is exceeded an [`Err`] containing [`DevTreeError::DepthLimitExceeded`] or
+/// [`DevTreeError::NodeLimitExceeded`] is returned and iteration ends.
+#[derive(Clone)]
+pub struct DevTreeIndexLimitIter<'a, 'i: 'a, 'dt: 'i> {
+    iter: DevTreeIndexIter<'a, 'i, 'dt>,
+    max_depth: usize,
+    max_nodes: usize,
+    nodes: usize,
+    done: bool,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexLimitIter<'a, 'i, 'dt> {
+    fn new(iter: DevTreeIndexIter<'a, 'i, 'dt>) -> Self {
+        Self {
+            iter,
+            max_depth: usize::MAX,
+            max_nodes: usize::MAX,
+            nodes: 0,
+            done: false,
+        }
+    }
+
+    /// Fail once a node nested deeper than `max_depth` is found. The root node has a depth of 1.
+    #[must_use]
+    pub fn limit_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = core::cmp::min(self.max_depth, max_depth);
+        self
+    }
+
+    /// Fail once more than `max_nodes` nodes have been found.
+    #[must_use]
+    pub fn limit_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = core::cmp::min(self.max_nodes, max_nodes);
+        self
+    }
+
+    /// Returns true if `node` is nested deeper than `max_depth`.
+    fn exceeds_depth(&self, node: &DTINode<'i, 'dt>) -> bool {
+        let mut depth = 1;
+        let mut cur = node;
+        while let Some(parent) = cur.parent() {
+            depth += 1;
+            if depth > self.max_depth {
+                return true;
+            }
+            cur = parent;
+        }
+        depth > self.max_depth
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexLimitIter<'a, 'i, 'dt> {
+    type Item = Result<DevTreeIndexItem<'a, 'i, 'dt>, DevTreeError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = match self.iter.next() {
+            Some(DevTreeIndexItem::Node(node)) => {
+                self.nodes += 1;
+                if self.exceeds_depth(node.node) {
+                    Err(DevTreeError::DepthLimitExceeded)
+                } else if self.nodes > self.max_nodes {
+                    Err(DevTreeError::NodeLimitExceeded)
+                } else {
+                    Ok(DevTreeIndexItem::Node(node))
+                }
+            }
+            Some(item) => Ok(item),
+            None => {
+                self.done = true;
+                return None;
+            }
+        };
+        self.done = res.is_err();
+        Some(res)
+    }
+}
+
+#[derive(Clone)]
+pub struct DevTreeIndexNodeIter<'a, 'i: 'a, 'dt: 'i>(pub DevTreeIndexIter<'a, 'i, 'dt>);
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodeIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_node()
+    }
+}
+
+#[derive(Clone)]
+pub struct DevTreeIndexPropIter<'a, 'i: 'a, 'dt: 'i>(pub DevTreeIndexIter<'a, 'i, 'dt>);
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexPropIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexProp<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_prop()
+    }
+}
+
+#[derive(Clone)]
+pub struct DevTreeIndexNodePropIter<'a, 'i: 'a, 'dt: 'i>(pub DevTreeIndexIter<'a, 'i, 'dt>);
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodePropIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexProp<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_node_prop()
+    }
+}
+
+/// Returns true if the absolute path of `node` is matched by the glob path `pattern`.
+fn path_matches_glob(node: &DevTreeIndexNode, pattern: &str) -> bool {
+    let components = match path_components(pattern) {
+        Some(components) => components,
+        None => return false,
+    };
+    let mut cur = *node;
+    if !components.is_empty() {
+        for component in components.rsplit('/') {
+            let parent = match cur.parent() {
+                Some(parent) => parent,
+                None => return false,
+            };
+            match cur.name() {
+                Ok(name) if component_matches_glob(name, component) => cur = parent,
+                _ => return false,
+            }
+        }
+    }
+    cur.parent().is_none()
+}
+
+/// An iterator over the [`DevTreeIndexNode`] objects whose paths match a glob pattern.
+///
+/// Created by [`DevTreeIndex::nodes_matching_path`].
+#[derive(Clone)]
+pub struct DevTreeIndexPathMatchIter<'p, 'a, 'i: 'a, 'dt: 'i> {
+    pub iter: DevTreeIndexNodeIter<'a, 'i, 'dt>,
+    pub pattern: &'p str,
+}
+impl<'p, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexPathMatchIter<'p, 'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let pattern = self.pattern;
+        self.iter.find(|node| path_matches_glob(node, pattern))
+    }
+}
+
+/// A property whose value contains a searched for byte pattern.
+///
+/// Returned by [`DevTreeIndexPropValueIter`].
+#[derive(Clone)]
+pub struct DevTreePropValueMatch<'a, 'i: 'a, 'dt: 'i> {
+    /// The matching property. Its node is available through [`PropReader::node`].
+    pub prop: DevTreeIndexProp<'a, 'i, 'dt>,
+    /// Offset of the first occurrence of the pattern within the property's value.
+    pub offset: usize,
+}
+
+/// An iterator over the properties whose values contain a byte pattern.
+///
+/// Created by [`DevTreeIndex::find_prop_value_containing`].
+#[derive(Clone)]
+pub struct DevTreeIndexPropValueIter<'p, 'a, 'i: 'a, 'dt: 'i> {
+    pub iter: DevTreeIndexPropIter<'a, 'i, 'dt>,
+    pub pattern: &'p [u8],
+}
+impl<'p, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexPropValueIter<'p, 'a, 'i, 'dt> {
+    type Item = DevTreePropValueMatch<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let pattern = self.pattern;
+        self.iter.find_map(|prop| {
+            find_subslice(prop.propbuf(), pattern)
+                .map(|offset| DevTreePropValueMatch { prop, offset })
+        })
+    }
+}
+
+/// An iterator over the properties whose values are longer than a threshold.
+///
+/// Created by [`DevTreeIndex::props_larger_than`].
+#[derive(Clone)]
+pub struct DevTreeIndexLargePropIter<'a, 'i: 'a, 'dt: 'i> {
+    pub iter: DevTreeIndexPropIter<'a, 'i, 'dt>,
+    pub threshold: usize,
+}
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexLargePropIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexProp<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let threshold = self.threshold;
+        self.iter.find(|prop| prop.length() > threshold)
+    }
+}
+
+/// An iterator over the canonical stream of an index. See [`crate::canonical`].
+///
+/// Created by [`DevTreeIndex::canonical_items`].
+#[derive(Clone)]
+pub struct DevTreeIndexCanonicalIter<'a, 'i: 'a, 'dt: 'i> {
+    nodes: DevTreeIndexNodeIter<'a, 'i, 'dt>,
+    order: PropOrder,
+    path: CanonicalPath<'dt>,
+    // The node whose properties are being returned.
+    node: Option<DevTreeIndexNode<'a, 'i, 'dt>>,
+    props: Option<DevTreeIndexNodePropIter<'a, 'i, 'dt>>,
+    last: Option<(&'dt str, usize)>,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexCanonicalIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>, order: PropOrder) -> Self {
+        Self {
+            nodes: index.nodes(),
+            order,
+            path: CanonicalPath::root(),
+            node: None,
+            props: None,
+            last: None,
+        }
+    }
+
+    fn item(&self, name: Option<&'dt str>, value: &'dt [u8]) -> CanonicalItem<'dt> {
+        CanonicalItem {
+            path: self.path,
+            name,
+            value,
+        }
+    }
+
+    fn next_prop(&mut self) -> Result<Option<CanonicalItem<'dt>>, DevTreeError> {
+        let node = match &self.node {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        if self.order == PropOrder::ByName {
+            let props = node.props().map(|p| p.name().map(|name| (name, p.propbuf())));
+            return match next_prop_by_name(fallible_iterator::convert(props), self.last)? {
+                Some((name, value, idx)) => {
+                    self.last = Some((name, idx));
+                    Ok(Some(self.item(Some(name), value)))
+                }
+                None => Ok(None),
+            };
+        }
+        match self.props.as_mut().and_then(Iterator::next) {
+            Some(prop) => Ok(Some(self.item(Some(prop.name()?), prop.propbuf()))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> FallibleIterator for DevTreeIndexCanonicalIter<'a, 'i, 'dt> {
+    type Error = DevTreeError;
+    type Item = CanonicalItem<'dt>;
+    fn next(&mut self) -> Result<Option<Self::Item>, DevTreeError> {
+        if let Some(item) = self.next_prop()? {
+            return Ok(Some(item));
+        }
+        let node = match self.nodes.next() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        // Nodes are returned in document order, so only the path below the parent changes.
+        let mut depth = 0usize;
+        let mut ancestor = node.parent();
+        while let Some(cur) = ancestor {
+            depth += 1;
+            ancestor = cur.parent();
+        }
+        self.path.truncate(depth.saturating_sub(1));
+        if depth > 0 {
+            self.path.push(node.name()?)?;
+        }
+
+        self.props = Some(node.props());
+        self.node = Some(node);
+        self.last = None;
+        Ok(Some(self.item(None, &[])))
+    }
+}
+
+/// An iterator over a single [`DevTreeIndexNode`]'s properties, classified by
+/// [`StandardProp`].
+#[derive(Clone)]
+pub struct DevTreeIndexNodeStandardPropIter<'a, 'i: 'a, 'dt: 'i>(
+    pub DevTreeIndexNodePropIter<'a, 'i, 'dt>,
+);
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodeStandardPropIter<'a, 'i, 'dt> {
+    type Item = StandardProp<DevTreeIndexProp<'a, 'i, 'dt>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(StandardProp::new)
+    }
+}
+
+pub struct DevTreeIndexCompatibleNodeIter<'s, 'a, 'i: 'a, 'dt: 'i, M: Matcher + ?Sized = str> {
+    pub iter: DevTreeIndexIter<'a, 'i, 'dt>,
+    pub matcher: &'s M,
+}
+impl<'s, 'a, 'i: 'a, 'dt: 'i, M: Matcher + ?Sized> Clone
+    for DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt, M>
+{
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            matcher: self.matcher,
+        }
+    }
+}
+impl<'s, 'a, 'i: 'a, 'dt: 'i, M: Matcher + ?Sized> Iterator
+    for DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt, M>
+{
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_compatible_node(self.matcher)
+    }
+}
+
+/// An iterator over the nodes whose `compatible` property contains a string. Created by
+/// [`DevTreeIndex::nodes_by_compatible`].
+#[derive(Clone)]
+pub struct DevTreeIndexCompatibleListIter<'s, 'a, 'i: 'a, 'dt: 'i>(CompatibleList<'s, 'a, 'i, 'dt>);
+
+#[derive(Clone)]
+enum CompatibleList<'s, 'a, 'i: 'a, 'dt: 'i> {
+    // The entries of the index's compatible index for the string.
+    Indexed(&'a DevTreeIndex<'i, 'dt>, core::slice::Iter<'i, CompatibleEntry<'i, 'dt>>),
+    // Every node of an index built without a compatible index.
+    Scan(DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt>),
+}
+
+impl<'s, 'a, 'i: 'a, 'dt: 'i> DevTreeIndexCompatibleListIter<'s, 'a, 'i, 'dt> {
+    pub(super) fn indexed(
+        index: &'a DevTreeIndex<'i, 'dt>,
+        entries: &'i [CompatibleEntry<'i, 'dt>],
+    ) -> Self {
+        Self(CompatibleList::Indexed(index, entries.iter()))
+    }
+
+    pub(super) fn scan(iter: DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt>) -> Self {
+        Self(CompatibleList::Scan(iter))
+    }
+}
+
+impl<'s, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexCompatibleListIter<'s, 'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            CompatibleList::Indexed(index, entries) => {
+                let (_, node) = entries.next()?;
+                // Unsafe OK. Every node of the index remains valid for 'i.
+                Some(DevTreeIndexNode::new(index, unsafe { &**node }))
+            }
+            CompatibleList::Scan(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            CompatibleList::Indexed(_, entries) => entries.size_hint(),
+            CompatibleList::Scan(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// An iterator over the distinct strings of the `compatible` properties of an index, each paired
+/// with an iterator over the nodes whose `compatible` property contains it. Created by
+/// [`DevTreeIndex::group_by_compatible`].
+#[derive(Clone)]
+pub struct DevTreeIndexCompatibleGroupIter<'a, 'i: 'a, 'dt: 'i>(CompatibleGroups<'a, 'i, 'dt>);
+
+#[derive(Clone)]
+enum CompatibleGroups<'a, 'i: 'a, 'dt: 'i> {
+    // The remaining entries of the index's compatible index, whose runs of equal strings are
+    // returned in sorted order.
+    Indexed(&'a DevTreeIndex<'i, 'dt>, &'i [CompatibleEntry<'i, 'dt>]),
+    // The strings of the index's properties, returned in the order they first appear.
+    Scan {
+        props: DevTreeIndexPropIter<'a, 'i, 'dt>,
+        // The `compatible` property whose strings are being returned, the remainder of its
+        // strings and the position of the next string within the property.
+        current: Option<(DevTreeIndexProp<'a, 'i, 'dt>, PropStrIter<'dt>, usize)>,
+    },
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexCompatibleGroupIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>) -> Self {
+        Self(CompatibleGroups::Scan {
+            props: index.props(),
+            current: None,
+        })
+    }
+
+    pub(super) fn indexed(
+        index: &'a DevTreeIndex<'i, 'dt>,
+        entries: &'i [CompatibleEntry<'i, 'dt>],
+    ) -> Self {
+        Self(CompatibleGroups::Indexed(index, entries))
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexCompatibleGroupIter<'a, 'i, 'dt> {
+    type Item = (&'dt str, DevTreeIndexCompatibleListIter<'dt, 'a, 'i, 'dt>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            CompatibleGroups::Indexed(index, entries) => loop {
+                let (string, _) = entries.first()?;
+                let len = entries.partition_point(|(s, _)| s == string);
+                let (run, rest) = entries.split_at(len);
+                *entries = rest;
+                // Strings which are not valid UTF-8 cannot be matched, so are skipped.
+                if let Ok(s) = from_utf8(run[0].0) {
+                    return Some((s, DevTreeIndexCompatibleListIter::indexed(index, run)));
+                }
+            },
+            CompatibleGroups::Scan { props, current } => loop {
+                if let Some((prop, strs, pos)) = current {
+                    // Strings which are not valid UTF-8 cannot be matched, so are skipped.
+                    for s in strs.by_ref() {
+                        *pos += 1;
+                        let s = match s {
+                            Ok(s) => s,
+                            Err(_) => continue,
+                        };
+                        // Only return the string at its first appearance: within the first node
+                        // which lists it, and at the first position within that node's property.
+                        let node = prop.node();
+                        let nodes = node.index().compatible_nodes(s);
+                        let first_node = nodes.clone().next().map(|first| first.id());
+                        if first_node == Some(node.id())
+                            && prop.iter_str().position(|e| e == Ok(s)) == Some(*pos - 1)
+                        {
+                            return Some((s, DevTreeIndexCompatibleListIter::scan(nodes)));
+                        }
+                    }
+                }
+                let prop = props.find(|prop| prop.name() == Ok("compatible"))?;
+                let strs = prop.iter_str();
+                *current = Some((prop, strs, 0));
+            },
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>) -> Self {
+        let mut this = Self::from_node(index.root());
+        this.initial_node_returned = false;
+        this
+    }
+
+    pub fn from_node(node: DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
+        Self {
+            index: node.index(),
+            initial_node_returned: true,
+            node: Some(node.node),
+            prop_idx: 0,
+        }
+    }
+
+    pub(super) fn from_first_child(node: &DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
+        Self {
+            index: node.index(),
+            initial_node_returned: true,
+            node: node.node.first_child(),
+            prop_idx: 0,
+        }
+    }
+
+    /// Bound this traversal to nodes nested at most `max_depth` deep. See
+    /// [`DevTreeIndexLimitIter`].
+    #[must_use]
+    pub fn limit_depth(self, max_depth: usize) -> DevTreeIndexLimitIter<'a, 'i, 'dt> {
+        DevTreeIndexLimitIter::new(self).limit_depth(max_depth)
+    }
+
+    /// Bound this traversal to at most `max_nodes` nodes. See [`DevTreeIndexLimitIter`].
+    #[must_use]
+    pub fn limit_nodes(self, max_nodes: usize) -> DevTreeIndexLimitIter<'a, 'i, 'dt> {
+        DevTreeIndexLimitIter::new(self).limit_nodes(max_nodes)
+    }
+
+    pub fn next_sibling(&mut self) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        self.node.map(|node| {
+            let cur = DevTreeIndexNode::new(self.index, node);
+            self.node = node.next_sibling();
+            cur
+        })
+    }
+
+    pub fn next_devtree_item(&mut self) -> Option<DevTreeIndexItem<'a, 'i, 'dt>> {
+        self.node.and_then(|cur_node| {
+            // Check if we've returned the first current node.
+            if !self.initial_node_returned {
+                self.initial_node_returned = true;
+                #[cfg(feature = "profiling")]
+                crate::profiling::record_node();
+                return Some(DevTreeIndexItem::Node(DevTreeIndexNode::new(
+                    self.index, cur_node,
+                )));
+            }
+
+            // First iterate through any properties if there are some available.
+            if self.prop_idx < cur_node.num_props() {
+                // Unsafe OK, we just checked the length of props.
+                let prop = unsafe { cur_node.prop_unchecked(self.prop_idx) };
+
+                self.prop_idx += 1;
+                return Some(DevTreeIndexItem::Prop(DevTreeIndexProp::new(
+                    self.index, &cur_node, prop,
+                )));
+            }
+
+            self.prop_idx = 0;
+
+            // Otherwise move on to the next node.
+            self.node = cur_node.next_dfs();
+            #[cfg(feature = "profiling")]
+            if self.node.is_some() {
+                crate::profiling::record_node();
+            }
+            self.node
+                .map(|cur_node| DevTreeIndexItem::Node(DevTreeIndexNode::new(self.index, cur_node)))
+        })
+    }
+
+    pub fn next_prop(&mut self) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+        loop {
+            match self.next() {
+                Some(item) => {
+                    if let Some(prop) = item.prop() {
+                        return Some(prop);
+                    }
+                    // Continue if a new node.
+                    continue;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    pub fn next_node(&mut self) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        loop {
+            match self.next() {
+                Some(item) => {
+                    if let Some(node) = item.node() {
+                        return Some(node);
+                    }
+                    // Continue if a new prop.
+                    continue;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    pub fn next_node_prop(&mut self) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+        // Don't advance past the current node once its properties are exhausted.
+        match self.node {
+            Some(node) if self.initial_node_returned && self.prop_idx >= node.num_props() => None,
+            _ => self.next().and_then(|item| item.prop()),
+        }
+    }
+
+    pub fn next_compatible_node<M: Matcher + ?Sized>(
+        &mut self,
+        matcher: &M,
+    ) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        #[cfg(feature = "profiling")]
+        let _search = crate::profiling::Search::begin();
+
+        // If there is another node, advance our iterator to that node.
+        self.next_node().and_then(|_| {
+            // Iterate through all remaining properties in the tree looking for the compatible
+            // string.
+            while let Some(prop) = self.next_prop() {
+                if prop.name() == Ok("compatible") && prop.strlist_matches(matcher) {
+                    return Some(prop.node());
+                }
+            }
+            None
+        })
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexItem<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_devtree_item()
+    }
+}
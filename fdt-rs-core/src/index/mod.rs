@@ -13,6 +13,17 @@
 //! which operate on an optimized index. Some operations such as finding a node's parent may
 //! require `O(n^2)` time. To avoid this issue, we provide this module and related utilites.
 //!
+//! # Iterator Costs
+//!
+//! Iterators over an index hold references into the index and never allocate. Most are four to
+//! eight machine words, so cloning one is a plain copy. Each call to `next` follows a link within
+//! the index rather than parsing the device tree, so iterating the whole index is `O(n)` in its
+//! number of nodes and properties. A [`DevTreeIndexNode`] is two words and a
+//! [`DevTreeIndexProp`] three.
+//!
+//! As with the [`crate::base`] iterators, [`iters::DevTreeIndexCanonicalIter`] stores the path of
+//! its current node inline and is several hundred bytes.
+//!
 //! # Examples
 //!
 //! The same [`IterableDevTree`] trait used to implement [`DevTree`] methods is also implemented by
@@ -38,16 +49,16 @@
 //! let devtree = unsafe { DevTree::new(fdt) }
 //!     .expect("Buffer does not contain a device tree.");
 //!
-//! // Get the layout required to build an index
-//! let layout = DevTreeIndex::get_layout(&devtree)
+//! // Get the buffer size required to build an index
+//! let size = DevTreeIndex::required_buffer_size(&devtree)
 //!     .expect("Failed to parse DTB - it is invalid.");
 //!
 //! // Allocate memory for the index.
 //! //
 //! // This could be performed without a dynamic allocation
-//! // if we allocated a static buffer or want to provide a
-//! // raw buffer into uninitialized memory.
-//! let mut vec = vec![0u8; layout.size() + layout.align()];
+//! // if we allocated a static buffer (see the `buffer` module)
+//! // or want to provide a raw buffer into uninitialized memory.
+//! let mut vec = vec![0u8; size];
 //! let raw_slice = vec.as_mut_slice();
 //!
 //! // Create the index of the device tree.
@@ -55,6 +66,8 @@
 //!
 //! ```
 //!
+//! [`crate::quick_index()`] performs each of these steps in a single call.
+//!
 #[cfg(doc)]
 use crate::doctest::*;
 
@@ -67,13 +80,18 @@ pub mod prop;
 #[doc(hidden)]
 pub mod tree;
 
+pub mod buffer;
+pub mod cache;
 pub mod iters;
+pub mod stitch;
 
 #[doc(inline)]
 pub use item::DevTreeIndexItem;
 #[doc(inline)]
-pub use node::DevTreeIndexNode;
+pub use node::{DevTreeIndexNode, NodeSnapshot, SubtreeStats};
 #[doc(inline)]
 pub use prop::DevTreeIndexProp;
 #[doc(inline)]
-pub use tree::DevTreeIndex;
+pub use stitch::{IndexFragment, MAX_INDEX_FRAGMENTS};
+#[doc(inline)]
+pub use tree::{DevTreeIndex, IndexProgress, PartialIndexError, SecondaryIndexes};
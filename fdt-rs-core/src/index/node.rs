@@ -0,0 +1,465 @@
+use core::ops::Range;
+use core::ptr;
+use core::str::from_utf8;
+
+use super::iters::{
+    DevTreeIndexIter, DevTreeIndexNodeDescendantIter, DevTreeIndexNodeItemIter,
+    DevTreeIndexNodePropIter, DevTreeIndexNodeSiblingIter, DevTreeIndexNodeStandardPropIter,
+};
+use super::tree::{DTINode, DevTreeIndex};
+use super::DevTreeIndexProp;
+use crate::base::iters::DevTreeIter;
+use crate::base::{DevTree, DevTreeNode};
+use crate::canonical::{CanonicalPath, MAX_CANONICAL_DEPTH};
+use crate::error::DevTreeError;
+use crate::priv_util::InvariantUnwrap;
+use crate::spec::{DuplicatePropPolicy, Status};
+use crate::util::{NodeRegIter, DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS};
+
+/// Counts of the contents of a node's subtree. Returned by [`DevTreeIndexNode::subtree_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SubtreeStats {
+    /// The number of descendant nodes (not including the node itself).
+    pub nodes: usize,
+    /// The number of properties of the node and its descendants.
+    pub props: usize,
+    /// The total length of the values of those properties, in bytes.
+    pub value_bytes: usize,
+}
+
+/// A copy of the frequently used fields of an index node which holds no borrow of the index.
+///
+/// Snapshots are plain data with a C layout, so they may be stored in statics, read from
+/// interrupt handlers or passed across an FFI boundary. [`DevTreeIndex::rehydrate()`] returns the
+/// node a snapshot was taken of. Created by [`DevTreeIndexNode::snapshot()`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeSnapshot {
+    /// The id of the node (see [`DevTreeIndexNode::id()`]).
+    pub id: usize,
+    /// The offset of the node's `FDT_BEGIN_NODE` token from the start of the device tree.
+    pub offset: usize,
+    /// The address of the node's name within the device tree. The name is not null terminated.
+    pub name_ptr: *const u8,
+    /// The length of the node's name in bytes.
+    pub name_len: usize,
+    /// The id of the node's first child, or [`NodeSnapshot::NO_NODE`] if it has none.
+    pub first_child: usize,
+    /// The id of the node's next sibling, or [`NodeSnapshot::NO_NODE`] if it has none.
+    pub next_sibling: usize,
+    /// The number of properties of the node.
+    pub prop_count: usize,
+}
+
+// Unsafe OK. The name pointer refers to the immutable device tree and is never dereferenced
+// through a snapshot.
+unsafe impl Send for NodeSnapshot {}
+unsafe impl Sync for NodeSnapshot {}
+
+impl NodeSnapshot {
+    /// The id stored in place of a missing first child or next sibling. No node has this id.
+    pub const NO_NODE: usize = usize::MAX;
+}
+
+#[derive(Clone, Copy)]
+pub struct DevTreeIndexNode<'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    pub(super) node: &'a DTINode<'i, 'dt>,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNode<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>, node: &'a DTINode<'i, 'dt>) -> Self {
+        Self { node, index }
+    }
+
+    pub fn index(&self) -> &'a DevTreeIndex<'i, 'dt> {
+        self.index
+    }
+
+    /// Returns the [`DevTree`] this node belongs to: the device tree its index was built over,
+    /// or the fragment of a [stitched](DevTreeIndex::new_stitched) index which added it.
+    #[inline]
+    #[must_use]
+    pub fn fdt(&self) -> &'a DevTree<'dt> {
+        self.index.tree_holding(self.node.name.as_ptr())
+    }
+
+    pub fn name(&self) -> Result<&'dt str, DevTreeError> {
+        from_utf8(self.node.name).map_err(DevTreeError::StrError)
+    }
+
+    pub fn siblings(&self) -> DevTreeIndexNodeSiblingIter<'a, 'i, 'dt> {
+        DevTreeIndexNodeSiblingIter::from(DevTreeIndexIter::from_node(*self))
+    }
+
+    /// Returns an iterator over this node's direct children.
+    pub fn children(&self) -> DevTreeIndexNodeSiblingIter<'a, 'i, 'dt> {
+        DevTreeIndexNodeSiblingIter::from(DevTreeIndexIter::from_first_child(self))
+    }
+
+    /// Returns an iterator over this node's descendant nodes (children, grandchildren, etc.), in
+    /// depth-first order. The node itself is not returned.
+    ///
+    /// Unlike [`DevTreeIndex::nodes`], iteration stops once depth-first traversal leaves this
+    /// node, so only the node's subtree is visited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let cpus = index.node_at_path("/cpus").unwrap();
+    /// assert!(cpus.descendants().any(|node| node.name().unwrap() == "cpu@0"));
+    /// assert!(cpus.descendants().all(|node| node.name().unwrap() != "memory@80000000"));
+    /// ```
+    #[must_use]
+    pub fn descendants(&self) -> DevTreeIndexNodeDescendantIter<'a, 'i, 'dt> {
+        DevTreeIndexNodeDescendantIter::new(self)
+    }
+
+    /// Returns the number of direct children of this node.
+    ///
+    /// The count is recorded while building the index, so this is O(1).
+    #[inline]
+    #[must_use]
+    pub fn child_count(&self) -> usize {
+        self.node.num_children()
+    }
+
+    /// Returns the number of properties of this node.
+    ///
+    /// The count is recorded while building the index, so this is O(1).
+    #[inline]
+    #[must_use]
+    pub fn prop_count(&self) -> usize {
+        self.node.num_props()
+    }
+
+    /// Returns the `n`th (zero based) direct child of this node, or `None` if `n` is not less than
+    /// [`child_count`](Self::child_count).
+    ///
+    /// This walks the node's children and is therefore O(n).
+    #[must_use]
+    pub fn child(&self, n: usize) -> Option<Self> {
+        if n >= self.child_count() {
+            return None;
+        }
+        self.children().nth(n)
+    }
+
+    pub fn props(&self) -> DevTreeIndexNodePropIter<'a, 'i, 'dt> {
+        #[cfg(feature = "coverage")]
+        crate::coverage::record(self.id());
+        DevTreeIndexNodePropIter(DevTreeIndexIter::from_node(*self))
+    }
+
+    /// Returns an iterator over this node's properties, classified by
+    /// [`StandardProp`](crate::spec::StandardProp).
+    pub fn standard_props(&self) -> DevTreeIndexNodeStandardPropIter<'a, 'i, 'dt> {
+        DevTreeIndexNodeStandardPropIter(self.props())
+    }
+
+    /// Returns the first property of this node named `name`.
+    ///
+    /// This is equivalent to [`prop_with_policy`](Self::prop_with_policy) with
+    /// [`DuplicatePropPolicy::FirstWins`].
+    pub fn prop(&self, name: &str) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+        self.props().find(|prop| prop.name() == Ok(name))
+    }
+
+    /// Returns the property of this node named `name`, resolving duplicate properties of that
+    /// name according to `policy`.
+    pub fn prop_with_policy(
+        &self,
+        name: &str,
+        policy: DuplicatePropPolicy,
+    ) -> Result<Option<DevTreeIndexProp<'a, 'i, 'dt>>, DevTreeError> {
+        let mut props = self.props().filter(|prop| prop.name() == Ok(name));
+        let first = match props.next() {
+            Some(prop) => prop,
+            None => return Ok(None),
+        };
+        match policy {
+            DuplicatePropPolicy::FirstWins => Ok(Some(first)),
+            DuplicatePropPolicy::LastWins => Ok(Some(props.last().unwrap_or(first))),
+            DuplicatePropPolicy::Strict => match props.next() {
+                Some(_) => Err(DevTreeError::DuplicateProperty),
+                None => Ok(Some(first)),
+            },
+        }
+    }
+
+    /// Returns the value of this node's `u32` property `name`, or `None` if it has no such
+    /// property.
+    pub fn prop_u32(&self, name: &str) -> Result<Option<u32>, DevTreeError> {
+        match self.prop(name) {
+            Some(prop) => prop.u32(0).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the `index`th 64-bit (two cell) value of this node's property `name`, or `None` if
+    /// it has no such property.
+    ///
+    /// A property too short to hold the value is reported as an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let memory = index.node_at_path("/memory").unwrap();
+    /// assert_eq!(memory.prop_u64_cells("reg", 0).unwrap(), Some(0x8000_0000));
+    /// assert_eq!(memory.prop_str("device_type").unwrap(), Some("memory"));
+    /// assert_eq!(memory.prop_u32("numa-node-id").unwrap(), None);
+    /// ```
+    pub fn prop_u64_cells(&self, name: &str, index: usize) -> Result<Option<u64>, DevTreeError> {
+        match self.prop(name) {
+            Some(prop) => {
+                let offset = index.checked_mul(8).ok_or(DevTreeError::InvalidOffset)?;
+                prop.u64(offset).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the (first) string of this node's property `name`, or `None` if it has no such
+    /// property.
+    pub fn prop_str(&self, name: &str) -> Result<Option<&'dt str>, DevTreeError> {
+        match self.prop(name) {
+            Some(prop) => prop.str().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the `(address, size)` entries of this node's `reg` property,
+    /// decoded using its parent's `#address-cells` and `#size-cells`.
+    ///
+    /// Cell counts the parent does not define take their default values
+    /// ([`DEFAULT_ADDRESS_CELLS`] and [`DEFAULT_SIZE_CELLS`]), as do those of the root node. The
+    /// iterator is empty if this node has no `reg` property.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let uart = index.node_at_path("/uart").unwrap();
+    /// let (base, size) = uart.reg().unwrap().next().unwrap().unwrap();
+    /// assert_eq!((base, size), (0x1000_0000, 0x100));
+    /// ```
+    pub fn reg(&self) -> Result<NodeRegIter<'dt, DevTreeIndexProp<'a, 'i, 'dt>>, DevTreeError> {
+        let (address_cells, size_cells) = match self.parent() {
+            Some(parent) => (
+                parent.prop_u32("#address-cells")?.unwrap_or(DEFAULT_ADDRESS_CELLS),
+                parent.prop_u32("#size-cells")?.unwrap_or(DEFAULT_SIZE_CELLS),
+            ),
+            None => (DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS),
+        };
+        Ok(NodeRegIter::new(self.prop("reg"), address_cells, size_cells))
+    }
+
+    /// Returns an iterator over this node's properties followed by its direct child nodes, in
+    /// document order.
+    pub fn items(&self) -> DevTreeIndexNodeItemIter<'a, 'i, 'dt> {
+        DevTreeIndexNodeItemIter::new(self)
+    }
+
+    /// Returns the byte offsets (from the start of the device tree) spanned by this node within
+    /// the structure block.
+    ///
+    /// The range begins at the node's `FDT_BEGIN_NODE` token and ends just after its
+    /// `FDT_END_NODE` token. It therefore covers all of the node's properties and descendants.
+    ///
+    /// The range lies within the node's own [`fdt()`](Self::fdt), so within a
+    /// [stitched](DevTreeIndex::new_stitched) index it does not cover the properties and
+    /// descendants added by fragments.
+    pub fn subtree_byte_range(&self) -> Range<usize> {
+        self.node.start_off()..self.node.end_off()
+    }
+
+    /// Returns the number of nodes, properties and property value bytes within this node's
+    /// subtree.
+    ///
+    /// The counts are computed from the index without parsing the device tree (or recording
+    /// accesses), in time linear in the size of the subtree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let chosen = index.node_at_path("/chosen").unwrap();
+    /// let stats = chosen.subtree_stats();
+    /// assert_eq!(stats.nodes, 0);
+    /// assert_eq!(stats.props, chosen.props().count());
+    /// assert!(stats.value_bytes < 64);
+    /// ```
+    #[must_use]
+    pub fn subtree_stats(&self) -> SubtreeStats {
+        let mut stats = SubtreeStats::default();
+        // The node itself is visited first, but is not counted.
+        for (descendants, node) in core::iter::once(*self).chain(self.descendants()).enumerate() {
+            let node = node.node;
+            stats.nodes = descendants;
+            stats.props += node.num_props();
+            for idx in 0..node.num_props() {
+                stats.value_bytes += unsafe { node.prop_unchecked(idx) }.propbuf.len();
+            }
+        }
+        stats
+    }
+
+    /// Returns true if this node's `status` property is `"okay"` (or the legacy `"ok"`), or if it
+    /// has no `status` property at all.
+    pub fn status_is_okay(&self) -> bool {
+        self.status() == Ok(Status::Okay)
+    }
+
+    /// Returns the parsed value of this node's `status` property ([`Status::Okay`] if absent).
+    pub fn status(&self) -> Result<Status<'dt>, DevTreeError> {
+        match self.prop("status") {
+            Some(prop) => Ok(Status::from(prop.str()?)),
+            None => Ok(Status::Okay),
+        }
+    }
+
+    /// Returns true if addresses on the bus described by this node can be translated to the
+    /// address space of its parent.
+    ///
+    /// A bus forwards addresses if it has a `ranges` property. An empty `ranges` property is an
+    /// identity mapping, while a missing one means the bus does not map its children's addresses
+    /// into its parent's address space (as with an I2C bus). The root node's address space is
+    /// the CPU's, so it is always translatable.
+    pub fn is_translatable(&self) -> bool {
+        self.node.parent().is_none() || self.prop("ranges").is_some()
+    }
+
+    /// Translate `address`, an address within the `reg` space of this node, into a CPU physical
+    /// address by walking the `ranges` of each bus above it.
+    ///
+    /// See [`translate_address`](crate::util::bus::translate_address).
+    pub fn translate_address(&self, address: u64) -> Result<Option<u64>, DevTreeError> {
+        crate::util::bus::translate_address(self, address)
+    }
+
+    /// Returns an id for this node which is unique among the nodes and properties of its index,
+    /// and less than [`DevTreeIndex::id_limit()`]. Ids are not contiguous.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.index.id_of(self.node)
+    }
+
+    /// Returns a [`NodeSnapshot`] of this node, which may be held without borrowing the index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let cpus = index.node_at_path("/cpus").unwrap();
+    /// let snapshot = cpus.snapshot();
+    /// assert_eq!(snapshot.name_len, "cpus".len());
+    /// assert_eq!(snapshot.first_child, cpus.children().next().unwrap().id());
+    ///
+    /// let node = index.rehydrate(&snapshot).unwrap();
+    /// assert_eq!(node.id(), cpus.id());
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self) -> NodeSnapshot {
+        let id_of = |node: Option<&DTINode>| match node {
+            Some(node) => self.index.id_of(node),
+            None => NodeSnapshot::NO_NODE,
+        };
+        NodeSnapshot {
+            id: self.id(),
+            offset: self.node.start_off(),
+            name_ptr: self.node.name.as_ptr(),
+            name_len: self.node.name.len(),
+            first_child: id_of(self.node.first_child()),
+            next_sibling: id_of(self.node.next_sibling()),
+            prop_count: self.node.num_props(),
+        }
+    }
+
+    /// Returns this node's parent, or `None` for the root node.
+    ///
+    /// The index stores each node's parent, so this does not search the tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+    /// let cpus = cpu.parent().unwrap();
+    /// assert_eq!(cpus.name().unwrap(), "cpus");
+    /// assert_eq!(cpus.parent().unwrap().name().unwrap(), "");
+    /// assert!(index.root().parent().is_none());
+    /// ```
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        self.node.parent().map(|par| Self::new(self.index, par))
+    }
+
+    /// Returns the path of this node.
+    ///
+    /// The path is built by following the node's parents. Nodes nested more deeply than
+    /// [`MAX_CANONICAL_DEPTH`] return [`DevTreeError::DepthLimitExceeded`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+    /// assert_eq!(cpu.path().unwrap().to_string(), "/cpus/cpu@0");
+    /// assert_eq!(index.root().path().unwrap().to_string(), "/");
+    /// ```
+    pub fn path(&self) -> Result<CanonicalPath<'dt>, DevTreeError> {
+        let mut names = [""; MAX_CANONICAL_DEPTH];
+        let mut depth = 0;
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            let slot = names
+                .get_mut(depth)
+                .ok_or(DevTreeError::DepthLimitExceeded)?;
+            *slot = node.name()?;
+            depth += 1;
+            node = parent;
+        }
+
+        let mut path = CanonicalPath::root();
+        for name in names[..depth].iter().rev() {
+            path.push(name)?;
+        }
+        Ok(path)
+    }
+
+    /// Returns the [`DevTreeNode`] handle for this node, allowing it to be used with the streaming
+    /// [`crate::base`] API. See [`DevTreeIndex::node_from_base`] for the reverse conversion.
+    ///
+    /// The node is parsed from its own [`fdt()`](Self::fdt), so nodes of a
+    /// [stitched](DevTreeIndex::new_stitched) index do not include what fragments added to them.
+    #[must_use]
+    pub fn to_base(&self) -> DevTreeNode<'a, 'dt> {
+        let fdt = self.fdt();
+        // The nodes of a fragment lie within its root node, which is not indexed.
+        let mut depth = if ptr::eq(fdt, self.index.fdt()) { 1 } else { 2 };
+        let mut node = self.node;
+        let in_fdt = |node: &DTINode| fdt.buf().as_ptr_range().contains(&node.name.as_ptr());
+        while let Some(parent) = node.parent().filter(|parent| in_fdt(parent)) {
+            depth += 1;
+            node = parent;
+        }
+
+        // Unsafe unwrap okay.
+        // The index was built by parsing the FDT_BEGIN_NODE token at this offset.
+        unsafe {
+            DevTreeIter::node_at(fdt, self.node.start_off(), depth)
+                .invariant_unwrap()
+                .invariant_unwrap()
+        }
+    }
+}
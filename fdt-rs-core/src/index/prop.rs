@@ -0,0 +1,193 @@
+use core::fmt::Write;
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::base::parse::ParsedProp;
+use crate::base::DevTree;
+use crate::error::DevTreeError;
+use crate::priv_util::InvariantUnwrap;
+use crate::spec::Phandle;
+
+use super::tree::{DTINode, DTIProp, DevTreeIndex};
+use super::DevTreeIndexNode;
+
+/// A wrapper around a device tree property within a [`DevTreeIndex`].
+///
+/// The most common [`PropReader`] methods are also provided inherently, so the trait need only
+/// be imported for the remaining methods or by generic code.
+#[derive(Clone)]
+pub struct DevTreeIndexProp<'a, 'i: 'a, 'dt: 'i> {
+    pub index: &'a DevTreeIndex<'i, 'dt>,
+    node: &'a DTINode<'i, 'dt>,
+    prop: &'a DTIProp<'dt>,
+}
+
+impl<'r, 'a: 'r, 'i: 'a, 'dt: 'i> DevTreeIndexProp<'a, 'i, 'dt> {
+    pub(super) fn new(
+        index: &'a DevTreeIndex<'i, 'dt>,
+        node: &'a DTINode<'i, 'dt>,
+        prop: &'a DTIProp<'dt>,
+    ) -> Self {
+        Self { index, node, prop }
+    }
+
+    /// Returns the [`DevTree`] this property belongs to: the device tree the index was built
+    /// over, or the fragment of a [stitched](DevTreeIndex::new_stitched) index which added it.
+    #[inline]
+    #[must_use]
+    pub fn tree(&self) -> &'a DevTree<'dt> {
+        self.index.tree_holding(self.prop.propbuf.as_ptr())
+    }
+
+    /// Returns the name of the property. See [`PropReader::name`].
+    #[inline]
+    pub fn name(&self) -> Result<&'dt str, DevTreeError> {
+        PropReader::name(self)
+    }
+
+    /// Returns the length of the property value. See [`PropReader::length`].
+    #[inline]
+    #[must_use]
+    pub fn length(&self) -> usize {
+        PropReader::length(self)
+    }
+
+    /// Returns the node which this property is contained within. See [`PropReader::node`].
+    #[inline]
+    #[must_use]
+    pub fn node(&self) -> DevTreeIndexNode<'a, 'i, 'dt> {
+        PropReader::node(self)
+    }
+
+    /// Reads a big-endian [`u32`] at `offset` within the value. See [`PropReader::u32`].
+    #[inline]
+    pub fn u32(&self, offset: usize) -> Result<u32, DevTreeError> {
+        PropReader::u32(self, offset)
+    }
+
+    /// Reads a big-endian [`u64`] at `offset` within the value. See [`PropReader::u64`].
+    #[inline]
+    pub fn u64(&self, offset: usize) -> Result<u64, DevTreeError> {
+        PropReader::u64(self, offset)
+    }
+
+    /// Reads the phandle at `offset` within the value. See [`PropReader::phandle`].
+    #[inline]
+    pub fn phandle(&self, offset: usize) -> Result<Phandle, DevTreeError> {
+        PropReader::phandle(self, offset)
+    }
+
+    /// Returns the value as a string. See [`PropReader::str`].
+    #[inline]
+    pub fn str(&self) -> Result<&'dt str, DevTreeError> {
+        PropReader::str(self)
+    }
+
+    /// Returns the raw value. See [`PropReader::raw`].
+    #[inline]
+    #[must_use]
+    pub fn raw(&self) -> &'dt [u8] {
+        PropReader::raw(self)
+    }
+
+    /// Returns true if the value's string list contains `s`. See
+    /// [`PropReader::strlist_contains`].
+    #[inline]
+    #[must_use]
+    pub fn strlist_contains(&self, s: &str) -> bool {
+        PropReader::strlist_contains(self, s)
+    }
+
+    /// Returns the name of the node this property belongs to.
+    #[inline]
+    pub fn node_name(&self) -> Result<&'dt str, DevTreeError> {
+        from_utf8(self.node.name).map_err(DevTreeError::StrError)
+    }
+
+    /// Writes the path of the node this property belongs to into `w`.
+    ///
+    /// The path is built by following the node's parents, so it is not limited in depth and
+    /// nothing is allocated. Each component is found by walking up from the node, so writing the
+    /// path takes time quadratic in its depth, but constant stack space.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let prop = index.node_at_path("/cpus/cpu@0").unwrap().prop("reg").unwrap();
+    /// assert_eq!(prop.node_name().unwrap(), "cpu@0");
+    ///
+    /// let mut path = String::new();
+    /// prop.node_path_write(&mut path).unwrap();
+    /// assert_eq!(path, "/cpus/cpu@0");
+    /// ```
+    pub fn node_path_write<W: Write>(&self, w: &mut W) -> Result<(), DevTreeError> {
+        if self.node.parent().is_none() {
+            w.write_str("/")?;
+            return Ok(());
+        }
+        write_components(self.node, w)
+    }
+
+    /// Returns an id for this property which is unique among the nodes and properties of its
+    /// index. See [`DevTreeIndexNode::id()`].
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.index.id_of(self.prop)
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> PropReader<'dt> for DevTreeIndexProp<'a, 'i, 'dt> {
+    type NodeType = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    #[inline]
+    fn propbuf(&self) -> &'dt [u8] {
+        #[cfg(feature = "coverage")]
+        crate::coverage::record(self.id());
+        self.prop.propbuf
+    }
+
+    #[inline]
+    fn nameoff(&self) -> usize {
+        self.prop.nameoff
+    }
+
+    #[inline]
+    fn fdt(&self) -> &DevTree<'dt> {
+        self.tree()
+    }
+
+    fn node(&self) -> DevTreeIndexNode<'a, 'i, 'dt> {
+        DevTreeIndexNode::new(self.index, self.node)
+    }
+}
+
+/// Writes `/name` for each node below the root along the path to `node`.
+///
+/// The ancestors are found by walking up from `node` once per component (rather than by
+/// recursing) so that a deeply nested tree cannot exhaust the stack.
+fn write_components<W: Write>(node: &DTINode, w: &mut W) -> Result<(), DevTreeError> {
+    let depth = core::iter::successors(node.parent(), |n| n.parent()).count();
+    for level in (0..depth).rev() {
+        let mut component = node;
+        for _ in 0..level {
+            // Unsafe OK. The node has `depth` ancestors, of which this is at most the last.
+            component = unsafe { component.parent().invariant_unwrap() };
+        }
+        w.write_char('/')?;
+        w.write_str(from_utf8(component.name)?)?;
+    }
+    Ok(())
+}
+
+impl<'dt> From<&ParsedProp<'dt>> for DTIProp<'dt> {
+    fn from(prop: &ParsedProp<'dt>) -> Self {
+        Self {
+            propbuf: prop.prop_buf,
+            nameoff: prop.name_offset,
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! Indexing a device tree along with fragments held in separate buffers.
+//!
+//! A system which applies overlays may hold each applied fragment in its own buffer rather than
+//! merge the fragments into a copy of the base device tree, which would require a buffer large
+//! enough for the whole merged tree. [`DevTreeIndex::new_stitched()`] builds a single index over
+//! the base device tree in which the nodes and properties of each [`IndexFragment`] appear at its
+//! target, as if the fragments had been merged.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//!
+//! let base = unsafe { DevTree::new(FDT) }.unwrap();
+//! // A fragment which adds a node to `/soc`. Here it is a copy of the doctest tree, whose
+//! // `/chosen` node is added. Each fragment must be held in a buffer of its own.
+//! let mut storage = vec![0u32; FDT.len() / 4];
+//! let copy = unsafe {
+//!     core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len())
+//! };
+//! copy.copy_from_slice(FDT);
+//! let fragments = [IndexFragment {
+//!     target: "/soc",
+//!     fdt: unsafe { DevTree::new(copy) }.unwrap(),
+//! }];
+//! let size = DevTreeIndex::required_buffer_size_stitched(&base, &fragments).unwrap();
+//! let mut buf = vec![0u8; size];
+//!
+//! let index = DevTreeIndex::new_stitched(base, &fragments, &mut buf).unwrap();
+//! let chosen = index.node_at_path("/soc/chosen").unwrap();
+//! assert!(core::ptr::eq(chosen.fdt(), &fragments[0].fdt));
+//! assert!(index.node_at_path("/chosen").is_some());
+//! ```
+use crate::prelude::*;
+
+use crate::base::parse::{DevTreeParseIter, ParsedProp, ParsedTok};
+use crate::base::DevTree;
+use crate::bounded::BoundedVec;
+use crate::error::DevTreeError;
+use crate::print::PrintTok;
+
+use super::tree::{tree_holding, SecondaryIndexes, TokenSource};
+use super::DevTreeIndex;
+
+/// The maximum number of fragments which [`DevTreeIndex::new_stitched()`] supports.
+pub const MAX_INDEX_FRAGMENTS: usize = 16;
+
+/// A device tree fragment to stitch into an index, see [`DevTreeIndex::new_stitched()`].
+#[derive(Clone, Copy, Debug)]
+pub struct IndexFragment<'f, 'dt> {
+    /// The path of the node of the base device tree which the fragment applies to, as accepted
+    /// by [`DevTree::node_at_path()`].
+    pub target: &'f str,
+    /// The fragment, whose root node holds the properties and child nodes to add to the target.
+    pub fdt: DevTree<'dt>,
+}
+
+/// The node of the base device tree a fragment applies to.
+struct Target {
+    // The offset of the node's `FDT_BEGIN_NODE` token within the base device tree.
+    offset: usize,
+    // The depth of the node while it is being parsed.
+    depth: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    // The properties of the fragment's root node.
+    Props,
+    // The child nodes of the fragment's root node.
+    Children,
+}
+
+/// The tokens of a fragment which are being added to its target.
+struct Injection<'a, 'dt> {
+    fragment: usize,
+    section: Section,
+    iter: DevTreeParseIter<'a, 'dt>,
+    // The number of open nodes of the fragment, including its root node.
+    depth: usize,
+}
+
+/// The fragments which remain to be added to the target node at `depth`.
+struct Pending {
+    section: Section,
+    depth: usize,
+    next_fragment: usize,
+}
+
+/// A token source which adds the tokens of each fragment to those of its target within the base
+/// device tree.
+struct StitchedSource<'a, 'dt> {
+    base: DevTreeParseIter<'a, 'dt>,
+    fragments: &'a [IndexFragment<'a, 'dt>],
+    targets: BoundedVec<Target, MAX_INDEX_FRAGMENTS>,
+    // The number of open nodes of the base device tree.
+    depth: usize,
+    // Whether the properties of a target node are being parsed.
+    in_target_header: bool,
+    // A token of the base device tree held back while a section of fragments is added before it.
+    held: Option<(usize, ParsedTok<'dt>, Section)>,
+    pending: Option<Pending>,
+    injection: Option<Injection<'a, 'dt>>,
+    offset: usize,
+}
+
+impl<'a, 'dt: 'a> StitchedSource<'a, 'dt> {
+    fn new(
+        base: &'a DevTree<'dt>,
+        fragments: &'a [IndexFragment<'a, 'dt>],
+    ) -> Result<Self, DevTreeError> {
+        if fragments.len() > MAX_INDEX_FRAGMENTS {
+            return Err(DevTreeError::TooManyFragments);
+        }
+        let base_buf = base.buf().as_ptr_range();
+        let mut targets = BoundedVec::new();
+        for fragment in fragments {
+            // Nodes and properties are attributed to the device tree whose buffer holds them.
+            let buf = fragment.fdt.buf().as_ptr_range();
+            if buf.start < base_buf.end && base_buf.start < buf.end {
+                return Err(DevTreeError::InvalidParameter(
+                    "Fragment shares a buffer with the device tree",
+                ));
+            }
+            if fragment.fdt.root()?.is_none() {
+                return Err(DevTreeError::ParseError);
+            }
+            let node = base
+                .node_at_path(fragment.target)?
+                .ok_or(DevTreeError::InvalidParameter("Fragment target not found"))?;
+            let offset = node.node_offset().ok_or(DevTreeError::ParseError)?;
+            targets
+                .push(Target {
+                    offset,
+                    depth: None,
+                })
+                .map_err(|_| DevTreeError::TooManyFragments)?;
+        }
+        Ok(Self {
+            base: DevTreeParseIter::new(base),
+            fragments,
+            targets,
+            depth: 0,
+            in_target_header: false,
+            held: None,
+            pending: None,
+            injection: None,
+            offset: base.off_dt_struct(),
+        })
+    }
+
+    /// Returns the fragments which apply to the open target node at `depth`, from `from`.
+    fn fragments_at(&self, depth: usize, from: usize) -> impl Iterator<Item = usize> + '_ {
+        (from..self.targets.len()).filter(move |&idx| self.targets[idx].depth == Some(depth))
+    }
+
+    /// Returns true if a fragment after `after` which applies to the open target node at
+    /// `depth` has a property named `name`.
+    fn overridden(
+        &self,
+        depth: usize,
+        after: Option<usize>,
+        name: &str,
+    ) -> Result<bool, DevTreeError> {
+        let from = after.map_or(0, |idx| idx + 1);
+        for idx in self.fragments_at(depth, from) {
+            let root = self.fragments[idx]
+                .fdt
+                .root()?
+                .ok_or(DevTreeError::ParseError)?;
+            if root.prop(name)?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn prop_name(fdt: &DevTree<'dt>, prop: &ParsedProp<'dt>) -> Result<&'dt str, DevTreeError> {
+        fdt.string_at(prop.name_offset)
+    }
+
+    /// Returns the next token of the fragments being added to a target, if any remain.
+    fn next_injected(&mut self) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError> {
+        loop {
+            if let Some(mut injection) = self.injection.take() {
+                if let Some(tok) = self.next_of(&mut injection)? {
+                    self.offset = injection.iter.offset;
+                    self.injection = Some(injection);
+                    return Ok(Some(tok));
+                }
+            }
+            let pending = match &self.pending {
+                Some(pending) => pending,
+                None => return Ok(None),
+            };
+            let (section, depth, from) = (pending.section, pending.depth, pending.next_fragment);
+            let next = self.fragments_at(depth, from).next();
+            let fragment = match next {
+                Some(fragment) => fragment,
+                None => {
+                    self.pending = None;
+                    return Ok(None);
+                }
+            };
+            if let Some(pending) = &mut self.pending {
+                pending.next_fragment = fragment + 1;
+            }
+            let mut iter = DevTreeParseIter::new(&self.fragments[fragment].fdt);
+            // Move past the fragment's root node.
+            while let Some(tok) = iter.next()? {
+                if let ParsedTok::BeginNode(_) = tok {
+                    break;
+                }
+            }
+            self.injection = Some(Injection {
+                fragment,
+                section,
+                iter,
+                depth: 1,
+            });
+        }
+    }
+
+    /// Returns the next token of `injection`'s section of its fragment.
+    fn next_of(
+        &self,
+        injection: &mut Injection<'a, 'dt>,
+    ) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError> {
+        loop {
+            let offset = injection.iter.offset;
+            let tok = match injection.iter.next()? {
+                Some(tok) => tok,
+                None => return Ok(None),
+            };
+            match (injection.section, tok) {
+                (_, ParsedTok::Nop) => continue,
+                (Section::Props, ParsedTok::Prop(prop)) => {
+                    let fdt = &self.fragments[injection.fragment].fdt;
+                    let name = Self::prop_name(fdt, &prop)?;
+                    // A later fragment applied to the same node replaces the property.
+                    if !self.overridden(self.depth, Some(injection.fragment), name)? {
+                        return Ok(Some((offset, tok)));
+                    }
+                }
+                (Section::Props, _) => return Ok(None),
+                // The properties of the root node were added along with the target's own.
+                (Section::Children, ParsedTok::Prop(_)) if injection.depth == 1 => continue,
+                (Section::Children, ParsedTok::BeginNode(_)) => {
+                    injection.depth += 1;
+                    return Ok(Some((offset, tok)));
+                }
+                (Section::Children, ParsedTok::EndNode) => {
+                    injection.depth -= 1;
+                    if injection.depth == 0 {
+                        return Ok(None);
+                    }
+                    return Ok(Some((offset, tok)));
+                }
+                (Section::Children, _) => return Ok(Some((offset, tok))),
+            }
+        }
+    }
+
+    /// Begin adding the given section of each fragment which applies to the current node before
+    /// the base device tree's token `tok`.
+    fn inject_before(&mut self, section: Section, offset: usize, tok: ParsedTok<'dt>) {
+        self.held = Some((offset, tok, section));
+        self.pending = Some(Pending {
+            section,
+            depth: self.depth,
+            next_fragment: 0,
+        });
+    }
+}
+
+impl<'a, 'dt: 'a> TokenSource<'dt> for StitchedSource<'a, 'dt> {
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError> {
+        loop {
+            if let Some(tok) = self.next_injected()? {
+                return Ok(Some(tok));
+            }
+            let (offset, tok, injected) = match self.held.take() {
+                Some((offset, tok, section)) => (offset, tok, Some(section)),
+                None => {
+                    let offset = self.base.offset;
+                    match self.base.next()? {
+                        Some(tok) => (offset, tok, None),
+                        None => return Ok(None),
+                    }
+                }
+            };
+            match tok {
+                ParsedTok::Prop(prop) if self.in_target_header => {
+                    let name = Self::prop_name(self.base.fdt, &prop)?;
+                    // The property is replaced by that of a fragment.
+                    if self.overridden(self.depth, None, name)? {
+                        continue;
+                    }
+                }
+                // The target's properties are followed by those of its fragments.
+                ParsedTok::BeginNode(_) | ParsedTok::EndNode if self.in_target_header => {
+                    self.in_target_header = false;
+                    self.inject_before(Section::Props, offset, tok);
+                    continue;
+                }
+                ParsedTok::EndNode if self.fragments_at(self.depth, 0).next().is_some() => {
+                    // The target's children are followed by those of its fragments, after which
+                    // the fragments no longer apply to an open node.
+                    if injected != Some(Section::Children) {
+                        self.inject_before(Section::Children, offset, tok);
+                        continue;
+                    }
+                    let closing = self.depth;
+                    for target in self.targets.iter_mut() {
+                        if target.depth == Some(closing) {
+                            target.depth = None;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            match tok {
+                ParsedTok::BeginNode(_) => {
+                    self.depth += 1;
+                    for target in self.targets.iter_mut() {
+                        if target.offset == offset {
+                            target.depth = Some(self.depth);
+                            self.in_target_header = true;
+                        }
+                    }
+                }
+                ParsedTok::EndNode => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+            self.offset = self.base.offset;
+            return Ok(Some((offset, tok)));
+        }
+    }
+}
+
+/// Returns the tokens to print of `base` with each of `fragments` stitched in at its target.
+pub(super) fn print_tokens<'a, 'dt: 'a>(
+    base: &'a DevTree<'dt>,
+    fragments: &'a [IndexFragment<'a, 'dt>],
+) -> crate::error::Result<impl FallibleIterator<Item = PrintTok<'dt>, Error = DevTreeError> + 'a> {
+    let mut source = StitchedSource::new(base, fragments)?;
+    let tokens = core::iter::from_fn(move || source.next_token().transpose());
+    let tokens = fallible_iterator::convert(tokens).filter_map(move |(_, tok)| {
+        let fdt = match &tok {
+            ParsedTok::Prop(prop) => tree_holding(base, fragments, prop.prop_buf.as_ptr()),
+            _ => base,
+        };
+        Ok(PrintTok::new(fdt, tok))
+    });
+    Ok(tokens)
+}
+
+impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
+    /// Returns the size in bytes of a buffer which is always sufficient to index `fdt` along
+    /// with `fragments` using [`DevTreeIndex::new_stitched()`].
+    pub fn required_buffer_size_stitched(
+        fdt: &'i DevTree<'dt>,
+        fragments: &'i [IndexFragment<'i, 'dt>],
+    ) -> Result<usize, DevTreeError> {
+        let mut size = Self::required_buffer_size(fdt)?;
+        for fragment in fragments {
+            size += Self::get_layout(&fragment.fdt)?.size();
+        }
+        Ok(size)
+    }
+
+    /// Build an index of `fdt` in which each of `fragments` is stitched in at its target, as
+    /// [`DevTreeIndex::new()`] does for a single device tree.
+    ///
+    /// The properties of a fragment's root node are added to its target node, replacing any
+    /// properties of the same name, and the children of its root node follow those of the
+    /// target. Fragments are added in order, so later fragments replace the properties of
+    /// earlier ones applied to the same node. Children are added as they are, and are not merged
+    /// with children of the same name. Each target must be a node of `fdt`.
+    ///
+    /// The index refers into `fdt` and each fragment, so no merged copy of the device tree is
+    /// made. [`DevTreeIndexNode::fdt()`] and [`DevTreeIndexProp::tree()`] return the device tree
+    /// which holds each node and property. The fragments are printed along with `fdt` by
+    /// [`DevTreeIndex::write_dts()`] and the other printing methods, but
+    /// [`DevTreeIndex::lint()`] rejects a stitched index.
+    ///
+    /// The following errors are returned:
+    ///
+    /// * [`DevTreeError::TooManyFragments`] if there are more than [`MAX_INDEX_FRAGMENTS`]
+    ///   fragments.
+    /// * [`DevTreeError::InvalidParameter`] if a fragment's target is not found within `fdt`, or
+    ///   if a fragment's buffer overlaps that of `fdt`.
+    /// * [`DevTreeError::NotEnoughMemory`] if `buf` is too small, which a buffer of
+    ///   [`DevTreeIndex::required_buffer_size_stitched()`] bytes never is.
+    ///
+    /// [`DevTreeIndexNode::fdt()`]: super::DevTreeIndexNode::fdt
+    /// [`DevTreeIndexProp::tree()`]: super::DevTreeIndexProp::tree
+    pub fn new_stitched(
+        fdt: DevTree<'dt>,
+        fragments: &'i [IndexFragment<'i, 'dt>],
+        buf: &'i mut [u8],
+    ) -> Result<Self, DevTreeError> {
+        let base = fdt;
+        let mut iter = StitchedSource::new(&base, fragments)?;
+        Ok(Self::build(fdt, buf, &mut iter, SecondaryIndexes::default(), fragments)?.0)
+    }
+}
@@ -0,0 +1,1460 @@
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::ptr::{self, null_mut};
+
+use crate::prelude::*;
+
+use super::iters::{
+    DevTreeIndexCanonicalIter, DevTreeIndexCompatibleGroupIter, DevTreeIndexCompatibleListIter,
+    DevTreeIndexCompatibleNodeIter, DevTreeIndexIter, DevTreeIndexLargePropIter,
+    DevTreeIndexNodeIter, DevTreeIndexPathMatchIter, DevTreeIndexPropIter,
+    DevTreeIndexPropValueIter,
+};
+use super::stitch::{print_tokens, IndexFragment};
+use super::{DevTreeIndexItem, DevTreeIndexNode, NodeSnapshot};
+use crate::base::item::DevTreeItem;
+use crate::base::iters::DevTreeIter;
+use crate::base::parse::{
+    DevTreeParseIter, DevTreeRawTokenIter, ParsedBeginNode, ParsedProp, ParsedTok,
+};
+use crate::base::{DevTree, DevTreeNode};
+use crate::bounded::Collected;
+use crate::canonical::PropOrder;
+use crate::common::path::{component_matches, split_alias};
+use crate::error::DevTreeError;
+use crate::lint::DevTreeLintIter;
+use crate::matcher::Matcher;
+use crate::priv_util::InvariantUnwrap;
+
+unsafe fn aligned_ptr_in<T>(buf: &mut [u8], offset: usize) -> Result<*mut T, DevTreeError> {
+    // Get the aligned offset
+    let ptr = buf.as_ptr().add(offset);
+    let aligned_offset = offset + ptr.align_offset(align_of::<T>());
+
+    let t_slice_ref = buf
+        .get_mut(aligned_offset..aligned_offset + size_of::<T>())
+        .ok_or(DevTreeError::NotEnoughMemory)?;
+    Ok(t_slice_ref.as_mut_ptr() as *mut T)
+}
+
+// Node and property ids are their offsets within the index buffer in units of their alignment.
+// Every entry is aligned and at least this large, so no two entries share an id.
+const ID_UNIT: usize = align_of::<usize>();
+
+pub(super) struct DTIProp<'dt> {
+    pub propbuf: &'dt [u8],
+    pub nameoff: usize,
+}
+
+#[derive(Debug)]
+pub struct DevTreeIndex<'i, 'dt: 'i> {
+    fdt: DevTree<'dt>,
+    // The fragments stitched into the index by `new_stitched()`.
+    fragments: &'i [IndexFragment<'i, 'dt>],
+    root: *const DTINode<'i, 'dt>,
+    alignment_padding: usize,
+    index_buf: core::ops::Range<*const u8>,
+    node_count: usize,
+    prop_count: usize,
+    // Every node, sorted by name, if built by `new_with_name_index()`.
+    name_index: Option<&'i [*const DTINode<'i, 'dt>]>,
+    // Each string of each node's `compatible` properties, sorted by string.
+    compatible_index: Option<&'i [CompatibleEntry<'i, 'dt>]>,
+}
+
+/// A string of a node's `compatible` property (excluding its null byte), and the node.
+pub(super) type CompatibleEntry<'i, 'dt> = (&'dt [u8], *const DTINode<'i, 'dt>);
+
+/// The secondary indexes to build along with an index, see
+/// [`DevTreeIndex::new_with_secondary_indexes()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SecondaryIndexes {
+    /// Sort the nodes by name for [`DevTreeIndex::find_node_by_name()`].
+    pub names: bool,
+    /// Sort the nodes by each string of their `compatible` properties for
+    /// [`DevTreeIndex::find_node_by_compatible()`] and [`DevTreeIndex::nodes_by_compatible()`].
+    pub compatible: bool,
+}
+
+/// Returns the device tree holding the bytes at `ptr`: the fragment whose buffer holds them, or
+/// otherwise `fdt`.
+pub(super) fn tree_holding<'a, 'dt>(
+    fdt: &'a DevTree<'dt>,
+    fragments: &'a [IndexFragment<'a, 'dt>],
+    ptr: *const u8,
+) -> &'a DevTree<'dt> {
+    fragments
+        .iter()
+        .map(|fragment| &fragment.fdt)
+        .find(|fragment| fragment.buf().as_ptr_range().contains(&ptr))
+        .unwrap_or(fdt)
+}
+
+/// Split `count` aligned, uninitialized entries of `T` from the front of `tail`.
+fn alloc_entries<T>(tail: &mut &mut [u8], count: usize) -> Result<*mut T, DevTreeError> {
+    let buf = core::mem::take(tail);
+    let start = buf.as_ptr().align_offset(align_of::<T>());
+    let end = count
+        .checked_mul(size_of::<T>())
+        .and_then(|len| len.checked_add(start))
+        .filter(|end| *end <= buf.len())
+        .ok_or(DevTreeError::NotEnoughMemory)?;
+    let (slot, rest) = buf.split_at_mut(end);
+    *tail = rest;
+    Ok(slot[start..].as_mut_ptr() as *mut T)
+}
+
+/// The error returned by [`DevTreeIndex::new_partial()`].
+///
+/// When the index ran out of memory, [`PartialIndexError::index`] holds the index of the nodes
+/// and properties which were processed before the buffer was exhausted.
+#[derive(Debug)]
+pub struct PartialIndexError<'i, 'dt: 'i> {
+    /// The partially built index, present if the error was [`DevTreeError::NotEnoughMemory`] and
+    /// the root node was indexed.
+    pub index: Option<DevTreeIndex<'i, 'dt>>,
+    /// The error which stopped the index from being built.
+    pub error: DevTreeError,
+}
+
+impl<'i, 'dt: 'i> From<DevTreeError> for PartialIndexError<'i, 'dt> {
+    fn from(error: DevTreeError) -> Self {
+        Self { index: None, error }
+    }
+}
+
+impl<'i, 'dt: 'i> From<PartialIndexError<'i, 'dt>> for DevTreeError {
+    fn from(e: PartialIndexError<'i, 'dt>) -> Self {
+        e.error
+    }
+}
+
+impl<'i, 'dt: 'i> core::fmt::Display for PartialIndexError<'i, 'dt> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+/// The progress of building an index, reported by [`DevTreeIndex::new_with_progress()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexProgress {
+    /// The number of bytes of the structure block which have been indexed.
+    pub processed: usize,
+    /// The size of the structure block, see [`DevTree::size_dt_struct()`].
+    pub total: usize,
+}
+
+struct DTIBuilder<'i, 'dt: 'i> {
+    buf: &'i mut [u8],
+    cur_node: *mut DTINode<'i, 'dt>,
+    prev_new_node: *mut DTINode<'i, 'dt>,
+    front_off: usize,
+
+    // Devtree Props may only occur before child nodes.
+    // We'll call this the "node_header".
+    in_node_header: bool,
+}
+
+// Fields are ordered by how often traversals read them, and counts and offsets are stored as
+// `u32` (the header limits a device tree to 4GiB), so that a node fits within a single 64 byte
+// cache line on 64-bit targets. This shrank the node from 72 to 56 bytes. The effect on
+// traversals may be measured with the `Index DFS` and `Index Prop Iter` benchmarks.
+#[repr(C)]
+pub(super) struct DTINode<'i, 'dt: 'i> {
+    first_child: *const Self,
+    // `next` is either
+    // 1. the next sibling node
+    // 2. the next node in DFS (some higher up node)
+    // It is 1 if (*next).parent == self.parent, otherwise it is 2.
+    next: *const Self,
+    pub(super) name: &'dt [u8],
+
+    // NOTE: We store props like C arrays. Props are a packed array after each node.
+    // This is the number of props after this node in memory.
+    num_props: u32,
+    // Number of direct children of this node.
+    num_children: u32,
+
+    parent: *const Self,
+
+    // Offsets of this node's BeginNode token and of the token following its EndNode token.
+    start_off: u32,
+    end_off: u32,
+    _index: PhantomData<&'i u8>,
+}
+
+impl<'i, 'dt: 'i> DTINode<'i, 'dt> {
+    #[inline]
+    pub fn num_props(&self) -> usize {
+        self.num_props as usize
+    }
+
+    #[inline]
+    pub fn num_children(&self) -> usize {
+        self.num_children as usize
+    }
+
+    #[inline]
+    pub fn start_off(&self) -> usize {
+        self.start_off as usize
+    }
+
+    #[inline]
+    pub fn end_off(&self) -> usize {
+        self.end_off as usize
+    }
+
+    pub unsafe fn prop_unchecked(&self, idx: usize) -> &'i DTIProp<'dt> {
+        #[cfg(feature = "checked")]
+        assert!(idx < self.num_props(), "property index out of bounds");
+        // Get the pointer to the props after ourself.
+        let prop_ptr = (self as *const Self).add(1) as *const DTIProp;
+        &*prop_ptr.add(idx)
+    }
+
+    pub fn first_child(&self) -> Option<&'i DTINode<'i, 'dt>> {
+        unsafe { self.first_child.as_ref() }
+    }
+
+    pub fn next_dfs(&self) -> Option<&'i DTINode<'i, 'dt>> {
+        unsafe { self.first_child().or_else(|| self.next.as_ref()) }
+    }
+
+    pub fn next_sibling(&self) -> Option<&'i DTINode<'i, 'dt>> {
+        unsafe {
+            self.next.as_ref().and_then(|next| {
+                if next.parent == self.parent {
+                    return Some(next);
+                }
+                None
+            })
+        }
+    }
+
+    pub fn parent(&self) -> Option<&'i DTINode<'i, 'dt>> {
+        unsafe { self.parent.as_ref() }
+    }
+}
+
+/// A source of structure block tokens for the index builder.
+pub(super) trait TokenSource<'dt> {
+    /// Returns the offset following the most recently returned token.
+    fn offset(&self) -> usize;
+    /// Returns the next token and its offset.
+    fn next_token(&mut self) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError>;
+}
+
+impl<'a, 'dt: 'a> TokenSource<'dt> for DevTreeParseIter<'a, 'dt> {
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError> {
+        let offset = self.offset;
+        Ok(self.next()?.map(|tok| (offset, tok)))
+    }
+}
+
+impl<'dt> TokenSource<'dt> for DevTreeRawTokenIter<'dt> {
+    fn offset(&self) -> usize {
+        DevTreeRawTokenIter::offset(self)
+    }
+
+    #[inline]
+    fn next_token(&mut self) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError> {
+        let offset = DevTreeRawTokenIter::offset(self);
+        Ok(self.next().map(|tok| (offset, tok)))
+    }
+}
+
+/// A token source which reports progress after each node it begins, and once it is exhausted.
+struct ProgressSource<S, F> {
+    inner: S,
+    start: usize,
+    total: usize,
+    progress: F,
+}
+
+impl<'dt, S: TokenSource<'dt>, F: FnMut(IndexProgress)> TokenSource<'dt> for ProgressSource<S, F> {
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, ParsedTok<'dt>)>, DevTreeError> {
+        let tok = self.inner.next_token()?;
+        let processed = match tok {
+            Some((_, ParsedTok::BeginNode(_))) => self.inner.offset().saturating_sub(self.start),
+            Some(_) => return Ok(tok),
+            None => self.total,
+        };
+        (self.progress)(IndexProgress {
+            processed: processed.min(self.total),
+            total: self.total,
+        });
+        Ok(tok)
+    }
+}
+
+impl<'i, 'dt: 'i> DTIBuilder<'i, 'dt> {
+    fn allocate_aligned_ptr<T>(&mut self) -> Result<*mut T, DevTreeError> {
+        unsafe {
+            let ptr = aligned_ptr_in::<T>(self.buf, self.front_off)?;
+            self.front_off = ptr.add(1) as usize - self.buf.as_ptr() as usize;
+            Ok(ptr)
+        }
+    }
+
+    pub fn parsed_node(
+        &mut self,
+        node: &ParsedBeginNode<'dt>,
+        offset: usize,
+    ) -> Result<(), DevTreeError> {
+        unsafe {
+            self.in_node_header = true;
+
+            let new_ptr = self.allocate_aligned_ptr::<DTINode>()?;
+            let parent = self.cur_node;
+
+            // Write the data
+            *new_ptr = DTINode {
+                // set by the next node we create
+                first_child: null_mut(),
+                // set by the next node we create
+                next: null_mut(),
+
+                name: node.name,
+                num_props: 0,
+                num_children: 0,
+                parent,
+
+                // Offsets lie within the device tree, whose size is a u32 header field.
+                start_off: offset as u32,
+                // set once we parse the node's end
+                end_off: offset as u32,
+                _index: PhantomData,
+            };
+
+            if !parent.is_null() {
+                debug_assert!(
+                    !self.prev_new_node.is_null(),
+                    "cur_node should not have been initialized without also intializing \
+                    prev_new_node"
+                );
+
+                // The parent's `next` temporarily tracks its most recently created child (our
+                // previous sibling). Read it before linking prev_new_node, which may be the
+                // parent itself.
+                let prev_sibling = (*parent).next as *mut DTINode;
+                (*self.prev_new_node).next = new_ptr;
+                if !prev_sibling.is_null() {
+                    (*prev_sibling).next = new_ptr;
+                }
+                (*parent).next = new_ptr;
+                (*parent).num_children += 1;
+
+                // If this new node is the first node that follows the current one, it is the current's
+                // first child.
+                if (*parent).first_child.is_null() {
+                    (*parent).first_child = new_ptr;
+                }
+            }
+
+            // Save the new node ptr.
+            self.cur_node = new_ptr;
+            self.prev_new_node = new_ptr;
+        }
+
+        Ok(())
+    }
+
+    pub fn parsed_prop(&mut self, prop: &ParsedProp<'dt>) -> Result<(), DevTreeError> {
+        if !self.in_node_header {
+            return Err(DevTreeError::ParseError);
+        }
+
+        unsafe {
+            let new_ptr = self.allocate_aligned_ptr::<DTIProp>()?;
+            (*self.cur_node).num_props += 1;
+            *new_ptr = DTIProp::from(prop);
+        }
+
+        Ok(())
+    }
+
+    pub fn parsed_end_node(&mut self, end_offset: usize) -> Result<(), DevTreeError> {
+        // There were more EndNode tokens than BeginNode ones.
+        if self.cur_node.is_null() {
+            return Err(DevTreeError::ParseError);
+        }
+        // Unsafe is Ok.
+        // Lifetime : self.cur_node is a pointer into a buffer with the same lifetime as self
+        // Alignment: parsed_node verifies alignment when creating self.cur_node
+        // NonNull  : We check that self.cur_node is non-null above
+        // Mutability: We cast from a *const to a *mut.
+        //             We're the only thread which has access to the buffer at this time, so this
+        //             is thread-safe.
+        unsafe {
+            (*self.cur_node).end_off = end_offset as u32;
+
+            // Change the current node back to the parent.
+            self.cur_node = (*self.cur_node).parent as *mut DTINode;
+        }
+
+        // We are no longer in a node header.
+        // We are either going to see a new node next or parse another end_node.
+        self.in_node_header = false;
+
+        Ok(())
+    }
+
+    /// Ends every node which is still open at `end_offset`, where building stopped.
+    pub fn truncate(&mut self, end_offset: usize) {
+        // Unsafe is Ok, see parsed_end_node.
+        unsafe {
+            while let Some(node) = self.cur_node.as_mut() {
+                node.end_off = end_offset as u32;
+                self.cur_node = node.parent as *mut DTINode;
+            }
+        }
+    }
+}
+
+impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
+    // Note: Our parsing method is unsafe - particularly due to its use of pointer arithmetic.
+    //
+    // We decide this is worth it for the following reasons:
+    // - It requires no allocator.
+    // - It has incredibly low overhead.
+    //   - This parsing method only requires a single allocation. (The buffer given as buf)
+    //   - This parsing method only requires a single iteration over the FDT.
+    // - It is very easy to test in isolation; parsing is entirely enclosed to this module.
+    unsafe fn init_builder<S: TokenSource<'dt>>(
+        buf: &'i mut [u8],
+        iter: &mut S,
+    ) -> Result<DTIBuilder<'i, 'dt>, DevTreeError> {
+        let mut builder = DTIBuilder {
+            front_off: 0,
+            buf,
+            cur_node: null_mut(),
+            prev_new_node: null_mut(),
+            in_node_header: false,
+        };
+
+        while let Some((offset, tok)) = iter.next_token()? {
+            match tok {
+                ParsedTok::BeginNode(node) => {
+                    builder.parsed_node(&node, offset)?;
+                    return Ok(builder);
+                }
+                ParsedTok::Nop => continue,
+                _ => break,
+            }
+        }
+        Err(DevTreeError::ParseError)
+    }
+
+    pub fn get_layout(fdt: &'i DevTree<'dt>) -> Result<Layout, DevTreeError> {
+        // Size may require alignment of DTINode.
+        let mut size = 0usize;
+
+        // We assert this because it makes size calculations easier.
+        // We don't have to worry about re-aligning between props and nodes.
+        // If they didn't have the same alignment, we would have to keep track
+        // of the last node and re-align depending on the last seen type.
+        //
+        // E.g. If we saw one node, two props, and then two nodes:
+        //
+        // size = \
+        // align_of::<DTINode> + size_of::<DTINode>
+        // + align_of::<DTIProp> + size_of::<DTIProp>
+        // + size_of::<DTIProp>
+        // + size_of::<DTIProp>
+        // + align_of::<DTINode> + size_of::<DTINode>
+        // + size_of::<DTINode>
+        const_assert_eq!(align_of::<DTINode>(), align_of::<DTIProp>());
+
+        let mut iter = DevTreeIter::new(fdt);
+        while let Some(item) = iter.next()? {
+            match item {
+                DevTreeItem::Node(_) => size += size_of::<DTINode>(),
+                DevTreeItem::Prop(_) => size += size_of::<DTIProp>(),
+            }
+        }
+
+        // Unsafe okay.
+        // - Size is not likely to be usize::MAX. (There's no way we find that many nodes.)
+        // - Align is a result of align_of, so it will be a non-zero power of two
+        unsafe {
+            Ok(Layout::from_size_align_unchecked(
+                size,
+                align_of::<DTINode>(),
+            ))
+        }
+    }
+
+    /// Returns the size in bytes of a buffer which is always sufficient to index `fdt`,
+    /// regardless of the buffer's alignment.
+    ///
+    /// This is the size of [`DevTreeIndex::get_layout()`] plus the most bytes which may be
+    /// skipped to align the start of an unaligned buffer.
+    pub fn required_buffer_size(fdt: &'i DevTree<'dt>) -> Result<usize, DevTreeError> {
+        let layout = Self::get_layout(fdt)?;
+        Ok(layout.size() + layout.align() - 1)
+    }
+
+    /// Build an index of `fdt` within `buf`.
+    ///
+    /// The index is stored from the first address within `buf` aligned to the alignment of
+    /// [`DevTreeIndex::get_layout()`]. The bytes before it are unused, see
+    /// [`DevTreeIndex::alignment_padding()`]. `buf` must therefore hold the size of the layout
+    /// after those bytes, which a buffer of [`DevTreeIndex::required_buffer_size()`] bytes always
+    /// does. Otherwise [`DevTreeError::NotEnoughMemory`] is returned.
+    pub fn new(fdt: DevTree<'dt>, buf: &'i mut [u8]) -> Result<Self, DevTreeError> {
+        Ok(Self::new_partial(fdt, buf)?)
+    }
+
+    /// Build an index as [`DevTreeIndex::new()`] does, calling `progress` as it is built.
+    ///
+    /// `progress` is called after each node is indexed and once the structure block has been
+    /// fully parsed, so that a long build of a large device tree may report its progress or feed
+    /// a watchdog. The progress is measured in bytes of the structure block, which is the only
+    /// measure known before the device tree has been parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let mut buf = vec![0u8; DevTreeIndex::required_buffer_size(&devtree).unwrap()];
+    ///
+    /// let mut reports = Vec::new();
+    /// let index = DevTreeIndex::new_with_progress(devtree, &mut buf, |progress| {
+    ///     reports.push(progress);
+    /// })
+    /// .unwrap();
+    ///
+    /// // One report per node, and a final report of the whole structure block.
+    /// assert_eq!(reports.len(), index.nodes().count() + 1);
+    /// let last = reports.last().unwrap();
+    /// assert_eq!(last.processed, last.total);
+    /// assert!(reports.windows(2).all(|w| w[0].processed <= w[1].processed));
+    /// ```
+    pub fn new_with_progress<F: FnMut(IndexProgress)>(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+        progress: F,
+    ) -> Result<Self, DevTreeError> {
+        let mut iter = ProgressSource {
+            inner: DevTreeParseIter::new(&fdt),
+            start: fdt.off_dt_struct(),
+            total: fdt.size_dt_struct(),
+            progress,
+        };
+        Ok(Self::build(fdt, buf, &mut iter, SecondaryIndexes::default(), &[])?.0)
+    }
+
+    /// Returns the size in bytes of a buffer which is always sufficient to index `fdt` with
+    /// [`DevTreeIndex::new_with_name_index()`].
+    ///
+    /// This is [`DevTreeIndex::required_buffer_size()`] plus a pointer per node.
+    pub fn required_buffer_size_with_name_index(
+        fdt: &'i DevTree<'dt>,
+    ) -> Result<usize, DevTreeError> {
+        let names = SecondaryIndexes {
+            names: true,
+            ..SecondaryIndexes::default()
+        };
+        Self::required_buffer_size_with(fdt, names)
+    }
+
+    /// Returns the size in bytes of a buffer which is always sufficient to index `fdt` with
+    /// [`DevTreeIndex::new_with_secondary_indexes()`].
+    ///
+    /// The name index requires a pointer per node, and the compatible index a string and
+    /// pointer per string of each `compatible` property.
+    pub fn required_buffer_size_with(
+        fdt: &'i DevTree<'dt>,
+        indexes: SecondaryIndexes,
+    ) -> Result<usize, DevTreeError> {
+        let mut size = Self::required_buffer_size(fdt)?;
+        if indexes.names {
+            size += fdt.nodes().count()? * size_of::<*const DTINode>();
+        }
+        if indexes.compatible {
+            let mut props = fdt.props();
+            while let Some(prop) = props.next()? {
+                if prop.name()? == "compatible" {
+                    let strings = prop.propbuf().iter().filter(|b| **b == 0).count();
+                    size += strings * size_of::<CompatibleEntry>();
+                }
+            }
+        }
+        Ok(size)
+    }
+
+    /// Build an index as [`DevTreeIndex::new()`] does, along with a secondary index of its nodes
+    /// sorted by name which is stored in `buf` after the index.
+    ///
+    /// [`DevTreeIndex::find_node_by_name()`] then performs a binary search rather than visiting
+    /// every node. `buf` must hold a pointer per node beyond the space the index requires, which
+    /// a buffer of [`DevTreeIndex::required_buffer_size_with_name_index()`] bytes always does.
+    /// Otherwise [`DevTreeError::NotEnoughMemory`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let size = DevTreeIndex::required_buffer_size_with_name_index(&devtree).unwrap();
+    /// let mut buf = vec![0u8; size];
+    ///
+    /// let index = DevTreeIndex::new_with_name_index(devtree, &mut buf).unwrap();
+    /// assert!(index.has_name_index());
+    /// let uart = index.find_node_by_name("uart@10000000").unwrap();
+    /// assert_eq!(uart.prop_str("compatible").unwrap(), Some("ns16550a"));
+    /// ```
+    pub fn new_with_name_index(fdt: DevTree<'dt>, buf: &'i mut [u8]) -> Result<Self, DevTreeError> {
+        let names = SecondaryIndexes {
+            names: true,
+            ..SecondaryIndexes::default()
+        };
+        Self::new_with_secondary_indexes(fdt, buf, names)
+    }
+
+    /// Build an index as [`DevTreeIndex::new()`] does, along with the given secondary indexes
+    /// which are stored in `buf` after the index.
+    ///
+    /// `buf` must hold the secondary indexes beyond the space the index requires, which a buffer
+    /// of [`DevTreeIndex::required_buffer_size_with()`] bytes always does. Otherwise
+    /// [`DevTreeError::NotEnoughMemory`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// use fdt_rs::index::SecondaryIndexes;
+    ///
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let indexes = SecondaryIndexes {
+    ///     names: true,
+    ///     compatible: true,
+    /// };
+    /// let size = DevTreeIndex::required_buffer_size_with(&devtree, indexes).unwrap();
+    /// let mut buf = vec![0u8; size];
+    ///
+    /// let index = DevTreeIndex::new_with_secondary_indexes(devtree, &mut buf, indexes).unwrap();
+    /// assert!(index.has_name_index() && index.has_compatible_index());
+    /// assert_eq!(index.nodes_by_compatible("virtio,mmio").count(), 8);
+    /// ```
+    pub fn new_with_secondary_indexes(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+        indexes: SecondaryIndexes,
+    ) -> Result<Self, DevTreeError> {
+        let mut iter = DevTreeParseIter::new(&fdt);
+        Ok(Self::build(fdt, buf, &mut iter, indexes, &[])?.0)
+    }
+
+    /// Build an index as [`DevTreeIndex::new()`] does, and also return the number of bytes of
+    /// `buf` which it consumed.
+    ///
+    /// `buf` may be larger than the index requires and need not be aligned. The count includes
+    /// the [alignment padding](DevTreeIndex::alignment_padding) skipped at its start, so the
+    /// index lies entirely within `buf[..used]` and an arena which `buf` was carved from need
+    /// only reserve `used` bytes for it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let mut buf = vec![0u8; 64 * 1024];
+    ///
+    /// // Build the index at an unaligned address within an oversized buffer.
+    /// let (index, used) = DevTreeIndex::new_with_used(devtree, &mut buf[1..]).unwrap();
+    /// let layout = DevTreeIndex::get_layout(&devtree).unwrap();
+    /// assert_eq!(used, index.alignment_padding() + layout.size());
+    /// assert!(index.node_at_path("/chosen").is_some());
+    /// ```
+    pub fn new_with_used(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+    ) -> Result<(Self, usize), DevTreeError> {
+        let mut iter = DevTreeParseIter::new(&fdt);
+        Ok(Self::build(fdt, buf, &mut iter, SecondaryIndexes::default(), &[])?)
+    }
+
+    /// Build an index as [`DevTreeIndex::new()`] does, but return the partially built index if
+    /// `buf` is too small.
+    ///
+    /// On [`DevTreeError::NotEnoughMemory`] the returned [`PartialIndexError`] holds an index of
+    /// the nodes and properties which precede the first one that did not fit. It may be queried
+    /// as a complete index would be, e.g. to read nodes which appear early in the tree such as
+    /// `/chosen` or `/memory` during early boot, and rebuilt within a larger buffer later.
+    /// Nodes which were still being indexed when the buffer was exhausted have their
+    /// [`DevTreeIndexNode::subtree_byte_range()`] end where indexing stopped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    /// let mut buf = vec![0u8; DevTreeIndex::required_buffer_size(&devtree).unwrap() / 2];
+    ///
+    /// let partial = DevTreeIndex::new_partial(devtree, &mut buf).unwrap_err();
+    /// assert_eq!(partial.error, fdt_rs::error::DevTreeError::NotEnoughMemory);
+    /// let index = partial.index.unwrap();
+    /// assert!(index.node_at_path("/chosen").is_some());
+    /// ```
+    // The partial index is returned inline to avoid allocating, which makes the error large.
+    #[allow(clippy::result_large_err)]
+    pub fn new_partial(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+    ) -> Result<Self, PartialIndexError<'i, 'dt>> {
+        let mut iter = DevTreeParseIter::new(&fdt);
+        Self::build(fdt, buf, &mut iter, SecondaryIndexes::default(), &[]).map(|(index, _)| index)
+    }
+
+    /// Build an index as [`DevTreeIndex::new()`] does, but parse the device tree with
+    /// [`DevTree::raw_tokens_unchecked()`].
+    ///
+    /// This avoids re-validating each token and is intended for large device trees which have
+    /// already been validated.
+    ///
+    /// # Safety
+    ///
+    /// [`DevTree::validate()`] must have returned `Ok` for `fdt`.
+    pub unsafe fn new_unchecked(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+    ) -> Result<Self, DevTreeError> {
+        let mut iter = fdt.raw_tokens_unchecked();
+        Ok(Self::build(fdt, buf, &mut iter, SecondaryIndexes::default(), &[])?.0)
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub(super) fn build<S: TokenSource<'dt>>(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+        iter: &mut S,
+        indexes: SecondaryIndexes,
+        fragments: &'i [IndexFragment<'i, 'dt>],
+    ) -> Result<(Self, usize), PartialIndexError<'i, 'dt>> {
+        let alignment_padding = buf.as_ptr().align_offset(align_of::<DTINode>());
+        let index_buf = buf.as_ptr_range();
+        let mut builder = unsafe { Self::init_builder(buf, iter) }?;
+
+        let mut this = Self {
+            fdt,
+            fragments,
+            root: builder.cur_node,
+            alignment_padding,
+            index_buf,
+            node_count: 1,
+            prop_count: 0,
+            name_index: None,
+            compatible_index: None,
+        };
+
+        // The builder should have setup a root node or returned an Err.
+        debug_assert!(!this.root.is_null());
+
+        // The buffer will be split into two parts, front and back:
+        //
+        // Front will be used as a temporary work section to  build the nodes as we parse them.
+        // The back will be used to save completely parsed nodes.
+        while let Some((offset, item)) = iter.next_token()? {
+            let res = match item {
+                ParsedTok::BeginNode(node) => builder
+                    .parsed_node(&node, offset)
+                    .map(|()| this.node_count += 1),
+                ParsedTok::Prop(prop) => builder
+                    .parsed_prop(&prop)
+                    .map(|()| this.prop_count += 1),
+                ParsedTok::EndNode => builder.parsed_end_node(iter.offset()),
+                ParsedTok::Nop => continue,
+            };
+            match res {
+                Ok(()) => {}
+                Err(DevTreeError::NotEnoughMemory) => {
+                    // Everything indexed so far remains linked and usable.
+                    builder.truncate(offset);
+                    return Err(PartialIndexError {
+                        index: Some(this),
+                        error: DevTreeError::NotEnoughMemory,
+                    });
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let used = builder.front_off;
+        if indexes == SecondaryIndexes::default() {
+            return Ok((this, used));
+        }
+        let (_, mut tail) = builder.buf.split_at_mut(used);
+        if indexes.names {
+            this.name_index = Some(this.build_name_index(&mut tail)?);
+        }
+        if indexes.compatible {
+            this.compatible_index = Some(this.build_compatible_index(&mut tail)?);
+        }
+        let used = tail.as_ptr() as usize - this.index_buf.start as usize;
+        Ok((this, used))
+    }
+
+    /// Returns the raw nodes of the index in document order.
+    fn raw_nodes(&self) -> impl Iterator<Item = &'i DTINode<'i, 'dt>> {
+        // Unsafe OK. Every node of the index remains valid for 'i.
+        let root = unsafe { self.root.as_ref() };
+        core::iter::successors(root, |node| node.next_dfs())
+    }
+
+    /// Returns the strings of each `compatible` property of `node`.
+    fn raw_compatible_strings(
+        &self,
+        node: &'i DTINode<'i, 'dt>,
+    ) -> impl Iterator<Item = &'dt [u8]> + 'i {
+        let (fdt, fragments) = (self.fdt, self.fragments);
+        (0..node.num_props())
+            // Unsafe OK. The index is less than the node's number of properties.
+            .map(move |idx| unsafe { node.prop_unchecked(idx) })
+            .filter(move |prop| {
+                let fdt = tree_holding(&fdt, fragments, prop.propbuf.as_ptr());
+                fdt.string_at(prop.nameoff) == Ok("compatible")
+            })
+            .flat_map(|prop| {
+                // Any bytes following the final null byte are not a string of the list.
+                let end = prop.propbuf.iter().rposition(|b| *b == 0);
+                end.map(|end| prop.propbuf[..end].split(|b| *b == 0))
+                    .into_iter()
+                    .flatten()
+            })
+    }
+
+    /// Store a pointer to each node of the index, sorted by name, at the front of `tail`.
+    fn build_name_index(
+        &self,
+        tail: &mut &'i mut [u8],
+    ) -> Result<&'i [*const DTINode<'i, 'dt>], DevTreeError> {
+        let count = self.node_count;
+        let entries = alloc_entries::<*const DTINode>(tail, count)?;
+        // Unsafe OK. The entries are aligned, hold `count` pointers and are borrowed for 'i.
+        let sorted = unsafe {
+            for (idx, node) in self.raw_nodes().take(count).enumerate() {
+                entries.add(idx).write(node);
+            }
+            core::slice::from_raw_parts_mut(entries, count)
+        };
+        // Nodes are stored in document order, so equal names remain in document order.
+        sorted.sort_unstable_by(|a, b| unsafe { (**a).name.cmp((**b).name).then(a.cmp(b)) });
+        Ok(sorted)
+    }
+
+    /// Store each string of each node's `compatible` properties along with the node, sorted by
+    /// string, at the front of `tail`.
+    fn build_compatible_index(
+        &self,
+        tail: &mut &'i mut [u8],
+    ) -> Result<&'i [CompatibleEntry<'i, 'dt>], DevTreeError> {
+        let strings = |node| self.raw_compatible_strings(node).count();
+        let count = self.raw_nodes().map(strings).sum();
+        let entries = alloc_entries::<CompatibleEntry>(tail, count)?;
+        let mut len = 0;
+        for node in self.raw_nodes() {
+            for string in self.raw_compatible_strings(node) {
+                // Unsafe OK. There are `count` strings, for which the entries are aligned.
+                unsafe { entries.add(len).write((string, node)) };
+                len += 1;
+            }
+        }
+        // Unsafe OK. Each of the `count` entries has been written, and is borrowed for 'i.
+        let sorted = unsafe { core::slice::from_raw_parts_mut(entries, len) };
+        // Nodes are stored in document order, so each string's nodes remain in document order.
+        sorted.sort_unstable();
+
+        // Drop the repeated entries of a node whose `compatible` repeats a string.
+        let mut unique = 0;
+        for idx in 0..sorted.len() {
+            if unique == 0 || sorted[idx] != sorted[unique - 1] {
+                sorted[unique] = sorted[idx];
+                unique += 1;
+            }
+        }
+        Ok(&sorted[..unique])
+    }
+
+    /// Returns the number of bytes at the start of the buffer the index was built in which were
+    /// skipped to align the index.
+    #[inline]
+    #[must_use]
+    pub fn alignment_padding(&self) -> usize {
+        self.alignment_padding
+    }
+
+    /// Returns the number of nodes within the index, including the root node.
+    ///
+    /// The count is recorded while building the index, so this is O(1). For a partial index (see
+    /// [`DevTreeIndex::new_partial()`]) only the nodes which were indexed are counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _buf) = doctest_index();
+    /// assert_eq!(index.node_count(), index.nodes().count());
+    /// assert_eq!(index.prop_count(), index.props().count());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Returns true if the index was built with a secondary index of its nodes sorted by name,
+    /// see [`DevTreeIndex::new_with_name_index()`].
+    #[inline]
+    #[must_use]
+    pub fn has_name_index(&self) -> bool {
+        self.name_index.is_some()
+    }
+
+    /// Returns the first node, in document order, whose full name (including its unit address)
+    /// is `name`.
+    ///
+    /// This is a binary search if the index was built by
+    /// [`DevTreeIndex::new_with_name_index()`], and otherwise visits every node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _buf) = doctest_index();
+    /// let cpu = index.find_node_by_name("cpu@0").unwrap();
+    /// assert_eq!(cpu.parent().unwrap().name().unwrap(), "cpus");
+    /// assert!(index.find_node_by_name("cpu").is_none());
+    /// ```
+    #[must_use]
+    pub fn find_node_by_name(&self, name: &str) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        let sorted = match self.name_index {
+            Some(sorted) => sorted,
+            None => return self.nodes().find(|node| node.name() == Ok(name)),
+        };
+        let name = name.as_bytes();
+        // Unsafe OK. The name index only holds nodes of this index.
+        unsafe {
+            let pos = sorted.partition_point(|node| (**node).name < name);
+            let node = sorted.get(pos)?.as_ref()?;
+            if node.name != name {
+                return None;
+            }
+            Some(DevTreeIndexNode::new(self, node))
+        }
+    }
+
+    /// Returns true if the index was built with a secondary index of its nodes sorted by the
+    /// strings of their `compatible` properties, see
+    /// [`DevTreeIndex::new_with_secondary_indexes()`].
+    #[inline]
+    #[must_use]
+    pub fn has_compatible_index(&self) -> bool {
+        self.compatible_index.is_some()
+    }
+
+    /// Returns the first node, in document order, whose `compatible` property contains
+    /// `compatible`.
+    ///
+    /// This is a binary search if the index was built with a compatible index (see
+    /// [`SecondaryIndexes`]), and otherwise visits every property as
+    /// [`DevTreeIndex::compatible_nodes()`] does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _buf) = doctest_index();
+    /// let uart = index.find_node_by_compatible("ns16550a").unwrap();
+    /// assert_eq!(uart.name().unwrap(), "uart@10000000");
+    /// ```
+    #[must_use]
+    pub fn find_node_by_compatible(
+        &self,
+        compatible: &str,
+    ) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        self.nodes_by_compatible(compatible).next()
+    }
+
+    /// Returns an iterator over the nodes, in document order, whose `compatible` property
+    /// contains `compatible`.
+    ///
+    /// With a compatible index (see [`SecondaryIndexes`]) the nodes are found by a binary search
+    /// and iterated without reading any properties. Otherwise this is equivalent to
+    /// [`DevTreeIndex::compatible_nodes()`].
+    #[must_use]
+    pub fn nodes_by_compatible<'a, 's>(
+        &'a self,
+        compatible: &'s str,
+    ) -> DevTreeIndexCompatibleListIter<'s, 'a, 'i, 'dt> {
+        let sorted = match self.compatible_index {
+            Some(sorted) => sorted,
+            None => return DevTreeIndexCompatibleListIter::scan(self.compatible_nodes(compatible)),
+        };
+        let string = compatible.as_bytes();
+        let start = sorted.partition_point(|(s, _)| *s < string);
+        let len = sorted[start..].partition_point(|(s, _)| *s == string);
+        DevTreeIndexCompatibleListIter::indexed(self, &sorted[start..start + len])
+    }
+
+    /// Returns the number of properties within the index.
+    ///
+    /// As with [`DevTreeIndex::node_count()`], this is O(1).
+    #[inline]
+    #[must_use]
+    pub fn prop_count(&self) -> usize {
+        self.prop_count
+    }
+
+    /// Returns the address range of the buffer the index was built in.
+    ///
+    /// The whole buffer must remain allocated for as long as the index is in use.
+    #[inline]
+    #[must_use]
+    pub fn index_buf_range(&self) -> core::ops::Range<*const u8> {
+        self.index_buf.clone()
+    }
+
+    /// Returns an exclusive upper bound on the ids of the index's nodes and properties (see
+    /// [`DevTreeIndexNode::id()`]).
+    #[must_use]
+    pub fn id_limit(&self) -> usize {
+        let len = self.index_buf.end as usize - self.index_buf.start as usize;
+        len.div_ceil(ID_UNIT)
+    }
+
+    /// Returns the id of the node or property stored at `entry` within the index buffer.
+    pub(super) fn id_of<T>(&self, entry: &T) -> usize {
+        (entry as *const T as usize - self.index_buf.start as usize) / ID_UNIT
+    }
+
+    pub fn root(&self) -> DevTreeIndexNode<'_, 'i, 'dt> {
+        // Unsafe OK. The root node always exits.
+        unsafe { DevTreeIndexNode::new(self, self.root.as_ref().invariant_unwrap()) }
+    }
+
+    /// Returns the node at the provided path (e.g. `"/soc/uart@10000000"`), or `None` if no such
+    /// node exists.
+    ///
+    /// The unit address of each path component may be omitted (e.g. `"/memory"`), in which case
+    /// the first node with a matching name is returned.
+    ///
+    /// A path which does not begin with `/` begins with an alias instead (e.g. `"serial0"` or
+    /// `"serial0/child"`), which is resolved using [`resolve_alias`](Self::resolve_alias). An
+    /// alias whose value is not a valid string refers to no node.
+    pub fn node_at_path(&self, path: &str) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        #[cfg(feature = "profiling")]
+        let _search = crate::profiling::Search::begin();
+
+        if path.starts_with('/') {
+            return Self::node_below(self.root(), path);
+        }
+        let (alias, rest) = split_alias(path);
+        let target = self.resolve_alias(alias).ok()??;
+        if !target.starts_with('/') {
+            return None;
+        }
+        let node = Self::node_below(self.root(), target)?;
+        Self::node_below(node, rest)
+    }
+
+    /// Returns the path an alias refers to, as given by the property of that name within the
+    /// `/aliases` node, or `None` if there is no such alias.
+    ///
+    /// As with [`DevTree::resolve_alias`](crate::base::DevTree::resolve_alias), an error is
+    /// returned if the alias's value is not a valid string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// // The doctest tree has no aliases.
+    /// assert_eq!(index.resolve_alias("serial0"), Ok(None));
+    /// assert!(index.node_at_path("serial0").is_none());
+    /// ```
+    pub fn resolve_alias(&self, alias: &str) -> Result<Option<&'dt str>, DevTreeError> {
+        let aliases = self
+            .root()
+            .children()
+            .find(|child| child.name() == Ok("aliases"));
+        let prop =
+            aliases.and_then(|aliases| aliases.props().find(|prop| prop.name() == Ok(alias)));
+        match prop {
+            Some(prop) => prop.str().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the node at `path` relative to `node`.
+    fn node_below<'a>(
+        mut node: DevTreeIndexNode<'a, 'i, 'dt>,
+        path: &str,
+    ) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            let child = node.children().find(|child| {
+                #[cfg(feature = "profiling")]
+                crate::profiling::record_node();
+                child.name().is_ok_and(|n| component_matches(n, component))
+            })?;
+            node = child;
+        }
+        Some(node)
+    }
+
+    /// Returns the index node referring to the same node as `node`, which was found using the
+    /// streaming [`crate::base`] API. See [`DevTreeIndexNode::to_base`] for the reverse conversion.
+    ///
+    /// Nodes are matched by their offset within the structure block, so this does not repeat
+    /// the search used to find `node`. Returns `None` if `node` belongs to a different device
+    /// tree buffer, including a fragment of a [stitched](Self::new_stitched) index.
+    pub fn node_from_base(
+        &self,
+        node: &DevTreeNode<'_, 'dt>,
+    ) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        if node.tree().buf().as_ptr() != self.fdt.buf().as_ptr() {
+            return None;
+        }
+        #[cfg(feature = "profiling")]
+        let _search = crate::profiling::Search::begin();
+
+        self.node_at_offset(node.node_offset()?)
+    }
+
+    /// Returns the node a [`NodeSnapshot`] was taken of, or `None` if the snapshot does not
+    /// describe a node of this index.
+    ///
+    /// Snapshots taken of another index built over the same device tree buffer return the
+    /// equivalent node of this index.
+    ///
+    /// The node is found by descending from the root through the nodes whose subtrees contain
+    /// the snapshot's offset, as [`DevTreeIndex::node_from_base()`] does, and is then checked
+    /// against the snapshot's id and name. Snapshots of nodes added by the fragments of a
+    /// [stitched](Self::new_stitched) index are therefore not rehydrated.
+    pub fn rehydrate(&self, snapshot: &NodeSnapshot) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        #[cfg(feature = "profiling")]
+        let _search = crate::profiling::Search::begin();
+
+        let node = self.node_at_offset(snapshot.offset)?;
+        if node.id() != snapshot.id
+            || node.node.name.as_ptr() != snapshot.name_ptr
+            || node.node.name.len() != snapshot.name_len
+        {
+            return None;
+        }
+        Some(node)
+    }
+
+    /// Returns the node whose `FDT_BEGIN_NODE` token is at `offset`.
+    fn node_at_offset(&self, offset: usize) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        // Descend through the nodes whose subtrees contain the offset.
+        let mut cur = self.root();
+        while cur.node.start_off() != offset {
+            cur = cur.children().find(|child| {
+                #[cfg(feature = "profiling")]
+                crate::profiling::record_node();
+                // The offsets of stitched nodes lie within their fragments.
+                child.subtree_byte_range().contains(&offset) && ptr::eq(child.fdt(), self.fdt())
+            })?;
+        }
+        Some(cur)
+    }
+
+    /// Returns the [`DevTree`] this index was built over. For a [stitched](Self::new_stitched)
+    /// index this is the base device tree, without its fragments.
+    pub fn fdt(&self) -> &DevTree<'dt> {
+        &self.fdt
+    }
+
+    /// Returns the fragments stitched into this index by [`DevTreeIndex::new_stitched()`].
+    #[must_use]
+    pub fn fragments(&self) -> &'i [IndexFragment<'i, 'dt>] {
+        self.fragments
+    }
+
+    /// Returns the device tree holding the bytes at `ptr`, which lie within the device tree the
+    /// index was built over or one of its fragments.
+    pub(super) fn tree_holding(&self, ptr: *const u8) -> &DevTree<'dt> {
+        tree_holding(&self.fdt, self.fragments, ptr)
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> DevTreeIndexNodeIter<'_, 'i, 'dt> {
+        DevTreeIndexNodeIter(self.items())
+    }
+
+    #[must_use]
+    pub fn props(&self) -> DevTreeIndexPropIter<'_, 'i, 'dt> {
+        DevTreeIndexPropIter(self.items())
+    }
+
+    #[must_use]
+    pub fn items(&self) -> DevTreeIndexIter<'_, 'i, 'dt> {
+        DevTreeIndexIter::new(self)
+    }
+
+    pub fn compatible_nodes<'a, 's>(
+        &'a self,
+        string: &'s str,
+    ) -> DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt> {
+        self.compatible_nodes_matching(string)
+    }
+
+    /// Returns an iterator over the distinct strings of the tree's `compatible` properties, each
+    /// paired with an iterator over the nodes whose `compatible` property contains it (as
+    /// returned by [`DevTreeIndex::nodes_by_compatible`]).
+    ///
+    /// This allows every instance of each driver to be processed together. If the index was
+    /// built with a compatible index (see [`SecondaryIndexes`]), the strings are returned in
+    /// sorted order by walking it. Otherwise they are returned in the order they first appear,
+    /// and as nothing is allocated, finding the nodes of each string searches the index from its
+    /// start.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let mut groups = index.group_by_compatible();
+    /// let (compatible, nodes) = groups.find(|(s, _)| *s == "virtio,mmio").unwrap();
+    /// assert_eq!(compatible, "virtio,mmio");
+    /// assert_eq!(nodes.count(), 8);
+    /// ```
+    #[must_use]
+    pub fn group_by_compatible(&self) -> DevTreeIndexCompatibleGroupIter<'_, 'i, 'dt> {
+        match self.compatible_index {
+            Some(sorted) => DevTreeIndexCompatibleGroupIter::indexed(self, sorted),
+            None => DevTreeIndexCompatibleGroupIter::new(self),
+        }
+    }
+
+    /// Returns an iterator over the [`DevTreeIndexNode`] objects whose compatible device tree
+    /// property contains any of the strings of `compatibles`.
+    ///
+    /// Each node is returned once, however many of its entries match, and the tree is only
+    /// traversed once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let nodes = index.compatible_nodes_any(&["virtio,mmio", "ns16550a"]);
+    /// assert_eq!(nodes.count(), 9);
+    /// ```
+    pub fn compatible_nodes_any<'a, 's>(
+        &'a self,
+        compatibles: &'s [&'s str],
+    ) -> DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt, [&'s str]> {
+        self.compatible_nodes_matching(compatibles)
+    }
+
+    /// Returns an iterator over [`DevTreeIndexNode`] objects with a compatible device tree
+    /// property accepted by the provided [`Matcher`].
+    pub fn compatible_nodes_matching<'a, 's, M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &'s M,
+    ) -> DevTreeIndexCompatibleNodeIter<'s, 'a, 'i, 'dt, M> {
+        DevTreeIndexCompatibleNodeIter {
+            iter: self.items(),
+            matcher,
+        }
+    }
+
+    /// Returns the first node with a `compatible` property accepted by `matcher`. See
+    /// [`IterableDevTree::find_first_compatible_node`].
+    #[inline]
+    pub fn find_first_compatible_node<'a, M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &M,
+    ) -> Result<Option<DevTreeIndexNode<'a, 'i, 'dt>>, DevTreeError> {
+        IterableDevTree::find_first_compatible_node(self, matcher)
+    }
+
+    /// Returns an iterator over the nodes whose absolute paths match `pattern`.
+    ///
+    /// Each component of `pattern` is matched against a node name as a
+    /// [`crate::matcher::Glob`]. As with [`DevTreeIndex::node_at_path`], a component without a
+    /// unit address also matches nodes which have one. A pattern which is not absolute matches no
+    /// nodes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// assert_eq!(index.nodes_matching_path("/virtio_mmio@*").count(), 8);
+    /// assert_eq!(index.nodes_matching_path("/soc/*").count(), 3);
+    /// assert_eq!(index.nodes_matching_path("/cpus/cpu").count(), 1);
+    /// ```
+    pub fn nodes_matching_path<'a, 'p>(
+        &'a self,
+        pattern: &'p str,
+    ) -> DevTreeIndexPathMatchIter<'p, 'a, 'i, 'dt> {
+        DevTreeIndexPathMatchIter {
+            iter: self.nodes(),
+            pattern,
+        }
+    }
+
+    /// Returns an iterator over the properties whose values contain `pattern`, in document order.
+    ///
+    /// Each property is returned once, along with the offset of the first occurrence of
+    /// `pattern` within its value. This is useful for locating a known value (such as a MAC
+    /// address or serial number) within an unfamiliar device tree. An empty `pattern` matches
+    /// every property.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let found = index.find_prop_value_containing(b"16550").next().unwrap();
+    /// assert_eq!(found.prop.name(), Ok("compatible"));
+    /// assert_eq!(found.prop.node().name(), Ok("uart@10000000"));
+    /// assert_eq!(found.offset, 2);
+    /// ```
+    pub fn find_prop_value_containing<'a, 'p>(
+        &'a self,
+        pattern: &'p [u8],
+    ) -> DevTreeIndexPropValueIter<'p, 'a, 'i, 'dt> {
+        DevTreeIndexPropValueIter {
+            iter: self.props(),
+            pattern,
+        }
+    }
+
+    /// Returns the canonical stream of this index, with each node's properties returned in the
+    /// given `order`. See [`crate::canonical`].
+    #[must_use]
+    pub fn canonical_items(&self, order: PropOrder) -> DevTreeIndexCanonicalIter<'_, 'i, 'dt> {
+        DevTreeIndexCanonicalIter::new(self, order)
+    }
+
+    /// Returns an iterator over the properties whose values are longer than `threshold` bytes,
+    /// in document order.
+    ///
+    /// This locates large payloads embedded within the tree (such as firmware blobs or EDID
+    /// data). [`PropReader::value_offset`] gives the location of each value within the device
+    /// tree and [`PropReader::raw_chunks`] copies it out piecewise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let large = index.props_larger_than(256).next().unwrap();
+    /// assert_eq!(large.name(), Ok("interrupt-map"));
+    ///
+    /// let mut copy = Vec::new();
+    /// for chunk in large.raw_chunks(64) {
+    ///     copy.extend_from_slice(chunk);
+    /// }
+    /// let offset = large.value_offset();
+    /// assert_eq!(copy, &index.buf()[offset..offset + large.length()]);
+    /// ```
+    pub fn props_larger_than(&self, threshold: usize) -> DevTreeIndexLargePropIter<'_, 'i, 'dt> {
+        DevTreeIndexLargePropIter {
+            iter: self.props(),
+            threshold,
+        }
+    }
+
+    /// Collects up to `N` nodes with a compatible property accepted by `matcher`. See
+    /// [`crate::bounded`].
+    pub fn collect_nodes<M: Matcher + ?Sized, const N: usize>(
+        &self,
+        matcher: &M,
+    ) -> Collected<DevTreeIndexNode<'_, 'i, 'dt>, N> {
+        self.compatible_nodes_matching(matcher).collect()
+    }
+
+    #[must_use]
+    pub fn buf(&self) -> &'dt [u8] {
+        self.fdt.buf()
+    }
+
+    /// Write an indented dump of the device tree into `w` without allocating. The nodes and
+    /// properties of any fragments stitched into the index are included.
+    ///
+    /// See [`crate::print::write_tree`] for details on the output format.
+    pub fn write_tree<W: core::fmt::Write>(
+        &self,
+        w: &mut W,
+        max_depth: usize,
+        max_value_bytes: usize,
+    ) -> Result<(), DevTreeError> {
+        let tokens = print_tokens(&self.fdt, self.fragments)?;
+        crate::print::write_tree_tokens(tokens, w, max_depth, max_value_bytes)
+    }
+
+    /// Write the device tree into `w` as device tree source without allocating. The nodes and
+    /// properties of any fragments stitched into the index are included.
+    ///
+    /// See [`crate::print::write_dts`] for details on the output format.
+    pub fn write_dts<W: core::fmt::Write>(&self, w: &mut W) -> Result<(), DevTreeError> {
+        let tokens = print_tokens(&self.fdt, self.fragments)?;
+        crate::print::write_dts_tokens(&self.fdt, tokens, w)
+    }
+
+    /// Returns an iterator over the violations of the specification within the device tree.
+    ///
+    /// See [`DevTree::lint()`] for details. The findings of an index with fragments stitched
+    /// in would not all refer to [`DevTreeIndex::fdt()`], so the iterator instead returns a
+    /// [`DevTreeError::InvalidParameter`]. Lint the device tree and each fragment separately.
+    #[must_use]
+    pub fn lint(&self) -> DevTreeLintIter<'_, 'dt> {
+        if self.fragments.is_empty() {
+            self.fdt.lint()
+        } else {
+            DevTreeLintIter::rejecting(
+                &self.fdt,
+                DevTreeError::InvalidParameter("Stitched indexes may not be linted"),
+            )
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> IterableDevTree<'a, 'dt> for DevTreeIndex<'i, 'dt> {
+    type Node = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    fn find_first_compatible_node<M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &M,
+    ) -> Result<Option<Self::Node>, DevTreeError> {
+        Ok(self.compatible_nodes_matching(matcher).next())
+    }
+}
+
+/// Iterates over the items of the index as [`DevTreeIndex::items()`] does.
+///
+/// # Example
+///
+/// ```
+/// # use fdt_rs::doctest::*;
+/// # let (index, _) = doctest_index();
+/// let mut nodes = 0;
+/// for item in &index {
+///     if let DevTreeIndexItem::Node(_) = item {
+///         nodes += 1;
+///     }
+/// }
+/// assert_eq!(nodes, index.nodes().count());
+/// ```
+impl<'a, 'i: 'a, 'dt: 'i> IntoIterator for &'a DevTreeIndex<'i, 'dt> {
+    type Item = DevTreeIndexItem<'a, 'i, 'dt>;
+    type IntoIter = DevTreeIndexIter<'a, 'i, 'dt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items()
+    }
+}
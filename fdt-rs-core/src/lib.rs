@@ -0,0 +1,149 @@
+//! The no-std device tree parser behind fdt-rs.
+//!
+//! fdt-rs re-exports this crate, along with the host tooling of fdt-rs-tools when its `std`
+//! feature is enabled. Depend on fdt-rs rather than on this crate; the examples within the
+//! documentation are written against it.
+#![deny(clippy::all, clippy::cargo)]
+#![allow(clippy::as_conversions)]
+// Test the readme if using nightly.
+#![cfg_attr(RUSTC_IS_NIGHTLY, feature(external_doc))]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(feature = "std")]
+extern crate core;
+extern crate endian_type_rs as endian_type;
+#[macro_use]
+extern crate memoffset;
+#[macro_use]
+extern crate static_assertions;
+extern crate fallible_iterator;
+#[cfg(feature = "serde")]
+extern crate serde;
+extern crate unsafe_unwrap;
+
+pub mod base;
+pub mod bounded;
+pub mod canonical;
+#[cfg(feature = "coverage")]
+#[cfg_attr(docsrs, doc(cfg(feature = "coverage")))]
+pub mod coverage;
+pub mod error;
+pub mod index;
+pub mod lint;
+pub mod matcher;
+#[cfg(feature = "overlay")]
+#[cfg_attr(docsrs, doc(cfg(feature = "overlay")))]
+pub mod overlay;
+pub mod prelude;
+pub mod print;
+#[cfg(feature = "profiling")]
+#[cfg_attr(docsrs, doc(cfg(feature = "profiling")))]
+pub mod profiling;
+pub mod quick;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serialize;
+pub mod spec;
+pub mod traits;
+pub mod util;
+pub mod view;
+
+#[doc(hidden)]
+pub mod common;
+
+pub(crate) mod priv_util;
+
+use base::DevTree;
+use error::{DevTreeError, Result};
+use index::DevTreeIndex;
+
+/// Parse the device tree at the start of `fdt_buf` and build an index of it within `index_buf`.
+///
+/// This performs each step of initialization in turn, returning the error of the first which
+/// fails:
+///
+/// 1. `fdt_buf` must be 32-bit aligned and hold at least a device tree header, otherwise
+///    [`DevTreeError::InvalidParameter`] is returned.
+/// 2. The header's magic number is checked ([`DevTreeError::InvalidMagicNumber`]).
+/// 3. `fdt_buf` must hold the header's `totalsize` bytes ([`DevTreeError::InvalidParameter`]).
+///    Any bytes beyond them are ignored.
+/// 4. The layout of the device tree's blocks is verified, as by [`DevTree::new()`].
+/// 5. The index is built, as by [`DevTreeIndex::new()`]. [`DevTreeError::NotEnoughMemory`] is
+///    returned if `index_buf` is too small.
+///
+/// Unlike [`DevTree::new()`] this is safe, as the buffer's alignment and length are verified
+/// before it is interpreted as a device tree.
+///
+/// # Example
+///
+/// ```
+/// # use fdt_rs::doctest::FDT;
+/// use fdt_rs::prelude::*;
+///
+/// let mut index_buf = vec![0u8; 16 * 1024];
+/// let (devtree, index) = fdt_rs::quick_index(FDT, &mut index_buf).unwrap();
+/// assert_eq!(devtree.totalsize(), FDT.len());
+/// assert!(index.node_at_path("/chosen").is_some());
+/// ```
+pub fn quick_index<'i, 'dt: 'i>(
+    fdt_buf: &'dt [u8],
+    index_buf: &'i mut [u8],
+) -> Result<(DevTree<'dt>, DevTreeIndex<'i, 'dt>)> {
+    if fdt_buf.len() < DevTree::MIN_HEADER_SIZE {
+        return Err(DevTreeError::InvalidParameter(
+            "Buffer is too small to hold a device tree header",
+        ));
+    }
+    // Unsafe OK. The buffer holds a header, and read_totalsize verifies its alignment.
+    let totalsize = unsafe { DevTree::read_totalsize(fdt_buf)? };
+    let fdt_buf = fdt_buf.get(..totalsize).ok_or(DevTreeError::InvalidParameter(
+        "Buffer is smaller than the device tree's totalsize",
+    ))?;
+    // Unsafe OK. The buffer is aligned and exactly totalsize bytes long.
+    let devtree = unsafe { DevTree::new(fdt_buf)? };
+    let index = DevTreeIndex::new(devtree, index_buf)?;
+    Ok((devtree, index))
+}
+
+// When the doctest feature is enabled, add these utility functions.
+#[cfg(any(feature = "doctest", doc))]
+#[doc(hidden)]
+// The examples never name the submodules which both `base` and `index` export.
+#[allow(ambiguous_glob_reexports)]
+pub mod doctest {
+    pub use crate::base::*;
+    pub use crate::index::*;
+    pub use crate::prelude::*;
+
+    // Include the readme for doctests
+    // https://doc.rust-lang.org/rustdoc/documentation-tests.html#include-items-only-when-collecting-doctests
+    #[cfg(RUSTC_IS_NIGHTLY)]
+    #[doc(include = "../../README.md")]
+    pub struct ReadmeDoctests;
+
+    #[repr(align(4))]
+    struct _Wrapper<T>(T);
+    pub const FDT: &[u8] = &_Wrapper(*include_bytes!("../../tests/riscv64-virt.dtb")).0;
+    pub const OVERLAY: &[u8] = &_Wrapper(*include_bytes!("../../tests/riscv64-virt-overlay.dtbo")).0;
+
+    pub fn doctest_index<'i, 'dt: 'i>() -> (DevTreeIndex<'i, 'dt>, Vec<u8>) {
+        // Create the device tree parser
+        let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+
+        // Get the buffer size required to build an index
+        let size = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+
+        // Allocate memory for the index.
+        //
+        // This could be performed without a dynamic allocation
+        // if we allocated a static buffer or want to provide a
+        // raw buffer into uninitialized memory.
+        let mut vec = vec![0u8; size];
+        let (p, s) = (vec.as_mut_ptr(), vec.len());
+        unsafe {
+            let vec_copy = core::slice::from_raw_parts_mut(p, s);
+            (DevTreeIndex::new(devtree, vec_copy).unwrap(), vec)
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Reporting of violations of the specification which do not prevent a device tree from being
+//! parsed.
+//!
+//! [`DevTree::lint`] walks the structure block and returns a [`LintFinding`] for each violation
+//! it finds, rather than failing at the first, so that boot code or tooling may report every
+//! problem of a device tree at once. Tokens which cannot be parsed at all end the walk with an
+//! [`Err`], as [`DevTree::validate`] would report.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::FDT;
+//! use fdt_rs::base::DevTree;
+//! use fdt_rs::prelude::*;
+//!
+//! let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+//! let mut findings = devtree.lint();
+//! while let Some(finding) = findings.next().unwrap() {
+//!     println!("{}", finding);
+//! }
+//! assert_eq!(devtree.lint().count().unwrap(), 0);
+//! ```
+use core::fmt;
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::base::parse::{DevTreeParseIter, ParsedTok};
+use crate::base::DevTree;
+use crate::error::{DevTreeError, Result};
+use crate::spec::{Phandle, MAX_NODE_NAME_LEN, MAX_PROP_NAME_LEN};
+use crate::view::validate_strlist;
+
+/// The largest `#address-cells` or `#size-cells` accepted: four cells, or 128 bits.
+pub const MAX_ADDRESS_CELLS: u32 = 4;
+
+/// Standard properties whose values are string lists, in addition to those named `*-names`.
+const STRING_PROPS: [&str; 4] = ["compatible", "model", "status", "device_type"];
+
+/// The most findings a single token may produce.
+const MAX_FINDINGS_PER_TOKEN: usize = 4;
+
+/// A violation of the specification found by [`DevTree::lint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    /// A property follows a child node of its node. Such properties are skipped by the
+    /// iterators of this crate.
+    PropAfterNode,
+    /// A property has the same name as an earlier property of its node.
+    DuplicateProp,
+    /// A node name (excluding its unit address) is longer than [`MAX_NODE_NAME_LEN`], or a
+    /// property name is longer than [`MAX_PROP_NAME_LEN`].
+    NameTooLong,
+    /// A property whose value must be a string list (`compatible`, `model`, `status`,
+    /// `device_type` or `*-names`) is not null terminated or is not valid UTF-8.
+    InvalidStringList,
+    /// A `#*-cells` property is not a single cell, or a `#address-cells` or `#size-cells`
+    /// property is greater than [`MAX_ADDRESS_CELLS`].
+    BadCells,
+    /// An `interrupt-parent` property refers to a phandle which no node has.
+    DanglingPhandle(Phandle),
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LintKind::PropAfterNode => write!(f, "Property follows a child node"),
+            LintKind::DuplicateProp => write!(f, "Duplicate property"),
+            LintKind::NameTooLong => write!(f, "Name is too long"),
+            LintKind::InvalidStringList => write!(f, "Invalid string list"),
+            LintKind::BadCells => write!(f, "Invalid cell count"),
+            LintKind::DanglingPhandle(phandle) => write!(f, "No node has phandle {:#x}", phandle),
+        }
+    }
+}
+
+/// A violation of the specification, and the node or property it was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LintFinding<'dt> {
+    /// The violation.
+    pub kind: LintKind,
+    /// The offset of the token of the node or property from the start of the device tree.
+    pub offset: usize,
+    /// The name of the node or property.
+    pub name: &'dt str,
+}
+
+impl<'dt> fmt::Display for LintFinding<'dt> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: `{}` at offset {:#x}", self.kind, self.name, self.offset)
+    }
+}
+
+/// An iterator over the [`LintFinding`]s of a device tree. Created by [`DevTree::lint`].
+pub struct DevTreeLintIter<'a, 'dt: 'a> {
+    iter: DevTreeParseIter<'a, 'dt>,
+    // Whether a child of the current node has ended, after which properties are misplaced.
+    after_child: bool,
+    // The offset of the token following the current node's FDT_BEGIN_NODE token, while only
+    // properties of the node have followed it.
+    props_start: Option<usize>,
+    // The findings of the most recently parsed token which have not yet been returned.
+    pending: [Option<LintKind>; MAX_FINDINGS_PER_TOKEN],
+    offset: usize,
+    name: &'dt str,
+    // An error returned in place of any findings.
+    error: Option<DevTreeError>,
+}
+
+impl<'a, 'dt: 'a> DevTreeLintIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self {
+            iter: fdt.parse_iter(),
+            after_child: false,
+            props_start: None,
+            pending: [None; MAX_FINDINGS_PER_TOKEN],
+            offset: 0,
+            name: "",
+            error: None,
+        }
+    }
+
+    /// Returns an iterator which returns `error` rather than linting `fdt`.
+    pub(crate) fn rejecting(fdt: &'a DevTree<'dt>, error: DevTreeError) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(fdt)
+        }
+    }
+
+    fn report(&mut self, kind: LintKind) {
+        if let Some(slot) = self.pending.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(kind);
+        }
+    }
+
+    /// Returns true if a property named `name` precedes the property at `offset` within its
+    /// node.
+    fn is_duplicate(&self, offset: usize, name: &str) -> Result<bool> {
+        let start = match self.props_start {
+            Some(start) => start,
+            None => return Ok(false),
+        };
+        let mut iter = DevTreeParseIter {
+            offset: start,
+            fdt: self.iter.fdt,
+        };
+        while iter.offset < offset {
+            if let Some(ParsedTok::Prop(prop)) = iter.next()? {
+                if self.iter.fdt.string_at(prop.name_offset)? == name {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the violation of the value of the property `name`, if its value has a type which
+    /// may be checked.
+    fn check_value(&self, name: &str, value: &[u8]) -> Result<Option<LintKind>> {
+        if STRING_PROPS.contains(&name) || name.ends_with("-names") {
+            if validate_strlist(value).is_err() {
+                return Ok(Some(LintKind::InvalidStringList));
+            }
+        } else if name.starts_with('#') && name.ends_with("-cells") {
+            let cells = match value {
+                [a, b, c, d] => u32::from_be_bytes([*a, *b, *c, *d]),
+                _ => return Ok(Some(LintKind::BadCells)),
+            };
+            if matches!(name, "#address-cells" | "#size-cells") && cells > MAX_ADDRESS_CELLS {
+                return Ok(Some(LintKind::BadCells));
+            }
+        } else if name == "interrupt-parent" {
+            if let [a, b, c, d] = *value {
+                let phandle = u32::from_be_bytes([a, b, c, d]);
+                if !self.has_phandle(phandle)? {
+                    return Ok(Some(LintKind::DanglingPhandle(phandle)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns true if a node's `phandle` (or legacy `linux,phandle`) property is `phandle`.
+    fn has_phandle(&self, phandle: Phandle) -> Result<bool> {
+        let mut props = self.iter.fdt.props();
+        while let Some(prop) = props.next()? {
+            if matches!(prop.name()?, "phandle" | "linux,phandle") && prop.u32(0) == Ok(phandle) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeLintIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = LintFinding<'dt>;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        loop {
+            if let Some(kind) = self.pending.iter_mut().find_map(Option::take) {
+                return Ok(Some(LintFinding {
+                    kind,
+                    offset: self.offset,
+                    name: self.name,
+                }));
+            }
+            let offset = self.iter.offset;
+            let name = match self.iter.next()? {
+                Some(ParsedTok::BeginNode(node)) => {
+                    let name = from_utf8(node.name)?;
+                    self.after_child = false;
+                    self.props_start = Some(self.iter.offset);
+                    let node_name = name.split('@').next().unwrap_or(name);
+                    if node_name.len() > MAX_NODE_NAME_LEN {
+                        self.report(LintKind::NameTooLong);
+                    }
+                    name
+                }
+                Some(ParsedTok::Prop(prop)) => {
+                    let name = self.iter.fdt.string_at(prop.name_offset)?;
+                    if self.after_child {
+                        self.report(LintKind::PropAfterNode);
+                    }
+                    if name.len() > MAX_PROP_NAME_LEN {
+                        self.report(LintKind::NameTooLong);
+                    }
+                    if self.is_duplicate(offset, name)? {
+                        self.report(LintKind::DuplicateProp);
+                    }
+                    if let Some(kind) = self.check_value(name, prop.prop_buf)? {
+                        self.report(kind);
+                    }
+                    name
+                }
+                Some(ParsedTok::EndNode) => {
+                    self.after_child = true;
+                    self.props_start = None;
+                    continue;
+                }
+                Some(ParsedTok::Nop) => continue,
+                None => return Ok(None),
+            };
+            self.offset = offset;
+            self.name = name;
+        }
+    }
+}
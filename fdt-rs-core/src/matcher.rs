@@ -0,0 +1,206 @@
+//! String matchers used by the search APIs of this library.
+//!
+//! Searches such as [`DevTree::compatible_nodes_matching`] accept any [`Matcher`] to decide
+//! whether a string found in the device tree is a match. This allows vendor quirks (e.g.
+//! `"ARM,pl011"` vs `"arm,pl011"`) to be handled without re-implementing the search itself.
+//!
+//! A plain `str` is a [`Matcher`] which performs an exact comparison.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::matcher::IgnoreCase;
+//!
+//! let node = index.compatible_nodes_matching(&IgnoreCase("NS16550A")).next();
+//! assert!(node.is_some());
+//! ```
+#[cfg(doc)]
+use crate::base::DevTree;
+
+/// A predicate over strings found within a device tree.
+pub trait Matcher {
+    /// Returns true if `candidate` is considered a match.
+    fn matches(&self, candidate: &str) -> bool;
+}
+
+/// Exact string comparison.
+impl Matcher for str {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        self == candidate
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for &M {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        (**self).matches(candidate)
+    }
+}
+
+/// Matches strings accepted by any of the matchers within the slice.
+///
+/// A slice of `&str` therefore matches strings which are exactly equal to any of its entries.
+impl<M: Matcher> Matcher for [M] {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        self.iter().any(|matcher| matcher.matches(candidate))
+    }
+}
+
+/// Matches strings which are exactly equal to the contained string.
+#[derive(Clone, Copy, Debug)]
+pub struct Exact<'s>(pub &'s str);
+
+impl<'s> Matcher for Exact<'s> {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        self.0 == candidate
+    }
+}
+
+/// Matches strings which are equal to the contained string, ignoring ASCII case.
+#[derive(Clone, Copy, Debug)]
+pub struct IgnoreCase<'s>(pub &'s str);
+
+impl<'s> Matcher for IgnoreCase<'s> {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        self.0.eq_ignore_ascii_case(candidate)
+    }
+}
+
+/// Matches strings which begin with the contained string.
+#[derive(Clone, Copy, Debug)]
+pub struct Prefix<'s>(pub &'s str);
+
+impl<'s> Matcher for Prefix<'s> {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        candidate.starts_with(self.0)
+    }
+}
+
+/// Matches strings for which the contained closure returns true.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchFn<F: Fn(&str) -> bool>(pub F);
+
+impl<F: Fn(&str) -> bool> Matcher for MatchFn<F> {
+    #[inline]
+    fn matches(&self, candidate: &str) -> bool {
+        (self.0)(candidate)
+    }
+}
+
+/// Matches strings against a glob pattern.
+///
+/// The pattern may contain the following wildcards, all other characters match themselves:
+///
+/// * `*` matches any sequence of characters, including an empty one.
+/// * `?` matches any single character.
+/// * `[...]` matches any single character within the brackets. Ranges such as `[0-9]` are
+///   supported and the set is negated if it begins with `!` or `^`. A `]` immediately after the
+///   opening bracket (or negation) is matched literally. A `[` without a closing bracket is
+///   matched literally.
+///
+/// Matching does not allocate.
+///
+/// # Example
+///
+/// ```
+/// use fdt_rs::matcher::{Glob, Matcher};
+///
+/// assert!(Glob("virtio_mmio@1000[0-8]000").matches("virtio_mmio@10008000"));
+/// assert!(Glob("uart@*").matches("uart@10000000"));
+/// assert!(!Glob("cpu@?").matches("cpu@10"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Glob<'s>(pub &'s str);
+
+/// Matches `c` against the bracket expression at the start of `class` (which begins with `[`).
+///
+/// Returns whether `c` matched and the length of the expression, or `None` if the expression is
+/// not terminated.
+fn match_class(class: &str, c: char) -> Option<(bool, usize)> {
+    let mut chars = class.char_indices().skip(1).peekable();
+    let negated = match chars.peek() {
+        Some(&(_, '!')) | Some(&(_, '^')) => {
+            chars.next();
+            true
+        }
+        _ => false,
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    while let Some((i, lo)) = chars.next() {
+        if lo == ']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        first = false;
+        let mut hi = lo;
+        if let Some(&(_, '-')) = chars.peek() {
+            let mut lookahead = chars.clone();
+            lookahead.next();
+            match lookahead.next() {
+                Some((_, end)) if end != ']' => {
+                    hi = end;
+                    chars = lookahead;
+                }
+                _ => {}
+            }
+        }
+        matched |= lo <= c && c <= hi;
+    }
+    None
+}
+
+impl<'s> Matcher for Glob<'s> {
+    fn matches(&self, candidate: &str) -> bool {
+        let pattern = self.0;
+        let (mut p, mut c) = (0, 0);
+        // The pattern offset following the most recent `*` and the candidate offset it is
+        // currently matched up to.
+        let mut star: Option<(usize, usize)> = None;
+
+        loop {
+            if let Some(pc) = pattern[p..].chars().next() {
+                if pc == '*' {
+                    p += 1;
+                    star = Some((p, c));
+                    continue;
+                }
+                if let Some(cc) = candidate[c..].chars().next() {
+                    let (matched, len) = match pc {
+                        '?' => (true, 1),
+                        '[' => match_class(&pattern[p..], cc).unwrap_or((cc == '[', 1)),
+                        _ => (pc == cc, pc.len_utf8()),
+                    };
+                    if matched {
+                        p += len;
+                        c += cc.len_utf8();
+                        continue;
+                    }
+                }
+            } else if c == candidate.len() {
+                return true;
+            }
+
+            // Mismatch: let the last `*` consume one more character.
+            match star {
+                Some((star_p, star_c)) => match candidate[star_c..].chars().next() {
+                    Some(skipped) => {
+                        let next = star_c + skipped.len_utf8();
+                        star = Some((star_p, next));
+                        p = star_p;
+                        c = next;
+                    }
+                    None => return false,
+                },
+                None => return false,
+            }
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Application of device tree overlays.
+//!
+//! An overlay (`.dtbo`) is a device tree whose root holds fragment nodes. Each fragment names a
+//! node of the base tree, by a `target` phandle or a `target-path`, and holds an `__overlay__`
+//! node whose properties and children are merged into that node. [`apply_overlay`] writes the
+//! merged tree into a caller provided buffer, without allocating.
+//!
+//! As with `fdtoverlay`, the following are resolved while merging:
+//!
+//! * The phandles of the overlay's own nodes are renumbered to follow the largest phandle of the
+//!   base tree, along with the references to them listed within `/__local_fixups__`.
+//! * References to labels of the base tree, listed within `/__fixups__` as `path:property:offset`
+//!   entries, are set to the phandle of the node named by the base tree's `/__symbols__`.
+//!
+//! The overlay's own `/__symbols__` are not added to the merged tree, and fragments may only
+//! target nodes of the base tree.
+use core::mem::size_of;
+
+use crate::prelude::*;
+
+use crate::base::{DevTree, DevTreeNode, DevTreeProp};
+use crate::bounded::BoundedVec;
+use crate::error::{DevTreeError, Result};
+use crate::spec::{fdt_header, fdt_reserve_entry, header_off, FdtTok, FDT_MAGIC};
+
+/// The maximum number of fragments of an overlay supported by [`apply_overlay`].
+pub const MAX_OVERLAY_FRAGMENTS: usize = 16;
+
+/// The maximum number of overlay nodes which [`apply_overlay`] supports merging into a single
+/// node.
+pub const MAX_MERGED_NODES: usize = 8;
+
+const PHANDLE_PROPS: [&str; 2] = ["phandle", "linux,phandle"];
+
+/// Apply `overlay` to `base`, writing the merged device tree into `dst`. Returns the number of
+/// bytes written, which is the `totalsize` of the merged device tree.
+///
+/// The merged device tree holds the memory reservations of `base`. Properties of the overlay
+/// replace base properties of the same name, and are otherwise appended to the node's
+/// properties. Child nodes are merged by name, and new children follow those of the base node.
+///
+/// The following errors are returned:
+///
+/// * [`DevTreeError::NotEnoughMemory`] if `dst` is too small.
+/// * [`DevTreeError::NodeLimitExceeded`] if the overlay has more than [`MAX_OVERLAY_FRAGMENTS`]
+///   fragments, or more than [`MAX_MERGED_NODES`] overlay nodes merge into a single node.
+/// * [`DevTreeError::InvalidParameter`] if a fragment's target or a label named by
+///   `/__fixups__` is not found within `base`.
+/// * [`DevTreeError::ParseError`] if a fixup lies outside of its property's value.
+///
+/// `dst` must be 32-bit aligned for the merged device tree to be parsed in place.
+pub fn apply_overlay(base: &DevTree, overlay: &DevTree, dst: &mut [u8]) -> Result<usize> {
+    let mut applier = Applier {
+        trees: Trees { base, overlay },
+        fragments: BoundedVec::new(),
+        phandle_delta: max_phandle(base)?,
+        out: Writer { buf: dst, off: 0 },
+    };
+    applier.find_fragments()?;
+
+    let out = &mut applier.out;
+    out.off = size_of::<fdt_header>();
+    out.align(size_of::<fdt_reserve_entry>())?;
+    let off_mem_rsvmap = out.off;
+    for entry in base.reserved_entries() {
+        out.bytes(&u64::from(entry.address).to_be_bytes())?;
+        out.bytes(&u64::from(entry.size).to_be_bytes())?;
+    }
+    out.bytes(&[0; size_of::<fdt_reserve_entry>()])?;
+
+    let off_dt_struct = applier.out.off;
+    let root = base.root()?.ok_or(DevTreeError::ParseError)?;
+    applier.emit_node("", Some(root), &[])?;
+    applier.out.u32(FdtTok::End as u32)?;
+    let size_dt_struct = applier.out.off - off_dt_struct;
+
+    let out = &mut applier.out;
+    let off_dt_strings = out.off;
+    out.bytes(base.strings_block())?;
+    out.bytes(overlay.strings_block())?;
+    let size_dt_strings = out.off - off_dt_strings;
+    let totalsize = out.off;
+
+    let header = [
+        (header_off::MAGIC, FDT_MAGIC),
+        (header_off::TOTALSIZE, totalsize as u32),
+        (header_off::OFF_DT_STRUCT, off_dt_struct as u32),
+        (header_off::OFF_DT_STRINGS, off_dt_strings as u32),
+        (header_off::OFF_MEM_RSVMAP, off_mem_rsvmap as u32),
+        (header_off::VERSION, 17),
+        (header_off::LAST_COMP_VERSION, 16),
+        (header_off::SIZE_DT_STRINGS, size_dt_strings as u32),
+        (header_off::SIZE_DT_STRUCT, size_dt_struct as u32),
+        (header_off::BOOT_CPUID_PHYS, base.boot_cpuid_phys()),
+    ];
+    for (off, value) in header.iter() {
+        out.buf[*off..*off + size_of::<u32>()].copy_from_slice(&value.to_be_bytes());
+    }
+    Ok(totalsize)
+}
+
+/// Returns the largest phandle of `fdt`, or zero if it has none.
+fn max_phandle(fdt: &DevTree) -> Result<u32> {
+    let mut max = 0;
+    let mut props = fdt.props();
+    while let Some(prop) = props.next()? {
+        if PHANDLE_PROPS.contains(&prop.name()?) {
+            max = core::cmp::max(max, prop.phandle(0)?);
+        }
+    }
+    Ok(max)
+}
+
+/// Returns the child of `node` named `name`.
+fn child_named<'a, 'dt>(
+    node: &DevTreeNode<'a, 'dt>,
+    name: &str,
+) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        if child.name()? == name {
+            return Ok(Some(child));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns true if `node` has a property named `name`.
+fn has_prop(node: &DevTreeNode, name: &str) -> Result<bool> {
+    Ok(node.prop(name)?.is_some())
+}
+
+/// Appends to a byte buffer, returning [`DevTreeError::NotEnoughMemory`] once it is full.
+struct Writer<'w> {
+    buf: &'w mut [u8],
+    off: usize,
+}
+
+impl<'w> Writer<'w> {
+    /// Append `bytes`, returning the offset they were written at.
+    fn bytes(&mut self, bytes: &[u8]) -> Result<usize> {
+        let start = self.off;
+        self.buf
+            .get_mut(start..start + bytes.len())
+            .ok_or(DevTreeError::NotEnoughMemory)?
+            .copy_from_slice(bytes);
+        self.off += bytes.len();
+        Ok(start)
+    }
+
+    fn u32(&mut self, value: u32) -> Result<usize> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// Pad with zeros to a multiple of `align`, which must be a power of two.
+    fn align(&mut self, align: usize) -> Result<()> {
+        while self.off & (align - 1) != 0 {
+            self.bytes(&[0])?;
+        }
+        Ok(())
+    }
+
+    /// Add `delta` to the cell at `off`.
+    fn add_to_cell(&mut self, off: usize, delta: u32) {
+        let cell = &mut self.buf[off..off + size_of::<u32>()];
+        let value = u32::from_be_bytes([cell[0], cell[1], cell[2], cell[3]]);
+        cell.copy_from_slice(&value.wrapping_add(delta).to_be_bytes());
+    }
+}
+
+struct Fragment<'a, 'dt> {
+    /// The offset of the target node within the base tree.
+    target: usize,
+    /// The fragment's `__overlay__` node.
+    content: DevTreeNode<'a, 'dt>,
+}
+
+/// The base tree and overlay, which resolve the references of the overlay.
+struct Trees<'a, 'dt> {
+    base: &'a DevTree<'dt>,
+    overlay: &'a DevTree<'dt>,
+}
+
+impl<'a, 'dt> Trees<'a, 'dt> {
+    /// Returns the base node targeted by `fragment`.
+    fn fragment_target(
+        &self,
+        fragment: &DevTreeNode<'a, 'dt>,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        if let Some(path) = fragment.prop("target-path")? {
+            return self.base.node_at_path(path.str()?);
+        }
+        let target = match fragment.prop("target")? {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+        let mut phandle = target.phandle(0)?;
+        self.for_each_fixup(fragment, "target", |off, resolved| {
+            if off == 0 {
+                phandle = resolved;
+            }
+            Ok(())
+        })?;
+        self.base_node_by_phandle(phandle)
+    }
+
+    fn base_node_by_phandle(&self, phandle: u32) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        let mut nodes = self.base.nodes();
+        while let Some(node) = nodes.next()? {
+            for name in PHANDLE_PROPS.iter() {
+                if let Some(prop) = node.prop(name)? {
+                    if prop.phandle(0)? == phandle {
+                        return Ok(Some(node));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the phandle of the base node named by the label `label` within the base tree's
+    /// `/__symbols__`.
+    fn symbol_phandle(&self, label: &str) -> Result<u32> {
+        let missing = DevTreeError::InvalidParameter("Overlay refers to an undefined symbol");
+        let symbols = self.base.node_at_path("/__symbols__")?.ok_or(missing)?;
+        let path = symbols.prop(label)?.ok_or(missing)?;
+        let node = self.base.node_at_path(path.str()?)?.ok_or(missing)?;
+        for name in PHANDLE_PROPS.iter() {
+            if let Some(prop) = node.prop(name)? {
+                return prop.phandle(0);
+            }
+        }
+        Err(missing)
+    }
+
+    /// Call `f` with the offset and resolved phandle of each `/__fixups__` entry which refers to
+    /// the property `prop` of the overlay node `node`.
+    fn for_each_fixup<F>(&self, node: &DevTreeNode, prop: &str, mut f: F) -> Result<()>
+    where
+        F: FnMut(usize, u32) -> Result<()>,
+    {
+        let fixups = match self.overlay.node_at_path("/__fixups__")? {
+            Some(fixups) => fixups,
+            None => return Ok(()),
+        };
+        let mut labels = fixups.props();
+        while let Some(label) = labels.next()? {
+            for entry in label.iter_str() {
+                let mut fields = entry?.split(':');
+                let (path, name, off) = match (fields.next(), fields.next(), fields.next()) {
+                    (Some(path), Some(name), Some(off)) => (path, name, off),
+                    _ => return Err(DevTreeError::ParseError),
+                };
+                if name != prop {
+                    continue;
+                }
+                let target = self.overlay.node_at_path(path)?;
+                if target.and_then(|t| t.node_offset()) != node.node_offset() {
+                    continue;
+                }
+                let off = off.parse().map_err(|_| DevTreeError::ParseError)?;
+                f(off, self.symbol_phandle(label.name()?)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the node of `/__local_fixups__` which describes the overlay node `node`.
+    fn local_fixups(&self, node: &DevTreeNode) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        let fixups = match self.overlay.node_at_path("/__local_fixups__")? {
+            Some(fixups) => fixups,
+            None => return Ok(None),
+        };
+        let root = self.overlay.root()?.ok_or(DevTreeError::ParseError)?;
+        Self::find_counterpart(fixups, root, node.node_offset())
+    }
+
+    /// Descend through `fixups` and the overlay node `node` in parallel, returning the node of
+    /// `fixups` which corresponds to the overlay node at `target`.
+    fn find_counterpart(
+        fixups: DevTreeNode<'a, 'dt>,
+        node: DevTreeNode<'a, 'dt>,
+        target: Option<usize>,
+    ) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        if node.node_offset() == target {
+            return Ok(Some(fixups));
+        }
+        let mut children = fixups.children();
+        while let Some(child) = children.next()? {
+            if let Some(counterpart) = child_named(&node, child.name()?)? {
+                if let Some(found) = Self::find_counterpart(child, counterpart, target)? {
+                    return Ok(Some(found));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+}
+
+struct Applier<'a, 'dt, 'w> {
+    trees: Trees<'a, 'dt>,
+    fragments: BoundedVec<Fragment<'a, 'dt>, MAX_OVERLAY_FRAGMENTS>,
+    phandle_delta: u32,
+    out: Writer<'w>,
+}
+
+impl<'a, 'dt, 'w> Applier<'a, 'dt, 'w> {
+    fn find_fragments(&mut self) -> Result<()> {
+        let root = self.trees.overlay.root()?.ok_or(DevTreeError::ParseError)?;
+        let mut children = root.children();
+        while let Some(fragment) = children.next()? {
+            let content = match child_named(&fragment, "__overlay__")? {
+                Some(content) => content,
+                None => continue,
+            };
+            let target = self
+                .trees
+                .fragment_target(&fragment)?
+                .and_then(|target| target.node_offset())
+                .ok_or(DevTreeError::InvalidParameter(
+                    "Overlay fragment target was not found",
+                ))?;
+            self.fragments
+                .push(Fragment { target, content })
+                .map_err(|_| DevTreeError::NodeLimitExceeded)?;
+        }
+        Ok(())
+    }
+
+    /// Write a property whose name lies at `nameoff` within the merged strings block.
+    fn emit_prop(&mut self, value: &[u8], nameoff: usize) -> Result<usize> {
+        self.out.u32(FdtTok::Prop as u32)?;
+        self.out.u32(value.len() as u32)?;
+        self.out.u32(nameoff as u32)?;
+        let start = self.out.bytes(value)?;
+        self.out.align(size_of::<u32>())?;
+        Ok(start)
+    }
+
+    /// Write the property `prop` of the overlay node `node`, resolving its fixups.
+    fn emit_overlay_prop(&mut self, node: &DevTreeNode, prop: &DevTreeProp) -> Result<()> {
+        let name = prop.name()?;
+        let value = prop.propbuf();
+        let nameoff = self.trees.base.strings_block().len() + prop.nameoff();
+        let start = self.emit_prop(value, nameoff)?;
+
+        let cell = |off: usize| match off.checked_add(size_of::<u32>()) {
+            Some(end) if end <= value.len() => Ok(start + off),
+            _ => Err(DevTreeError::ParseError),
+        };
+        let delta = self.phandle_delta;
+        if PHANDLE_PROPS.contains(&name) {
+            self.out.add_to_cell(cell(0)?, delta);
+        }
+        if let Some(fixups) = self.trees.local_fixups(node)? {
+            if let Some(offsets) = fixups.prop(name)? {
+                for off in offsets.iter_u32() {
+                    self.out.add_to_cell(cell(off as usize)?, delta);
+                }
+            }
+        }
+        let out = &mut self.out;
+        self.trees.for_each_fixup(node, name, |off, phandle| {
+            let off = cell(off)?;
+            out.buf[off..off + size_of::<u32>()].copy_from_slice(&phandle.to_be_bytes());
+            Ok(())
+        })
+    }
+
+    /// Write the node `name`, merging the overlay nodes `merged` (in order of application) into
+    /// the base node `base`, along with the contents of any fragments which target it.
+    fn emit_node(
+        &mut self,
+        name: &str,
+        base: Option<DevTreeNode<'a, 'dt>>,
+        merged: &[DevTreeNode<'a, 'dt>],
+    ) -> Result<()> {
+        let mut overlays: BoundedVec<DevTreeNode<'a, 'dt>, MAX_MERGED_NODES> = BoundedVec::new();
+        let targets = self.fragments.iter().filter(|fragment| {
+            matches!(&base, Some(base) if base.node_offset() == Some(fragment.target))
+        });
+        for node in merged.iter().chain(targets.map(|fragment| &fragment.content)) {
+            overlays
+                .push(node.clone())
+                .map_err(|_| DevTreeError::NodeLimitExceeded)?;
+        }
+
+        self.out.u32(FdtTok::BeginNode as u32)?;
+        self.out.bytes(name.as_bytes())?;
+        self.out.bytes(&[0])?;
+        self.out.align(size_of::<u32>())?;
+
+        // Base properties, replaced by the last overlay property of the same name.
+        if let Some(base) = &base {
+            let mut props = base.props();
+            while let Some(prop) = props.next()? {
+                let mut replacement = None;
+                for node in overlays.iter().rev() {
+                    if let Some(new) = node.prop(prop.name()?)? {
+                        replacement = Some((node, new));
+                        break;
+                    }
+                }
+                match replacement {
+                    Some((node, new)) => self.emit_overlay_prop(node, &new)?,
+                    None => {
+                        self.emit_prop(prop.propbuf(), prop.nameoff())?;
+                    }
+                }
+            }
+        }
+        // New properties, written once by the last overlay node to set them.
+        for (i, node) in overlays.iter().enumerate() {
+            let mut props = node.props();
+            while let Some(prop) = props.next()? {
+                let name = prop.name()?;
+                if let Some(base) = &base {
+                    if has_prop(base, name)? {
+                        continue;
+                    }
+                }
+                let mut later = false;
+                for next in &overlays[i + 1..] {
+                    later |= has_prop(next, name)?;
+                }
+                if !later {
+                    self.emit_overlay_prop(node, &prop)?;
+                }
+            }
+        }
+
+        // Base children, merged with the overlay children of the same name.
+        if let Some(base) = &base {
+            let mut children = base.children();
+            while let Some(child) = children.next()? {
+                let child_name = child.name()?;
+                let mut child_merged: BoundedVec<_, MAX_MERGED_NODES> = BoundedVec::new();
+                for node in overlays.iter() {
+                    if let Some(new) = child_named(node, child_name)? {
+                        // Cannot fail, as there are no more children than overlays.
+                        let _ = child_merged.push(new);
+                    }
+                }
+                self.emit_node(child_name, Some(child), &child_merged)?;
+            }
+        }
+        // New children, written once by the first overlay node to add them.
+        for (i, node) in overlays.iter().enumerate() {
+            let mut children = node.children();
+            while let Some(child) = children.next()? {
+                let child_name = child.name()?;
+                if let Some(base) = &base {
+                    if child_named(base, child_name)?.is_some() {
+                        continue;
+                    }
+                }
+                let mut earlier = false;
+                for prev in &overlays[..i] {
+                    earlier |= child_named(prev, child_name)?.is_some();
+                }
+                if earlier {
+                    continue;
+                }
+                let mut child_merged: BoundedVec<_, MAX_MERGED_NODES> = BoundedVec::new();
+                let _ = child_merged.push(child.clone());
+                for next in &overlays[i + 1..] {
+                    if let Some(new) = child_named(next, child_name)? {
+                        let _ = child_merged.push(new);
+                    }
+                }
+                self.emit_node(child_name, None, &child_merged)?;
+            }
+        }
+
+        self.out.u32(FdtTok::EndNode as u32)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+//! Module exporting traits of this library.
+//!
+//! The concrete types of this library provide their most common operations as inherent methods,
+//! so these traits are mainly needed by code which is generic over the backend, and to iterate
+//! the [`FallibleIterator`]s returned by [`crate::base`].
+pub(crate) use crate::priv_util::SliceRead;
+
+pub use crate::error::ErrorContext;
+pub use crate::matcher::Matcher;
+pub use crate::traits::{IterableDevTree, PropReader, UnwrappableDevTreeItem};
+
+pub use fallible_iterator::FallibleIterator;
@@ -0,0 +1,303 @@
+//! Minimal device tree printing utilities which do not require an allocator.
+//!
+//! These utilities write directly into a [`core::fmt::Write`] implementation (such as a UART
+//! console writer) and are intended for early-boot debugging.
+//!
+//! [`write_dts`] renders the whole tree as device tree source which may be read by `dtc`, e.g. to
+//! inspect what a bootloader actually passed. With the `std` feature, fdt-rs also provides
+//! `print::to_dts` and the `ToDts` trait of its prelude, which return it as a `String`.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! let mut out = String::new();
+//!
+//! // Print the first two levels of the tree, showing at most 8 bytes of each value.
+//! index.write_tree(&mut out, 1, 8).unwrap();
+//! assert!(out.starts_with("/\n"));
+//!
+//! let dts = index.to_dts().unwrap();
+//! assert!(dts.starts_with("/dts-v1/;\n\n/ {\n"));
+//! assert!(dts.contains("\tuart@10000000 {\n\t\tinterrupts = <0xa>;\n"));
+//! ```
+
+use core::fmt::Write;
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::base::parse::ParsedTok;
+use crate::base::DevTree;
+use crate::error::{DevTreeError, Result};
+
+/// Number of spaces used to indent each level of the tree.
+const INDENT: usize = 2;
+
+/// A token of the structure block to print, with the name of a property looked up.
+pub(crate) enum PrintTok<'dt> {
+    BeginNode(&'dt [u8]),
+    Prop(Result<&'dt str>, &'dt [u8]),
+    EndNode,
+}
+
+impl<'dt> PrintTok<'dt> {
+    /// Returns the token to print for `tok`, a token of `fdt`, or `None` if it is not printed.
+    pub(crate) fn new(fdt: &DevTree<'dt>, tok: ParsedTok<'dt>) -> Option<Self> {
+        match tok {
+            ParsedTok::BeginNode(node) => Some(PrintTok::BeginNode(node.name)),
+            ParsedTok::Prop(prop) => Some(PrintTok::Prop(
+                fdt.string_at(prop.name_offset),
+                prop.prop_buf,
+            )),
+            ParsedTok::EndNode => Some(PrintTok::EndNode),
+            ParsedTok::Nop => None,
+        }
+    }
+}
+
+/// Returns the tokens of `fdt` to print.
+fn tokens<'a, 'dt: 'a>(
+    fdt: &'a DevTree<'dt>,
+) -> impl FallibleIterator<Item = PrintTok<'dt>, Error = DevTreeError> + 'a {
+    fdt.parse_iter()
+        .filter_map(move |tok| Ok(PrintTok::new(fdt, tok)))
+}
+
+/// Write an indented dump of `fdt` into `w`.
+///
+/// Nodes deeper than `max_depth` (the root node has a depth of 0) are elided and replaced with a
+/// single `...` line. At most `max_value_bytes` bytes of each property value are printed; longer
+/// values are followed by `...`.
+///
+/// Property values are printed as strings when they look like a string list, as 32-bit cells
+/// when their length is a multiple of four, and as raw bytes otherwise.
+pub fn write_tree<W: Write>(
+    fdt: &DevTree,
+    w: &mut W,
+    max_depth: usize,
+    max_value_bytes: usize,
+) -> Result<()> {
+    write_tree_tokens(tokens(fdt), w, max_depth, max_value_bytes)
+}
+
+/// Write an indented dump of the device tree whose tokens are `iter`, see [`write_tree`].
+pub(crate) fn write_tree_tokens<'dt, I, W>(
+    mut iter: I,
+    w: &mut W,
+    max_depth: usize,
+    max_value_bytes: usize,
+) -> Result<()>
+where
+    I: FallibleIterator<Item = PrintTok<'dt>, Error = DevTreeError>,
+    W: Write,
+{
+    // Depth of the next node to begin.
+    let mut depth = 0usize;
+    // Depth at which we last wrote an elision marker.
+    let mut elided_at = None;
+
+    while let Some(tok) = iter.next()? {
+        match tok {
+            PrintTok::BeginNode(name) => {
+                if depth <= max_depth {
+                    elided_at = None;
+                    write_indent(w, depth)?;
+                    match core::str::from_utf8(name) {
+                        Ok("") if depth == 0 => w.write_str("/")?,
+                        Ok(name) => w.write_str(name)?,
+                        Err(_) => w.write_str("<invalid name>")?,
+                    }
+                    w.write_char('\n')?;
+                } else if depth == max_depth + 1 && elided_at != Some(depth) {
+                    elided_at = Some(depth);
+                    write_indent(w, depth)?;
+                    w.write_str("...\n")?;
+                }
+                depth += 1;
+            }
+            PrintTok::Prop(name, value) => {
+                if depth == 0 || depth - 1 > max_depth {
+                    continue;
+                }
+                write_indent(w, depth)?;
+                w.write_str(name.unwrap_or("<invalid name>"))?;
+                if !value.is_empty() {
+                    w.write_str(" = ")?;
+                    write_value(w, value, ValueStyle::Dump(max_value_bytes))?;
+                }
+                w.write_char('\n')?;
+            }
+            PrintTok::EndNode => depth = depth.saturating_sub(1),
+        }
+    }
+    Ok(())
+}
+
+fn write_indent<W: Write>(w: &mut W, depth: usize) -> core::fmt::Result {
+    for _ in 0..depth * INDENT {
+        w.write_char(' ')?;
+    }
+    Ok(())
+}
+
+/// How a property value is written.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueStyle {
+    /// As by [`write_tree`], showing at most the given number of bytes.
+    Dump(usize),
+    /// As device tree source, by [`write_dts`].
+    Dts,
+}
+
+/// Returns true if the value is a list of non-empty, null-terminated strings which may be
+/// written as (escaped) string literals in the given style. Device tree source may also hold
+/// tabs and line breaks.
+pub(crate) fn is_strlist(value: &[u8], style: ValueStyle) -> bool {
+    if value.first() == Some(&0) || value.last() != Some(&0) {
+        return false;
+    }
+    let printable = |b: u8| match style {
+        ValueStyle::Dump(_) => b.is_ascii_graphic() || b == b' ',
+        ValueStyle::Dts => b.is_ascii_graphic() || matches!(b, b' ' | b'\t' | b'\n' | b'\r'),
+    };
+    value.windows(2).all(|w| w != [0, 0]) && value.iter().all(|&b| b == 0 || printable(b))
+}
+
+fn write_value<W: Write>(w: &mut W, value: &[u8], style: ValueStyle) -> core::fmt::Result {
+    let max_value_bytes = match style {
+        ValueStyle::Dump(max_value_bytes) => max_value_bytes,
+        ValueStyle::Dts => value.len(),
+    };
+    let truncated = value.len() > max_value_bytes;
+    let shown = &value[..core::cmp::min(value.len(), max_value_bytes)];
+
+    if is_strlist(value, style) {
+        // Drop the final null terminator, it is implied by the closing quote.
+        let shown = match shown.last() {
+            Some(0) => &shown[..shown.len() - 1],
+            _ => shown,
+        };
+        for (i, s) in shown.split(|&b| b == 0).enumerate() {
+            if i != 0 {
+                w.write_str(", ")?;
+            }
+            w.write_char('"')?;
+            for &b in s {
+                match b {
+                    b'"' => w.write_str("\\\"")?,
+                    b'\\' => w.write_str("\\\\")?,
+                    b'\t' => w.write_str("\\t")?,
+                    b'\n' => w.write_str("\\n")?,
+                    b'\r' => w.write_str("\\r")?,
+                    _ => w.write_char(b as char)?,
+                }
+            }
+            w.write_char('"')?;
+        }
+    } else if value.len() & 3 == 0 {
+        w.write_char('<')?;
+        for (i, cell) in shown.chunks(4).enumerate() {
+            if i != 0 {
+                w.write_char(' ')?;
+            }
+            if cell.len() == 4 {
+                let cell = u32::from_be_bytes([cell[0], cell[1], cell[2], cell[3]]);
+                write!(w, "{:#x}", cell)?;
+            } else {
+                for b in cell {
+                    write!(w, "{:02x}", b)?;
+                }
+            }
+        }
+        w.write_char('>')?;
+    } else {
+        w.write_char('[')?;
+        for (i, b) in shown.iter().enumerate() {
+            if i != 0 {
+                w.write_char(' ')?;
+            }
+            write!(w, "{:02x}", b)?;
+        }
+        w.write_char(']')?;
+    }
+
+    if truncated {
+        w.write_str("...")?;
+    }
+    Ok(())
+}
+
+/// Write `fdt` into `w` as device tree source.
+///
+/// Nothing is allocated, so this may be used at early boot to dump the device tree to a console.
+/// Entries of the memory reservation block are written as `/memreserve/` directives. Property
+/// values are written as strings when they are a list of non-empty printable strings (escaping
+/// quotes, backslashes and control characters), as 32-bit cells when their length is a multiple
+/// of four, and as bytes otherwise. Nodes are indented by a tab per level, as `dtc` does.
+///
+/// Node and property names which are not valid UTF-8 are reported as a
+/// [`crate::error::DevTreeError::StrError`].
+pub fn write_dts<W: Write>(fdt: &DevTree, w: &mut W) -> Result<()> {
+    write_dts_tokens(fdt, tokens(fdt), w)
+}
+
+/// Write the device tree whose tokens are `iter` into `w` as device tree source, see
+/// [`write_dts`]. The memory reservations are those of `fdt`.
+pub(crate) fn write_dts_tokens<'dt, I, W>(fdt: &DevTree, mut iter: I, w: &mut W) -> Result<()>
+where
+    I: FallibleIterator<Item = PrintTok<'dt>, Error = DevTreeError>,
+    W: Write,
+{
+    w.write_str("/dts-v1/;\n\n")?;
+    let mut reserved = false;
+    for entry in fdt.reserved_entries() {
+        let (address, size) = (u64::from(entry.address), u64::from(entry.size));
+        writeln!(w, "/memreserve/ {:#018x} {:#018x};", address, size)?;
+        reserved = true;
+    }
+    if reserved {
+        w.write_char('\n')?;
+    }
+
+    let mut depth = 0usize;
+    while let Some(tok) = iter.next()? {
+        match tok {
+            PrintTok::BeginNode(name) => {
+                if depth != 0 {
+                    w.write_char('\n')?;
+                }
+                write_dts_indent(w, depth)?;
+                match from_utf8(name)? {
+                    "" if depth == 0 => w.write_char('/')?,
+                    name => w.write_str(name)?,
+                }
+                w.write_str(" {\n")?;
+                depth += 1;
+            }
+            PrintTok::Prop(name, value) => {
+                write_dts_indent(w, depth)?;
+                w.write_str(name?)?;
+                if !value.is_empty() {
+                    w.write_str(" = ")?;
+                    write_value(w, value, ValueStyle::Dts)?;
+                }
+                w.write_str(";\n")?;
+            }
+            PrintTok::EndNode => {
+                depth = depth.saturating_sub(1);
+                write_dts_indent(w, depth)?;
+                w.write_str("};\n")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_dts_indent<W: Write>(w: &mut W, depth: usize) -> core::fmt::Result {
+    for _ in 0..depth {
+        w.write_char('\t')?;
+    }
+    Ok(())
+}
@@ -1,6 +1,9 @@
 use core::mem::size_of;
 use core::ptr::read_unaligned;
 
+#[cfg(not(feature = "checked"))]
+use unsafe_unwrap::UnsafeUnwrap;
+
 #[derive(Debug, Copy, Clone)]
 pub enum SliceReadError {
     InvalidOffset(usize, usize),
@@ -69,10 +72,50 @@ impl<'a> SliceRead<'a> for &'a [u8] {
     unsafe fn nread_bstring0(&self, pos: usize, len: usize) -> SliceReadResult<&'a [u8]> {
         let end = core::cmp::min(len + pos, self.len());
         for i in pos..end {
-            if *self.get_unchecked(i) == 0 {
+            #[cfg(feature = "checked")]
+            let byte = self[i];
+            #[cfg(not(feature = "checked"))]
+            let byte = *self.get_unchecked(i);
+            if byte == 0 {
                 return Ok(&self[pos..i]);
             }
         }
         Err(SliceReadError::UnexpectedEndOfInput)
     }
 }
+
+/// Unwrapping of values which are present by construction.
+///
+/// By default no check is performed. With the `checked` feature enabled a violated invariant
+/// panics instead of causing undefined behavior.
+pub(crate) trait InvariantUnwrap<T> {
+    unsafe fn invariant_unwrap(self) -> T;
+}
+
+impl<T> InvariantUnwrap<T> for Option<T> {
+    #[inline]
+    unsafe fn invariant_unwrap(self) -> T {
+        #[cfg(feature = "checked")]
+        return self.expect("device tree invariant violated");
+        #[cfg(not(feature = "checked"))]
+        return self.unsafe_unwrap();
+    }
+}
+
+impl<T, E> InvariantUnwrap<T> for Result<T, E> {
+    #[inline]
+    unsafe fn invariant_unwrap(self) -> T {
+        #[cfg(feature = "checked")]
+        return self.ok().invariant_unwrap();
+        #[cfg(not(feature = "checked"))]
+        return self.unsafe_unwrap();
+    }
+}
+
+/// Returns the offset of the first occurrence of `needle` within `haystack`.
+pub(crate) fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,97 @@
+//! Statistics on the cost of device tree searches.
+//!
+//! When the `profiling` feature is enabled, searches record the number of structure block tokens
+//! they scanned and the number of nodes they visited. These statistics may be used to decide
+//! empirically whether building a [`crate::index::DevTreeIndex`] is worthwhile for a given tree
+//! and access pattern.
+//!
+//! The following calls are recorded as searches:
+//!
+//! * Advancing a compatible node iterator of either backend (and therefore
+//!   [`crate::prelude::IterableDevTree::find_first_compatible_node`]).
+//! * [`crate::index::DevTreeIndex::node_at_path`]
+//! * [`crate::index::DevTreeIndex::node_from_base`]
+//!
+//! The index does not rescan the structure block, so its searches only visit nodes.
+//!
+//! Statistics are stored globally. If searches run concurrently, [`last_search_stats`] reports
+//! whichever completed last and its counts may include work done by the others.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::profiling::last_search_stats;
+//!
+//! let fdt = index.fdt();
+//! fdt.compatible_nodes("riscv,plic0").next().unwrap();
+//! let streaming = last_search_stats();
+//!
+//! index.compatible_nodes("riscv,plic0").next().unwrap();
+//! let indexed = last_search_stats();
+//!
+//! assert!(streaming.tokens_scanned > 0);
+//! assert_eq!(indexed.tokens_scanned, 0);
+//! assert!(indexed.nodes_visited > 0);
+//! ```
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+static TOKENS: AtomicUsize = AtomicUsize::new(0);
+static NODES: AtomicUsize = AtomicUsize::new(0);
+static LAST_TOKENS: AtomicUsize = AtomicUsize::new(0);
+static LAST_NODES: AtomicUsize = AtomicUsize::new(0);
+
+/// The cost of a single search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Number of structure block tokens parsed.
+    pub tokens_scanned: usize,
+    /// Number of nodes visited.
+    pub nodes_visited: usize,
+}
+
+/// Returns the statistics of the most recently completed search.
+#[must_use]
+pub fn last_search_stats() -> SearchStats {
+    SearchStats {
+        tokens_scanned: LAST_TOKENS.load(Ordering::Relaxed),
+        nodes_visited: LAST_NODES.load(Ordering::Relaxed),
+    }
+}
+
+// Only loads and stores are used so that targets without atomic read-modify-write instructions
+// are supported.
+fn increment(counter: &AtomicUsize) {
+    counter.store(counter.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+}
+
+/// Records that a structure block token was parsed.
+#[inline]
+pub(crate) fn record_token() {
+    increment(&TOKENS);
+}
+
+/// Records that a node was visited.
+#[inline]
+pub(crate) fn record_node() {
+    increment(&NODES);
+}
+
+/// A search in progress. The statistics of the search are published when this is dropped.
+pub(crate) struct Search(());
+
+impl Search {
+    pub(crate) fn begin() -> Self {
+        TOKENS.store(0, Ordering::Relaxed);
+        NODES.store(0, Ordering::Relaxed);
+        Search(())
+    }
+}
+
+impl Drop for Search {
+    fn drop(&mut self) {
+        LAST_TOKENS.store(TOKENS.load(Ordering::Relaxed), Ordering::Relaxed);
+        LAST_NODES.store(NODES.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
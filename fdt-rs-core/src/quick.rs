@@ -0,0 +1,172 @@
+//! Minimal queries over a raw device tree buffer.
+//!
+//! The functions within this module walk the structure block of a flattened device tree
+//! directly, without building a [`crate::base::DevTree`] or using any of the iterator or trait
+//! machinery of the rest of the crate. They are intended for early boot code (such as stage-1
+//! loaders) where code size and simplicity matter more than ergonomics.
+//!
+//! All reads are bounds checked; a malformed buffer results in `None` rather than a panic.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::FDT;
+//! use fdt_rs::quick;
+//!
+//! let (uart_base, _offset) = quick::find_compatible(FDT, "ns16550a").unwrap();
+//! assert_eq!(uart_base, 0x1000_0000);
+//!
+//! let (ram_base, _ram_size) = quick::memory(FDT).unwrap();
+//! assert_eq!(ram_base, 0x8000_0000);
+//! ```
+use core::convert::TryFrom;
+use core::mem::size_of;
+
+use crate::spec::{fdt_prop_header, FdtTok, FDT_MAGIC};
+
+/// Maximum node depth tracked while searching. Deeper nodes are not inspected.
+const MAX_DEPTH: usize = 16;
+
+/// Byte offset of the `off_dt_struct` field within the `fdt_header`.
+const OFF_DT_STRUCT_FIELD: usize = 8;
+/// Byte offset of the `off_dt_strings` field within the `fdt_header`.
+const OFF_DT_STRINGS_FIELD: usize = 12;
+
+fn read_u32(buf: &[u8], off: usize) -> Option<u32> {
+    let bytes = buf.get(off..off.checked_add(size_of::<u32>())?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_cells(buf: &[u8], off: usize, cells: u32) -> Option<u64> {
+    match cells {
+        0 => Some(0),
+        1 => read_u32(buf, off).map(u64::from),
+        2 => Some(u64::from(read_u32(buf, off)?) << 32 | u64::from(read_u32(buf, off + 4)?)),
+        _ => None,
+    }
+}
+
+fn align_u32(off: usize) -> usize {
+    (off + size_of::<u32>() - 1) & !(size_of::<u32>() - 1)
+}
+
+/// Returns true if the null terminated string at `off` within `buf` equals `name`.
+fn str_at_eq(buf: &[u8], off: usize, name: &[u8]) -> bool {
+    buf.get(off..off + name.len()) == Some(name) && buf.get(off + name.len()) == Some(&0)
+}
+
+/// Returns true if the stringlist `value` contains `s`.
+fn strlist_contains(value: &[u8], s: &[u8]) -> bool {
+    value
+        .split(|&b| b == 0)
+        .any(|entry| !entry.is_empty() && entry == s)
+}
+
+/// Search for the first node with a property for which `matches(name_off, value)` returns true.
+///
+/// `name_off` is the absolute offset of the property's name within `fdt`. Returns the first
+/// `reg` entry of the matching node (decoded using its parent's cell counts) and the offset of
+/// the node within `fdt`. Matching nodes without a `reg` property are skipped.
+fn find_node<F>(fdt: &[u8], matches: F) -> Option<(u64, u64, usize)>
+where
+    F: Fn(usize, &[u8]) -> bool,
+{
+    if read_u32(fdt, 0)? != FDT_MAGIC {
+        return None;
+    }
+    let strings = read_u32(fdt, OFF_DT_STRINGS_FIELD)? as usize;
+    let mut off = read_u32(fdt, OFF_DT_STRUCT_FIELD)? as usize;
+
+    // The (#address-cells, #size-cells) of the node at each depth, as applied to its children.
+    let mut cells = [(2u32, 1u32); MAX_DEPTH + 1];
+    let mut depth = 0usize;
+
+    // State of the node currently being parsed.
+    let mut node_off = 0;
+    let mut matched = false;
+    let mut reg: Option<&[u8]> = None;
+
+    loop {
+        let tok_off = off;
+        let tok = read_u32(fdt, off)?;
+        off += size_of::<u32>();
+
+        // The properties of a node precede its children, so any new node or the end of the
+        // current one completes the current node.
+        if tok == FdtTok::BeginNode as u32 || tok == FdtTok::EndNode as u32 {
+            if let (true, Some(reg), Some(&(address_cells, size_cells))) =
+                (matched, reg, depth.checked_sub(1).and_then(|d| cells.get(d)))
+            {
+                let address = read_cells(reg, 0, address_cells)?;
+                let size = read_cells(reg, address_cells as usize * 4, size_cells)?;
+                return Some((address, size, node_off));
+            }
+            matched = false;
+            reg = None;
+        }
+
+        match tok {
+            t if t == FdtTok::BeginNode as u32 => {
+                depth += 1;
+                if let Some(c) = cells.get_mut(depth) {
+                    *c = (2, 1);
+                }
+                node_off = tok_off;
+                let name_len = fdt.get(off..)?.iter().position(|&b| b == 0)?;
+                off = align_u32(off + name_len + 1);
+            }
+            t if t == FdtTok::Prop as u32 => {
+                let len = read_u32(fdt, off)? as usize;
+                let name_off = strings.checked_add(read_u32(fdt, off + 4)? as usize)?;
+                let data = off + size_of::<fdt_prop_header>();
+                let value = fdt.get(data..data.checked_add(len)?)?;
+                off = align_u32(data + len);
+
+                if depth > MAX_DEPTH {
+                    continue;
+                }
+                if str_at_eq(fdt, name_off, b"reg") {
+                    reg = Some(value);
+                } else if str_at_eq(fdt, name_off, b"#address-cells") {
+                    cells[depth].0 = read_u32(value, 0)?;
+                } else if str_at_eq(fdt, name_off, b"#size-cells") {
+                    cells[depth].1 = read_u32(value, 0)?;
+                }
+                matched |= matches(name_off, value);
+            }
+            t if t == FdtTok::EndNode as u32 => depth = depth.checked_sub(1)?,
+            t if t == FdtTok::Nop as u32 => {}
+            _ => return None,
+        }
+    }
+}
+
+/// Find the first node which is compatible with `compatible`.
+///
+/// Returns the base address from the node's first `reg` entry and the offset of the node's
+/// `FDT_BEGIN_NODE` token within `fdt`. Compatible nodes without a `reg` property are skipped.
+///
+/// Returns `None` if no such node exists, if the base address does not fit within a `usize`, or
+/// if `fdt` is malformed.
+#[must_use]
+pub fn find_compatible(fdt: &[u8], compatible: &str) -> Option<(usize, usize)> {
+    let (address, _, offset) = find_node(fdt, |name_off, value| {
+        str_at_eq(fdt, name_off, b"compatible") && strlist_contains(value, compatible.as_bytes())
+    })?;
+    Some((usize::try_from(address).ok()?, offset))
+}
+
+/// Find the first memory region, as described by the first `reg` entry of the first node with a
+/// `device_type` of `"memory"`.
+///
+/// Returns the `(base, size)` of the region or `None` if no such node exists, if the region does
+/// not fit within a `usize`, or if `fdt` is malformed.
+///
+/// See [`crate::util::memory`] to enumerate every memory node and region.
+#[must_use]
+pub fn memory(fdt: &[u8]) -> Option<(usize, usize)> {
+    let (address, size, _) = find_node(fdt, |name_off, value| {
+        str_at_eq(fdt, name_off, b"device_type") && value == b"memory\0"
+    })?;
+    Some((usize::try_from(address).ok()?, usize::try_from(size).ok()?))
+}
@@ -0,0 +1,74 @@
+//! Serialization of an index with [`serde`], e.g. to convert a device tree to JSON or YAML.
+//!
+//! A [`DevTreeIndex`] serializes as its root node. Each node is a map from the names of its
+//! properties to their values, followed by the names of its child nodes to the children
+//! themselves. Property values are serialized by their apparent type, as `dtc` guesses them
+//! when decompiling:
+//!
+//! * An empty value (a boolean property) as `true`.
+//! * A list of printable strings as a string, or a sequence of strings if it holds more than one.
+//! * A value whose length is a multiple of four as a sequence of 32-bit cells.
+//! * Any other value as bytes.
+//!
+//! Names which are not valid UTF-8 are reported as an error of the serializer.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _buf) = doctest_index();
+//! let json = serde_json::to_value(&index).unwrap();
+//! assert_eq!(json["#address-cells"], serde_json::json!([2]));
+//! assert_eq!(json["uart@10000000"]["compatible"], "ns16550a");
+//! ```
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::prelude::*;
+
+use crate::index::{DevTreeIndex, DevTreeIndexNode, DevTreeIndexProp};
+use crate::print::{is_strlist, ValueStyle};
+
+impl<'i, 'dt: 'i> Serialize for DevTreeIndex<'i, 'dt> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.root().serialize(serializer)
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Serialize for DevTreeIndexNode<'a, 'i, 'dt> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for prop in self.props() {
+            map.serialize_entry(prop.name().map_err(S::Error::custom)?, &prop)?;
+        }
+        for child in self.children() {
+            map.serialize_entry(child.name().map_err(S::Error::custom)?, &child)?;
+        }
+        map.end()
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Serialize for DevTreeIndexProp<'a, 'i, 'dt> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.propbuf();
+        if value.is_empty() {
+            serializer.serialize_bool(true)
+        } else if is_strlist(value, ValueStyle::Dts) {
+            if self.iter_str().count() == 1 {
+                return serializer.serialize_str(self.str().map_err(S::Error::custom)?);
+            }
+            let mut seq = serializer.serialize_seq(None)?;
+            for s in self.iter_str() {
+                seq.serialize_element(s.map_err(S::Error::custom)?)?;
+            }
+            seq.end()
+        } else if value.len() & 3 == 0 {
+            let mut seq = serializer.serialize_seq(Some(value.len() / 4))?;
+            for cell in value.chunks_exact(4) {
+                seq.serialize_element(&u32::from_be_bytes([cell[0], cell[1], cell[2], cell[3]]))?;
+            }
+            seq.end()
+        } else {
+            serializer.serialize_bytes(value)
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Definitions of structs and enums from the device tree specification.
+use core::mem::size_of;
+
+use endian_type::types::{u32_be, u64_be};
+use num_derive::FromPrimitive;
+
+/// Magic number used to denote the beginning of a device tree (as a native machine number).
+pub const FDT_MAGIC: u32 = 0xd00d_feed;
+/// Maximum length of a device tree node name (including null byte)
+pub const MAX_NODE_NAME_LEN: usize = 31;
+/// Maximum length of a node's full name, including its `@` separator and unit address but
+/// excluding the null byte, accepted by the parser.
+///
+/// The specification limits the node name to 31 characters but does not limit the unit address.
+/// 63 characters are allowed for the unit address, which is ample for addresses of several
+/// 64-bit cells written in hexadecimal.
+pub const MAX_FULL_NODE_NAME_LEN: usize = 31 + 1 + 63;
+/// Maximum length of a node's full path (excluding the null byte) accepted by the parser within
+/// the `FDT_BEGIN_NODE` tokens of device trees older than version 16, which name each node by
+/// its full path rather than its name.
+#[cfg(feature = "legacy-fdt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "legacy-fdt")))]
+pub const MAX_LEGACY_NODE_PATH_LEN: usize = 4095;
+/// Maximum length of a property name (excluding the null byte)
+pub const MAX_PROP_NAME_LEN: usize = 31;
+
+/// Definition of the parsed phandle as a native machine number
+pub type Phandle = u32;
+
+/// An enumeration of the tokens used to separate sections within the `dt_struct` section of the FDT.
+#[derive(FromPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FdtTok {
+    BeginNode = 0x1,
+    EndNode = 0x2,
+    Prop = 0x3,
+    Nop = 0x4,
+    End = 0x9,
+}
+
+/// The `fdt_header` (Flattened Device Tree Header) as described by the specification
+#[repr(C)]
+pub struct fdt_header {
+    pub magic: u32_be,
+    pub totalsize: u32_be,
+    pub off_dt_struct: u32_be,
+    pub off_dt_strings: u32_be,
+    pub off_mem_rsvmap: u32_be,
+    pub version: u32_be,
+    pub last_comp_version: u32_be,
+    pub boot_cpuid_phys: u32_be,
+    pub size_dt_strings: u32_be,
+    pub size_dt_struct: u32_be,
+}
+
+/// Byte offsets of the fields of [`fdt_header`], for reading them from a buffer.
+pub(crate) mod header_off {
+    pub const MAGIC: usize = 0;
+    pub const TOTALSIZE: usize = 4;
+    pub const OFF_DT_STRUCT: usize = 8;
+    pub const OFF_DT_STRINGS: usize = 12;
+    pub const OFF_MEM_RSVMAP: usize = 16;
+    pub const VERSION: usize = 20;
+    pub const LAST_COMP_VERSION: usize = 24;
+    pub const BOOT_CPUID_PHYS: usize = 28;
+    pub const SIZE_DT_STRINGS: usize = 32;
+    pub const SIZE_DT_STRUCT: usize = 36;
+}
+
+// The header's fields are consecutive u32s, so the offsets above follow from their order.
+const_assert_eq!(size_of::<fdt_header>(), 10 * size_of::<u32>());
+
+/// The `fdt_prop_header` (Flattened Device Tree Property header) as described by the specification
+#[repr(C)]
+pub struct fdt_prop_header {
+    /// Length of the property data
+    pub len: u32_be,
+    /// Offset of the property name string within the dt_strings section
+    pub nameoff: u32_be,
+}
+
+// Packed, as a device tree need only be 32-bit aligned.
+#[repr(C, packed(4))]
+pub struct fdt_reserve_entry {
+    /// Starting address of the reserved memory region
+    pub address: u64_be,
+    /// Size of the reserved memory region
+    pub size: u64_be,
+}
+
+/// The value of a node's `status` property as described by the specification.
+///
+/// A node without a `status` property is [`Status::Okay`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status<'dt> {
+    /// The device is operational (`"okay"`, or the legacy `"ok"`).
+    Okay,
+    /// The device is not presently operational, but it might become operational in the future.
+    Disabled,
+    /// The device is operational, but should not be used (e.g. it is controlled by other
+    /// software).
+    Reserved,
+    /// The device is not operational due to a serious error (`"fail"`). A `"fail-sss"` status
+    /// carries the device specific error condition `sss`.
+    Fail(Option<&'dt str>),
+    /// A status value not defined by the specification, such as a vendor extension.
+    Unknown(&'dt str),
+}
+
+impl<'dt> From<&'dt str> for Status<'dt> {
+    fn from(status: &'dt str) -> Self {
+        match status {
+            "okay" | "ok" => Status::Okay,
+            "disabled" => Status::Disabled,
+            "reserved" => Status::Reserved,
+            "fail" => Status::Fail(None),
+            s if s.starts_with("fail-") => Status::Fail(Some(&s["fail-".len()..])),
+            s => Status::Unknown(s),
+        }
+    }
+}
+
+/// How lookups by name handle a node containing multiple properties of the same name.
+///
+/// The specification requires property names to be unique within a node, but malformed device
+/// trees may violate this.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePropPolicy {
+    /// The first property of the name, in document order, is returned.
+    #[default]
+    FirstWins,
+    /// The last property of the name, in document order, is returned.
+    LastWins,
+    /// A [`crate::error::DevTreeError::DuplicateProperty`] error is returned.
+    Strict,
+}
+
+/// How the structure block is parsed when it ends without an `FDT_END` token.
+///
+/// The end of the structure block is known from the `size_dt_struct` header field, which is
+/// present from version 17 of the format. Earlier device trees' structure blocks are taken to end
+/// at the start of their strings block, see [`crate::base::DevTree::size_dt_struct`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingEndPolicy {
+    /// Parsing stops at the end of the structure block as if an `FDT_END` token were present.
+    Permissive,
+    /// A [`crate::error::DevTreeError::MissingEndToken`] error is returned. This is the default.
+    Strict,
+}
+
+/// A property classified by its name, for the properties defined by the specification which this
+/// crate decodes.
+///
+/// Each variant carries the property itself so its value may still be read. Adding a standard
+/// property to this enum is a breaking change, so exhaustive matches are revisited whenever the
+/// set of recognized properties grows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StandardProp<P> {
+    /// `reg`
+    Reg(P),
+    /// `compatible`
+    Compatible(P),
+    /// `status`
+    Status(P),
+    /// `#address-cells`
+    AddressCells(P),
+    /// `#size-cells`
+    SizeCells(P),
+    /// `interrupts`
+    Interrupts(P),
+    /// `ranges`
+    Ranges(P),
+    /// Any other property, including properties whose name could not be read. The name is
+    /// available through the property itself.
+    Other(P),
+}
+
+impl<P> StandardProp<P> {
+    /// Classifies `prop` by its name.
+    pub fn new<'dt>(prop: P) -> Self
+    where
+        P: crate::prelude::PropReader<'dt>,
+    {
+        match prop.name() {
+            Ok("reg") => StandardProp::Reg(prop),
+            Ok("compatible") => StandardProp::Compatible(prop),
+            Ok("status") => StandardProp::Status(prop),
+            Ok("#address-cells") => StandardProp::AddressCells(prop),
+            Ok("#size-cells") => StandardProp::SizeCells(prop),
+            Ok("interrupts") => StandardProp::Interrupts(prop),
+            Ok("ranges") => StandardProp::Ranges(prop),
+            _ => StandardProp::Other(prop),
+        }
+    }
+
+    /// Returns the classified property.
+    pub fn prop(&self) -> &P {
+        match self {
+            StandardProp::Reg(p)
+            | StandardProp::Compatible(p)
+            | StandardProp::Status(p)
+            | StandardProp::AddressCells(p)
+            | StandardProp::SizeCells(p)
+            | StandardProp::Interrupts(p)
+            | StandardProp::Ranges(p)
+            | StandardProp::Other(p) => p,
+        }
+    }
+}
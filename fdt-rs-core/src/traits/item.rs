@@ -1,12 +1,18 @@
 use crate::prelude::*;
 
+/// An item (node or property) returned by an iterator over a device tree, which may be
+/// unwrapped into the node or property it holds.
 pub trait UnwrappableDevTreeItem<'dt> {
+    /// The property type of this backend.
     type TreeProp: PropReader<'dt>;
     // TODO lands this should be defined to Self::TreeProp::NodeType.
     // feature(associated_type_defaults)
     // https://github.com/rust-lang/rust/issues/29661
+    /// The node type of this backend.
     type TreeNode;
 
+    /// Returns the node held by this item, or `None` if it is a property.
     fn node(self) -> Option<Self::TreeNode>;
+    /// Returns the property held by this item, or `None` if it is a node.
     fn prop(self) -> Option<Self::TreeProp>;
 }
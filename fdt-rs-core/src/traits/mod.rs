@@ -0,0 +1,31 @@
+//! Traits implemented by both device tree backends.
+//!
+//! The types of [`crate::base`] and [`crate::index`] provide their most common operations as
+//! inherent methods. These traits allow code to be written once, generic over the backend:
+//!
+//! * [`IterableDevTree`] is implemented by [`DevTree`] and [`DevTreeIndex`].
+//! * [`PropReader`] is implemented by [`DevTreeProp`] and [`DevTreeIndexProp`].
+//! * [`UnwrappableDevTreeItem`] is implemented by [`DevTreeItem`] and [`DevTreeIndexItem`].
+//!
+//! Each is also exported by the [`crate::prelude`].
+//!
+//! # Lifetimes
+//!
+//! The lifetime parameters of this crate's types are currently ordered from the shortest to the
+//! longest, ending with `'dt`, the lifetime of the device tree buffer. For example the nodes of
+//! an index, `DevTreeIndexNode<'a, 'i, 'dt>`, borrow an index (`'a`) whose buffer lives for
+//! `'i`. A future release will reverse this order, so that `'dt` comes first.
+//!
+//! [`DevTree`]: crate::base::DevTree
+//! [`DevTreeProp`]: crate::base::DevTreeProp
+//! [`DevTreeItem`]: crate::base::DevTreeItem
+//! [`DevTreeIndex`]: crate::index::DevTreeIndex
+//! [`DevTreeIndexProp`]: crate::index::DevTreeIndexProp
+//! [`DevTreeIndexItem`]: crate::index::DevTreeIndexItem
+mod item;
+mod prop;
+mod tree;
+
+pub use item::UnwrappableDevTreeItem;
+pub use prop::PropReader;
+pub use tree::IterableDevTree;
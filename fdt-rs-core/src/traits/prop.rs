@@ -0,0 +1,485 @@
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::base::DevTree;
+use crate::bounded::Collected;
+use crate::error::DevTreeError;
+use crate::matcher::Matcher;
+use crate::priv_util::InvariantUnwrap;
+use crate::spec::Phandle;
+use crate::view::{PropCellIter, PropStrIter, PropU32Iter, PropView, PropViews};
+
+#[cfg(doc)]
+use crate::base::DevTreeProp;
+
+/// Read access to a device tree property.
+///
+/// This trait is implemented by the properties of both backends ([`DevTreeProp`] and
+/// [`crate::index::DevTreeIndexProp`]), so code which only reads property values may be written
+/// once, generic over `P: PropReader<'dt>`.
+pub trait PropReader<'dt> {
+    type NodeType;
+
+    /// Returns the buffer associtated with the property's data.
+    #[doc(hidden)]
+    fn propbuf(&self) -> &'dt [u8];
+
+    /// Returns offset of this property's name in the device tree buffer.
+    #[doc(hidden)]
+    fn nameoff(&self) -> usize;
+
+    #[doc(hidden)]
+    fn fdt(&self) -> &DevTree<'dt>;
+
+    /// Returns the name of the property within the device tree.
+    #[inline]
+    fn name(&self) -> Result<&'dt str, DevTreeError> {
+        PropTraitWrap(self).get_prop_str()
+    }
+
+    /// Returns the offset of the property's name within the strings block.
+    ///
+    /// Properties with equal offsets have the same name, so the offset may serve as a cheap
+    /// identifier for the name (e.g. as a key when interning names). The converse only holds if
+    /// each distinct name is stored once within the strings block, as `dtc` does. See
+    /// [`DevTree::string_at()`].
+    #[inline]
+    #[must_use]
+    fn name_offset(&self) -> usize {
+        self.nameoff()
+    }
+
+    /// Returns the length of the property value within the device tree
+    #[inline]
+    #[must_use]
+    fn length(&self) -> usize {
+        self.propbuf().len()
+    }
+
+    /// Returns the node which this property is contained within.
+    fn node(&self) -> Self::NodeType;
+
+    /// Reads a big-endian [`u32`] from `offset` within this property's value, converted to the
+    /// machine's native format.
+    ///
+    /// `offset` need not be aligned. An [`Err`] containing [`DevTreeError::InvalidOffset`] is
+    /// returned if the read would extend beyond the end of the value. Properties are not strongly
+    /// typed, so the value is read regardless of the type its binding defines.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # use fdt_rs::error::DevTreeError;
+    /// # let (index, _) = doctest_index();
+    /// let reg = index.node_at_path("/uart").unwrap().prop("reg").unwrap();
+    /// assert_eq!(reg.u32(12), Ok(0x100));
+    /// assert_eq!(reg.u32(16), Err(DevTreeError::InvalidOffset));
+    /// ```
+    #[inline]
+    fn u32(&self, offset: usize) -> Result<u32, DevTreeError> {
+        unsafe { self.propbuf().read_be_u32(offset) }.or(Err(DevTreeError::InvalidOffset))
+    }
+
+    /// Reads a big-endian [`u64`] from `offset` within this property's value, converted to the
+    /// machine's native format. See [`PropReader::u32`].
+    #[inline]
+    fn u64(&self, offset: usize) -> Result<u64, DevTreeError> {
+        unsafe { self.propbuf().read_be_u64(offset) }.or(Err(DevTreeError::InvalidOffset))
+    }
+
+    /// Reads the [`Phandle`] at `offset` within this property's value. See [`PropReader::u32`].
+    #[inline]
+    fn phandle(&self, offset: usize) -> Result<Phandle, DevTreeError> {
+        self.u32(offset)
+    }
+
+    /// Returns this property's value as a string.
+    ///
+    /// The value must begin with a null terminated UTF-8 string; any further strings of a string
+    /// list are ignored.
+    #[inline]
+    fn str(&self) -> Result<&'dt str, DevTreeError> {
+        self.str_at(0)
+    }
+
+    /// Returns this property's value as a raw slice.
+    #[inline]
+    #[must_use]
+    fn raw(&self) -> &'dt [u8] {
+        self.propbuf()
+    }
+
+    /// Read a big-endian [`u32`] from the provided offset in this device tree property's value.
+    /// Convert the read value into the machines' native [`u32`] format and return it.
+    ///
+    /// If an offset which would cause this read to access memory outside of this property's value
+    /// an [`Err`] containing [`DevTreeError::InvalidOffset`] will be returned.
+    ///
+    /// # Safety
+    ///
+    /// Device Tree Properties are not strongly typed therefore any dereference could return
+    /// unexpected data.
+    ///
+    /// This method will access memory using [`core::ptr::read_unaligned`]; therefore an unaligned
+    /// offset may be provided.
+    ///
+    /// This method will *not* panic.
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::u32` instead")]
+    unsafe fn get_u32(&self, offset: usize) -> Result<u32, DevTreeError> {
+        self.u32(offset)
+    }
+
+    /// Read a big-endian [`u64`] from the provided offset in this device tree property's value.
+    /// Convert the read value into the machines' native [`u64`] format and return it.
+    ///
+    /// If an offset which would cause this read to access memory outside of this property's value
+    /// an [`Err`] containing [`DevTreeError::InvalidOffset`] will be returned.
+    ///
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::u64` instead")]
+    unsafe fn get_u64(&self, offset: usize) -> Result<u64, DevTreeError> {
+        self.u64(offset)
+    }
+
+    /// A Phandle is simply defined as a u32 value, as such this method performs the same action as
+    /// [`self.get_u32`]
+    ///
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::phandle` instead")]
+    unsafe fn get_phandle(&self, offset: usize) -> Result<Phandle, DevTreeError> {
+        self.phandle(offset)
+    }
+
+    /// Returns the string property as a string if it can be parsed as one.
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::str` instead")]
+    unsafe fn get_str(&self) -> Result<&'dt str, DevTreeError> {
+        self.str()
+    }
+
+    /// Returns the `str` at the given offset within the property.
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::str_at` instead")]
+    unsafe fn get_str_at(&self, offset: usize) -> Result<&'dt str, DevTreeError> {
+        self.str_at(offset)
+    }
+
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::str_count` instead")]
+    unsafe fn get_str_count(&self) -> Result<usize, DevTreeError> {
+        self.str_count()
+    }
+
+    /// Fills the supplied slice of references with [`str`] slices parsed from the given property.
+    /// If parsing is successful, the number of parsed strings will be returned.
+    ///
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::strlist` instead")]
+    unsafe fn get_strlist(&self, list: &mut [Option<&'dt str>]) -> Result<usize, DevTreeError> {
+        self.strlist(list)
+    }
+
+    /// Collects up to `N` strings from this property's string list. See [`crate::bounded`].
+    ///
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[deprecated(note = "collect the safe `PropReader::iter_str` into a `Collected` instead")]
+    unsafe fn collect_strs<const N: usize>(&self) -> Result<Collected<&'dt str, N>, DevTreeError> {
+        self.iter_str().collect()
+    }
+
+    /// Returns the null terminated UTF-8 string at `offset` within this property's value.
+    ///
+    /// An [`Err`] is returned if no null byte follows `offset` within the value, or if the string
+    /// is not valid UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let compatible = index.root().prop("compatible").unwrap();
+    /// assert_eq!(compatible.str_at(0), Ok("riscv-virtio"));
+    /// assert_eq!(compatible.str_at(6), Ok("virtio"));
+    /// assert!(compatible.str_at(compatible.length()).is_err());
+    /// ```
+    #[inline]
+    fn str_at(&self, offset: usize) -> Result<&'dt str, DevTreeError> {
+        let (_, s) = PropTraitWrap(self).get_string(offset, true)?;
+        // Unsafe OK. get_string returns Some(s) when asked to parse the string.
+        Ok(unsafe { s.invariant_unwrap() })
+    }
+
+    /// Returns the number of strings within this property's string list.
+    ///
+    /// The strings are not parsed as UTF-8, but an [`Err`] is returned if the final string is not
+    /// null terminated.
+    #[inline]
+    fn str_count(&self) -> Result<usize, DevTreeError> {
+        PropTraitWrap(self).iter_str_list(None)
+    }
+
+    /// Fills `list` with the strings of this property's string list, returning the number of
+    /// strings.
+    ///
+    /// An [`Err`] containing [`DevTreeError::NotEnoughMemory`] is returned if `list` cannot hold
+    /// every string, or another [`Err`] if a string cannot be parsed. [`PropReader::iter_str`]
+    /// returns the strings without requiring their number to be known in advance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let compatible = index.props().find(|prop| prop.name() == Ok("compatible")).unwrap();
+    ///
+    /// let mut str_list: [Option<&str>; 3] = [None; 3];
+    /// assert_eq!(compatible.strlist(&mut str_list), Ok(1));
+    /// assert!(str_list[0].is_some());
+    /// ```
+    #[inline]
+    fn strlist(&self, list: &mut [Option<&'dt str>]) -> Result<usize, DevTreeError> {
+        PropTraitWrap(self).iter_str_list(Some(list))
+    }
+
+    /// Returns true if any string within this property's string list is accepted by `matcher`.
+    ///
+    /// The property value is scanned in place; no strings are copied. Entries which are not valid
+    /// UTF-8 or not null terminated are skipped.
+    fn strlist_matches<M: Matcher + ?Sized>(&self, matcher: &M) -> bool {
+        let mut buf = self.propbuf();
+        while let Some(end) = buf.iter().position(|&b| b == 0) {
+            if let Ok(s) = from_utf8(&buf[..end]) {
+                if matcher.matches(s) {
+                    return true;
+                }
+            }
+            buf = &buf[end + 1..];
+        }
+        false
+    }
+
+    /// Returns true if this property's string list contains `s`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let plic = index.compatible_nodes("riscv,plic0").next().unwrap();
+    /// let compatible = plic.props().find(|p| p.name() == Ok("compatible")).unwrap();
+    /// assert!(compatible.strlist_contains("riscv,plic0"));
+    /// assert!(!compatible.strlist_contains("riscv"));
+    /// ```
+    #[inline]
+    fn strlist_contains(&self, s: &str) -> bool {
+        self.strlist_matches(s)
+    }
+
+    /// Returns a view of `len` bytes of this property's value, starting at `offset`.
+    ///
+    /// If the view would extend beyond the end of the value, an [`Err`] containing
+    /// [`DevTreeError::InvalidOffset`] is returned. See [`crate::view`].
+    #[inline]
+    fn subview(&self, offset: usize, len: usize) -> Result<PropView<'_, 'dt, Self>, DevTreeError>
+    where
+        Self: Sized,
+    {
+        let end = offset.checked_add(len).ok_or(DevTreeError::InvalidOffset)?;
+        let buf = self
+            .propbuf()
+            .get(offset..end)
+            .ok_or(DevTreeError::InvalidOffset)?;
+        Ok(PropView::new(self, buf))
+    }
+
+    /// Returns an iterator over consecutive views of `len` bytes of this property's value.
+    ///
+    /// Trailing bytes which do not form a complete view are not returned. See [`crate::view`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    #[inline]
+    fn subviews(&self, len: usize) -> PropViews<'_, 'dt, Self>
+    where
+        Self: Sized,
+    {
+        PropViews::new(self, len)
+    }
+
+    /// Returns an iterator over the big-endian [`u32`] cells of this property's value.
+    ///
+    /// Trailing bytes which do not form a complete cell are not returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let reg = index.node_at_path("/memory").unwrap().prop("reg").unwrap();
+    /// let cells: Vec<u32> = reg.iter_u32().collect();
+    /// assert_eq!(cells, [0, 0x8000_0000, 0, 0x800_0000]);
+    ///
+    /// // Each entry of `reg` is an address and a size of two cells.
+    /// let values: Vec<u64> = reg.iter_u64().collect();
+    /// assert_eq!(values, [0x8000_0000, 0x800_0000]);
+    /// ```
+    #[inline]
+    fn iter_u32(&self) -> PropU32Iter<'dt> {
+        PropU32Iter::new(self.propbuf())
+    }
+
+    /// Returns an iterator over the big-endian [`u64`] values (of two cells each) of this
+    /// property's value.
+    ///
+    /// Trailing bytes which do not form a complete value are not returned.
+    #[inline]
+    fn iter_u64(&self) -> PropCellIter<'dt> {
+        PropCellIter::new(self.propbuf(), 2)
+    }
+
+    /// Returns an iterator over the values of this property's value, each of which is `cells`
+    /// big-endian cells long (e.g. an address described by `#address-cells`).
+    ///
+    /// Trailing bytes which do not form a complete value are not returned. Values of more than two
+    /// cells do not fit within a [`u64`], so a `cells` of 0 or greater than 2 is reported as a
+    /// [`DevTreeError::ParseError`]. Such values may be decoded with
+    /// [`subviews`](PropReader::subviews) instead.
+    #[inline]
+    fn iter_cells(&self, cells: u32) -> Result<PropCellIter<'dt>, DevTreeError> {
+        match cells {
+            1 | 2 => Ok(PropCellIter::new(self.propbuf(), cells as usize)),
+            _ => Err(DevTreeError::ParseError),
+        }
+    }
+
+    /// Returns an iterator over the strings of this property's string list, such as the entries
+    /// of a `compatible` property.
+    ///
+    /// Nothing is copied or allocated, so unlike [`strlist`](PropReader::strlist) the
+    /// number of strings need not be known in advance. See [`PropStrIter`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let compatible = index.root().prop("compatible").unwrap();
+    /// let mut strs = compatible.iter_str();
+    /// assert_eq!(strs.next(), Some(Ok("riscv-virtio")));
+    /// assert_eq!(strs.next(), None);
+    /// ```
+    #[inline]
+    fn iter_str(&self) -> PropStrIter<'dt> {
+        PropStrIter::new(self.propbuf())
+    }
+
+    /// Returns the offset of this property's value from the start of the device tree.
+    #[inline]
+    fn value_offset(&self) -> usize {
+        self.propbuf().as_ptr() as usize - self.fdt().buf().as_ptr() as usize
+    }
+
+    /// Returns an iterator over consecutive chunks of at most `chunk_size` bytes of this
+    /// property's value, borrowed directly from the device tree.
+    ///
+    /// The final chunk is shorter if the value's length is not a multiple of `chunk_size`. This
+    /// allows large values (such as embedded firmware) to be copied out piecewise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    #[inline]
+    fn raw_chunks(&self, chunk_size: usize) -> core::slice::Chunks<'dt, u8> {
+        self.propbuf().chunks(chunk_size)
+    }
+
+    /// Returns this property's data as a raw slice
+    ///
+    /// # Safety
+    ///
+    /// See the safety note of [`PropReader::get_u32`]
+    #[inline]
+    #[deprecated(note = "use the safe `PropReader::raw` instead")]
+    unsafe fn get_raw(&self) -> &'dt [u8] {
+        self.raw()
+    }
+}
+
+struct PropTraitWrap<'r, T: ?Sized>(&'r T);
+
+impl<'r, 'dt: 'r, T: PropReader<'dt> + ?Sized> PropTraitWrap<'r, T> {
+    fn get_prop_str(&self) -> Result<&'dt str, DevTreeError> {
+        self.0.fdt().string_at(self.0.nameoff())
+    }
+
+    fn get_string(
+        &self,
+        offset: usize,
+        parse: bool,
+    ) -> Result<(usize, Option<&'dt str>), DevTreeError> {
+        // Unsafe OK. The read is bounds checked.
+        match unsafe { self.0.propbuf().read_bstring0(offset) } {
+            Ok(res_u8) => {
+                // Include null byte
+                let len = res_u8.len() + 1;
+
+                if parse {
+                    match from_utf8(res_u8) {
+                        Ok(s) => Ok((len, Some(s))),
+                        Err(e) => Err(e.into()),
+                    }
+                } else {
+                    Ok((len, None))
+                }
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn iter_str_list(
+        &self,
+        mut list_opt: Option<&mut [Option<&'dt str>]>,
+    ) -> Result<usize, DevTreeError> {
+        let mut offset = 0;
+        for count in 0.. {
+            if offset == self.0.length() {
+                return Ok(count);
+            }
+
+            let (len, s) = self.get_string(offset, list_opt.is_some())?;
+            offset += len;
+
+            if let Some(list) = list_opt.as_deref_mut() {
+                let slot = list.get_mut(count).ok_or(DevTreeError::NotEnoughMemory)?;
+                // Unsafe OK. get_string returns Some(s) when asked to parse the string.
+                *slot = Some(unsafe { s.invariant_unwrap() });
+            };
+        }
+        // Unreachable due to infinite for loop.
+        unreachable!();
+    }
+}
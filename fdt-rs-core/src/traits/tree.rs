@@ -0,0 +1,38 @@
+use crate::error::Result;
+use crate::matcher::Matcher;
+
+#[cfg(doc)]
+use crate::base::DevTree;
+#[cfg(doc)]
+use crate::index::DevTreeIndex;
+
+/// Queries shared by both device tree backends ([`DevTree`] and [`DevTreeIndex`]).
+///
+/// Both implementations have identical semantics, so generic code written over
+/// `T: IterableDevTree<'a, 'dt>` behaves the same regardless of the backend it is given.
+pub trait IterableDevTree<'a, 'dt: 'a> {
+    /// The node type of this backend.
+    type Node;
+
+    /// Returns the first node, in document order, with a `compatible` property containing an
+    /// entry accepted by `matcher`, or `None` if there is no such node.
+    ///
+    /// The root node is considered, and any entry of the `compatible` stringlist may match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use fdt_rs::doctest::*;
+    /// # let (index, _) = doctest_index();
+    /// let node = index.find_first_compatible_node("ns16550a").unwrap().unwrap();
+    /// assert_eq!(node.name().unwrap(), "uart@10000000");
+    ///
+    /// // The root node is compatible with "riscv-virtio".
+    /// let root = index.fdt().find_first_compatible_node("riscv-virtio").unwrap().unwrap();
+    /// assert_eq!(root.name().unwrap(), "");
+    /// ```
+    fn find_first_compatible_node<M: Matcher + ?Sized>(
+        &'a self,
+        matcher: &M,
+    ) -> Result<Option<Self::Node>>;
+}
@@ -0,0 +1,124 @@
+//! Typed views of the Arm Generic Interrupt Controller (GIC) bindings.
+//!
+//! Devices whose interrupt parent is a GIC describe each interrupt with three cells: the
+//! interrupt type, its number and its trigger flags. [`gic_interrupts`] decodes these into
+//! [`GicInterrupt`] values.
+//!
+//! This module requires the `bindings-arm` feature.
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+
+/// The number of cells of a GIC interrupt specifier.
+pub const GIC_INTERRUPT_CELLS: usize = 3;
+
+/// The type of a GIC interrupt (the first cell of its specifier).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GicInterruptKind {
+    /// A shared peripheral interrupt.
+    Spi,
+    /// A private peripheral interrupt.
+    Ppi,
+    /// An extended shared peripheral interrupt.
+    ExtendedSpi,
+    /// An extended private peripheral interrupt.
+    ExtendedPpi,
+}
+
+/// The trigger of an interrupt (the low bits of the last cell of its specifier).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IrqTrigger {
+    /// Triggered on a rising edge.
+    EdgeRising,
+    /// Triggered on a falling edge.
+    EdgeFalling,
+    /// Triggered while the line is high.
+    LevelHigh,
+    /// Triggered while the line is low.
+    LevelLow,
+}
+
+/// A decoded GIC interrupt specifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GicInterrupt {
+    /// The type of the interrupt.
+    pub kind: GicInterruptKind,
+    /// The interrupt number, relative to the first interrupt of its type.
+    pub number: u32,
+    /// The trigger of the interrupt.
+    pub trigger: IrqTrigger,
+    /// For PPIs, the mask of CPUs the interrupt is wired to. Zero otherwise.
+    pub cpu_mask: u8,
+}
+
+impl GicInterrupt {
+    /// Decode an interrupt specifier.
+    ///
+    /// An unknown interrupt type or a trigger other than a single edge or level is reported as
+    /// a [`DevTreeError::ParseError`].
+    pub fn from_cells(cells: [u32; GIC_INTERRUPT_CELLS]) -> Result<Self> {
+        let kind = match cells[0] {
+            0 => GicInterruptKind::Spi,
+            1 => GicInterruptKind::Ppi,
+            2 => GicInterruptKind::ExtendedSpi,
+            3 => GicInterruptKind::ExtendedPpi,
+            _ => return Err(DevTreeError::ParseError),
+        };
+        let trigger = match cells[2] & 0xf {
+            1 => IrqTrigger::EdgeRising,
+            2 => IrqTrigger::EdgeFalling,
+            4 => IrqTrigger::LevelHigh,
+            8 => IrqTrigger::LevelLow,
+            _ => return Err(DevTreeError::ParseError),
+        };
+        let cpu_mask = match kind {
+            GicInterruptKind::Ppi | GicInterruptKind::ExtendedPpi => (cells[2] >> 8) as u8,
+            _ => 0,
+        };
+        Ok(Self {
+            kind,
+            number: cells[1],
+            trigger,
+            cpu_mask,
+        })
+    }
+}
+
+/// An iterator over the GIC interrupt specifiers of an `interrupts` property.
+///
+/// Created by [`gic_interrupts`].
+pub struct GicInterruptIter<'p, 'dt, P: PropReader<'dt>> {
+    prop: &'p P,
+    offset: usize,
+    _dt: core::marker::PhantomData<&'dt [u8]>,
+}
+
+impl<'p, 'dt, P: PropReader<'dt>> FallibleIterator for GicInterruptIter<'p, 'dt, P> {
+    type Item = GicInterrupt;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let entry_size = GIC_INTERRUPT_CELLS * 4;
+        if self.offset + entry_size > self.prop.length() {
+            return Ok(None);
+        }
+        let mut cells = [0u32; GIC_INTERRUPT_CELLS];
+        for (i, cell) in cells.iter_mut().enumerate() {
+            *cell = self.prop.u32(self.offset + i * 4)?;
+        }
+        self.offset += entry_size;
+        GicInterrupt::from_cells(cells).map(Some)
+    }
+}
+
+/// Returns an iterator over the interrupts of `prop`, an `interrupts` property whose interrupt
+/// parent is a GIC.
+///
+/// Any trailing bytes which do not form a complete specifier are ignored.
+pub fn gic_interrupts<'p, 'dt, P: PropReader<'dt>>(prop: &'p P) -> GicInterruptIter<'p, 'dt, P> {
+    GicInterruptIter {
+        prop,
+        offset: 0,
+        _dt: core::marker::PhantomData,
+    }
+}
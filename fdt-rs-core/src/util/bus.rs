@@ -0,0 +1,382 @@
+//! Decoding of `reg` and `ranges` entries with bus specific address spaces.
+//!
+//! PCI and ISA buses encode the address space of each address within its first (`phys.hi`) cell.
+//! [`bus_reg`] and [`bus_ranges`] decode these cells into typed flags ([`PciSpaceFlags`] and
+//! [`IsaSpaceFlags`]) so callers don't need to mask the raw cell themselves. Addresses on other
+//! buses are decoded as plain numbers.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::bus::{bus_ranges, PciSpace, SpaceFlags};
+//!
+//! let pci = index.compatible_nodes("pci-host-ecam-generic").next().unwrap();
+//! let mut ranges = bus_ranges(&pci).unwrap().unwrap();
+//!
+//! // The first window maps the PCI I/O space.
+//! let io = ranges.next().unwrap().unwrap();
+//! match io.child.flags {
+//!     Some(SpaceFlags::Pci(flags)) => assert_eq!(flags.space(), PciSpace::Io),
+//!     _ => panic!("expected a PCI address"),
+//! }
+//! assert_eq!(io.parent.address, 0x300_0000);
+//! ```
+//!
+//! [`translate_address`] uses the `ranges` of each bus to translate a `reg` address to a CPU
+//! address.
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+use crate::index::{DevTreeIndexNode, DevTreeIndexProp};
+
+use super::{
+    entry_size, find_prop, prop_u32_or, read_cells, DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS,
+};
+
+/// The type of a bus, which determines how the addresses of its children are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusKind {
+    /// A PCI bus (`device_type` of `"pci"` or `"pciex"`), using three cell addresses.
+    Pci,
+    /// An ISA bus (`device_type` of `"isa"`), using two cell addresses.
+    Isa,
+    /// Any other bus, whose addresses are plain numbers.
+    Other,
+}
+
+impl BusKind {
+    /// Returns the kind of the bus described by `node`, as given by its `device_type` property.
+    #[must_use]
+    pub fn of(node: &DevTreeIndexNode) -> Self {
+        match find_prop(node, "device_type").map(|p| p.str()) {
+            Some(Ok("pci")) | Some(Ok("pciex")) => BusKind::Pci,
+            Some(Ok("isa")) => BusKind::Isa,
+            _ => BusKind::Other,
+        }
+    }
+}
+
+/// The address space of a PCI address, encoded by the `ss` bits of its `phys.hi` cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PciSpace {
+    /// Configuration space.
+    Config,
+    /// I/O space.
+    Io,
+    /// 32-bit memory space.
+    Memory32,
+    /// 64-bit memory space.
+    Memory64,
+}
+
+/// The flags of a PCI address, from the `npt000ss` bits of its `phys.hi` cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PciSpaceFlags(u32);
+
+impl PciSpaceFlags {
+    /// The `n` bit is set; the region is not relocatable.
+    pub const NON_RELOCATABLE: Self = Self(1 << 31);
+    /// The `p` bit is set; the region is prefetchable.
+    pub const PREFETCHABLE: Self = Self(1 << 30);
+    /// The `t` bit is set; the region is aliased (I/O space) or below 1MB (memory space).
+    pub const ALIASED: Self = Self(1 << 29);
+
+    const SPACE_SHIFT: u32 = 24;
+    const MASK: u32 = 0xff << Self::SPACE_SHIFT;
+
+    /// Extracts the flags from the `phys.hi` cell of a PCI address.
+    #[inline]
+    #[must_use]
+    pub fn from_phys_hi(phys_hi: u32) -> Self {
+        Self(phys_hi & Self::MASK)
+    }
+
+    /// Returns the raw flag bits, as positioned within the `phys.hi` cell.
+    #[inline]
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all flags within `other` are set.
+    #[inline]
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the address space of the address.
+    #[must_use]
+    pub fn space(self) -> PciSpace {
+        match (self.0 >> Self::SPACE_SHIFT) & 0b11 {
+            0b00 => PciSpace::Config,
+            0b01 => PciSpace::Io,
+            0b10 => PciSpace::Memory32,
+            _ => PciSpace::Memory64,
+        }
+    }
+}
+
+/// The flags of an ISA address, from its `phys.hi` cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IsaSpaceFlags(u32);
+
+impl IsaSpaceFlags {
+    /// The `i` bit is set; the address is within I/O space rather than memory space.
+    pub const IO: Self = Self(1 << 0);
+
+    /// Extracts the flags from the `phys.hi` cell of an ISA address.
+    #[inline]
+    #[must_use]
+    pub fn from_phys_hi(phys_hi: u32) -> Self {
+        Self(phys_hi & Self::IO.0)
+    }
+
+    /// Returns the raw flag bits, as positioned within the `phys.hi` cell.
+    #[inline]
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all flags within `other` are set.
+    #[inline]
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// The address space flags of a bus address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpaceFlags {
+    /// The flags of an address on a PCI bus.
+    Pci(PciSpaceFlags),
+    /// The flags of an address on an ISA bus.
+    Isa(IsaSpaceFlags),
+}
+
+/// An address on a bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusAddress {
+    /// The address space flags, for addresses on PCI and ISA buses.
+    pub flags: Option<SpaceFlags>,
+    /// The address within its address space (excluding the `phys.hi` cell on PCI and ISA buses).
+    pub address: u64,
+}
+
+/// An entry of a `reg` property.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusReg {
+    /// The address of the region on the parent bus.
+    pub address: BusAddress,
+    /// The size of the region in bytes.
+    pub size: u64,
+}
+
+/// An entry of a `ranges` property, mapping `size` bytes of the bus at `child` to the parent bus
+/// at `parent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusRange {
+    /// The start of the window on the bus described by the node.
+    pub child: BusAddress,
+    /// The start of the window on the node's parent bus.
+    pub parent: BusAddress,
+    /// The size of the window in bytes.
+    pub size: u64,
+}
+
+/// The encoding of addresses on a bus.
+#[derive(Clone, Copy)]
+struct AddressFormat {
+    kind: BusKind,
+    cells: u32,
+}
+
+impl AddressFormat {
+    fn of(bus: Option<&DevTreeIndexNode>) -> Result<Self> {
+        Ok(match bus {
+            Some(bus) => Self {
+                kind: BusKind::of(bus),
+                cells: prop_u32_or(bus, "#address-cells", DEFAULT_ADDRESS_CELLS)?,
+            },
+            None => Self {
+                kind: BusKind::Other,
+                cells: DEFAULT_ADDRESS_CELLS,
+            },
+        })
+    }
+
+    fn read(self, prop: &DevTreeIndexProp, offset: usize) -> Result<BusAddress> {
+        let phys_hi = || prop.u32(offset);
+        Ok(match (self.kind, self.cells) {
+            (BusKind::Pci, 3) => BusAddress {
+                flags: Some(SpaceFlags::Pci(PciSpaceFlags::from_phys_hi(phys_hi()?))),
+                address: read_cells(prop, offset + 4, 2)?,
+            },
+            (BusKind::Isa, 2) => BusAddress {
+                flags: Some(SpaceFlags::Isa(IsaSpaceFlags::from_phys_hi(phys_hi()?))),
+                address: read_cells(prop, offset + 4, 1)?,
+            },
+            (_, cells) => BusAddress {
+                flags: None,
+                address: read_cells(prop, offset, cells)?,
+            },
+        })
+    }
+}
+
+/// Returns the `#size-cells` of `bus`, or the default if `bus` is `None`.
+fn size_cells(bus: Option<&DevTreeIndexNode>) -> Result<u32> {
+    match bus {
+        Some(bus) => prop_u32_or(bus, "#size-cells", DEFAULT_SIZE_CELLS),
+        None => Ok(DEFAULT_SIZE_CELLS),
+    }
+}
+
+/// An iterator over the entries of a `reg` property. Created by [`bus_reg`].
+pub struct BusRegIter<'a, 'i: 'a, 'dt: 'i> {
+    prop: DevTreeIndexProp<'a, 'i, 'dt>,
+    address: AddressFormat,
+    size_cells: u32,
+    offset: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> FallibleIterator for BusRegIter<'a, 'i, 'dt> {
+    type Item = BusReg;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let entry_size = entry_size(&[self.address.cells, self.size_cells])?;
+        let end = self
+            .offset
+            .checked_add(entry_size)
+            .ok_or(DevTreeError::ParseError)?;
+        if entry_size == 0 || end > self.prop.length() {
+            return Ok(None);
+        }
+        let address = self.address.read(&self.prop, self.offset)?;
+        let size_offset = self.offset + 4 * self.address.cells as usize;
+        let size = read_cells(&self.prop, size_offset, self.size_cells)?;
+        self.offset += entry_size;
+        Ok(Some(BusReg { address, size }))
+    }
+}
+
+/// An iterator over the entries of a `ranges` property. Created by [`bus_ranges`].
+pub struct BusRangesIter<'a, 'i: 'a, 'dt: 'i> {
+    prop: DevTreeIndexProp<'a, 'i, 'dt>,
+    child: AddressFormat,
+    parent: AddressFormat,
+    size_cells: u32,
+    offset: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> FallibleIterator for BusRangesIter<'a, 'i, 'dt> {
+    type Item = BusRange;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let entry_size = entry_size(&[self.child.cells, self.parent.cells, self.size_cells])?;
+        let end = self
+            .offset
+            .checked_add(entry_size)
+            .ok_or(DevTreeError::ParseError)?;
+        if entry_size == 0 || end > self.prop.length() {
+            return Ok(None);
+        }
+        let child = self.child.read(&self.prop, self.offset)?;
+        let parent_offset = self.offset + 4 * self.child.cells as usize;
+        let parent = self.parent.read(&self.prop, parent_offset)?;
+        let size_offset = parent_offset + 4 * self.parent.cells as usize;
+        let size = read_cells(&self.prop, size_offset, self.size_cells)?;
+        self.offset += entry_size;
+        Ok(Some(BusRange {
+            child,
+            parent,
+            size,
+        }))
+    }
+}
+
+/// Returns an iterator over the entries of `node`'s `reg` property, decoded according to the
+/// kind of its parent bus. Returns `Ok(None)` if `node` has no `reg` property.
+///
+/// Any trailing bytes which do not form a complete entry are ignored.
+pub fn bus_reg<'a, 'i: 'a, 'dt: 'i>(
+    node: &DevTreeIndexNode<'a, 'i, 'dt>,
+) -> Result<Option<BusRegIter<'a, 'i, 'dt>>> {
+    let prop = match find_prop(node, "reg") {
+        Some(prop) => prop,
+        None => return Ok(None),
+    };
+    let bus = node.parent();
+    Ok(Some(BusRegIter {
+        prop,
+        address: AddressFormat::of(bus.as_ref())?,
+        size_cells: size_cells(bus.as_ref())?,
+        offset: 0,
+    }))
+}
+
+/// Returns an iterator over the entries of `node`'s `ranges` property. Child addresses are
+/// decoded according to the kind of `node` and parent addresses according to the kind of its
+/// parent bus. Returns `Ok(None)` if `node` has no `ranges` property.
+///
+/// An empty `ranges` property (an identity mapping) results in an empty iterator. Any trailing
+/// bytes which do not form a complete entry are ignored.
+pub fn bus_ranges<'a, 'i: 'a, 'dt: 'i>(
+    node: &DevTreeIndexNode<'a, 'i, 'dt>,
+) -> Result<Option<BusRangesIter<'a, 'i, 'dt>>> {
+    let prop = match find_prop(node, "ranges") {
+        Some(prop) => prop,
+        None => return Ok(None),
+    };
+    Ok(Some(BusRangesIter {
+        prop,
+        child: AddressFormat::of(Some(node))?,
+        parent: AddressFormat::of(node.parent().as_ref())?,
+        size_cells: size_cells(Some(node))?,
+        offset: 0,
+    }))
+}
+
+/// Translate `address`, an address within the `reg` space of `node` (the address space of its
+/// parent bus), into the address space of the root node.
+///
+/// Each bus between `node` and the root is traversed in turn. A bus with an empty `ranges`
+/// property maps addresses unchanged. Returns `Ok(None)` if a bus has no `ranges` property (see
+/// [`DevTreeIndexNode::is_translatable`]) or if none of its windows contain the address. The
+/// space flags of PCI and ISA windows are not considered when matching addresses.
+pub fn translate_address(node: &DevTreeIndexNode, address: u64) -> Result<Option<u64>> {
+    let mut address = address;
+    let mut bus = node.parent();
+    while let Some(cur) = bus {
+        if cur.parent().is_none() {
+            break;
+        }
+        let mut ranges = match bus_ranges(&cur)? {
+            Some(ranges) => ranges,
+            None => return Ok(None),
+        };
+        if ranges.prop.length() != 0 {
+            let mut translated = None;
+            while let Some(range) = ranges.next()? {
+                let offset = match address.checked_sub(range.child.address) {
+                    Some(offset) if offset < range.size => offset,
+                    _ => continue,
+                };
+                translated = range.parent.address.checked_add(offset);
+                break;
+            }
+            address = match translated {
+                Some(address) => address,
+                None => return Ok(None),
+            };
+        }
+        bus = cur.parent();
+    }
+    Ok(Some(address))
+}
@@ -0,0 +1,150 @@
+//! Decoding of the `/chosen` node, which passes parameters from the boot program.
+//!
+//! [`DevTree::chosen`] and [`DevTreeIndex::chosen`] read the properties of `/chosen` which
+//! nearly every kernel consults while booting in a single call. [`DevTree::stdout`] and
+//! [`DevTreeIndex::stdout`] further resolve `stdout-path` to the console's node.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _buf) = doctest_index();
+//! let chosen = index.chosen().unwrap();
+//! assert_eq!(chosen.stdout_path, Some("/uart@10000000"));
+//! assert_eq!(chosen.bootargs, Some(""));
+//! assert_eq!(chosen.initrd, None);
+//!
+//! // The same properties are read from the device tree itself.
+//! assert_eq!(index.fdt().chosen().unwrap(), chosen);
+//!
+//! let (uart, options) = index.stdout().unwrap().unwrap();
+//! assert_eq!(uart.name().unwrap(), "uart@10000000");
+//! assert_eq!(options, None);
+//! ```
+use crate::prelude::*;
+
+use crate::base::{DevTree, DevTreeNode};
+use crate::error::{DevTreeError, Result};
+use crate::index::{DevTreeIndex, DevTreeIndexNode};
+
+/// The parameters passed by the boot program through the `/chosen` node.
+///
+/// Each field is `None` if its property (or the `/chosen` node itself) is absent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Chosen<'dt> {
+    /// The kernel command line, as given by the `bootargs` property.
+    pub bootargs: Option<&'dt str>,
+    /// The path (or alias) of the console device, as given by the `stdout-path` property (or
+    /// the legacy `linux,stdout-path` property). Any options following a `:` are included.
+    pub stdout_path: Option<&'dt str>,
+    /// The physical start and end (exclusive) of the initial ramdisk, as given by the
+    /// `linux,initrd-start` and `linux,initrd-end` properties. `None` unless both are present.
+    pub initrd: Option<(u64, u64)>,
+}
+
+impl<'dt> Chosen<'dt> {
+    /// Returns the path (or alias) of the console device and its options (e.g. `"115200n8"`),
+    /// which follow the first `:` of [`Chosen::stdout_path`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fdt_rs::util::chosen::Chosen;
+    ///
+    /// let chosen = Chosen {
+    ///     stdout_path: Some("serial0:115200n8"),
+    ///     ..Chosen::default()
+    /// };
+    /// assert_eq!(chosen.stdout(), Some(("serial0", Some("115200n8"))));
+    /// ```
+    #[must_use]
+    pub fn stdout(&self) -> Option<(&'dt str, Option<&'dt str>)> {
+        let path = self.stdout_path?;
+        Some(match path.find(':') {
+            Some(pos) => (&path[..pos], Some(&path[pos + 1..])),
+            None => (path, None),
+        })
+    }
+}
+
+/// Read an `linux,initrd-*` address, which is written as either one or two cells.
+fn read_initrd_address<'dt, P: PropReader<'dt>>(prop: &P) -> Result<u64> {
+    match prop.length() {
+        4 => prop.u32(0).map(u64::from),
+        8 => prop.u64(0),
+        _ => Err(DevTreeError::ParseError),
+    }
+}
+
+/// Decode the `/chosen` properties returned by `prop`.
+fn decode<'dt, P: PropReader<'dt>>(
+    prop: impl Fn(&str) -> Result<Option<P>>,
+) -> Result<Chosen<'dt>> {
+    let string = |name| match prop(name)? {
+        Some(prop) => prop.str().map(Some),
+        None => Ok(None),
+    };
+    let initrd = match (prop("linux,initrd-start")?, prop("linux,initrd-end")?) {
+        (Some(start), Some(end)) => {
+            Some((read_initrd_address(&start)?, read_initrd_address(&end)?))
+        }
+        _ => None,
+    };
+    Ok(Chosen {
+        bootargs: string("bootargs")?,
+        stdout_path: match string("stdout-path")? {
+            Some(path) => Some(path),
+            None => string("linux,stdout-path")?,
+        },
+        initrd,
+    })
+}
+
+impl<'dt> DevTree<'dt> {
+    /// Returns the parameters passed by the boot program through the `/chosen` node.
+    ///
+    /// An `linux,initrd-*` property which is not one or two cells long is reported as a
+    /// [`DevTreeError::ParseError`]. See [`crate::util::chosen`].
+    pub fn chosen(&self) -> Result<Chosen<'dt>> {
+        match self.node_at_path("/chosen")? {
+            Some(node) => decode(|name| node.prop(name)),
+            None => Ok(Chosen::default()),
+        }
+    }
+
+    /// Returns the console device named by `/chosen/stdout-path`, along with the options which
+    /// followed its path (see [`Chosen::stdout()`]).
+    ///
+    /// The path may begin with an alias, as for [`DevTree::node_at_path()`]. `None` is returned
+    /// if there is no `stdout-path` or it does not name a node.
+    pub fn stdout(&self) -> Result<Option<(DevTreeNode<'_, 'dt>, Option<&'dt str>)>> {
+        let (path, options) = match self.chosen()?.stdout() {
+            Some(stdout) => stdout,
+            None => return Ok(None),
+        };
+        Ok(self.node_at_path(path)?.map(|node| (node, options)))
+    }
+}
+
+impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
+    /// Returns the parameters passed by the boot program through the `/chosen` node.
+    ///
+    /// See [`DevTree::chosen()`].
+    pub fn chosen(&self) -> Result<Chosen<'dt>> {
+        match self.node_at_path("/chosen") {
+            Some(node) => decode(|name| Ok(node.prop(name))),
+            None => Ok(Chosen::default()),
+        }
+    }
+
+    /// Returns the console device named by `/chosen/stdout-path`, along with its options.
+    ///
+    /// See [`DevTree::stdout()`].
+    pub fn stdout(&self) -> Result<Option<(DevTreeIndexNode<'_, 'i, 'dt>, Option<&'dt str>)>> {
+        let (path, options) = match self.chosen()?.stdout() {
+            Some(stdout) => stdout,
+            None => return Ok(None),
+        };
+        Ok(self.node_at_path(path).map(|node| (node, options)))
+    }
+}
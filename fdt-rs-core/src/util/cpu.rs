@@ -0,0 +1,140 @@
+//! Decoding of the methods used to start secondary CPUs.
+//!
+//! Each `/cpus/cpu` node may describe how it is brought online through its `enable-method`
+//! property. [`enable_method`] decodes this property (along with `cpu-release-addr` for
+//! spin-tables), and [`psci`] decodes the `/psci` node used by the PSCI method, so SMP bring-up
+//! code can dispatch secondary core startup without parsing properties itself.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::cpu::{enable_method, psci};
+//!
+//! // RISC-V harts are started through the SBI rather than an enable-method.
+//! let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+//! assert_eq!(enable_method(&cpu).unwrap(), None);
+//! assert_eq!(psci(&index).unwrap(), None);
+//! ```
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+use crate::index::{DevTreeIndex, DevTreeIndexNode};
+use crate::matcher::Prefix;
+
+use super::find_prop;
+
+/// The method used to start a secondary CPU, as described by its `enable-method` property.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnableMethod<'dt> {
+    /// The CPU is started through the Power State Coordination Interface. See [`psci`].
+    Psci,
+    /// The CPU spins until its entry point is written to `release_addr` (the CPU's
+    /// `cpu-release-addr`).
+    SpinTable { release_addr: u64 },
+    /// A method not defined by the specification, such as a vendor specific method.
+    Unknown(&'dt str),
+}
+
+/// The instruction used to invoke PSCI functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PsciConduit {
+    /// Secure monitor call (`"smc"`).
+    Smc,
+    /// Hypervisor call (`"hvc"`).
+    Hvc,
+}
+
+/// The PSCI configuration described by the `/psci` node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Psci {
+    /// The instruction used to invoke PSCI functions.
+    pub conduit: PsciConduit,
+    /// The function ID of `CPU_ON`, which is only provided by PSCI 0.1 device trees. Later
+    /// versions use the standard function IDs.
+    pub cpu_on: Option<u32>,
+}
+
+/// The organization of a cache, as described by the `cache-unified` property of a CPU or cache
+/// node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheOrganization {
+    /// A single cache holds both instructions and data.
+    Unified,
+    /// Instructions and data are held in separate caches (a Harvard architecture).
+    Split,
+}
+
+/// Returns the organization of the cache described by `node`.
+///
+/// Per the specification, a node without the `cache-unified` property describes split
+/// instruction and data caches.
+pub fn cache_organization(node: &DevTreeIndexNode) -> CacheOrganization {
+    match find_prop(node, "cache-unified") {
+        Some(_) => CacheOrganization::Unified,
+        None => CacheOrganization::Split,
+    }
+}
+
+/// Returns the method used to start `cpu`, or `None` if it has no `enable-method` property.
+///
+/// When `enable-method` lists several methods, the first method defined by the specification is
+/// returned. If none are, the first entry is returned as [`EnableMethod::Unknown`].
+///
+/// A `"spin-table"` CPU without a valid `cpu-release-addr` property is reported as a
+/// [`DevTreeError::ParseError`].
+pub fn enable_method<'dt>(
+    cpu: &DevTreeIndexNode<'_, '_, 'dt>,
+) -> Result<Option<EnableMethod<'dt>>> {
+    let prop = match find_prop(cpu, "enable-method") {
+        Some(prop) => prop,
+        None => return Ok(None),
+    };
+
+    let mut first = None;
+    for entry in prop.propbuf().split(|&b| b == 0).filter(|s| !s.is_empty()) {
+        match entry {
+            b"psci" => return Ok(Some(EnableMethod::Psci)),
+            b"spin-table" => {
+                let release_addr = match find_prop(cpu, "cpu-release-addr") {
+                    Some(addr) if addr.length() == 8 => addr.u64(0)?,
+                    Some(addr) if addr.length() == 4 => u64::from(addr.u32(0)?),
+                    _ => return Err(DevTreeError::ParseError),
+                };
+                return Ok(Some(EnableMethod::SpinTable { release_addr }));
+            }
+            _ => {
+                if first.is_none() {
+                    first = Some(from_utf8(entry)?);
+                }
+            }
+        }
+    }
+    Ok(first.map(EnableMethod::Unknown))
+}
+
+/// Returns the PSCI configuration of the device tree, or `None` if it has no node compatible
+/// with `"arm,psci"` (or a later `"arm,psci-*"` version).
+///
+/// An unrecognized `method` is reported as a [`DevTreeError::ParseError`].
+pub fn psci(index: &DevTreeIndex) -> Result<Option<Psci>> {
+    let node = match index.compatible_nodes_matching(&Prefix("arm,psci")).next() {
+        Some(node) => node,
+        None => return Ok(None),
+    };
+
+    let method = find_prop(&node, "method").ok_or(DevTreeError::ParseError)?;
+    let conduit = match method.str()? {
+        "smc" => PsciConduit::Smc,
+        "hvc" => PsciConduit::Hvc,
+        _ => return Err(DevTreeError::ParseError),
+    };
+    let cpu_on = match find_prop(&node, "cpu_on") {
+        Some(prop) => Some(prop.u32(0)?),
+        None => None,
+    };
+    Ok(Some(Psci { conduit, cpu_on }))
+}
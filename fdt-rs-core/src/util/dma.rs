@@ -0,0 +1,168 @@
+//! Computation of a device's DMA addressing capability from `dma-ranges`.
+//!
+//! Each bus between a device and the root may restrict (and translate) the addresses its
+//! children can use for DMA through a `dma-ranges` property. [`dma_limit`] intersects these
+//! windows to find the highest address a device may use, from which drivers can derive a DMA
+//! mask.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::dma::dma_limit;
+//!
+//! // The virtio devices of the QEMU virt machine are not restricted by any bus.
+//! let virtio = index.compatible_nodes("virtio,mmio").next().unwrap();
+//! let limit = dma_limit(&virtio).unwrap().unwrap();
+//! assert_eq!(limit.mask(), u64::MAX);
+//! ```
+use crate::error::{DevTreeError, Result};
+use crate::index::{DevTreeIndexNode, DevTreeIndexProp};
+
+use super::{
+    entry_size, find_prop, prop_u32_or, read_cells, DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS,
+};
+
+/// The DMA addressing capability of a device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DmaLimit {
+    /// The highest device (bus) address the device may use for DMA.
+    pub max_address: u64,
+    /// The value which is added (wrapping) to a device address to produce a CPU physical address.
+    pub cpu_offset: u64,
+}
+
+impl DmaLimit {
+    /// Returns the smallest all-ones mask which covers [`DmaLimit::max_address`].
+    #[must_use]
+    pub fn mask(&self) -> u64 {
+        // A maximum address of zero has 64 leading zeros, which may not be shifted by.
+        u64::MAX
+            .checked_shr(self.max_address.leading_zeros())
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of address bits the device may use for DMA.
+    #[must_use]
+    pub fn bits(&self) -> u32 {
+        64 - self.max_address.leading_zeros()
+    }
+
+    /// Translate a device address into a CPU physical address.
+    #[must_use]
+    pub fn to_cpu(&self, device_address: u64) -> u64 {
+        device_address.wrapping_add(self.cpu_offset)
+    }
+}
+
+/// Whether DMA performed by a device is coherent with the CPU caches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmaCoherence {
+    /// The `dma-coherent` property is present.
+    Coherent,
+    /// The `dma-noncoherent` property is present.
+    NonCoherent,
+}
+
+/// Returns the DMA coherence of `node`, as described by the nearest `dma-coherent` or
+/// `dma-noncoherent` property found on the node or its ancestors.
+///
+/// Returns `Ok(None)` if neither property is present, in which case the architecture's default
+/// applies. A node with both properties is reported as a [`DevTreeError::ParseError`].
+pub fn dma_coherence(node: &DevTreeIndexNode) -> Result<Option<DmaCoherence>> {
+    let mut cur = Some(*node);
+    while let Some(node) = cur {
+        let coherent = find_prop(&node, "dma-coherent").is_some();
+        let noncoherent = find_prop(&node, "dma-noncoherent").is_some();
+        match (coherent, noncoherent) {
+            (true, true) => return Err(DevTreeError::ParseError),
+            (true, false) => return Ok(Some(DmaCoherence::Coherent)),
+            (false, true) => return Ok(Some(DmaCoherence::NonCoherent)),
+            (false, false) => cur = node.parent(),
+        }
+    }
+    Ok(None)
+}
+
+/// Read an address of `cells` cells. Three cell (PCI) addresses have their first cell, which
+/// holds flags rather than address bits, skipped.
+fn read_address(prop: &DevTreeIndexProp, offset: usize, cells: u32) -> Result<u64> {
+    match cells {
+        3 => read_cells(prop, offset + 4, 2),
+        _ => read_cells(prop, offset, cells),
+    }
+}
+
+/// Compute the DMA addressing capability of `node` by intersecting the `dma-ranges` of each bus
+/// between it and the root node.
+///
+/// Buses without a `dma-ranges` property, or with an empty one, are treated as an identity
+/// mapping which does not restrict their children. When a bus provides multiple windows, the
+/// window reaching the highest device address is used.
+///
+/// Returns `Ok(None)` if no window is reachable by the device (i.e. it cannot perform DMA).
+pub fn dma_limit(node: &DevTreeIndexNode) -> Result<Option<DmaLimit>> {
+    // The highest usable device address, and the offset from device addresses to addresses
+    // within the current bus. i128 avoids any overflow when combining 64-bit values.
+    let mut limit = i128::from(u64::MAX);
+    let mut offset = 0i128;
+
+    let mut bus = node.parent();
+    while let Some(cur) = bus {
+        let parent = cur.parent();
+        bus = parent;
+
+        let prop = match find_prop(&cur, "dma-ranges") {
+            Some(prop) if prop.length() != 0 => prop,
+            _ => continue,
+        };
+
+        let child_cells = prop_u32_or(&cur, "#address-cells", DEFAULT_ADDRESS_CELLS)?;
+        let size_cells = prop_u32_or(&cur, "#size-cells", DEFAULT_SIZE_CELLS)?;
+        let parent_cells = match parent {
+            Some(parent) => prop_u32_or(&parent, "#address-cells", DEFAULT_ADDRESS_CELLS)?,
+            None => child_cells,
+        };
+
+        let entry_size = entry_size(&[child_cells, parent_cells, size_cells])?;
+        if entry_size == 0 || prop.length() % entry_size != 0 {
+            return Err(DevTreeError::ParseError);
+        }
+
+        let mut best: Option<(i128, i128)> = None;
+        for entry in (0..prop.length()).step_by(entry_size) {
+            let child = i128::from(read_address(&prop, entry, child_cells)?);
+            let parent_addr = entry + 4 * child_cells as usize;
+            let parent = i128::from(read_address(&prop, parent_addr, parent_cells)?);
+            let size_addr = parent_addr + 4 * parent_cells as usize;
+            let size = i128::from(read_cells(&prop, size_addr, size_cells)?);
+            if size == 0 {
+                continue;
+            }
+
+            // The range of device addresses which fall within this window.
+            let low = child - offset;
+            let high = core::cmp::min(limit, child + size - 1 - offset);
+            if high < 0 || low > high {
+                continue;
+            }
+            if best.is_none_or(|(best_high, _)| high > best_high) {
+                best = Some((high, offset + parent - child));
+            }
+        }
+
+        match best {
+            Some((high, new_offset)) => {
+                limit = high;
+                offset = new_offset;
+            }
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(DmaLimit {
+        max_address: limit as u64,
+        cpu_offset: offset as u64,
+    }))
+}
@@ -0,0 +1,50 @@
+//! Matching of nodes against driver tables.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::driver::match_driver;
+//!
+//! // The test device is compatible with "sifive,test1" and "sifive,test0" (in that order).
+//! let table = [("sifive,test0", "test0 driver"), ("sifive,test1", "test1 driver")];
+//! let node = index.compatible_nodes("sifive,test0").next().unwrap();
+//! assert_eq!(match_driver(&node, &table), Some((&"test1 driver", 0)));
+//! ```
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::index::DevTreeIndexNode;
+
+use super::find_prop;
+
+/// Returns the entry of `table` which best matches the `compatible` property of `node`, along
+/// with the position of the matching string within that property.
+///
+/// As with Linux's `of_match_node`, the strings of `compatible` are considered in order (from
+/// most to least specific), and the first string which matches any entry of `table` selects that
+/// entry. The returned position is therefore a priority where 0 is the best possible match. If
+/// multiple table entries contain the same string, the first is returned.
+///
+/// Returns `None` if `node` has no `compatible` property or none of its strings appear within
+/// `table`.
+pub fn match_driver<'t, T>(
+    node: &DevTreeIndexNode,
+    table: &'t [(&str, T)],
+) -> Option<(&'t T, usize)> {
+    let compatible = find_prop(node, "compatible")?;
+    compatible
+        .propbuf()
+        .split(|&b| b == 0)
+        .filter(|s| !s.is_empty())
+        .enumerate()
+        .find_map(|(priority, s)| {
+            let s = from_utf8(s).ok()?;
+            table
+                .iter()
+                .find(|(name, _)| *name == s)
+                .map(|(_, data)| (data, priority))
+        })
+}
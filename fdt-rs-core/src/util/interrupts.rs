@@ -0,0 +1,172 @@
+//! Decoding of `interrupts` properties and resolution of interrupt parents.
+//!
+//! The interrupts of a device are described by its `interrupts` property, a list of specifiers
+//! whose length is given by the `#interrupt-cells` of the device's interrupt parent. The
+//! interrupt parent is named by an `interrupt-parent` phandle (on the node or one of its
+//! ancestors), or is otherwise the device's parent node. [`interrupt_parent`] performs this
+//! search and [`interrupts`] decodes each specifier, so drivers for controllers such as a PLIC or
+//! GIC need not chase phandles and cell counts themselves.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::interrupts::interrupts;
+//!
+//! let uart = index.node_at_path("/uart").unwrap();
+//! let mut irqs = interrupts(&uart).unwrap().unwrap();
+//! assert!(irqs.parent().prop("interrupt-controller").is_some());
+//!
+//! let irq = irqs.next().unwrap().unwrap();
+//! assert_eq!(irq.cells(), &[10]);
+//! assert!(irqs.next().unwrap().is_none());
+//! ```
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+use crate::index::{DevTreeIndex, DevTreeIndexNode, DevTreeIndexProp};
+use crate::spec::Phandle;
+
+use super::find_prop;
+
+/// The maximum `#interrupt-cells` of an interrupt parent supported by [`interrupts`].
+pub const MAX_INTERRUPT_CELLS: usize = 4;
+
+/// An interrupt specifier: the cells which identify an interrupt to its interrupt parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterruptSpecifier {
+    cells: [u32; MAX_INTERRUPT_CELLS],
+    len: usize,
+}
+
+impl InterruptSpecifier {
+    /// Returns the cells of the specifier. Their meaning is defined by the interrupt parent's
+    /// binding.
+    #[must_use]
+    pub fn cells(&self) -> &[u32] {
+        &self.cells[..self.len]
+    }
+}
+
+/// Returns the node whose `phandle` (or legacy `linux,phandle`) property is `phandle`, or `None`
+/// if no node has that phandle.
+pub fn node_by_phandle<'a, 'i: 'a, 'dt: 'i>(
+    index: &'a DevTreeIndex<'i, 'dt>,
+    phandle: Phandle,
+) -> Result<Option<DevTreeIndexNode<'a, 'i, 'dt>>> {
+    for node in index.nodes() {
+        let prop = match find_prop(&node, "phandle") {
+            Some(prop) => prop,
+            None => match find_prop(&node, "linux,phandle") {
+                Some(prop) => prop,
+                None => continue,
+            },
+        };
+        if prop.phandle(0)? == phandle {
+            return Ok(Some(node));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the interrupt parent of `node`, or `None` if it has none.
+///
+/// The nearest `interrupt-parent` property of `node` or its ancestors is followed if one exists,
+/// otherwise the parent node is used. As in Linux, nodes which are not interrupt controllers
+/// (which have no `#interrupt-cells`) are skipped by repeating the search from them, so the
+/// result is always an interrupt controller or nexus.
+///
+/// An `interrupt-parent` phandle which refers to no node is reported as a
+/// [`DevTreeError::ParseError`].
+pub fn interrupt_parent<'a, 'i: 'a, 'dt: 'i>(
+    node: &DevTreeIndexNode<'a, 'i, 'dt>,
+) -> Result<Option<DevTreeIndexNode<'a, 'i, 'dt>>> {
+    let mut cur = *node;
+    // Each step moves to a different node, so more steps than nodes indicates a cycle.
+    for _ in 0..=node.index().nodes().count() {
+        let next = match find_prop(&cur, "interrupt-parent") {
+            Some(prop) => {
+                let phandle = prop.phandle(0)?;
+                node_by_phandle(cur.index(), phandle)?.ok_or(DevTreeError::ParseError)?
+            }
+            None => match cur.parent() {
+                Some(parent) => parent,
+                None => return Ok(None),
+            },
+        };
+        if find_prop(&next, "#interrupt-cells").is_some() {
+            return Ok(Some(next));
+        }
+        cur = next;
+    }
+    Err(DevTreeError::ParseError)
+}
+
+/// An iterator over the specifiers of an `interrupts` property. Created by [`interrupts`].
+pub struct InterruptIter<'a, 'i: 'a, 'dt: 'i> {
+    prop: DevTreeIndexProp<'a, 'i, 'dt>,
+    parent: DevTreeIndexNode<'a, 'i, 'dt>,
+    cells: usize,
+    offset: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> InterruptIter<'a, 'i, 'dt> {
+    /// Returns the interrupt parent the specifiers are interpreted by.
+    #[must_use]
+    pub fn parent(&self) -> DevTreeIndexNode<'a, 'i, 'dt> {
+        self.parent
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> FallibleIterator for InterruptIter<'a, 'i, 'dt> {
+    type Item = InterruptSpecifier;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.offset + self.cells * 4 > self.prop.length() {
+            return Ok(None);
+        }
+        let mut specifier = InterruptSpecifier {
+            cells: [0; MAX_INTERRUPT_CELLS],
+            len: self.cells,
+        };
+        for cell in &mut specifier.cells[..self.cells] {
+            *cell = self.prop.u32(self.offset)?;
+            self.offset += 4;
+        }
+        Ok(Some(specifier))
+    }
+}
+
+/// Returns an iterator over the specifiers of `node`'s `interrupts` property, decoded using the
+/// `#interrupt-cells` of its [`interrupt_parent`]. Returns `Ok(None)` if `node` has no
+/// `interrupts` property.
+///
+/// Interrupts described by `interrupts-extended`, which names a parent per specifier, are not
+/// decoded. A node with `interrupts` but no interrupt parent, or whose parent's
+/// `#interrupt-cells` is zero or greater than [`MAX_INTERRUPT_CELLS`], is reported as a
+/// [`DevTreeError::ParseError`]. Any trailing bytes which do not form a complete specifier are
+/// ignored.
+pub fn interrupts<'a, 'i: 'a, 'dt: 'i>(
+    node: &DevTreeIndexNode<'a, 'i, 'dt>,
+) -> Result<Option<InterruptIter<'a, 'i, 'dt>>> {
+    let prop = match find_prop(node, "interrupts") {
+        Some(prop) => prop,
+        None => return Ok(None),
+    };
+    let parent = interrupt_parent(node)?.ok_or(DevTreeError::ParseError)?;
+    let cells = match find_prop(&parent, "#interrupt-cells") {
+        Some(prop) => prop.u32(0)? as usize,
+        None => return Err(DevTreeError::ParseError),
+    };
+    if cells == 0 || cells > MAX_INTERRUPT_CELLS {
+        return Err(DevTreeError::ParseError);
+    }
+    Ok(Some(InterruptIter {
+        prop,
+        parent,
+        cells,
+        offset: 0,
+    }))
+}
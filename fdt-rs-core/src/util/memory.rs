@@ -0,0 +1,132 @@
+//! Enumeration of the physical memory described by `/memory` nodes.
+//!
+//! A device tree may describe its memory with several root-level nodes (e.g. one per socket or
+//! NUMA node), each of which may list several regions. [`memory_nodes`] returns every such node
+//! along with its `hotpluggable` flag, `numa-node-id` and `initial-mapped-area`, so memory
+//! managers can build a complete picture of physical memory rather than relying on the first node
+//! alone.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::memory::memory_nodes;
+//!
+//! let mut nodes = memory_nodes(&index).unwrap();
+//! let memory = nodes.next().unwrap().unwrap();
+//! assert!(!memory.hotpluggable);
+//! assert_eq!(memory.numa_node_id, None);
+//! assert_eq!(memory.initial_mapped_area, None);
+//!
+//! let (base, _size) = memory.regions().next().unwrap().unwrap();
+//! assert_eq!(base, 0x8000_0000);
+//! assert!(nodes.next().unwrap().is_none());
+//! ```
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+use crate::index::iters::DevTreeIndexNodeSiblingIter;
+use crate::index::{DevTreeIndex, DevTreeIndexNode, DevTreeIndexProp};
+
+use super::{find_prop, prop_u32_or, read_reg, RegIter, DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS};
+
+/// The region mapped by the boot program before entering the client program, as given by a
+/// memory node's `initial-mapped-area` property.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitialMappedArea {
+    /// The effective (virtual) address of the region.
+    pub effective_address: u64,
+    /// The physical address of the region.
+    pub physical_address: u64,
+    /// The size of the region in bytes.
+    pub size: u32,
+}
+
+/// A root-level node with a `device_type` of `"memory"`.
+#[derive(Clone)]
+pub struct MemoryNode<'a, 'i: 'a, 'dt: 'i> {
+    /// The memory node itself.
+    pub node: DevTreeIndexNode<'a, 'i, 'dt>,
+    /// True if the `hotpluggable` property is present; the memory may be removed at runtime.
+    pub hotpluggable: bool,
+    /// The NUMA node the memory belongs to, as given by its `numa-node-id` property.
+    pub numa_node_id: Option<u32>,
+    /// The region mapped by the boot program, as given by its `initial-mapped-area` property.
+    pub initial_mapped_area: Option<InitialMappedArea>,
+    reg: DevTreeIndexProp<'a, 'i, 'dt>,
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> MemoryNode<'a, 'i, 'dt> {
+    /// Returns an iterator over the `(base, size)` regions listed by the node's `reg` property.
+    pub fn regions(&self) -> RegIter<'_, 'dt, DevTreeIndexProp<'a, 'i, 'dt>> {
+        read_reg(&self.reg, self.address_cells, self.size_cells)
+    }
+}
+
+/// An iterator over the memory nodes of a device tree.
+///
+/// Created by [`memory_nodes`].
+pub struct MemoryNodeIter<'a, 'i: 'a, 'dt: 'i> {
+    children: DevTreeIndexNodeSiblingIter<'a, 'i, 'dt>,
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> FallibleIterator for MemoryNodeIter<'a, 'i, 'dt> {
+    type Item = MemoryNode<'a, 'i, 'dt>;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        for node in &mut self.children {
+            match find_prop(&node, "device_type") {
+                Some(prop) if prop.str() == Ok("memory") => {}
+                _ => continue,
+            }
+            let reg = match find_prop(&node, "reg") {
+                Some(reg) => reg,
+                None => continue,
+            };
+            let numa_node_id = match find_prop(&node, "numa-node-id") {
+                Some(prop) => Some(prop.u32(0)?),
+                None => None,
+            };
+            let initial_mapped_area = match find_prop(&node, "initial-mapped-area") {
+                Some(prop) => Some(InitialMappedArea {
+                    effective_address: prop.u64(0)?,
+                    physical_address: prop.u64(8)?,
+                    size: prop.u32(16)?,
+                }),
+                None => None,
+            };
+            return Ok(Some(MemoryNode {
+                hotpluggable: find_prop(&node, "hotpluggable").is_some(),
+                numa_node_id,
+                initial_mapped_area,
+                reg,
+                address_cells: self.address_cells,
+                size_cells: self.size_cells,
+                node,
+            }));
+        }
+        Ok(None)
+    }
+}
+
+/// Returns an iterator over every root-level node with a `device_type` of `"memory"`.
+///
+/// Memory nodes without a `reg` property describe no memory and are skipped. A `numa-node-id`
+/// which is not a `u32`, or an `initial-mapped-area` which is not a 64-bit effective address,
+/// 64-bit physical address and 32-bit size, is reported as an error by the iterator.
+pub fn memory_nodes<'a, 'i: 'a, 'dt: 'i>(
+    index: &'a DevTreeIndex<'i, 'dt>,
+) -> Result<MemoryNodeIter<'a, 'i, 'dt>> {
+    let root = index.root();
+    Ok(MemoryNodeIter {
+        address_cells: prop_u32_or(&root, "#address-cells", DEFAULT_ADDRESS_CELLS)?,
+        size_cells: prop_u32_or(&root, "#size-cells", DEFAULT_SIZE_CELLS)?,
+        children: root.children(),
+    })
+}
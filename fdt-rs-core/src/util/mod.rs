@@ -0,0 +1,218 @@
+//! Helpers which interpret standard device tree nodes and properties.
+//!
+//! The utilities within this module decode common bindings (as described by the device tree
+//! specification) on top of the [`crate::index`] module.
+//!
+//! Helpers which only require a property (such as [`read_reg`]) are generic over
+//! [`PropReader`] and may be used with properties from either the [`crate::base`] or
+//! [`crate::index`] backends.
+//!
+//! Errors report only what went wrong. To identify where, annotate them with the node (or
+//! property) the helper was given using [`crate::error::ErrorContext`]:
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::error::ErrorContext;
+//! use fdt_rs::util::interrupts::interrupts;
+//!
+//! let uart = index.node_at_path("/uart").unwrap();
+//! let irqs = interrupts(&uart).node_context(&uart);
+//! assert!(irqs.is_ok());
+//! ```
+//!
+//! Architecture specific bindings are enabled by the `bindings-riscv` and `bindings-arm`
+//! features.
+
+#[cfg(feature = "bindings-arm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bindings-arm")))]
+pub mod arm;
+pub mod bus;
+pub mod chosen;
+pub mod cpu;
+pub mod dma;
+pub mod driver;
+pub mod interrupts;
+pub mod memory;
+pub mod reserved_memory;
+#[cfg(feature = "bindings-riscv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bindings-riscv")))]
+pub mod riscv;
+
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+use crate::index::{DevTreeIndexNode, DevTreeIndexProp};
+
+/// Default value of `#address-cells` when a node does not define it.
+pub const DEFAULT_ADDRESS_CELLS: u32 = 2;
+/// Default value of `#size-cells` when a node does not define it.
+pub const DEFAULT_SIZE_CELLS: u32 = 1;
+
+/// Returns the first property of `node` with the given name.
+pub(crate) fn find_prop<'a, 'i: 'a, 'dt: 'i>(
+    node: &DevTreeIndexNode<'a, 'i, 'dt>,
+    name: &str,
+) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+    node.prop(name)
+}
+
+/// Returns the `u32` value of the named property of `node`, or `default` if it does not exist.
+pub(crate) fn prop_u32_or(node: &DevTreeIndexNode, name: &str, default: u32) -> Result<u32> {
+    match find_prop(node, name) {
+        Some(prop) => prop.u32(0),
+        None => Ok(default),
+    }
+}
+
+/// Returns the size in bytes of an entry made up of values of each of the given numbers of cells.
+///
+/// The numbers of cells are read from the device tree, so a size which overflows is reported as
+/// a [`DevTreeError::ParseError`].
+pub(crate) fn entry_size(cells: &[u32]) -> Result<usize> {
+    cells
+        .iter()
+        .try_fold(0u32, |sum, cells| sum.checked_add(*cells))
+        .and_then(|sum| sum.checked_mul(4))
+        .map(|size| size as usize)
+        .ok_or(DevTreeError::ParseError)
+}
+
+/// Read a big-endian value made up of `cells` 32-bit cells from `offset` within `prop`.
+///
+/// Values wider than 64 bits (more than two cells) cannot be represented and are reported as a
+/// [`DevTreeError::ParseError`].
+pub fn read_cells<'dt, P: PropReader<'dt>>(
+    prop: &P,
+    offset: usize,
+    cells: u32,
+) -> Result<u64> {
+    match cells {
+        0 => Ok(0),
+        1 => prop.u32(offset).map(u64::from),
+        2 => prop.u64(offset),
+        _ => Err(DevTreeError::ParseError),
+    }
+}
+
+/// An iterator over the `(address, size)` entries of a `reg` (or similarly encoded) property.
+///
+/// Created by [`read_reg`].
+pub struct RegIter<'p, 'dt, P: PropReader<'dt>> {
+    prop: &'p P,
+    address_cells: u32,
+    size_cells: u32,
+    offset: usize,
+    _dt: core::marker::PhantomData<&'dt [u8]>,
+}
+
+impl<'p, 'dt, P: PropReader<'dt>> FallibleIterator for RegIter<'p, 'dt, P> {
+    type Item = (u64, u64);
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        next_reg_entry(self.prop, &mut self.offset, self.address_cells, self.size_cells)
+    }
+}
+
+/// Read the `(address, size)` entry of `prop` at `offset`, advancing `offset` past it.
+fn next_reg_entry<'dt, P: PropReader<'dt>>(
+    prop: &P,
+    offset: &mut usize,
+    address_cells: u32,
+    size_cells: u32,
+) -> Result<Option<(u64, u64)>> {
+    let entry_size = entry_size(&[address_cells, size_cells])?;
+    let end = offset
+        .checked_add(entry_size)
+        .ok_or(DevTreeError::ParseError)?;
+    if entry_size == 0 || end > prop.length() {
+        return Ok(None);
+    }
+    let address = read_cells(prop, *offset, address_cells)?;
+    let size = read_cells(prop, *offset + address_cells as usize * 4, size_cells)?;
+    *offset += entry_size;
+    Ok(Some((address, size)))
+}
+
+/// Returns an iterator over the `(address, size)` entries of `prop`, a property encoded like
+/// `reg` using the given `#address-cells` and `#size-cells` of its node's parent.
+///
+/// Any trailing bytes which do not form a complete entry are ignored.
+///
+/// # Example
+///
+/// ```
+/// # use fdt_rs::doctest::*;
+/// # let (index, _) = doctest_index();
+/// use fdt_rs::util::read_reg;
+///
+/// let uart = index.compatible_nodes("ns16550a").next().unwrap();
+/// let reg = uart.props().find(|p| p.name() == Ok("reg")).unwrap();
+/// let (base, size) = read_reg(&reg, 2, 2).next().unwrap().unwrap();
+/// assert_eq!((base, size), (0x1000_0000, 0x100));
+/// ```
+pub fn read_reg<'p, 'dt, P: PropReader<'dt>>(
+    prop: &'p P,
+    address_cells: u32,
+    size_cells: u32,
+) -> RegIter<'p, 'dt, P> {
+    RegIter {
+        prop,
+        address_cells,
+        size_cells,
+        offset: 0,
+        _dt: core::marker::PhantomData,
+    }
+}
+
+/// An iterator over the `(address, size)` entries of a node's `reg` property.
+///
+/// Unlike [`RegIter`], this owns the property it reads. Created by
+/// [`DevTreeIndexNode::reg`] and [`DevTreeNode::reg`](crate::base::DevTreeNode::reg).
+pub struct NodeRegIter<'dt, P: PropReader<'dt>> {
+    prop: Option<P>,
+    address_cells: u32,
+    size_cells: u32,
+    offset: usize,
+    _dt: core::marker::PhantomData<&'dt [u8]>,
+}
+
+impl<'dt, P: PropReader<'dt>> NodeRegIter<'dt, P> {
+    /// Create an iterator over `prop`, or an empty iterator if the node has no `reg` property.
+    pub(crate) fn new(prop: Option<P>, address_cells: u32, size_cells: u32) -> Self {
+        Self {
+            prop,
+            address_cells,
+            size_cells,
+            offset: 0,
+            _dt: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the `#address-cells` used to decode each address.
+    #[must_use]
+    pub fn address_cells(&self) -> u32 {
+        self.address_cells
+    }
+
+    /// Returns the `#size-cells` used to decode each size.
+    #[must_use]
+    pub fn size_cells(&self) -> u32 {
+        self.size_cells
+    }
+}
+
+impl<'dt, P: PropReader<'dt>> FallibleIterator for NodeRegIter<'dt, P> {
+    type Item = (u64, u64);
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        match &self.prop {
+            Some(prop) => {
+                next_reg_entry(prop, &mut self.offset, self.address_cells, self.size_cells)
+            }
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Population of physical memory managers from the `/reserved-memory` node.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::reserved_memory::{reserve_all, ReservedFlags, ReservedMemorySink};
+//!
+//! struct Counter(usize);
+//!
+//! impl ReservedMemorySink for Counter {
+//!     fn reserve(&mut self, _base: u64, _size: u64, _flags: ReservedFlags) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let mut counter = Counter(0);
+//! let count = reserve_all(&index, &mut counter).unwrap();
+//! assert_eq!(count, counter.0);
+//! ```
+//!
+//! [`reserved_regions`] instead returns every region which must not be allocated, including the
+//! memory occupied by the device tree and its index:
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::reserved_memory::{reserved_regions, ReservedRegionSource};
+//!
+//! let mut regions = reserved_regions(&index).unwrap();
+//! let dtb = regions.next().unwrap().unwrap();
+//! assert_eq!(dtb.source, ReservedRegionSource::DeviceTree);
+//! assert_eq!(dtb.size, index.fdt().totalsize() as u64);
+//! ```
+use crate::prelude::*;
+
+use crate::base::iters::DevTreeReserveEntryIter;
+use crate::error::{DevTreeError, Result};
+use crate::index::iters::DevTreeIndexNodeSiblingIter;
+use crate::index::{DevTreeIndex, DevTreeIndexNode, DevTreeIndexProp};
+
+use super::{
+    entry_size, find_prop, prop_u32_or, read_cells, read_reg, DEFAULT_ADDRESS_CELLS,
+    DEFAULT_SIZE_CELLS,
+};
+
+/// Flags describing how a reserved memory region may be used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReservedFlags(u32);
+
+impl ReservedFlags {
+    /// No flags set; the region must simply not be allocated.
+    pub const NONE: Self = Self(0);
+    /// The `no-map` property is present; the region must not be mapped by the OS.
+    pub const NO_MAP: Self = Self(1 << 0);
+    /// The `reusable` property is present; the OS may use the region while its owner does not.
+    pub const REUSABLE: Self = Self(1 << 1);
+
+    /// Returns the raw flag bits.
+    #[inline]
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all flags within `other` are set.
+    #[inline]
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for ReservedFlags {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// A consumer of reserved memory regions (e.g. a physical memory manager).
+pub trait ReservedMemorySink {
+    /// Reserve the region `[base, base + size)` with the provided flags.
+    fn reserve(&mut self, base: u64, size: u64, flags: ReservedFlags);
+}
+
+/// Returns the flags described by the properties of a `/reserved-memory` child node.
+pub fn reserved_flags(node: &DevTreeIndexNode) -> ReservedFlags {
+    let mut flags = ReservedFlags::NONE;
+    if find_prop(node, "no-map").is_some() {
+        flags = flags | ReservedFlags::NO_MAP;
+    }
+    if find_prop(node, "reusable").is_some() {
+        flags = flags | ReservedFlags::REUSABLE;
+    }
+    flags
+}
+
+/// Pass every statically placed region described by the children of `/reserved-memory` to
+/// `sink`, returning the number of regions reserved.
+///
+/// Each entry of a child's `reg` property is reserved individually. Children without a `reg`
+/// property (dynamically allocated regions described by `size` and `alloc-ranges`) are skipped as
+/// they do not yet have a location. If the tree has no `/reserved-memory` node, no regions are
+/// reserved.
+pub fn reserve_all<S: ReservedMemorySink + ?Sized>(
+    index: &DevTreeIndex,
+    sink: &mut S,
+) -> Result<usize> {
+    let parent = match index
+        .root()
+        .children()
+        .find(|node| node.name() == Ok("reserved-memory"))
+    {
+        Some(node) => node,
+        None => return Ok(0),
+    };
+
+    let address_cells = prop_u32_or(&parent, "#address-cells", DEFAULT_ADDRESS_CELLS)?;
+    let size_cells = prop_u32_or(&parent, "#size-cells", DEFAULT_SIZE_CELLS)?;
+
+    let mut count = 0;
+    for child in parent.children() {
+        let reg = match find_prop(&child, "reg") {
+            Some(reg) => reg,
+            None => continue,
+        };
+        let flags = reserved_flags(&child);
+
+        let mut entries = read_reg(&reg, address_cells, size_cells);
+        while let Some((base, size)) = entries.next()? {
+            sink.reserve(base, size, flags);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Where a [`ReservedRegion`] was described.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservedRegionSource {
+    /// The memory occupied by the device tree blob itself.
+    DeviceTree,
+    /// The buffer the [`DevTreeIndex`] was built in.
+    Index,
+    /// An entry of the device tree header's memory reservation block.
+    MemReserve,
+    /// A statically placed child of `/reserved-memory`.
+    ReservedMemory,
+}
+
+/// A region of memory which must not be allocated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReservedRegion {
+    /// The first address of the region.
+    pub base: u64,
+    /// The size of the region in bytes.
+    pub size: u64,
+    /// How the region may be used. Only regions from `/reserved-memory` have flags set.
+    pub flags: ReservedFlags,
+    /// Where the region was described.
+    pub source: ReservedRegionSource,
+}
+
+/// An iterator over every reserved region of a device tree.
+///
+/// Created by [`reserved_regions`].
+pub struct ReservedRegionIter<'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    // The number of implicit (device tree and index) regions returned so far.
+    implicit: usize,
+    mem_reserve: DevTreeReserveEntryIter<'a, 'dt>,
+    children: Option<DevTreeIndexNodeSiblingIter<'a, 'i, 'dt>>,
+    // The `reg` property being read and the flags of its node.
+    reg: Option<(DevTreeIndexProp<'a, 'i, 'dt>, ReservedFlags)>,
+    reg_offset: usize,
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> ReservedRegionIter<'a, 'i, 'dt> {
+    fn next_implicit(&mut self) -> Option<ReservedRegion> {
+        let (base, size, source) = match self.implicit {
+            0 => {
+                let buf = self.index.buf();
+                let size = self.index.fdt().totalsize();
+                (buf.as_ptr(), size, ReservedRegionSource::DeviceTree)
+            }
+            1 => {
+                let range = self.index.index_buf_range();
+                let size = range.end as usize - range.start as usize;
+                (range.start, size, ReservedRegionSource::Index)
+            }
+            _ => return None,
+        };
+        self.implicit += 1;
+        Some(ReservedRegion {
+            base: base as usize as u64,
+            size: size as u64,
+            flags: ReservedFlags::NONE,
+            source,
+        })
+    }
+
+    fn next_reg_entry(&mut self) -> Result<Option<ReservedRegion>> {
+        let entry_size = entry_size(&[self.address_cells, self.size_cells])?;
+        while let Some(children) = &mut self.children {
+            if let Some((reg, flags)) = &self.reg {
+                let end = self.reg_offset.checked_add(entry_size);
+                if entry_size != 0 && matches!(end, Some(end) if end <= reg.length()) {
+                    let base = read_cells(reg, self.reg_offset, self.address_cells)?;
+                    let size_offset = self.reg_offset + self.address_cells as usize * 4;
+                    let size = read_cells(reg, size_offset, self.size_cells)?;
+                    self.reg_offset += entry_size;
+                    return Ok(Some(ReservedRegion {
+                        base,
+                        size,
+                        flags: *flags,
+                        source: ReservedRegionSource::ReservedMemory,
+                    }));
+                }
+            }
+            match children.next() {
+                Some(child) => {
+                    self.reg = find_prop(&child, "reg").map(|reg| (reg, reserved_flags(&child)));
+                    self.reg_offset = 0;
+                }
+                None => self.children = None,
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> FallibleIterator for ReservedRegionIter<'a, 'i, 'dt> {
+    type Item = ReservedRegion;
+    type Error = DevTreeError;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if let Some(region) = self.next_implicit() {
+            return Ok(Some(region));
+        }
+        if let Some(entry) = self.mem_reserve.next() {
+            return Ok(Some(ReservedRegion {
+                base: u64::from(entry.address),
+                size: u64::from(entry.size),
+                flags: ReservedFlags::NONE,
+                source: ReservedRegionSource::MemReserve,
+            }));
+        }
+        self.next_reg_entry()
+    }
+}
+
+/// Returns an iterator over every region of memory which must not be allocated.
+///
+/// The iterator first yields the memory occupied by the device tree blob (its `totalsize` bytes)
+/// and the buffer backing `index`, at the addresses they are accessed through. These are
+/// followed by the entries of the header's memory reservation block and then by every region
+/// [`reserve_all`] would reserve from `/reserved-memory`.
+pub fn reserved_regions<'a, 'i: 'a, 'dt: 'i>(
+    index: &'a DevTreeIndex<'i, 'dt>,
+) -> Result<ReservedRegionIter<'a, 'i, 'dt>> {
+    let parent = index
+        .root()
+        .children()
+        .find(|node| node.name() == Ok("reserved-memory"));
+    let (address_cells, size_cells) = match &parent {
+        Some(parent) => (
+            prop_u32_or(parent, "#address-cells", DEFAULT_ADDRESS_CELLS)?,
+            prop_u32_or(parent, "#size-cells", DEFAULT_SIZE_CELLS)?,
+        ),
+        None => (DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS),
+    };
+    Ok(ReservedRegionIter {
+        index,
+        implicit: 0,
+        mem_reserve: DevTreeReserveEntryIter::new(index.fdt()),
+        children: parent.map(|parent| parent.children()),
+        reg: None,
+        reg_offset: 0,
+        address_cells,
+        size_cells,
+    })
+}
@@ -0,0 +1,221 @@
+//! Typed views of the RISC-V CPU bindings.
+//!
+//! [`mmu_type`] decodes the `mmu-type` of a hart and [`isa_extensions`] decodes the extensions it
+//! implements, from either the `riscv,isa-extensions` string list or the older `riscv,isa`
+//! string.
+//!
+//! This module requires the `bindings-riscv` feature.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! use fdt_rs::util::riscv::{isa_extensions, mmu_type, IsaExtensions, MmuType};
+//!
+//! let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+//! assert_eq!(mmu_type(&cpu).unwrap(), Some(MmuType::Sv48));
+//!
+//! let extensions = isa_extensions(&cpu).unwrap().unwrap();
+//! assert!(extensions.contains(IsaExtensions::I | IsaExtensions::M | IsaExtensions::C));
+//! assert!(!extensions.contains(IsaExtensions::V));
+//! ```
+use core::str::from_utf8;
+
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+use crate::index::DevTreeIndexNode;
+
+use super::find_prop;
+
+/// The virtual memory scheme of a hart, as described by its `mmu-type` property.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MmuType {
+    /// No address translation (`"riscv,none"`).
+    Bare,
+    /// `"riscv,sv32"`
+    Sv32,
+    /// `"riscv,sv39"`
+    Sv39,
+    /// `"riscv,sv48"`
+    Sv48,
+    /// `"riscv,sv57"`
+    Sv57,
+}
+
+/// A set of RISC-V ISA extensions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IsaExtensions(u32);
+
+impl IsaExtensions {
+    /// No extensions.
+    pub const NONE: Self = Self(0);
+    /// The base integer instruction set.
+    pub const I: Self = Self(1 << 0);
+    /// Integer multiplication and division.
+    pub const M: Self = Self(1 << 1);
+    /// Atomic instructions.
+    pub const A: Self = Self(1 << 2);
+    /// Single-precision floating point.
+    pub const F: Self = Self(1 << 3);
+    /// Double-precision floating point.
+    pub const D: Self = Self(1 << 4);
+    /// Quad-precision floating point.
+    pub const Q: Self = Self(1 << 5);
+    /// Compressed instructions.
+    pub const C: Self = Self(1 << 6);
+    /// Vector operations.
+    pub const V: Self = Self(1 << 7);
+    /// The hypervisor extension.
+    pub const H: Self = Self(1 << 8);
+    /// Control and status register instructions.
+    pub const ZICSR: Self = Self(1 << 9);
+    /// Instruction-fetch fence.
+    pub const ZIFENCEI: Self = Self(1 << 10);
+    /// Pause hint.
+    pub const ZIHINTPAUSE: Self = Self(1 << 11);
+    /// Cache-block management instructions.
+    pub const ZICBOM: Self = Self(1 << 12);
+    /// Cache-block zero instructions.
+    pub const ZICBOZ: Self = Self(1 << 13);
+    /// Address generation bit manipulation.
+    pub const ZBA: Self = Self(1 << 14);
+    /// Basic bit manipulation.
+    pub const ZBB: Self = Self(1 << 15);
+    /// Single-bit instructions.
+    pub const ZBS: Self = Self(1 << 16);
+    /// Supervisor-mode timer interrupts.
+    pub const SSTC: Self = Self(1 << 17);
+    /// Page-based memory types.
+    pub const SVPBMT: Self = Self(1 << 18);
+    /// NAPOT translation contiguity.
+    pub const SVNAPOT: Self = Self(1 << 19);
+    /// Fine-grained address-translation cache invalidation.
+    pub const SVINVAL: Self = Self(1 << 20);
+
+    /// Returns the extension with the given (lower case) name, or `None` if it is not known.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "i" => Self::I,
+            "m" => Self::M,
+            "a" => Self::A,
+            "f" => Self::F,
+            "d" => Self::D,
+            "q" => Self::Q,
+            "c" => Self::C,
+            "v" => Self::V,
+            "h" => Self::H,
+            "zicsr" => Self::ZICSR,
+            "zifencei" => Self::ZIFENCEI,
+            "zihintpause" => Self::ZIHINTPAUSE,
+            "zicbom" => Self::ZICBOM,
+            "zicboz" => Self::ZICBOZ,
+            "zba" => Self::ZBA,
+            "zbb" => Self::ZBB,
+            "zbs" => Self::ZBS,
+            "sstc" => Self::SSTC,
+            "svpbmt" => Self::SVPBMT,
+            "svnapot" => Self::SVNAPOT,
+            "svinval" => Self::SVINVAL,
+            _ => return None,
+        })
+    }
+
+    /// Returns the raw extension bits.
+    #[inline]
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all extensions within `other` are present.
+    #[inline]
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for IsaExtensions {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Returns the virtual memory scheme of `cpu`, or `None` if it has no `mmu-type` property.
+///
+/// An unrecognized `mmu-type` is reported as a [`DevTreeError::ParseError`].
+pub fn mmu_type(cpu: &DevTreeIndexNode) -> Result<Option<MmuType>> {
+    let prop = match find_prop(cpu, "mmu-type") {
+        Some(prop) => prop,
+        None => return Ok(None),
+    };
+    Ok(Some(match prop.str()? {
+        "riscv,none" => MmuType::Bare,
+        "riscv,sv32" => MmuType::Sv32,
+        "riscv,sv39" => MmuType::Sv39,
+        "riscv,sv48" => MmuType::Sv48,
+        "riscv,sv57" => MmuType::Sv57,
+        _ => return Err(DevTreeError::ParseError),
+    }))
+}
+
+/// Decode a `riscv,isa` string such as `"rv64imafdc_zicsr_zifencei"`.
+fn parse_isa_string(isa: &str) -> Result<IsaExtensions> {
+    let rest = match isa.get(..4) {
+        Some("rv32") | Some("rv64") => &isa[4..],
+        _ => return Err(DevTreeError::ParseError),
+    };
+
+    let mut extensions = IsaExtensions::NONE;
+    let mut parts = rest.split('_');
+    // The first part lists single letter extensions, later parts are multi-letter extensions.
+    for letter in parts.next().unwrap_or("").chars() {
+        if letter == 'g' {
+            extensions = extensions
+                | IsaExtensions::I
+                | IsaExtensions::M
+                | IsaExtensions::A
+                | IsaExtensions::F
+                | IsaExtensions::D
+                | IsaExtensions::ZICSR
+                | IsaExtensions::ZIFENCEI;
+            continue;
+        }
+        let mut buf = [0u8; 4];
+        if let Some(extension) = IsaExtensions::from_name(letter.encode_utf8(&mut buf)) {
+            extensions = extensions | extension;
+        }
+    }
+    for name in parts {
+        if let Some(extension) = IsaExtensions::from_name(name) {
+            extensions = extensions | extension;
+        }
+    }
+    Ok(extensions)
+}
+
+/// Returns the ISA extensions implemented by `cpu`, or `None` if it has neither a
+/// `riscv,isa-extensions` nor a `riscv,isa` property.
+///
+/// `riscv,isa-extensions` is preferred when both are present. Extensions which are not
+/// represented by [`IsaExtensions`] are ignored. A `riscv,isa` string which does not begin with
+/// `rv32` or `rv64` is reported as a [`DevTreeError::ParseError`].
+pub fn isa_extensions(cpu: &DevTreeIndexNode) -> Result<Option<IsaExtensions>> {
+    if let Some(prop) = find_prop(cpu, "riscv,isa-extensions") {
+        let mut extensions = IsaExtensions::NONE;
+        for name in prop.propbuf().split(|&b| b == 0).filter(|s| !s.is_empty()) {
+            if let Some(extension) = IsaExtensions::from_name(from_utf8(name)?) {
+                extensions = extensions | extension;
+            }
+        }
+        return Ok(Some(extensions));
+    }
+    match find_prop(cpu, "riscv,isa") {
+        Some(prop) => parse_isa_string(prop.str()?).map(Some),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,291 @@
+//! Bounds checked views of a portion of a property's value.
+//!
+//! Composite properties (such as `ranges` or `interrupt-map`) are made up of fixed size
+//! entries. [`PropReader::subview`] and [`PropReader::subviews`] slice such values into
+//! [`PropView`] objects which implement [`PropReader`] themselves, so each entry may be decoded
+//! with the usual typed readers using offsets relative to the start of the entry.
+//!
+//! Values which are plain arrays of cells are more simply decoded with
+//! [`PropReader::iter_u32`], [`PropReader::iter_u64`] and [`PropReader::iter_cells`], and string
+//! lists with [`PropReader::iter_str`]. String lists may be checked with [`validate_strlist`], and
+//! with the `writer` feature encoded with `write_strlist`.
+//!
+//! # Example
+//!
+//! ```
+//! # use fdt_rs::doctest::*;
+//! # let (index, _) = doctest_index();
+//! // The flash node has two (address, size) entries of four cells each.
+//! let flash = index.node_at_path("/flash").unwrap();
+//! let reg = flash.props().find(|p| p.name() == Ok("reg")).unwrap();
+//!
+//! let mut entries = reg.subviews(16);
+//! let first = entries.next().unwrap();
+//! assert_eq!(first.u64(0).unwrap(), 0x2000_0000);
+//! assert_eq!(entries.count(), 1);
+//!
+//! // Views are bounds checked against the property's value.
+//! assert!(reg.subview(16, 32).is_err());
+//! ```
+use crate::prelude::*;
+
+use core::str::from_utf8;
+
+use crate::base::DevTree;
+use crate::error::DevTreeError;
+
+/// A bounds checked view of a portion of a property's value.
+///
+/// Created by [`PropReader::subview`] or [`PropReader::subviews`]. Offsets passed to the
+/// [`PropReader`] methods of a view are relative to the start of the view.
+#[derive(Clone, Copy)]
+pub struct PropView<'p, 'dt, P: PropReader<'dt>> {
+    prop: &'p P,
+    buf: &'dt [u8],
+}
+
+impl<'p, 'dt, P: PropReader<'dt>> PropView<'p, 'dt, P> {
+    pub(crate) fn new(prop: &'p P, buf: &'dt [u8]) -> Self {
+        Self { prop, buf }
+    }
+
+    /// Returns the property this view was created from.
+    #[inline]
+    #[must_use]
+    pub fn prop(&self) -> &'p P {
+        self.prop
+    }
+}
+
+impl<'p, 'dt, P: PropReader<'dt>> PropReader<'dt> for PropView<'p, 'dt, P> {
+    type NodeType = P::NodeType;
+
+    #[inline]
+    fn propbuf(&self) -> &'dt [u8] {
+        self.buf
+    }
+
+    #[inline]
+    fn nameoff(&self) -> usize {
+        self.prop.nameoff()
+    }
+
+    #[inline]
+    fn fdt(&self) -> &DevTree<'dt> {
+        self.prop.fdt()
+    }
+
+    #[inline]
+    fn node(&self) -> Self::NodeType {
+        self.prop.node()
+    }
+}
+
+/// An iterator over consecutive, equally sized [`PropView`] objects of a property's value.
+///
+/// Created by [`PropReader::subviews`]. Any trailing bytes which do not form a complete view are
+/// not returned.
+#[derive(Clone)]
+pub struct PropViews<'p, 'dt, P: PropReader<'dt>> {
+    prop: &'p P,
+    chunks: core::slice::ChunksExact<'dt, u8>,
+}
+
+impl<'p, 'dt, P: PropReader<'dt>> PropViews<'p, 'dt, P> {
+    pub(crate) fn new(prop: &'p P, len: usize) -> Self {
+        Self {
+            prop,
+            chunks: prop.propbuf().chunks_exact(len),
+        }
+    }
+}
+
+impl<'p, 'dt, P: PropReader<'dt>> Iterator for PropViews<'p, 'dt, P> {
+    type Item = PropView<'p, 'dt, P>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|buf| PropView::new(self.prop, buf))
+    }
+}
+
+/// Decodes a big-endian integer of up to 8 bytes.
+#[inline]
+fn read_be(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, &byte| value << 8 | u64::from(byte))
+}
+
+/// An iterator over the big-endian [`u32`] cells of a property's value.
+///
+/// Created by [`PropReader::iter_u32`]. Any trailing bytes which do not form a complete cell are
+/// not returned.
+#[derive(Clone)]
+pub struct PropU32Iter<'dt> {
+    chunks: core::slice::ChunksExact<'dt, u8>,
+}
+
+impl<'dt> PropU32Iter<'dt> {
+    pub(crate) fn new(buf: &'dt [u8]) -> Self {
+        Self {
+            chunks: buf.chunks_exact(4),
+        }
+    }
+}
+
+impl<'dt> Iterator for PropU32Iter<'dt> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|cell| read_be(cell) as u32)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'dt> ExactSizeIterator for PropU32Iter<'dt> {}
+
+/// An iterator over the values of a property which is an array of equally sized, one or two cell
+/// values.
+///
+/// Created by [`PropReader::iter_u64`] or [`PropReader::iter_cells`]. Any trailing bytes which do
+/// not form a complete value are not returned.
+#[derive(Clone)]
+pub struct PropCellIter<'dt> {
+    chunks: core::slice::ChunksExact<'dt, u8>,
+}
+
+impl<'dt> PropCellIter<'dt> {
+    pub(crate) fn new(buf: &'dt [u8], cells: usize) -> Self {
+        Self {
+            chunks: buf.chunks_exact(cells * 4),
+        }
+    }
+}
+
+impl<'dt> Iterator for PropCellIter<'dt> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(read_be)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'dt> ExactSizeIterator for PropCellIter<'dt> {}
+
+/// An iterator over the strings of a property whose value is a string list.
+///
+/// Created by [`PropReader::iter_str`]. Each string is borrowed from the device tree. A string
+/// which is not valid UTF-8 is returned as a [`DevTreeError::StrError`], and trailing bytes
+/// without a null terminator as a [`DevTreeError::ParseError`], after which iteration stops.
+#[derive(Clone)]
+pub struct PropStrIter<'dt> {
+    buf: &'dt [u8],
+}
+
+impl<'dt> PropStrIter<'dt> {
+    pub(crate) fn new(buf: &'dt [u8]) -> Self {
+        Self { buf }
+    }
+}
+
+impl<'dt> Iterator for PropStrIter<'dt> {
+    type Item = Result<&'dt str, DevTreeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+        let end = match self.buf.iter().position(|&b| b == 0) {
+            Some(end) => end,
+            None => {
+                self.buf = &[];
+                return Some(Err(DevTreeError::ParseError));
+            }
+        };
+        let s = &self.buf[..end];
+        self.buf = &self.buf[end + 1..];
+        match from_utf8(s) {
+            Ok(s) => Some(Ok(s)),
+            Err(e) => {
+                self.buf = &[];
+                Some(Err(DevTreeError::StrError(e)))
+            }
+        }
+    }
+}
+
+/// Verify that `value` is a list of null terminated UTF-8 strings, returning the number of
+/// strings.
+///
+/// The errors are those of [`PropStrIter`]. An empty value is an empty list.
+pub fn validate_strlist(value: &[u8]) -> Result<usize, DevTreeError> {
+    let mut count = 0;
+    for s in PropStrIter::new(value) {
+        s?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Returns the number of bytes the strings of `strs` occupy when encoded as a string list.
+#[cfg(feature = "writer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "writer")))]
+pub fn strlist_len<'s, I>(strs: I) -> usize
+where
+    I: IntoIterator<Item = &'s str>,
+{
+    strs.into_iter().map(|s| s.len() + 1).sum()
+}
+
+/// Encode the strings of `strs` at the start of `buf` as a list of null terminated strings, as
+/// used by properties such as `compatible`. Returns the number of bytes written.
+///
+/// A string containing a null byte would be read back as two strings, so it is rejected with a
+/// [`DevTreeError::InvalidParameter`], as is a `buf` shorter than [`strlist_len`] of `strs`.
+/// Nothing is written if an error is returned.
+///
+/// # Example
+///
+/// ```
+/// use fdt_rs::view::{validate_strlist, write_strlist};
+///
+/// let mut buf = [0u8; 32];
+/// let len = write_strlist(&mut buf, ["vendor,uart", "ns16550a"].iter().copied()).unwrap();
+/// assert_eq!(&buf[..len], b"vendor,uart\0ns16550a\0");
+/// assert_eq!(validate_strlist(&buf[..len]), Ok(2));
+///
+/// assert!(write_strlist(&mut buf, ["ns16550a\0"].iter().copied()).is_err());
+/// ```
+#[cfg(feature = "writer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "writer")))]
+pub fn write_strlist<'s, I>(buf: &mut [u8], strs: I) -> Result<usize, DevTreeError>
+where
+    I: IntoIterator<Item = &'s str>,
+    I::IntoIter: Clone,
+{
+    let strs = strs.into_iter();
+    if strs.clone().any(|s| s.as_bytes().contains(&0)) {
+        return Err(DevTreeError::InvalidParameter("String list entry contains a null byte"));
+    }
+    let len = strlist_len(strs.clone());
+    if buf.len() < len {
+        return Err(DevTreeError::InvalidParameter("Buffer is too small for the string list"));
+    }
+    let mut off = 0;
+    for s in strs {
+        buf[off..off + s.len()].copy_from_slice(s.as_bytes());
+        buf[off + s.len()] = 0;
+        off += s.len() + 1;
+    }
+    Ok(off)
+}
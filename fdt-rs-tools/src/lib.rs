@@ -0,0 +1,50 @@
+//! Host tooling for fdt-rs which requires the standard library.
+//!
+//! fdt-rs re-exports this crate when its `std` feature is enabled. Depend on fdt-rs rather than
+//! on this crate; the examples within the documentation are written against it.
+#![deny(clippy::all, clippy::cargo)]
+
+use fdt_rs_core::base::DevTree;
+use fdt_rs_core::error::Result;
+use fdt_rs_core::index::DevTreeIndex;
+use fdt_rs_core::print::write_dts;
+
+/// Returns `fdt` as device tree source. See [`write_dts`] for details on the output format.
+pub fn to_dts(fdt: &DevTree) -> Result<String> {
+    let mut out = String::new();
+    write_dts(fdt, &mut out)?;
+    Ok(out)
+}
+
+/// A device tree which may be returned as device tree source.
+///
+/// # Example
+///
+/// ```
+/// # use fdt_rs::doctest::*;
+/// let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+/// let dts = devtree.to_dts().unwrap();
+/// assert!(dts.starts_with("/dts-v1/;\n\n/ {\n"));
+/// ```
+pub trait ToDts {
+    /// Returns the device tree as device tree source.
+    ///
+    /// See [`write_dts`] for details on the output format.
+    fn to_dts(&self) -> Result<String>;
+}
+
+impl<'dt> ToDts for DevTree<'dt> {
+    fn to_dts(&self) -> Result<String> {
+        to_dts(self)
+    }
+}
+
+/// The nodes and properties of any fragments stitched into the index are included, see
+/// [`DevTreeIndex::write_dts()`].
+impl<'i, 'dt: 'i> ToDts for DevTreeIndex<'i, 'dt> {
+    fn to_dts(&self) -> Result<String> {
+        let mut out = String::new();
+        self.write_dts(&mut out)?;
+        Ok(out)
+    }
+}
@@ -1,2 +0,0 @@
-pub mod item;
-pub mod prop;
@@ -0,0 +1,129 @@
+//! An example of the API of each of the crate's features.
+//!
+//! Each module below holds an example which is compiled and run as a doctest when its feature is
+//! enabled. The documentation of items which require a feature is marked with that feature.
+//!
+//! | Module        | Feature           |
+//! |---------------|-------------------|
+//! | [`index`]     | none              |
+//! | `writer`      | `writer`          |
+//! | `overlay`     | `overlay`         |
+//! | `serialize`   | `serde`           |
+//! | `bindings`    | `bindings-riscv`  |
+//! | `bindings`    | `bindings-arm`    |
+
+/// Parsing a device tree and querying an index of it.
+///
+/// ```
+/// # use fdt_rs::doctest::FDT;
+/// use fdt_rs::prelude::*;
+///
+/// let mut index_buf = vec![0u8; 16 * 1024];
+/// let (_, index) = fdt_rs::quick_index(FDT, &mut index_buf).unwrap();
+///
+/// let uart = index.find_first_compatible_node("ns16550a").unwrap().unwrap();
+/// assert_eq!(uart.name().unwrap(), "uart@10000000");
+/// let reg = uart.reg().unwrap().next().unwrap().unwrap();
+/// assert_eq!(reg, (0x1000_0000, 0x100));
+/// ```
+pub mod index {}
+
+/// Patching a device tree before passing it to the next boot stage.
+///
+/// ```
+/// # use fdt_rs::doctest::FDT;
+/// use fdt_rs::base::DevTreeMut;
+/// use fdt_rs::view::write_strlist;
+///
+/// let mut words = vec![0u32; FDT.len() / 4];
+/// let buf =
+///     unsafe { core::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, FDT.len()) };
+/// buf.copy_from_slice(FDT);
+/// let mut fdt = unsafe { DevTreeMut::new(buf) }.unwrap();
+///
+/// let mut value = [0u8; 16];
+/// let len = write_strlist(&mut value, ["ns16550"].iter().copied()).unwrap();
+/// assert!(fdt.set_prop("/uart", "compatible", &value[..len]).unwrap());
+///
+/// let devtree = fdt.devtree();
+/// let uart = devtree.node_at_path("/uart").unwrap().unwrap();
+/// assert_eq!(uart.prop_str("compatible").unwrap(), Some("ns16550"));
+/// ```
+#[cfg(feature = "writer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "writer")))]
+pub mod writer {}
+
+/// Applying an overlay which sets `/chosen/bootargs` and adds a GPIO controller to `/soc`.
+///
+/// ```
+/// # use fdt_rs::doctest::{FDT, OVERLAY};
+/// use fdt_rs::base::DevTree;
+/// use fdt_rs::overlay::apply_overlay;
+///
+/// let base = unsafe { DevTree::new(FDT) }.unwrap();
+/// let overlay = unsafe { DevTree::new(OVERLAY) }.unwrap();
+///
+/// let mut words = vec![0u32; (FDT.len() + OVERLAY.len()) / 4];
+/// let dst = unsafe {
+///     core::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, words.len() * 4)
+/// };
+/// let len = apply_overlay(&base, &overlay, dst).unwrap();
+///
+/// let merged = unsafe { DevTree::new(&dst[..len]) }.unwrap();
+/// let chosen = merged.node_at_path("/chosen").unwrap().unwrap();
+/// assert_eq!(chosen.prop_str("bootargs").unwrap(), Some("console=ttyS0"));
+/// assert!(merged.node_at_path("/soc/gpio@10060000").unwrap().is_some());
+/// ```
+#[cfg(feature = "overlay")]
+#[cfg_attr(docsrs, doc(cfg(feature = "overlay")))]
+pub mod overlay {}
+
+/// Converting a device tree to JSON for tooling or golden-file tests.
+///
+/// ```
+/// # use fdt_rs::doctest::FDT;
+/// let mut index_buf = vec![0u8; 16 * 1024];
+/// let (_, index) = fdt_rs::quick_index(FDT, &mut index_buf).unwrap();
+///
+/// let json = serde_json::to_value(&index).unwrap();
+/// assert_eq!(json["chosen"]["stdout-path"], "/uart@10000000");
+/// ```
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serialize {}
+
+/// Decoding architecture specific bindings.
+///
+/// With the `bindings-riscv` feature, the extensions of a hart are decoded by
+/// [`crate::util::riscv`]:
+///
+/// ```
+/// # #[cfg(feature = "bindings-riscv")] {
+/// # use fdt_rs::doctest::*;
+/// # let (index, _) = doctest_index();
+/// use fdt_rs::util::riscv::{isa_extensions, IsaExtensions};
+///
+/// let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+/// let extensions = isa_extensions(&cpu).unwrap().unwrap();
+/// assert!(extensions.contains(IsaExtensions::A));
+/// # }
+/// ```
+///
+/// With the `bindings-arm` feature, GIC interrupt specifiers are decoded by
+/// [`crate::util::arm`]:
+///
+/// ```
+/// # #[cfg(feature = "bindings-arm")] {
+/// use fdt_rs::util::arm::{GicInterrupt, GicInterruptKind, IrqTrigger};
+///
+/// let irq = GicInterrupt::from_cells([0, 33, 4]).unwrap();
+/// assert_eq!(irq.kind, GicInterruptKind::Spi);
+/// assert_eq!(irq.trigger, IrqTrigger::LevelHigh);
+/// # }
+/// ```
+#[cfg(any(feature = "bindings-riscv", feature = "bindings-arm"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "bindings-riscv", feature = "bindings-arm")))
+)]
+pub mod bindings {}
@@ -6,6 +6,16 @@
 //! * [Low-level FDT parsing utilities to build your own library](base::parse)
 //! * [Simple utilites based on in-order parsing of the FDT](base)
 //! * [Performant utilities which leverage an index built over the FDT](index)
+//! * [Allocation-free utilities to print the FDT](print)
+//! * [Helpers which decode standard nodes and properties](util)
+//! * [Minimal queries for early boot code](quick)
+//! * [Parsing and indexing a device tree in a single call](quick_index)
+//! * [Bounds checked views of composite property values](view)
+//! * [Fixed capacity collections for gathering results without allocating](bounded)
+//! * [A canonical stream of a tree's contents for comparing trees](canonical)
+//! * [Reporting of violations of the specification within a parsable tree](lint)
+//! * [Application of device tree overlays](overlay) (with the `overlay` feature)
+//! * [Traits for code which is generic over the backend](traits)
 //!
 //! ## Features
 //!
@@ -18,71 +28,81 @@
 //! default-features = false
 //! ```
 //!
+//! The parser is implemented by the no-std `fdt-rs-core` crate, and host tooling which requires
+//! the standard library (such as [`prelude::ToDts`]) by the `fdt-rs-tools` crate. This crate
+//! re-exports both, and only builds `fdt-rs-tools` with the `std` feature, so that the tooling
+//! adds nothing to the dependencies or code size of `no-std` users.
+//!
+//! The `profiling` feature records the cost of searches, see [`profiling`].
+//!
+//! The `coverage` feature records which nodes and properties of an index are accessed, see
+//! [`coverage`].
+//!
+//! The `legacy-fdt` feature adds support for reading device trees older than version 16, which
+//! store full paths as node names and align large property values to 8 bytes. The [`quick`] and
+//! [`index::buffer`] modules do not support these layouts.
+//!
+//! The `bindings-riscv` and `bindings-arm` features provide typed views of architecture specific
+//! bindings within [`util`].
+//!
+//! The `writer` feature adds [`base::DevTreeMut`], which overwrites property values in place, and
+//! encoders for property values such as [`view::write_strlist`].
+//!
+//! The `overlay` feature adds [`overlay`], which applies device tree overlays.
+//!
+//! The `serde` feature implements `Serialize` for an index and its nodes, see [`serialize`].
+//!
+//! The `checked` feature replaces the unchecked fast paths used internally (such as unwrapping
+//! values which are present by construction, or unchecked indexing) with checked equivalents
+//! which panic if an invariant is violated. This trades performance for the absence of undefined
+//! behavior should an invariant not hold, e.g. for use in certified environments.
+//!
+//! The [`gallery`] holds an example of each feature's API. Items which require a feature are
+//! marked as such within the documentation.
+//!
 //! ## Examples
 //!
 //!
 #![deny(clippy::all, clippy::cargo)]
-#![allow(clippy::as_conversions)]
-// Test the readme if using nightly.
-#![cfg_attr(RUSTC_IS_NIGHTLY, feature(external_doc))]
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
-#[cfg(feature = "std")]
-extern crate core;
-extern crate endian_type_rs as endian_type;
-#[macro_use]
-extern crate memoffset;
-#[macro_use]
-extern crate static_assertions;
-extern crate fallible_iterator;
-extern crate unsafe_unwrap;
+pub use fdt_rs_core::*;
 
-pub mod base;
-pub mod error;
-pub mod index;
-pub mod prelude;
-pub mod spec;
+pub mod prelude {
+    //! Module exporting traits of this library.
+    //!
+    //! The concrete types of this library provide their most common operations as inherent
+    //! methods, so these traits are mainly needed by code which is generic over the backend, and
+    //! to iterate the [`FallibleIterator`]s returned by [`crate::base`].
+    pub use fdt_rs_core::prelude::*;
 
-#[doc(hidden)]
-pub mod common;
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub use fdt_rs_tools::ToDts;
+}
 
-pub(crate) mod priv_util;
+pub mod print {
+    //! Minimal device tree printing utilities which do not require an allocator.
+    //!
+    //! See [`fdt_rs_core::print`]. With the `std` feature, [`to_dts`] returns a device tree as
+    //! device tree source in a [`String`].
+    pub use fdt_rs_core::print::*;
 
-// When the doctest feature is enabled, add these utility functions.
-#[cfg(any(feature = "doctest", doc))]
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub use fdt_rs_tools::to_dts;
+}
+
+#[cfg(feature = "doctest")]
 #[doc(hidden)]
 pub mod doctest {
-    pub use crate::base::*;
-    pub use crate::index::*;
-    pub use crate::prelude::*;
-
-    // Include the readme for doctests
-    // https://doc.rust-lang.org/rustdoc/documentation-tests.html#include-items-only-when-collecting-doctests
-    #[cfg(RUSTC_IS_NIGHTLY)]
-    #[doc(include = "../README.md")]
-    pub struct ReadmeDoctests;
+    pub use fdt_rs_core::doctest::*;
 
-    #[repr(align(4))]
-    struct _Wrapper<T>(T);
-    pub const FDT: &[u8] = &_Wrapper(*include_bytes!("../tests/riscv64-virt.dtb")).0;
-
-    pub fn doctest_index<'i, 'dt: 'i>() -> (DevTreeIndex<'i, 'dt>, Vec<u8>) {
-        // Create the device tree parser
-        let devtree = unsafe { DevTree::new(FDT) }.unwrap();
-
-        // Get the layout required to build an index
-        let layout = DevTreeIndex::get_layout(&devtree).unwrap();
-
-        // Allocate memory for the index.
-        //
-        // This could be performed without a dynamic allocation
-        // if we allocated a static buffer or want to provide a
-        // raw buffer into uninitialized memory.
-        let mut vec = vec![0u8; layout.size() + layout.align()];
-        let (p, s) = (vec.as_mut_ptr(), vec.len());
-        unsafe {
-            let vec_copy = core::slice::from_raw_parts_mut(p, s);
-            (DevTreeIndex::new(devtree, vec_copy).unwrap(), vec)
-        }
-    }
+    #[cfg(feature = "std")]
+    pub use fdt_rs_tools::ToDts;
 }
+
+// Built for the documentation, and when collecting doctests so that its examples are run.
+#[cfg(any(doc, doctest))]
+pub mod gallery;
@@ -1,7 +0,0 @@
-//! Module exporting traits of this library.
-pub(crate) use crate::common::item::UnwrappableDevTreeItem;
-pub(crate) use crate::priv_util::SliceRead;
-
-pub use crate::common::prop::PropReader;
-
-pub use fallible_iterator::FallibleIterator;
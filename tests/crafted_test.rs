@@ -0,0 +1,3294 @@
+// Some imports are only used by the tests which require the std feature.
+#![cfg_attr(not(feature = "std"), allow(unused_imports))]
+
+extern crate fdt_rs;
+
+use fdt_rs::base::parse::{DevTreeParseIter, ParsedTok};
+use fdt_rs::base::{Check, DevTree, DevTreeItem};
+#[cfg(feature = "writer")]
+use fdt_rs::base::DevTreeMut;
+use fdt_rs::canonical::{PropOrder, MAX_CANONICAL_DEPTH};
+use fdt_rs::error::{DevTreeError, ErrorContext};
+use fdt_rs::index::{DevTreeIndex, IndexFragment, MAX_INDEX_FRAGMENTS};
+use fdt_rs::lint::LintKind;
+#[cfg(feature = "overlay")]
+use fdt_rs::overlay::apply_overlay;
+use fdt_rs::prelude::*;
+use fdt_rs::spec::{DuplicatePropPolicy, FdtTok, MissingEndPolicy, Status};
+use fdt_rs::util::bus::{
+    bus_ranges, bus_reg, translate_address, BusKind, IsaSpaceFlags, PciSpace, PciSpaceFlags,
+    SpaceFlags,
+};
+use fdt_rs::util::chosen::Chosen;
+use fdt_rs::util::cpu::{
+    cache_organization, enable_method, psci, CacheOrganization, EnableMethod, Psci, PsciConduit,
+};
+use fdt_rs::util::dma::{dma_coherence, dma_limit, DmaCoherence, DmaLimit};
+use fdt_rs::util::driver::match_driver;
+use fdt_rs::util::interrupts::{interrupt_parent, interrupts, node_by_phandle};
+use fdt_rs::util::memory::memory_nodes;
+use fdt_rs::util::reserved_memory::{
+    reserve_all, reserved_regions, ReservedFlags, ReservedMemorySink, ReservedRegionSource,
+};
+#[cfg(feature = "writer")]
+use fdt_rs::view::{strlist_len, validate_strlist, write_strlist};
+
+/// Byte offsets of the `fdt_header` fields we patch in these tests.
+const OFF_DT_STRUCT: usize = 8;
+const OFF_DT_STRINGS: usize = 12;
+const OFF_MEM_RSVMAP: usize = 16;
+const VERSION: usize = 20;
+const LAST_COMP_VERSION: usize = 24;
+const BOOT_CPUID_PHYS: usize = 28;
+const SIZE_DT_STRINGS: usize = 32;
+const SIZE_DT_STRUCT: usize = 36;
+
+/// A tiny FDT assembler used to craft edge-case (and malformed) device trees.
+#[derive(Default)]
+struct Blob {
+    reservations: Vec<(u64, u64)>,
+    structure: Vec<u8>,
+    strings: Vec<u8>,
+}
+
+impl Blob {
+    fn token(&mut self, tok: u32) -> &mut Self {
+        self.structure.extend_from_slice(&tok.to_be_bytes());
+        self
+    }
+
+    fn align(&mut self) {
+        while self.structure.len() % 4 != 0 {
+            self.structure.push(0);
+        }
+    }
+
+    fn begin_node(&mut self, name: &str) -> &mut Self {
+        self.token(0x1);
+        self.structure.extend_from_slice(name.as_bytes());
+        self.structure.push(0);
+        self.align();
+        self
+    }
+
+    fn end_node(&mut self) -> &mut Self {
+        self.token(0x2)
+    }
+
+    fn prop(&mut self, name: &str, value: &[u8]) -> &mut Self {
+        let nameoff = self.string_offset(name);
+        self.raw_prop(nameoff, value)
+    }
+
+    /// Emit a property whose name is at `nameoff` within the strings block.
+    fn raw_prop(&mut self, nameoff: u32, value: &[u8]) -> &mut Self {
+        self.token(0x3);
+        self.structure
+            .extend_from_slice(&(value.len() as u32).to_be_bytes());
+        self.structure.extend_from_slice(&nameoff.to_be_bytes());
+        self.structure.extend_from_slice(value);
+        self.align();
+        self
+    }
+
+    /// Emit a property laid out as in device trees older than version 16, where values of 8 or
+    /// more bytes are 8 byte aligned relative to the start of the structure block.
+    #[cfg(feature = "legacy-fdt")]
+    fn legacy_prop(&mut self, name: &str, value: &[u8]) -> &mut Self {
+        let nameoff = self.string_offset(name);
+        self.token(0x3);
+        self.structure
+            .extend_from_slice(&(value.len() as u32).to_be_bytes());
+        self.structure.extend_from_slice(&nameoff.to_be_bytes());
+        if value.len() >= 8 && self.structure.len() % 8 != 0 {
+            self.structure.extend_from_slice(&[0; 4]);
+        }
+        self.structure.extend_from_slice(value);
+        self.align();
+        self
+    }
+
+    fn nop(&mut self) -> &mut Self {
+        self.token(0x4)
+    }
+
+    fn end(&mut self) -> &mut Self {
+        self.token(0x9)
+    }
+
+    fn string_offset(&mut self, name: &str) -> u32 {
+        let mut needle = name.as_bytes().to_vec();
+        needle.push(0);
+        if let Some(pos) = self
+            .strings
+            .windows(needle.len())
+            .position(|w| w == needle.as_slice())
+        {
+            return pos as u32;
+        }
+        let off = self.strings.len();
+        self.strings.extend_from_slice(&needle);
+        off as u32
+    }
+
+    fn build(&self) -> Dtb {
+        const HEADER_SIZE: usize = 40;
+        let rsvmap_size = (self.reservations.len() + 1) * 16;
+
+        let off_struct = HEADER_SIZE + rsvmap_size;
+        let off_strings = off_struct + self.structure.len();
+        let totalsize = off_strings + self.strings.len();
+
+        let mut bytes = Vec::with_capacity(totalsize);
+        for field in &[
+            0xd00d_feed,
+            totalsize as u32,
+            off_struct as u32,
+            off_strings as u32,
+            HEADER_SIZE as u32,
+            17,
+            16,
+            0,
+            self.strings.len() as u32,
+            self.structure.len() as u32,
+        ] {
+            bytes.extend_from_slice(&u32::to_be_bytes(*field));
+        }
+        for (address, size) in &self.reservations {
+            bytes.extend_from_slice(&address.to_be_bytes());
+            bytes.extend_from_slice(&size.to_be_bytes());
+        }
+        bytes.extend_from_slice(&[0u8; 16]);
+        bytes.extend_from_slice(&self.structure);
+        bytes.extend_from_slice(&self.strings);
+        Dtb::from_bytes(&bytes)
+    }
+}
+
+/// A 32-bit aligned device tree buffer.
+struct Dtb {
+    words: Vec<u32>,
+    len: usize,
+}
+
+impl Dtb {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut words = vec![0u32; (bytes.len() + 3) / 4];
+        let len = bytes.len();
+        unsafe {
+            core::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, len)
+                .copy_from_slice(bytes);
+        }
+        Self { words, len }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.words.as_ptr() as *const u8, self.len) }
+    }
+
+    fn set_header_field(&mut self, offset: usize, value: u32) {
+        self.words[offset / 4] = value.to_be();
+    }
+
+    fn header_field(&self, offset: usize) -> u32 {
+        u32::from_be(self.words[offset / 4])
+    }
+
+    /// Returns the structure block, as described by the header.
+    fn struct_block(&self) -> &[u8] {
+        let off = self.header_field(OFF_DT_STRUCT) as usize;
+        let size = self.header_field(SIZE_DT_STRUCT) as usize;
+        &self.bytes()[off..off + size]
+    }
+
+    fn devtree(&self) -> Result<DevTree<'_>, DevTreeError> {
+        unsafe { DevTree::new(self.bytes()) }
+    }
+
+    #[cfg(feature = "writer")]
+    fn devtree_mut(&mut self) -> Result<DevTreeMut<'_>, DevTreeError> {
+        let ptr = self.words.as_mut_ptr() as *mut u8;
+        unsafe { DevTreeMut::new(core::slice::from_raw_parts_mut(ptr, self.len)) }
+    }
+
+    /// Build an index over this device tree using `buf` as backing storage.
+    fn index<'i>(&'i self, buf: &'i mut Vec<u8>) -> DevTreeIndex<'i, 'i> {
+        let devtree = self.devtree().unwrap();
+        let size = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+        buf.resize(size, 0);
+        DevTreeIndex::new(devtree, buf.as_mut_slice()).unwrap()
+    }
+}
+
+fn cells(values: &[u32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|v| v.to_be_bytes().to_vec())
+        .collect()
+}
+
+fn simple_tree() -> Blob {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"test\0")
+        .begin_node("child")
+        .end_node()
+        .end_node()
+        .end();
+    blob
+}
+
+#[test]
+fn crafted_tree_parses() {
+    let dtb = simple_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    let prop = devtree.props().next().unwrap().unwrap();
+    assert_eq!(prop.name().unwrap(), "compatible");
+}
+
+#[test]
+#[cfg(feature = "writer")]
+fn property_values_are_overwritten_in_place() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("chosen")
+        .prop("bootargs", b"console=ttyS0 root=/dev/vda\0")
+        .prop("stdout-path", b"/uart\0")
+        .end_node()
+        .end_node()
+        .end();
+    let mut dtb = blob.build();
+    let before = dtb.bytes().len();
+
+    let mut fdt = dtb.devtree_mut().unwrap();
+    assert!(fdt.set_prop("/chosen", "bootargs", b"quiet\0").unwrap());
+    assert!(!fdt.set_prop("/chosen", "missing", b"").unwrap());
+    assert!(!fdt.set_prop("/missing", "bootargs", b"").unwrap());
+    assert!(matches!(
+        fdt.set_prop("/chosen", "stdout-path", b"/serial@0\0"),
+        Err(DevTreeError::InvalidParameter(_))
+    ));
+
+    // The space freed by the shorter value is filled with FDT_NOP tokens.
+    let devtree = fdt.devtree();
+    devtree.validate().unwrap();
+    let nops = devtree
+        .parse_iter()
+        .filter(|tok| Ok(matches!(tok, ParsedTok::Nop)))
+        .count()
+        .unwrap();
+    assert_eq!(nops, 5);
+    let chosen = devtree.node_at_path("/chosen").unwrap().unwrap();
+    assert_eq!(chosen.prop_str("bootargs").unwrap(), Some("quiet"));
+    assert_eq!(chosen.prop_str("stdout-path").unwrap(), Some("/uart"));
+    assert_eq!(dtb.bytes().len(), before);
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let chosen = index.node_at_path("/chosen").unwrap();
+    let names: Vec<_> = chosen.props().map(|p| p.name().unwrap()).collect();
+    assert_eq!(names, ["bootargs", "stdout-path"]);
+}
+
+#[test]
+#[cfg(feature = "overlay")]
+fn overlays_merge_into_their_targets() {
+    let mut base = Blob::default();
+    base.reservations.push((0x8000_0000, 0x1000));
+    base.begin_node("")
+        .begin_node("intc")
+        .prop("phandle", &cells(&[1]))
+        .end_node()
+        .begin_node("soc")
+        .prop("phandle", &cells(&[2]))
+        .begin_node("uart")
+        .prop("status", b"disabled\0")
+        .prop("compatible", b"ns16550a\0")
+        .end_node()
+        .end_node()
+        .begin_node("__symbols__")
+        .prop("intc", b"/intc\0")
+        .prop("soc", b"/soc\0")
+        .end_node()
+        .end_node()
+        .end();
+    let base = base.build();
+
+    let mut overlay = Blob::default();
+    overlay
+        .begin_node("")
+        .begin_node("fragment@0")
+        .prop("target", &cells(&[0xffff_ffff]))
+        .begin_node("__overlay__")
+        .begin_node("uart")
+        .prop("status", b"okay\0")
+        .end_node()
+        .begin_node("clk")
+        .prop("phandle", &cells(&[1]))
+        .end_node()
+        .begin_node("dev")
+        .prop("interrupt-parent", &cells(&[0xffff_ffff]))
+        .prop("clocks", &cells(&[1, 7]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .begin_node("fragment@1")
+        .prop("target-path", b"/intc\0")
+        .begin_node("__overlay__")
+        .prop("interrupt-controller", &[])
+        .end_node()
+        .end_node()
+        .begin_node("__fixups__")
+        .prop("soc", b"/fragment@0:target:0\0")
+        .prop("intc", b"/fragment@0/__overlay__/dev:interrupt-parent:0\0")
+        .end_node()
+        .begin_node("__local_fixups__")
+        .begin_node("fragment@0")
+        .begin_node("__overlay__")
+        .begin_node("dev")
+        .prop("clocks", &cells(&[0]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let overlay = overlay.build();
+
+    let base = base.devtree().unwrap();
+    let overlay = overlay.devtree().unwrap();
+    let mut dst = vec![0u32; 256];
+    let bytes = unsafe { core::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut u8, 1024) };
+    let len = apply_overlay(&base, &overlay, bytes).unwrap();
+    assert_eq!(
+        apply_overlay(&base, &overlay, &mut bytes[..len - 1]),
+        Err(DevTreeError::NotEnoughMemory)
+    );
+
+    let merged = Dtb::from_bytes(&bytes[..len]);
+    let devtree = merged.devtree().unwrap();
+    devtree.validate().unwrap();
+    assert_eq!(devtree.totalsize(), len);
+    let reserved: Vec<_> = devtree
+        .reserved_entries()
+        .map(|entry| (u64::from(entry.address), u64::from(entry.size)))
+        .collect();
+    assert_eq!(reserved, [(0x8000_0000, 0x1000)]);
+
+    let mut buf = Vec::new();
+    let index = merged.index(&mut buf);
+    let paths: Vec<_> = index
+        .canonical_items(PropOrder::Document)
+        .filter(|item| Ok(item.name.is_none()))
+        .map(|item| Ok(item.path.to_string()))
+        .collect()
+        .unwrap();
+    assert_eq!(
+        paths,
+        ["/", "/intc", "/soc", "/soc/uart", "/soc/clk", "/soc/dev", "/__symbols__"]
+    );
+
+    // Replaced properties keep their position.
+    let uart = index.node_at_path("/soc/uart").unwrap();
+    let props: Vec<_> = uart
+        .props()
+        .map(|p| (p.name().unwrap(), p.str().unwrap()))
+        .collect();
+    assert_eq!(props, [("status", "okay"), ("compatible", "ns16550a")]);
+    let intc = index.node_at_path("/intc").unwrap();
+    assert!(intc.prop("interrupt-controller").is_some());
+
+    // Overlay phandles follow those of the base, and references are resolved.
+    let clk = index.node_at_path("/soc/clk").unwrap();
+    assert_eq!(clk.prop_u32("phandle").unwrap(), Some(3));
+    let dev = index.node_at_path("/soc/dev").unwrap();
+    assert_eq!(dev.prop_u32("interrupt-parent").unwrap(), Some(1));
+    let clocks: Vec<_> = dev.prop("clocks").unwrap().iter_u32().collect();
+    assert_eq!(clocks, [3, 7]);
+
+    // Labels missing from the base tree are reported.
+    let mut unresolved = Blob::default();
+    unresolved
+        .begin_node("")
+        .begin_node("fragment@0")
+        .prop("target", &cells(&[0xffff_ffff]))
+        .begin_node("__overlay__")
+        .end_node()
+        .end_node()
+        .begin_node("__fixups__")
+        .prop("missing", b"/fragment@0:target:0\0")
+        .end_node()
+        .end_node()
+        .end();
+    let unresolved = unresolved.build();
+    assert!(matches!(
+        apply_overlay(&base, &unresolved.devtree().unwrap(), bytes),
+        Err(DevTreeError::InvalidParameter(_))
+    ));
+}
+
+#[test]
+fn fragments_are_stitched_into_an_index_at_their_targets() {
+    let mut base = Blob::default();
+    base.begin_node("")
+        .prop("model", b"base\0")
+        .begin_node("soc")
+        .prop("status", b"disabled\0")
+        .prop("ranges", b"")
+        .begin_node("uart@1000")
+        .end_node()
+        .end_node()
+        .begin_node("chosen")
+        .end_node()
+        .end_node()
+        .end();
+    let base = base.build();
+
+    let mut first = Blob::default();
+    first
+        .begin_node("")
+        .prop("status", b"okay\0")
+        .prop("label", b"first\0")
+        .begin_node("gpio@2000")
+        .prop("label", b"gpio\0")
+        .begin_node("pin")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let first = first.build();
+
+    let mut second = Blob::default();
+    second
+        .nop()
+        .begin_node("")
+        .prop("label", b"second\0")
+        .begin_node("spi@3000")
+        .end_node()
+        .end_node()
+        .end();
+    let second = second.build();
+
+    let fdt = base.devtree().unwrap();
+    let fragments = [
+        IndexFragment {
+            target: "/soc",
+            fdt: first.devtree().unwrap(),
+        },
+        IndexFragment {
+            target: "/soc",
+            fdt: second.devtree().unwrap(),
+        },
+    ];
+    let size = DevTreeIndex::required_buffer_size_stitched(&fdt, &fragments).unwrap();
+    let mut buf = vec![0u8; size];
+    let index = DevTreeIndex::new_stitched(fdt, &fragments, &mut buf).unwrap();
+
+    // Fragment properties follow the target's own, replacing those of the same name, and later
+    // fragments replace earlier ones.
+    let soc = index.node_at_path("/soc").unwrap();
+    let props: Vec<_> = soc
+        .props()
+        .map(|prop| (prop.name().unwrap(), prop.raw()))
+        .collect();
+    assert_eq!(
+        props,
+        [
+            ("ranges", &b""[..]),
+            ("status", &b"okay\0"[..]),
+            ("label", &b"second\0"[..]),
+        ]
+    );
+    assert!(core::ptr::eq(soc.fdt(), index.fdt()));
+    assert!(core::ptr::eq(
+        soc.prop("status").unwrap().tree(),
+        &fragments[0].fdt
+    ));
+
+    // Fragment children follow the target's own, in fragment order.
+    let children: Vec<_> = soc.children().map(|node| node.name().unwrap()).collect();
+    assert_eq!(children, ["uart@1000", "gpio@2000", "spi@3000"]);
+    let descendants: Vec<_> = soc.descendants().map(|node| node.name().unwrap()).collect();
+    assert_eq!(descendants, ["uart@1000", "gpio@2000", "pin", "spi@3000"]);
+    let root_children: Vec<_> = index
+        .root()
+        .children()
+        .map(|node| node.name().unwrap())
+        .collect();
+    assert_eq!(root_children, ["soc", "chosen"]);
+
+    let gpio = index.node_at_path("/soc/gpio@2000").unwrap();
+    assert!(core::ptr::eq(gpio.fdt(), &fragments[0].fdt));
+    assert_eq!(gpio.prop("label").unwrap().str(), Ok("gpio"));
+    assert_eq!(gpio.path().unwrap().to_string(), "/soc/gpio@2000");
+    let pin = index.node_at_path("/soc/gpio@2000/pin").unwrap();
+    assert_eq!(pin.parent().unwrap().name(), Ok("gpio@2000"));
+    assert_eq!(pin.to_base().name(), Ok("pin"));
+    assert_eq!(gpio.to_base().name(), Ok("gpio@2000"));
+    assert!(index.node_at_path("/soc/spi@3000").is_some());
+    assert_eq!(
+        index.node_at_path("/chosen").unwrap().to_base().name(),
+        Ok("chosen")
+    );
+
+    // The fragments are printed at their targets, but a stitched index may not be linted.
+    let mut dts = String::new();
+    index.write_dts(&mut dts).unwrap();
+    assert!(dts.contains(
+        "\t\tstatus = \"okay\";\n\t\tlabel = \"second\";\n\n\t\tuart@1000 {\n"
+    ));
+    assert!(dts.contains("\t\tgpio@2000 {\n\t\t\tlabel = \"gpio\";\n"));
+    assert!(matches!(
+        index.lint().next(),
+        Err(DevTreeError::InvalidParameter(_))
+    ));
+
+    // Targets must be nodes of the base device tree.
+    let missing = [IndexFragment {
+        target: "/soc/gpio@2000",
+        fdt: first.devtree().unwrap(),
+    }];
+    let mut buf = vec![0u8; size];
+    assert!(matches!(
+        DevTreeIndex::new_stitched(fdt, &missing, &mut buf),
+        Err(DevTreeError::InvalidParameter(_))
+    ));
+    let too_many = vec![fragments[0]; MAX_INDEX_FRAGMENTS + 1];
+    assert!(matches!(
+        DevTreeIndex::new_stitched(fdt, &too_many, &mut buf),
+        Err(DevTreeError::TooManyFragments)
+    ));
+
+    // Fragments must not be held in the buffer of the base device tree.
+    let aliased = [IndexFragment {
+        target: "/soc",
+        fdt: base.devtree().unwrap(),
+    }];
+    assert!(matches!(
+        DevTreeIndex::new_stitched(fdt, &aliased, &mut buf),
+        Err(DevTreeError::InvalidParameter(_))
+    ));
+}
+
+#[test]
+fn reservations_are_read_from_32_bit_aligned_trees() {
+    let mut blob = simple_tree();
+    blob.reservations.push((0x8000_0000, 0x1000));
+    let dtb = blob.build();
+
+    // Place the tree 4 bytes past an 8 byte boundary, so its 64-bit fields are misaligned.
+    let len = dtb.bytes().len();
+    let mut words = vec![0u64; (len + 4) / 8 + 1];
+    let buf = unsafe {
+        core::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, words.len() * 8)
+    };
+    let buf = &mut buf[4..4 + len];
+    buf.copy_from_slice(dtb.bytes());
+
+    let devtree = unsafe { DevTree::new(buf) }.unwrap();
+    let reserved: Vec<_> = devtree
+        .reserved_entries()
+        .map(|entry| (u64::from(entry.address), u64::from(entry.size)))
+        .collect();
+    assert_eq!(reserved, [(0x8000_0000, 0x1000)]);
+}
+
+#[test]
+fn header_versions_are_reported() {
+    let mut dtb = simple_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.version(), 17);
+    assert_eq!(devtree.last_comp_version(), 16);
+    assert_eq!(devtree.check_version(), Ok(17));
+    assert_eq!(devtree.boot_cpuid_phys(), 0);
+
+    dtb.set_header_field(VERSION, 42);
+    dtb.set_header_field(LAST_COMP_VERSION, 17);
+    dtb.set_header_field(BOOT_CPUID_PHYS, 3);
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.version(), 42);
+    assert_eq!(devtree.last_comp_version(), 17);
+    assert_eq!(devtree.check_version(), Ok(42));
+    assert_eq!(devtree.boot_cpuid_phys(), 3);
+
+    // A device tree which is not backwards compatible with the latest version is rejected.
+    dtb.set_header_field(LAST_COMP_VERSION, 18);
+    assert_eq!(
+        dtb.devtree().unwrap_err(),
+        DevTreeError::VersionNotSupported(42)
+    );
+
+    #[cfg(not(feature = "legacy-fdt"))]
+    {
+        dtb.set_header_field(VERSION, 15);
+        dtb.set_header_field(LAST_COMP_VERSION, 1);
+        assert_eq!(
+            dtb.devtree().unwrap_err(),
+            DevTreeError::VersionNotSupported(15)
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn version_16_structure_blocks_end_at_the_strings_block() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"test\0")
+        .begin_node("child")
+        .end_node()
+        .end_node();
+    let mut dtb = blob.build();
+    dtb.set_header_field(VERSION, 16);
+    // The field does not exist in version 16 and must be ignored.
+    dtb.set_header_field(SIZE_DT_STRUCT, 0xffff_ffff);
+
+    let devtree = dtb.devtree().unwrap();
+    let struct_size = dtb.header_field(OFF_DT_STRINGS) - dtb.header_field(OFF_DT_STRUCT);
+    assert_eq!(devtree.size_dt_struct(), struct_size as usize);
+    // The strings block is not parsed as tokens of the structure block.
+    assert_eq!(devtree.validate(), Err(DevTreeError::MissingEndToken));
+    let permissive = devtree.with_missing_end_policy(MissingEndPolicy::Permissive);
+    let names: Vec<_> = permissive.nodes().map(|n| n.name()).collect().unwrap();
+    assert_eq!(names, ["", "child"]);
+
+    // Without a following strings block, the structure block is bounded by the totalsize.
+    dtb.set_header_field(OFF_DT_STRINGS, dtb.header_field(OFF_MEM_RSVMAP));
+    let devtree = dtb.devtree().unwrap();
+    let totalsize = dtb.bytes().len() as u32;
+    let struct_size = totalsize - dtb.header_field(OFF_DT_STRUCT);
+    assert_eq!(devtree.size_dt_struct(), struct_size as usize);
+}
+
+#[cfg(feature = "legacy-fdt")]
+#[test]
+fn legacy_layouts_parse_on_both_backends() {
+    let mut blob = Blob::default();
+    blob.begin_node("/")
+        .legacy_prop("compatible", b"legacy\0")
+        .begin_node("/soc")
+        .legacy_prop("reg", &cells(&[0x1000, 0x2000, 0x3000]))
+        .begin_node("/soc/uart@1000")
+        .legacy_prop("clock-frequency", &cells(&[0, 115_200]))
+        .legacy_prop("status", b"okay\0")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+
+    for &version in &[3, 2] {
+        let mut dtb = blob.build();
+        dtb.set_header_field(VERSION, version);
+        dtb.set_header_field(LAST_COMP_VERSION, 1);
+        if version < 3 {
+            // The field does not exist in version 2 and must be ignored.
+            dtb.set_header_field(SIZE_DT_STRINGS, 0xffff_ffff);
+        }
+        let devtree = dtb.devtree().unwrap();
+        devtree.validate().unwrap();
+
+        let names: Vec<_> = devtree
+            .nodes()
+            .map(|node| Ok(node.name()?.to_string()))
+            .collect()
+            .unwrap();
+        assert_eq!(names, ["", "soc", "uart@1000"]);
+
+        let reg = devtree
+            .props()
+            .find(|prop| Ok(prop.name()? == "reg"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reg.propbuf(), cells(&[0x1000, 0x2000, 0x3000]).as_slice());
+
+        let mut buf = Vec::new();
+        let index = dtb.index(&mut buf);
+        let uart = index.node_at_path("/soc/uart@1000").unwrap();
+        let values: Vec<_> = uart
+            .props()
+            .map(|prop| (prop.name().unwrap(), prop.propbuf().to_vec()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("clock-frequency", cells(&[0, 115_200])),
+                ("status", b"okay\0".to_vec()),
+            ]
+        );
+        assert_eq!(
+            index.root().props().next().unwrap().str(),
+            Ok("legacy")
+        );
+    }
+}
+
+#[cfg(feature = "legacy-fdt")]
+#[test]
+fn legacy_node_paths_may_exceed_the_node_name_limit() {
+    let bus = format!("/{}", "bus@10000000".repeat(8));
+    let uart = format!("{}/uart@1000", bus);
+    assert!(uart.len() > fdt_rs::spec::MAX_FULL_NODE_NAME_LEN);
+
+    let mut blob = Blob::default();
+    blob.begin_node("/")
+        .begin_node(&bus)
+        .begin_node(&uart)
+        .legacy_prop("status", b"okay\0")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let mut dtb = blob.build();
+    dtb.set_header_field(VERSION, 3);
+    dtb.set_header_field(LAST_COMP_VERSION, 1);
+
+    let devtree = dtb.devtree().unwrap();
+    devtree.validate().unwrap();
+    let names: Vec<_> = devtree
+        .nodes()
+        .map(|node| Ok(node.name()?.to_string()))
+        .collect()
+        .unwrap();
+    assert_eq!(names, ["", &bus[1..], "uart@1000"]);
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let node = index.node_at_path(&uart).unwrap();
+    assert_eq!(node.props().next().unwrap().str(), Ok("okay"));
+}
+
+#[test]
+fn strings_block_offset_beyond_totalsize_is_rejected() {
+    let mut dtb = simple_tree().build();
+    let totalsize = dtb.bytes().len() as u32;
+    dtb.set_header_field(OFF_DT_STRINGS, totalsize + 4);
+    assert_eq!(
+        dtb.devtree().unwrap_err(),
+        DevTreeError::InvalidStringsBlock
+    );
+}
+
+#[test]
+fn strings_block_size_beyond_totalsize_is_rejected() {
+    let mut dtb = simple_tree().build();
+    let totalsize = dtb.bytes().len() as u32;
+    dtb.set_header_field(SIZE_DT_STRINGS, totalsize);
+    assert_eq!(
+        dtb.devtree().unwrap_err(),
+        DevTreeError::InvalidStringsBlock
+    );
+}
+
+#[test]
+fn precondition_reports_list_every_failure() {
+    let mut dtb = simple_tree().build();
+    let report = DevTree::check_preconditions(dtb.bytes());
+    assert!(report.checks().iter().all(|(_, check)| *check == Check::Passed));
+    assert_eq!(report.version, Some((17, 16)));
+
+    // Overlap the strings block with the structure block, and claim an unsupported version.
+    dtb.set_header_field(OFF_DT_STRINGS, dtb.header_field(OFF_DT_STRUCT));
+    dtb.set_header_field(LAST_COMP_VERSION, 18);
+    let report = DevTree::check_preconditions(dtb.bytes());
+    assert!(matches!(report.version_supported, Check::Failed(_)));
+    assert!(matches!(report.blocks_disjoint, Check::Failed(_)));
+    assert_eq!(report.strings_block, Check::Passed);
+    assert_eq!(report.blocks_ordered, Check::Passed);
+    assert_eq!(report.result(), Err(DevTreeError::VersionNotSupported(17)));
+
+    // A reservation block beyond the end of the device tree is never terminated.
+    let mut dtb = simple_tree().build();
+    let totalsize = dtb.bytes().len() as u32;
+    dtb.set_header_field(OFF_MEM_RSVMAP, totalsize);
+    let report = DevTree::check_preconditions(dtb.bytes());
+    assert!(matches!(report.mem_rsvmap, Check::Failed(_)));
+    assert!(matches!(report.blocks_ordered, Check::Failed(_)));
+    assert_eq!(report.struct_block, Check::Passed);
+    assert_eq!(report.length, Check::Passed);
+
+    // The header is read from an unaligned buffer.
+    let dtb = simple_tree().build();
+    let mut shifted = vec![0u8; dtb.bytes().len() + 1];
+    shifted[1..].copy_from_slice(dtb.bytes());
+    let report = DevTree::check_preconditions(&shifted[1..]);
+    assert!(matches!(report.aligned, Check::Failed(DevTreeError::InvalidParameter(_))));
+    assert!(report.checks()[1..].iter().all(|(_, check)| *check == Check::Passed));
+
+    // Nothing beyond the header is checked without a device tree's magic number.
+    let report = DevTree::check_preconditions(&dtb.bytes()[4..]);
+    assert_eq!(report.magic, Check::Failed(DevTreeError::InvalidMagicNumber));
+    assert_eq!(report.length, Check::Skipped);
+    assert_eq!(report.totalsize, None);
+    let report = DevTree::check_preconditions(&dtb.bytes()[..16]);
+    assert!(matches!(report.header, Check::Failed(_)));
+    assert_eq!(report.magic, Check::Skipped);
+}
+
+#[test]
+fn tree_without_props_allows_empty_strings_block() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("child")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    assert!(devtree.strings_block().is_empty());
+    assert_eq!(devtree.nodes().count().unwrap(), 2);
+}
+
+#[test]
+fn prop_name_with_empty_strings_block_fails_cleanly() {
+    let mut dtb = simple_tree().build();
+    dtb.set_header_field(SIZE_DT_STRINGS, 0);
+    let devtree = dtb.devtree().unwrap();
+    let prop = devtree.props().next().unwrap().unwrap();
+    assert_eq!(prop.name().unwrap_err(), DevTreeError::EmptyStringsBlock);
+}
+
+#[test]
+fn prop_name_outside_strings_block_fails_cleanly() {
+    let mut dtb = simple_tree().build();
+    dtb.set_header_field(SIZE_DT_STRINGS, 4);
+    let devtree = dtb.devtree().unwrap();
+    let mut props = devtree.props();
+    let prop = props.next().unwrap().unwrap();
+    // "compatible" is not terminated within the truncated strings block.
+    assert_eq!(prop.name().unwrap_err(), DevTreeError::ParseError);
+}
+
+#[derive(Default)]
+struct Reservations(Vec<(u64, u64, ReservedFlags)>);
+
+impl ReservedMemorySink for Reservations {
+    fn reserve(&mut self, base: u64, size: u64, flags: ReservedFlags) {
+        self.0.push((base, size, flags));
+    }
+}
+
+fn reserved_memory_tree() -> Blob {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("reserved-memory")
+        .prop("#address-cells", &cells(&[2]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("ranges", &[])
+        .begin_node("firmware@80000000")
+        .prop(
+            "reg",
+            &cells(&[0, 0x8000_0000, 0x20000, 0, 0x9000_0000, 0x1000]),
+        )
+        .prop("no-map", &[])
+        .end_node()
+        .begin_node("cma")
+        .prop("size", &cells(&[0x100_0000]))
+        .prop("reusable", &[])
+        .end_node()
+        .begin_node("framebuffer@a0000000")
+        .prop("reg", &cells(&[0, 0xa000_0000, 0x80_0000]))
+        .prop("reusable", &[])
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    blob
+}
+
+#[test]
+fn reserved_memory_children_are_reserved_with_flags() {
+    let dtb = reserved_memory_tree().build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let mut reservations = Reservations::default();
+    assert_eq!(reserve_all(&index, &mut reservations).unwrap(), 3);
+    assert_eq!(
+        reservations.0,
+        vec![
+            (0x8000_0000, 0x20000, ReservedFlags::NO_MAP),
+            (0x9000_0000, 0x1000, ReservedFlags::NO_MAP),
+            (0xa000_0000, 0x80_0000, ReservedFlags::REUSABLE),
+        ]
+    );
+}
+
+#[test]
+fn reserved_memory_rejects_overflowing_cell_counts() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("reserved-memory")
+        .prop("#address-cells", &cells(&[0xffff_ffff]))
+        .prop("#size-cells", &cells(&[2]))
+        .begin_node("firmware@80000000")
+        .prop("reg", &cells(&[0x8000_0000, 0x20000]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let mut reservations = Reservations::default();
+    assert_eq!(reserve_all(&index, &mut reservations), Err(DevTreeError::ParseError));
+}
+
+#[test]
+fn missing_reserved_memory_node_reserves_nothing() {
+    let dtb = simple_tree().build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let mut reservations = Reservations::default();
+    assert_eq!(reserve_all(&index, &mut reservations).unwrap(), 0);
+    assert!(reservations.0.is_empty());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn reserved_regions_include_the_dtb_and_index() {
+    let mut blob = reserved_memory_tree();
+    blob.reservations = vec![(0x4000_0000, 0x1000), (0x4100_0000, 0x2000)];
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let regions: Vec<_> = reserved_regions(&index)
+        .unwrap()
+        .map(|r| Ok((r.base, r.size, r.flags, r.source)))
+        .collect()
+        .unwrap();
+    assert_eq!(
+        regions,
+        vec![
+            (
+                dtb.bytes().as_ptr() as u64,
+                dtb.bytes().len() as u64,
+                ReservedFlags::NONE,
+                ReservedRegionSource::DeviceTree,
+            ),
+            (
+                buf.as_ptr() as u64,
+                buf.len() as u64,
+                ReservedFlags::NONE,
+                ReservedRegionSource::Index,
+            ),
+            (
+                0x4000_0000,
+                0x1000,
+                ReservedFlags::NONE,
+                ReservedRegionSource::MemReserve,
+            ),
+            (
+                0x4100_0000,
+                0x2000,
+                ReservedFlags::NONE,
+                ReservedRegionSource::MemReserve,
+            ),
+            (
+                0x8000_0000,
+                0x20000,
+                ReservedFlags::NO_MAP,
+                ReservedRegionSource::ReservedMemory,
+            ),
+            (
+                0x9000_0000,
+                0x1000,
+                ReservedFlags::NO_MAP,
+                ReservedRegionSource::ReservedMemory,
+            ),
+            (
+                0xa000_0000,
+                0x80_0000,
+                ReservedFlags::REUSABLE,
+                ReservedRegionSource::ReservedMemory,
+            ),
+        ]
+    );
+
+    let dtb = simple_tree().build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    assert_eq!(reserved_regions(&index).unwrap().count().unwrap(), 2);
+}
+
+fn status_tree() -> Blob {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("uart@0")
+        .prop("compatible", b"vendor,uart\0ns16550a\0")
+        .prop("status", b"okay\0")
+        .end_node()
+        .begin_node("uart@1")
+        .prop("compatible", b"ns16550a\0")
+        .prop("status", b"disabled\0")
+        .end_node()
+        .begin_node("timer")
+        .end_node()
+        .begin_node("dma")
+        .prop("status", b"fail-sss\0")
+        .end_node()
+        .begin_node("wdt")
+        .prop("status", b"vendor,parked\0")
+        .end_node()
+        .end_node()
+        .end();
+    blob
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn compatible_matches_any_strlist_entry() {
+    let dtb = status_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    let names: Vec<_> = devtree
+        .compatible_nodes("ns16550a")
+        .map(|node| Ok(node.name()?.to_string()))
+        .collect()
+        .unwrap();
+    assert_eq!(names, &["uart@0", "uart@1"]);
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let compatible = index
+        .props()
+        .find(|prop| prop.name() == Ok("compatible"))
+        .unwrap();
+    assert!(compatible.strlist_contains("vendor,uart"));
+    assert!(compatible.strlist_contains("ns16550a"));
+    assert!(!compatible.strlist_contains("ns16550"));
+    assert_eq!(index.compatible_nodes("ns16550a").count(), 2);
+}
+
+/// Generic over both backends.
+fn first_compatible<'a, 'dt: 'a, T: IterableDevTree<'a, 'dt>>(
+    tree: &'a T,
+    compatible: &str,
+) -> Option<T::Node> {
+    tree.find_first_compatible_node(compatible).unwrap()
+}
+
+#[test]
+fn find_first_compatible_node_is_consistent() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"vendor,board\0generic-board\0")
+        .begin_node("soc")
+        .prop("compatible", b"simple-bus\0")
+        .begin_node("uart@0")
+        .prop("compatible", b"vendor,uart\0generic-board\0")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    for (compatible, expected) in &[
+        ("vendor,board", Some("")),
+        // Entries other than the first also match, and the root precedes its descendants.
+        ("generic-board", Some("")),
+        ("simple-bus", Some("soc")),
+        ("vendor,uart", Some("uart@0")),
+        ("vendor", None),
+    ] {
+        let base = first_compatible(&devtree, compatible).map(|node| node.name().unwrap());
+        let indexed = first_compatible(&index, compatible).map(|node| node.name().unwrap());
+        assert_eq!(base, *expected, "{}", compatible);
+        assert_eq!(indexed, *expected, "{}", compatible);
+    }
+}
+
+#[test]
+fn match_driver_prefers_most_specific_compatible() {
+    let dtb = status_tree().build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let uart0 = index.node_at_path("/uart@0").unwrap();
+    let uart1 = index.node_at_path("/uart@1").unwrap();
+    let timer = index.node_at_path("/timer").unwrap();
+
+    // Table order does not matter; the node's compatible order does.
+    let table = [("ns16550a", 1), ("vendor,uart", 2), ("ns16550a", 3)];
+    assert_eq!(match_driver(&uart0, &table), Some((&2, 0)));
+    assert_eq!(match_driver(&uart1, &table), Some((&1, 0)));
+    assert_eq!(match_driver(&uart0, &table[..1]), Some((&1, 1)));
+    assert_eq!(match_driver(&uart0, &table[2..]), Some((&3, 1)));
+    assert_eq!(match_driver(&uart0, &[("ns16550", 0)]), None);
+    assert_eq!(match_driver(&timer, &table), None);
+}
+
+#[test]
+fn duplicate_props_follow_policy() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("dev")
+        .prop("reg", &cells(&[1]))
+        .prop("status", b"okay\0")
+        .prop("reg", &cells(&[2]))
+        .prop("reg", &cells(&[3]))
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let dev = index.node_at_path("/dev").unwrap();
+
+    let reg = |policy| {
+        dev.prop_with_policy("reg", policy)
+            .map(|prop| prop.unwrap().u32(0).unwrap())
+    };
+    assert_eq!(dev.prop("reg").unwrap().u32(0), Ok(1));
+    assert_eq!(reg(DuplicatePropPolicy::FirstWins), Ok(1));
+    assert_eq!(reg(DuplicatePropPolicy::LastWins), Ok(3));
+    assert_eq!(reg(DuplicatePropPolicy::Strict), Err(DevTreeError::DuplicateProperty));
+    assert!(dev
+        .prop_with_policy("status", DuplicatePropPolicy::Strict)
+        .unwrap()
+        .is_some());
+    assert!(dev.prop("missing").is_none());
+
+    let devtree = dtb.devtree().unwrap();
+    let mut nodes = devtree.nodes();
+    nodes.next().unwrap();
+    let dev = nodes.next().unwrap().unwrap();
+    let reg = |policy| {
+        dev.prop_with_policy("reg", policy)
+            .map(|prop| prop.unwrap().u32(0).unwrap())
+    };
+    assert_eq!(dev.prop("reg").unwrap().unwrap().u32(0), Ok(1));
+    assert_eq!(reg(DuplicatePropPolicy::FirstWins), Ok(1));
+    assert_eq!(reg(DuplicatePropPolicy::LastWins), Ok(3));
+    assert_eq!(reg(DuplicatePropPolicy::Strict), Err(DevTreeError::DuplicateProperty));
+    assert!(dev.prop("missing").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn status_is_okay() {
+    let dtb = status_tree().build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let status: Vec<_> = index
+        .nodes()
+        .map(|node| (node.name().unwrap(), node.status_is_okay()))
+        .collect();
+    assert_eq!(
+        status,
+        &[
+            ("", true),
+            ("uart@0", true),
+            ("uart@1", false),
+            ("timer", true),
+            ("dma", false),
+            ("wdt", false)
+        ]
+    );
+
+    let devtree = dtb.devtree().unwrap();
+    let base_status: Vec<_> = devtree
+        .nodes()
+        .map(|node| node.status_is_okay())
+        .collect()
+        .unwrap();
+    assert_eq!(base_status, &[true, true, false, true, false, false]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn status_is_parsed() {
+    let dtb = status_tree().build();
+    let expected = [
+        Status::Okay,
+        Status::Okay,
+        Status::Disabled,
+        Status::Okay,
+        Status::Fail(Some("sss")),
+        Status::Unknown("vendor,parked"),
+    ];
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let status: Vec<_> = index.nodes().map(|node| node.status().unwrap()).collect();
+    assert_eq!(status, expected);
+
+    let devtree = dtb.devtree().unwrap();
+    let status: Vec<_> = devtree.nodes().map(|node| node.status()).collect().unwrap();
+    assert_eq!(status, expected);
+
+    assert_eq!(Status::from("fail"), Status::Fail(None));
+    assert_eq!(Status::from("ok"), Status::Okay);
+    assert_eq!(Status::from("reserved"), Status::Reserved);
+}
+
+#[test]
+fn try_iterators_report_corruption() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"test\0")
+        .begin_node("child")
+        .token(0xff)
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+
+    // The invalid token follows the root node (8 bytes), its property (20 bytes) and the child
+    // node (12 bytes).
+    let corrupt = DevTreeError::MalformedToken {
+        offset: devtree.off_dt_struct() + 40,
+        token: None,
+    };
+    let nodes: Vec<_> = devtree
+        .try_nodes()
+        .map(|node| node.map(|n| n.name().unwrap()))
+        .collect();
+    assert_eq!(nodes, vec![Ok(""), Ok("child"), Err(corrupt)]);
+    assert_eq!(devtree.try_items().count(), 4);
+    assert_eq!(devtree.try_props().last().unwrap().err(), Some(corrupt));
+
+    // An intact tree simply ends.
+    let dtb = simple_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    assert!(devtree.try_items().all(|item| item.is_ok()));
+}
+
+#[test]
+fn subtree_byte_range_spans_node_tokens() {
+    let dtb = simple_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    let mut nodes = devtree.nodes();
+
+    // Header, memory reservation block, then the structure block.
+    let struct_start = 56;
+    let root = nodes.next().unwrap().unwrap();
+    // BEGIN_NODE "" (8) + PROP "test\0" (20) + child (16) + END_NODE (4)
+    assert_eq!(root.subtree_byte_range().unwrap(), struct_start..struct_start + 48);
+
+    let child = nodes.next().unwrap().unwrap();
+    // BEGIN_NODE "child\0" (12) + END_NODE (4)
+    let child_start = struct_start + 28;
+    assert_eq!(child.subtree_byte_range().unwrap(), child_start..child_start + 16);
+}
+
+fn nested_tree(depth: usize) -> Blob {
+    let mut blob = Blob::default();
+    blob.begin_node("");
+    for _ in 1..depth {
+        blob.begin_node("nested");
+    }
+    for _ in 0..depth {
+        blob.end_node();
+    }
+    blob.end();
+    blob
+}
+
+#[test]
+fn deep_node_paths_are_written_without_recursing() {
+    const DEPTH: usize = 5_000;
+    let mut blob = Blob::default();
+    blob.begin_node("");
+    for _ in 1..DEPTH {
+        blob.begin_node("nested");
+    }
+    blob.prop("reg", &cells(&[0]));
+    for _ in 0..DEPTH {
+        blob.end_node();
+    }
+    blob.end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let reg = index.props().next().unwrap();
+    let mut path = String::new();
+    reg.node_path_write(&mut path).unwrap();
+    assert_eq!(path, "/nested".repeat(DEPTH - 1));
+}
+
+#[test]
+fn limit_depth_stops_deep_traversals() {
+    let dtb = nested_tree(4).build();
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.items().limit_depth(4).count().unwrap(), 4);
+
+    let mut iter = devtree.items().limit_depth(3);
+    for _ in 0..3 {
+        assert!(iter.next().unwrap().is_some());
+    }
+    assert_eq!(iter.next().err(), Some(DevTreeError::DepthLimitExceeded));
+    assert!(iter.next().unwrap().is_none());
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    assert!(index.items().limit_depth(4).all(|item| item.is_ok()));
+    let res: Vec<_> = index.items().limit_depth(3).map(|item| item.err()).collect();
+    assert_eq!(
+        res,
+        vec![None, None, None, Some(DevTreeError::DepthLimitExceeded)]
+    );
+}
+
+#[test]
+fn path_stacks_track_the_current_node() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("soc")
+        .begin_node("uart@0")
+        .prop("reg", &cells(&[0]))
+        .end_node()
+        .end_node()
+        .begin_node("chosen")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+
+    let mut stack = [""; 2];
+    let mut iter = devtree.items().with_path_stack(&mut stack);
+    let mut paths = Vec::new();
+    while let Some(item) = iter.next().unwrap() {
+        let mut path = String::new();
+        iter.write_path(&mut path).unwrap();
+        let name = match item {
+            DevTreeItem::Node(node) => node.name().unwrap(),
+            DevTreeItem::Prop(prop) => prop.name().unwrap(),
+        };
+        paths.push((name.to_string(), path));
+    }
+    let expected = [
+        ("", "/"),
+        ("soc", "/soc"),
+        ("uart@0", "/soc/uart@0"),
+        ("reg", "/soc/uart@0"),
+        ("chosen", "/chosen"),
+    ];
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(name, path)| (name.to_string(), path.to_string()))
+        .collect();
+    assert_eq!(paths, expected);
+
+    let dtb = nested_tree(4).build();
+    let devtree = dtb.devtree().unwrap();
+    let mut stack = [""; 2];
+    let mut iter = devtree.items().with_path_stack(&mut stack);
+    for _ in 0..3 {
+        assert!(iter.next().unwrap().is_some());
+    }
+    assert_eq!(iter.path(), &["nested", "nested"]);
+    assert_eq!(iter.next().err(), Some(DevTreeError::DepthLimitExceeded));
+    assert!(iter.next().unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn lint_reports_every_finding() {
+    let long_name = "a-property-name-longer-than-31-chars";
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[5]))
+        .prop("#size-cells", &[0, 1])
+        .begin_node("intc")
+        .prop("phandle", &cells(&[1]))
+        .end_node()
+        .begin_node("uart@0")
+        .prop("compatible", b"ns16550")
+        .prop("interrupt-parent", &cells(&[1]))
+        .prop("status", b"okay\0")
+        .prop("status", b"okay\0")
+        .prop(long_name, &[])
+        .end_node()
+        .begin_node("a-node-name-longer-than-31-chars@0")
+        .prop("interrupt-parent", &cells(&[2]))
+        .end_node()
+        .prop("model", b"late\0")
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+
+    let findings: Vec<_> = devtree
+        .lint()
+        .map(|finding| Ok((finding.kind, finding.name)))
+        .collect()
+        .unwrap();
+    assert_eq!(
+        findings,
+        [
+            (LintKind::BadCells, "#address-cells"),
+            (LintKind::BadCells, "#size-cells"),
+            (LintKind::InvalidStringList, "compatible"),
+            (LintKind::DuplicateProp, "status"),
+            (LintKind::NameTooLong, long_name),
+            (LintKind::NameTooLong, "a-node-name-longer-than-31-chars@0"),
+            (LintKind::DanglingPhandle(2), "interrupt-parent"),
+            (LintKind::PropAfterNode, "model"),
+        ]
+    );
+
+    // The first property follows the root node's token and its empty, padded name.
+    let finding = devtree.lint().next().unwrap().unwrap();
+    assert_eq!(finding.offset, dtb.header_field(OFF_DT_STRUCT) as usize + 8);
+}
+
+#[test]
+fn limit_nodes_stops_large_traversals() {
+    let dtb = status_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    // Six nodes and six properties.
+    assert_eq!(devtree.items().limit_nodes(6).count().unwrap(), 12);
+    assert_eq!(
+        devtree.items().limit_nodes(5).count().err(),
+        Some(DevTreeError::NodeLimitExceeded)
+    );
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    assert_eq!(index.items().limit_nodes(6).count(), 12);
+    assert_eq!(
+        index.items().limit_nodes(5).last().unwrap().err(),
+        Some(DevTreeError::NodeLimitExceeded)
+    );
+    assert_eq!(
+        index
+            .items()
+            .limit_depth(2)
+            .limit_nodes(2)
+            .last()
+            .unwrap()
+            .err(),
+        Some(DevTreeError::NodeLimitExceeded)
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn canonical_streams_ignore_encoding() {
+    let mut first = Blob::default();
+    first
+        .begin_node("")
+        .prop("compatible", b"test\0")
+        .prop("model", b"board\0")
+        .begin_node("soc")
+        .prop("reg", &cells(&[0x1000]))
+        .prop("#size-cells", &cells(&[1]))
+        .begin_node("uart@1000")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+
+    // The same tree with its properties reordered, extra NOPs and a different strings block.
+    let mut second = Blob::default();
+    second.string_offset("unused");
+    second
+        .begin_node("")
+        .nop()
+        .prop("model", b"board\0")
+        .prop("compatible", b"test\0")
+        .begin_node("soc")
+        .prop("#size-cells", &cells(&[1]))
+        .nop()
+        .prop("reg", &cells(&[0x1000]))
+        .begin_node("uart@1000")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+
+    let first = first.build();
+    let second = second.build();
+    let (first, second) = (first.devtree().unwrap(), second.devtree().unwrap());
+    assert!(!first
+        .canonical_items(PropOrder::Document)
+        .eq(second.canonical_items(PropOrder::Document))
+        .unwrap());
+    assert!(first
+        .canonical_items(PropOrder::ByName)
+        .eq(second.canonical_items(PropOrder::ByName))
+        .unwrap());
+
+    let mut buf = Vec::new();
+    let index = DevTreeIndex::new(first, {
+        buf.resize(DevTreeIndex::required_buffer_size(&first).unwrap(), 0);
+        &mut buf
+    })
+    .unwrap();
+    for &order in &[PropOrder::Document, PropOrder::ByName] {
+        assert!(index
+            .canonical_items(order)
+            .eq(first.canonical_items(order))
+            .unwrap());
+    }
+
+    let items: Vec<_> = index
+        .canonical_items(PropOrder::ByName)
+        .map(|item| Ok((item.path.to_string(), item.name)))
+        .collect()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            ("/".to_string(), None),
+            ("/".to_string(), Some("compatible")),
+            ("/".to_string(), Some("model")),
+            ("/soc".to_string(), None),
+            ("/soc".to_string(), Some("#size-cells")),
+            ("/soc".to_string(), Some("reg")),
+            ("/soc/uart@1000".to_string(), None),
+        ]
+    );
+
+    let deep = nested_tree(MAX_CANONICAL_DEPTH + 2).build();
+    let deep = deep.devtree().unwrap();
+    let items = deep.canonical_items(PropOrder::Document);
+    assert_eq!(
+        items.count().map(|_| ()),
+        Err(DevTreeError::DepthLimitExceeded)
+    );
+}
+
+fn dma_tree(soc_window: u32) -> Blob {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[2]))
+        .prop("#size-cells", &cells(&[2]))
+        .begin_node("soc")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("dma-ranges", &cells(&[0x0, 0x0, 0x8000_0000, soc_window]))
+        .begin_node("bus")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("dma-ranges", &cells(&[0x1000_0000, 0x0, 0x2000_0000]))
+        .begin_node("nic")
+        .end_node()
+        .end_node()
+        .begin_node("uart")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    blob
+}
+
+#[test]
+fn dma_limit_intersects_bus_windows() {
+    let dtb = dma_tree(0x4000_0000).build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let nic = index.node_at_path("/soc/bus/nic").unwrap();
+    let limit = dma_limit(&nic).unwrap().unwrap();
+    assert_eq!(
+        limit,
+        DmaLimit {
+            max_address: 0x2fff_ffff,
+            cpu_offset: 0x7000_0000
+        }
+    );
+    assert_eq!(limit.mask(), 0x3fff_ffff);
+    assert_eq!(limit.bits(), 30);
+    assert_eq!(limit.to_cpu(0x1000_0000), 0x8000_0000);
+
+    let uart = index.node_at_path("/soc/uart").unwrap();
+    let limit = dma_limit(&uart).unwrap().unwrap();
+    assert_eq!(limit.max_address, 0x3fff_ffff);
+    assert_eq!(limit.to_cpu(0), 0x8000_0000);
+
+    let soc = index.node_at_path("/soc").unwrap();
+    assert_eq!(dma_limit(&soc).unwrap().unwrap().mask(), u64::MAX);
+}
+
+#[test]
+fn dma_limit_is_bounded_by_upstream_bus() {
+    let dtb = dma_tree(0x800_0000).build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let nic = index.node_at_path("/soc/bus/nic").unwrap();
+    let limit = dma_limit(&nic).unwrap().unwrap();
+    assert_eq!(limit.max_address, 0x17ff_ffff);
+    assert_eq!(limit.mask(), 0x1fff_ffff);
+}
+
+#[test]
+fn reg_rejects_overflowing_cell_counts() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[0xffff_ffff]))
+        .prop("#size-cells", &cells(&[1]))
+        .begin_node("uart")
+        .prop("reg", &cells(&[0, 0x100]))
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let uart = index.node_at_path("/uart").unwrap();
+    assert_eq!(uart.reg().unwrap().next(), Err(DevTreeError::ParseError));
+    let base = uart.to_base();
+    assert_eq!(base.reg().unwrap().next(), Err(DevTreeError::ParseError));
+}
+
+#[test]
+fn bus_entries_reject_overflowing_cell_counts() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .begin_node("soc")
+        .prop("#address-cells", &cells(&[0xffff_ffff]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("ranges", &cells(&[0, 0, 0x1000]))
+        .begin_node("uart")
+        .prop("reg", &cells(&[0, 0x100]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let soc = index.node_at_path("/soc").unwrap();
+    let mut ranges = bus_ranges(&soc).unwrap().unwrap();
+    assert_eq!(ranges.next().err(), Some(DevTreeError::ParseError));
+    let uart = index.node_at_path("/soc/uart").unwrap();
+    let mut reg = bus_reg(&uart).unwrap().unwrap();
+    assert_eq!(reg.next().err(), Some(DevTreeError::ParseError));
+}
+
+#[test]
+fn dma_limit_rejects_overflowing_cell_counts() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[0x8000_0000]))
+        .begin_node("soc")
+        .prop("#address-cells", &cells(&[0x8000_0000]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("dma-ranges", &cells(&[0, 0, 0x1000]))
+        .begin_node("nic")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let nic = index.node_at_path("/soc/nic").unwrap();
+    assert_eq!(dma_limit(&nic), Err(DevTreeError::ParseError));
+
+    let limit = DmaLimit {
+        max_address: 0,
+        cpu_offset: 0,
+    };
+    assert_eq!(limit.mask(), 0);
+    assert_eq!(limit.bits(), 0);
+}
+
+#[test]
+fn bus_addresses_decode_space_flags() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[2]))
+        .prop("#size-cells", &cells(&[2]))
+        .begin_node("pci")
+        .prop("device_type", b"pci\0")
+        .prop("#address-cells", &cells(&[3]))
+        .prop("#size-cells", &cells(&[2]))
+        .prop(
+            "ranges",
+            &cells(&[
+                0x4300_0000, 0x1, 0x0, 0x1, 0x0, 0x0, 0x4000_0000, // prefetchable 64-bit
+            ]),
+        )
+        .begin_node("isa")
+        .prop("device_type", b"isa\0")
+        .prop("#address-cells", &cells(&[2]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("reg", &cells(&[0x0000_0800, 0x0, 0x0, 0x0, 0x0]))
+        .begin_node("serial")
+        .prop("reg", &cells(&[0x1, 0x3f8, 0x8]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let pci = index.node_at_path("/pci").unwrap();
+    assert_eq!(BusKind::of(&pci), BusKind::Pci);
+    let mut ranges = bus_ranges(&pci).unwrap().unwrap();
+    let range = ranges.next().unwrap().unwrap();
+    let flags = match range.child.flags {
+        Some(SpaceFlags::Pci(flags)) => flags,
+        other => panic!("unexpected flags {:?}", other),
+    };
+    assert_eq!(flags.space(), PciSpace::Memory64);
+    assert!(flags.contains(PciSpaceFlags::PREFETCHABLE));
+    assert!(!flags.contains(PciSpaceFlags::NON_RELOCATABLE));
+    assert_eq!(range.child.address, 0x1_0000_0000);
+    assert_eq!(range.parent.flags, None);
+    assert_eq!(range.parent.address, 0x1_0000_0000);
+    assert_eq!(range.size, 0x4000_0000);
+    assert!(ranges.next().unwrap().is_none());
+
+    // The bridge's reg is a PCI config space address (device 1).
+    let isa = index.node_at_path("/pci/isa").unwrap();
+    let reg = bus_reg(&isa).unwrap().unwrap().next().unwrap().unwrap();
+    match reg.address.flags {
+        Some(SpaceFlags::Pci(flags)) => assert_eq!(flags.space(), PciSpace::Config),
+        other => panic!("unexpected flags {:?}", other),
+    }
+
+    let serial = index.node_at_path("/pci/isa/serial").unwrap();
+    let reg = bus_reg(&serial).unwrap().unwrap().next().unwrap().unwrap();
+    assert_eq!(
+        reg.address.flags,
+        Some(SpaceFlags::Isa(IsaSpaceFlags::IO))
+    );
+    assert_eq!((reg.address.address, reg.size), (0x3f8, 0x8));
+    assert!(bus_ranges(&serial).unwrap().is_none());
+}
+
+#[test]
+fn empty_ranges_translate_as_identity() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .begin_node("soc")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("ranges", &[])
+        .begin_node("bus")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("ranges", &cells(&[0x0, 0x4000_0000, 0x1000]))
+        .begin_node("uart@100")
+        .prop("reg", &cells(&[0x100, 0x100]))
+        .end_node()
+        .end_node()
+        .begin_node("timer@1000")
+        .prop("reg", &cells(&[0x1000, 0x100]))
+        .end_node()
+        .begin_node("i2c")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[0]))
+        .begin_node("eeprom@50")
+        .prop("reg", &cells(&[0x50]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    assert!(index.root().is_translatable());
+    assert!(index.node_at_path("/soc").unwrap().is_translatable());
+    assert!(index.node_at_path("/soc/bus").unwrap().is_translatable());
+    assert!(!index.node_at_path("/soc/i2c").unwrap().is_translatable());
+
+    let soc = index.node_at_path("/soc").unwrap();
+    assert_eq!(translate_address(&soc, 0x1234).unwrap(), Some(0x1234));
+    let timer = index.node_at_path("/soc/timer@1000").unwrap();
+    assert_eq!(translate_address(&timer, 0x1000).unwrap(), Some(0x1000));
+
+    let uart = index.node_at_path("/soc/bus/uart@100").unwrap();
+    assert_eq!(translate_address(&uart, 0x100).unwrap(), Some(0x4000_0100));
+    // Addresses outside of every window cannot be translated.
+    assert_eq!(translate_address(&uart, 0x1000).unwrap(), None);
+
+    // A bus without `ranges` does not forward addresses.
+    let eeprom = index.node_at_path("/soc/i2c/eeprom@50").unwrap();
+    assert_eq!(translate_address(&eeprom, 0x50).unwrap(), None);
+}
+
+#[test]
+fn nested_ranges_translate_through_each_bus() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[2]))
+        .prop("#size-cells", &cells(&[2]))
+        .begin_node("soc")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        // Two windows: 0x0 -> 0x1_0000_0000 and 0x8000_0000 -> 0x2_0000_0000.
+        .prop(
+            "ranges",
+            &cells(&[0x0, 0x1, 0x0, 0x1000_0000, 0x8000_0000, 0x2, 0x0, 0x1000_0000]),
+        )
+        .begin_node("bridge@8000000")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[1]))
+        .prop("reg", &cells(&[0x800_0000, 0x1000]))
+        .prop("ranges", &cells(&[0x0, 0x8000_1000, 0x1000]))
+        .begin_node("gpio@40")
+        .prop("reg", &cells(&[0x40, 0x20]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let bridge = index.node_at_path("/soc/bridge").unwrap();
+    assert_eq!(bridge.translate_address(0x800_0000).unwrap(), Some(0x1_0800_0000));
+
+    // The bridge maps into the second window of `soc`, which maps into the root's space.
+    let gpio = index.node_at_path("/soc/bridge/gpio@40").unwrap();
+    let (address, _) = gpio.reg().unwrap().next().unwrap().unwrap();
+    assert_eq!(gpio.translate_address(address).unwrap(), Some(0x2_0000_1040));
+    assert_eq!(gpio.translate_address(0x1000).unwrap(), None);
+}
+
+#[test]
+fn interrupts_are_decoded_with_the_parent_cells() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("gic@1000")
+        .prop("interrupt-controller", &[])
+        .prop("#interrupt-cells", &cells(&[3]))
+        .prop("phandle", &cells(&[1]))
+        .end_node()
+        .begin_node("soc")
+        .prop("interrupt-parent", &cells(&[1]))
+        .begin_node("intc@2000")
+        .prop("interrupt-controller", &[])
+        .prop("#interrupt-cells", &cells(&[1]))
+        .prop("linux,phandle", &cells(&[2]))
+        .prop("interrupts", &cells(&[0, 40, 4]))
+        .end_node()
+        .begin_node("timer@3000")
+        .prop("interrupts", &cells(&[1, 13, 8, 1, 14, 8]))
+        .end_node()
+        .begin_node("uart@4000")
+        .prop("interrupt-parent", &cells(&[2]))
+        .prop("interrupts", &cells(&[5]))
+        .end_node()
+        .begin_node("broken@5000")
+        .prop("interrupt-parent", &cells(&[7]))
+        .prop("interrupts", &cells(&[5]))
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    assert_eq!(node_by_phandle(&index, 2).unwrap().unwrap().name(), Ok("intc@2000"));
+    assert!(node_by_phandle(&index, 3).unwrap().is_none());
+    assert!(interrupt_parent(&index.root()).unwrap().is_none());
+
+    // Inherited from `soc`, which is not an interrupt controller itself.
+    let timer = index.node_at_path("/soc/timer").unwrap();
+    let mut irqs = interrupts(&timer).unwrap().unwrap();
+    assert_eq!(irqs.parent().name(), Ok("gic@1000"));
+    assert_eq!(irqs.next().unwrap().unwrap().cells(), &[1, 13, 8]);
+    assert_eq!(irqs.next().unwrap().unwrap().cells(), &[1, 14, 8]);
+    assert!(irqs.next().unwrap().is_none());
+
+    // A controller's own interrupts are routed to its interrupt parent.
+    let intc = index.node_at_path("/soc/intc").unwrap();
+    let mut irqs = interrupts(&intc).unwrap().unwrap();
+    assert_eq!(irqs.parent().name(), Ok("gic@1000"));
+    assert_eq!(irqs.next().unwrap().unwrap().cells(), &[0, 40, 4]);
+
+    let uart = index.node_at_path("/soc/uart").unwrap();
+    let mut irqs = interrupts(&uart).unwrap().unwrap();
+    assert_eq!(irqs.parent().name(), Ok("intc@2000"));
+    assert_eq!(irqs.next().unwrap().unwrap().cells(), &[5]);
+
+    let soc = index.node_at_path("/soc").unwrap();
+    assert!(interrupts(&soc).unwrap().is_none());
+    let broken = index.node_at_path("/soc/broken").unwrap();
+    assert_eq!(interrupt_parent(&broken).err(), Some(DevTreeError::ParseError));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn memory_nodes_report_hotplug_and_numa() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("#address-cells", &cells(&[2]))
+        .prop("#size-cells", &cells(&[1]))
+        .begin_node("memory@80000000")
+        .prop("device_type", b"memory\0")
+        .prop("numa-node-id", &cells(&[0]))
+        .prop("reg", &cells(&[0x0, 0x8000_0000, 0x1000, 0x1, 0x0, 0x2000]))
+        .end_node()
+        .begin_node("chosen")
+        .end_node()
+        .begin_node("memory@200000000")
+        .prop("device_type", b"memory\0")
+        .prop("numa-node-id", &cells(&[1]))
+        .prop("hotpluggable", b"")
+        .prop("initial-mapped-area", &cells(&[0xffff_ffff, 0xc000_0000, 0x2, 0x0, 0x1000]))
+        .prop("reg", &cells(&[0x2, 0x0, 0x4000]))
+        .end_node()
+        .begin_node("memory@300000000")
+        .prop("device_type", b"memory\0")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let mut nodes = memory_nodes(&index).unwrap();
+    let first = nodes.next().unwrap().unwrap();
+    assert_eq!(first.node.name(), Ok("memory@80000000"));
+    assert!(!first.hotpluggable);
+    assert_eq!(first.numa_node_id, Some(0));
+    assert_eq!(first.initial_mapped_area, None);
+    let regions: Vec<_> = first.regions().collect().unwrap();
+    assert_eq!(regions, [(0x8000_0000, 0x1000), (0x1_0000_0000, 0x2000)]);
+
+    let second = nodes.next().unwrap().unwrap();
+    assert_eq!(second.node.name(), Ok("memory@200000000"));
+    assert!(second.hotpluggable);
+    assert_eq!(second.numa_node_id, Some(1));
+    let area = second.initial_mapped_area.unwrap();
+    assert_eq!(area.effective_address, 0xffff_ffff_c000_0000);
+    assert_eq!(area.physical_address, 0x2_0000_0000);
+    assert_eq!(area.size, 0x1000);
+    let regions: Vec<_> = second.regions().collect().unwrap();
+    assert_eq!(regions, [(0x2_0000_0000, 0x4000)]);
+
+    // The node without a reg property describes no memory.
+    assert!(nodes.next().unwrap().is_none());
+}
+
+#[test]
+fn chosen_parameters_are_read_from_either_backend() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("chosen")
+        .prop("bootargs", b"console=ttyS0\0")
+        .prop("stdout-path", b"serial0:115200n8\0")
+        .prop("linux,initrd-start", &cells(&[0x8200_0000]))
+        .prop("linux,initrd-end", &cells(&[0x1, 0x0]))
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let chosen = index.chosen().unwrap();
+    assert_eq!(chosen.bootargs, Some("console=ttyS0"));
+    assert_eq!(chosen.stdout_path, Some("serial0:115200n8"));
+    assert_eq!(chosen.initrd, Some((0x8200_0000, 0x1_0000_0000)));
+    assert_eq!(dtb.devtree().unwrap().chosen().unwrap(), chosen);
+
+    // An initrd address of three cells is malformed.
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("chosen")
+        .prop("linux,initrd-start", &cells(&[0, 0, 0]))
+        .prop("linux,initrd-end", &cells(&[0]))
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    assert_eq!(
+        dtb.devtree().unwrap().chosen(),
+        Err(DevTreeError::ParseError)
+    );
+
+    // A tree without a chosen node passes no parameters.
+    let dtb = simple_tree().build();
+    assert_eq!(dtb.devtree().unwrap().chosen(), Ok(Chosen::default()));
+}
+
+#[test]
+fn stdout_path_resolves_aliases_and_options() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("aliases")
+        .prop("serial0", b"/soc/uart@1000\0")
+        .end_node()
+        .begin_node("chosen")
+        .prop("stdout-path", b"serial0:115200n8\0")
+        .end_node()
+        .begin_node("soc")
+        .begin_node("uart@1000")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    let (uart, options) = devtree.stdout().unwrap().unwrap();
+    assert_eq!(uart.name().unwrap(), "uart@1000");
+    assert_eq!(options, Some("115200n8"));
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let (uart, options) = index.stdout().unwrap().unwrap();
+    assert_eq!(uart.name().unwrap(), "uart@1000");
+    assert_eq!(options, Some("115200n8"));
+
+    // The legacy property is used in the absence of stdout-path, and a path naming no node
+    // resolves to nothing.
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("chosen")
+        .prop("linux,stdout-path", b"/missing\0")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.chosen().unwrap().stdout(), Some(("/missing", None)));
+    assert!(devtree.stdout().unwrap().is_none());
+}
+
+#[test]
+fn cpu_enable_methods() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("psci")
+        .prop("compatible", b"arm,psci-1.0\0arm,psci-0.2\0")
+        .prop("method", b"hvc\0")
+        .end_node()
+        .begin_node("cpus")
+        .begin_node("cpu@0")
+        .prop("enable-method", b"psci\0")
+        .end_node()
+        .begin_node("cpu@1")
+        .prop("enable-method", b"spin-table\0")
+        .prop("cpu-release-addr", &cells(&[0x0, 0x8000_fff8]))
+        .end_node()
+        .begin_node("cpu@2")
+        .prop("enable-method", b"vendor,boot\0spin-table\0")
+        .prop("cpu-release-addr", &cells(&[0x1000]))
+        .end_node()
+        .begin_node("cpu@3")
+        .prop("enable-method", b"vendor,boot\0")
+        .end_node()
+        .begin_node("cpu@4")
+        .end_node()
+        .begin_node("cpu@5")
+        .prop("enable-method", b"spin-table\0")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let method = |path: &str| enable_method(&index.node_at_path(path).unwrap());
+    assert_eq!(method("/cpus/cpu@0"), Ok(Some(EnableMethod::Psci)));
+    assert_eq!(
+        method("/cpus/cpu@1"),
+        Ok(Some(EnableMethod::SpinTable {
+            release_addr: 0x8000_fff8
+        }))
+    );
+    assert_eq!(
+        method("/cpus/cpu@2"),
+        Ok(Some(EnableMethod::SpinTable {
+            release_addr: 0x1000
+        }))
+    );
+    assert_eq!(
+        method("/cpus/cpu@3"),
+        Ok(Some(EnableMethod::Unknown("vendor,boot")))
+    );
+    assert_eq!(method("/cpus/cpu@4"), Ok(None));
+    // A spin-table requires a release address.
+    assert_eq!(method("/cpus/cpu@5"), Err(DevTreeError::ParseError));
+
+    assert_eq!(
+        psci(&index),
+        Ok(Some(Psci {
+            conduit: PsciConduit::Hvc,
+            cpu_on: None
+        }))
+    );
+}
+
+#[test]
+fn binding_flags_decode_to_enums() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("cpus")
+        .begin_node("cpu@0")
+        .prop("cache-unified", b"")
+        .end_node()
+        .begin_node("cpu@1")
+        .end_node()
+        .end_node()
+        .begin_node("soc")
+        .prop("dma-coherent", b"")
+        .begin_node("inherits")
+        .end_node()
+        .begin_node("overrides")
+        .prop("dma-noncoherent", b"")
+        .end_node()
+        .begin_node("conflicting")
+        .prop("dma-coherent", b"")
+        .prop("dma-noncoherent", b"")
+        .end_node()
+        .end_node()
+        .begin_node("unspecified")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let node = |path: &str| index.node_at_path(path).unwrap();
+
+    assert_eq!(cache_organization(&node("/cpus/cpu@0")), CacheOrganization::Unified);
+    assert_eq!(cache_organization(&node("/cpus/cpu@1")), CacheOrganization::Split);
+
+    assert_eq!(dma_coherence(&node("/soc/inherits")), Ok(Some(DmaCoherence::Coherent)));
+    assert_eq!(dma_coherence(&node("/soc/overrides")), Ok(Some(DmaCoherence::NonCoherent)));
+    assert_eq!(dma_coherence(&node("/soc/conflicting")), Err(DevTreeError::ParseError));
+    assert_eq!(dma_coherence(&node("/unspecified")), Ok(None));
+}
+
+#[cfg(feature = "bindings-riscv")]
+#[test]
+fn riscv_bindings_decode_to_enums() {
+    use fdt_rs::util::riscv::{isa_extensions, mmu_type, IsaExtensions, MmuType};
+
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("cpus")
+        .begin_node("cpu@0")
+        .prop("mmu-type", b"riscv,sv39\0")
+        .prop("riscv,isa", b"rv64imac\0")
+        .prop("riscv,isa-extensions", b"i\0m\0zicsr\0zbb\0xvendor\0")
+        .end_node()
+        .begin_node("cpu@1")
+        .prop("riscv,isa", b"rv64gcv_zicsr_zba_svpbmt_xvendor\0")
+        .end_node()
+        .begin_node("cpu@2")
+        .prop("mmu-type", b"riscv,sv99\0")
+        .prop("riscv,isa", b"x86\0")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let node = |path: &str| index.node_at_path(path).unwrap();
+
+    assert_eq!(mmu_type(&node("/cpus/cpu@0")), Ok(Some(MmuType::Sv39)));
+    assert_eq!(mmu_type(&node("/cpus/cpu@1")), Ok(None));
+    assert_eq!(mmu_type(&node("/cpus/cpu@2")), Err(DevTreeError::ParseError));
+
+    // riscv,isa-extensions takes precedence over riscv,isa.
+    assert_eq!(
+        isa_extensions(&node("/cpus/cpu@0")),
+        Ok(Some(
+            IsaExtensions::I | IsaExtensions::M | IsaExtensions::ZICSR | IsaExtensions::ZBB
+        ))
+    );
+    let extensions = isa_extensions(&node("/cpus/cpu@1")).unwrap().unwrap();
+    for extension in &[
+        IsaExtensions::I,
+        IsaExtensions::M,
+        IsaExtensions::A,
+        IsaExtensions::F,
+        IsaExtensions::D,
+        IsaExtensions::C,
+        IsaExtensions::V,
+        IsaExtensions::ZICSR,
+        IsaExtensions::ZIFENCEI,
+        IsaExtensions::ZBA,
+        IsaExtensions::SVPBMT,
+    ] {
+        assert!(extensions.contains(*extension));
+    }
+    assert!(!extensions.contains(IsaExtensions::H));
+    assert_eq!(
+        isa_extensions(&node("/cpus/cpu@2")),
+        Err(DevTreeError::ParseError)
+    );
+    assert_eq!(isa_extensions(&node("/cpus")), Ok(None));
+}
+
+#[cfg(feature = "bindings-arm")]
+#[test]
+fn arm_gic_interrupts_decode_to_enums() {
+    use fdt_rs::util::arm::{gic_interrupts, GicInterrupt, GicInterruptKind, IrqTrigger};
+
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("timer")
+        .prop("interrupts", &cells(&[1, 13, 0xf08, 0, 33, 4]))
+        .end_node()
+        .begin_node("bad")
+        .prop("interrupts", &cells(&[0, 1, 3]))
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let timer = index.node_at_path("/timer").unwrap();
+    let interrupts: Vec<_> = gic_interrupts(&timer.prop("interrupts").unwrap())
+        .collect()
+        .unwrap();
+    assert_eq!(
+        interrupts,
+        [
+            GicInterrupt {
+                kind: GicInterruptKind::Ppi,
+                number: 13,
+                trigger: IrqTrigger::LevelLow,
+                cpu_mask: 0xf,
+            },
+            GicInterrupt {
+                kind: GicInterruptKind::Spi,
+                number: 33,
+                trigger: IrqTrigger::LevelHigh,
+                cpu_mask: 0,
+            },
+        ]
+    );
+
+    // Both edges is not a valid trigger.
+    let bad = index.node_at_path("/bad").unwrap();
+    let prop = bad.prop("interrupts").unwrap();
+    let mut interrupts = gic_interrupts(&prop);
+    assert_eq!(interrupts.next(), Err(DevTreeError::ParseError));
+}
+
+/// Decode `(child, parent, size)` entries of a `ranges` property with 1, 2 and 1 cells.
+fn decode_ranges<'dt, P: PropReader<'dt>>(ranges: &P) -> Vec<(u32, u64, u32)> {
+    ranges
+        .subviews(16)
+        .map(|entry| {
+            (
+                entry.u32(0).unwrap(),
+                entry.u64(4).unwrap(),
+                entry.u32(12).unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn prop_subviews_decode_entries() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("bus")
+        .prop(
+            "ranges",
+            &cells(&[0x0, 0x1, 0x4000_0000, 0x1000, 0x2000, 0x0, 0x5000, 0x100, 0xdead]),
+        )
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let expected = vec![(0x0, 0x1_4000_0000, 0x1000), (0x2000, 0x5000, 0x100)];
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let ranges = index.props().find(|p| p.name() == Ok("ranges")).unwrap();
+    assert_eq!(decode_ranges(&ranges), expected);
+
+    let devtree = dtb.devtree().unwrap();
+    let base_ranges = devtree
+        .props()
+        .find(|p| Ok(p.name()? == "ranges"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(decode_ranges(&base_ranges), expected);
+
+    // Views are bounds checked, and reads within them are relative to the view.
+    let second = ranges.subview(16, 16).unwrap();
+    assert_eq!(second.length(), 16);
+    assert_eq!(second.name().unwrap(), "ranges");
+    assert_eq!(second.node().name().unwrap(), "bus");
+    assert_eq!(second.u32(12), Ok(0x100));
+    assert_eq!(second.u32(16), Err(DevTreeError::InvalidOffset));
+    let parent = second.subview(4, 8).unwrap();
+    assert_eq!(parent.u64(0), Ok(0x5000));
+    assert!(second.subview(12, 8).is_err());
+    assert!(ranges.subview(usize::MAX, 2).is_err());
+}
+
+#[test]
+fn helper_errors_report_their_node() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("soc")
+        .begin_node("dev@0")
+        .prop("interrupts", &cells(&[1]))
+        .prop("short", &[0x12])
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let dev = index.node_at_path("/soc/dev@0").unwrap();
+    assert_eq!(dev.path().unwrap().to_string(), "/soc/dev@0");
+
+    // No ancestor is an interrupt controller.
+    let err = interrupts(&dev).node_context(&dev).err().unwrap();
+    assert_eq!(err.error, DevTreeError::ParseError);
+    assert_eq!(err.path.unwrap().components(), &["soc", "dev@0"]);
+    assert_eq!(err.prop, None);
+    assert!(err.to_string().starts_with("/soc/dev@0: "));
+
+    let short = dev.prop("short").unwrap();
+    let err = short.u32(0).prop_context(&short).unwrap_err();
+    assert_eq!(err.prop, Some("short"));
+    assert!(err.to_string().starts_with("/soc/dev@0: short: Invalid offset"));
+    assert_eq!(DevTreeError::from(err), DevTreeError::InvalidOffset);
+
+    // Nodes nested too deeply for a path are still reported.
+    let dtb = nested_tree(MAX_CANONICAL_DEPTH + 2).build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let deepest = index.nodes().last().unwrap();
+    assert_eq!(deepest.path().err(), Some(DevTreeError::DepthLimitExceeded));
+    let err = Err::<(), _>(DevTreeError::ParseError)
+        .node_context(&deepest)
+        .unwrap_err();
+    assert_eq!(err.path, None);
+    assert!(err.to_string().starts_with("<unknown node>: "));
+}
+
+#[test]
+fn props_report_their_node_path() {
+    // Paths deeper than a canonical path may still be written.
+    let depth = MAX_CANONICAL_DEPTH + 1;
+    let mut blob = Blob::default();
+    blob.begin_node("");
+    for _ in 0..depth {
+        blob.begin_node("nested");
+    }
+    blob.prop("leaf", &[]);
+    for _ in 0..=depth {
+        blob.end_node();
+    }
+    blob.end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let leaf = index.props().find(|prop| prop.name() == Ok("leaf")).unwrap();
+    assert_eq!(leaf.node_name().unwrap(), "nested");
+    let mut path = String::new();
+    leaf.node_path_write(&mut path).unwrap();
+    assert_eq!(path, "/nested".repeat(depth));
+
+    let mut blob = Blob::default();
+    blob.begin_node("").prop("model", b"m\0").end_node().end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let model = index.root().prop("model").unwrap();
+    assert_eq!(model.node_name().unwrap(), "");
+    let mut path = String::new();
+    model.node_path_write(&mut path).unwrap();
+    assert_eq!(path, "/");
+}
+
+#[test]
+fn string_lists_iterate_without_a_maximum_count() {
+    let many: Vec<u8> = (0..100).flat_map(|_| b"abc\0".iter().copied()).collect();
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"vendor,dev\0\0generic\0")
+        .prop("many", &many)
+        .prop("empty", &[])
+        .prop("unterminated", b"one\0two")
+        .prop("invalid", b"one\0\xff\0three\0")
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let root = index.root();
+
+    let strs = |name| root.prop(name).unwrap().iter_str().collect::<Vec<_>>();
+    assert_eq!(strs("compatible"), [Ok("vendor,dev"), Ok(""), Ok("generic")]);
+    assert_eq!(strs("many").len(), 100);
+    assert!(strs("empty").is_empty());
+    assert_eq!(strs("unterminated"), [Ok("one"), Err(DevTreeError::ParseError)]);
+    let invalid = strs("invalid");
+    assert_eq!(invalid.len(), 2);
+    assert!(matches!(invalid[1], Err(DevTreeError::StrError(_))));
+
+    // Agrees with the fixed slice API.
+    let compatible = root.prop("compatible").unwrap();
+    let mut list = [None; 3];
+    assert_eq!(compatible.strlist(&mut list), Ok(3));
+    assert!(list.iter().map(|s| Ok(s.unwrap())).eq(compatible.iter_str()));
+    assert_eq!(compatible.strlist(&mut [None; 2]), Err(DevTreeError::NotEnoughMemory));
+    assert_eq!(compatible.str_count(), Ok(3));
+    assert_eq!(compatible.str_at(11), Ok(""));
+    assert_eq!(compatible.str_at(12), Ok("generic"));
+
+    let devtree = dtb.devtree().unwrap();
+    let base_root = devtree.root().unwrap().unwrap();
+    let base = base_root.prop("compatible").unwrap().unwrap();
+    assert!(base.iter_str().eq(compatible.iter_str()));
+}
+
+#[test]
+#[cfg(feature = "writer")]
+fn string_lists_round_trip_through_the_encoder() {
+    let strs = ["vendor,dev", "", "generic"];
+    let mut value = [0xffu8; 32];
+    let len = write_strlist(&mut value, strs.iter().copied()).unwrap();
+    assert_eq!(len, strlist_len(strs.iter().copied()));
+    assert_eq!(&value[..len], b"vendor,dev\0\0generic\0");
+    assert_eq!(validate_strlist(&value[..len]), Ok(3));
+
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", &value[..len])
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    let root = devtree.root().unwrap().unwrap();
+    let compatible = root.prop("compatible").unwrap().unwrap();
+    assert!(compatible.iter_str().eq(strs.iter().map(|s| Ok(*s))));
+
+    // Rejected lists leave the buffer untouched.
+    let mut small = [0xffu8; 8];
+    assert!(write_strlist(&mut small, strs.iter().copied()).is_err());
+    assert!(write_strlist(&mut small, ["a\0b"].iter().copied()).is_err());
+    assert_eq!(small, [0xff; 8]);
+    assert_eq!(write_strlist(&mut small, core::iter::empty()), Ok(0));
+
+    assert_eq!(validate_strlist(b""), Ok(0));
+    assert_eq!(validate_strlist(b"one\0two"), Err(DevTreeError::ParseError));
+    assert!(matches!(validate_strlist(b"\xff\0"), Err(DevTreeError::StrError(_))));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn dts_output_escapes_and_formats_values() {
+    let mut blob = Blob::default();
+    blob.reservations.push((0x8000_0000, 0x1000));
+    blob.begin_node("")
+        .prop("model", b"say \"hi\"\\\n\0")
+        .prop("compatible", b"vendor,board\0generic\0")
+        .begin_node("node@10")
+        .prop("reg", &cells(&[0x10, 0x20]))
+        .token(FdtTok::Nop as u32)
+        .prop("mac", &[0x02, 0x00, 0xff])
+        .prop("empty-string", b"\0")
+        .prop("flag", b"")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+
+    let expected = "/dts-v1/;\n\
+                    \n\
+                    /memreserve/ 0x0000000080000000 0x0000000000001000;\n\
+                    \n\
+                    / {\n\
+                    \tmodel = \"say \\\"hi\\\"\\\\\\n\";\n\
+                    \tcompatible = \"vendor,board\", \"generic\";\n\
+                    \n\
+                    \tnode@10 {\n\
+                    \t\treg = <0x10 0x20>;\n\
+                    \t\tmac = [02 00 ff];\n\
+                    \t\tempty-string = [00];\n\
+                    \t\tflag;\n\
+                    \t};\n\
+                    };\n";
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.to_dts().unwrap(), expected);
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let mut out = String::new();
+    index.write_dts(&mut out).unwrap();
+    assert_eq!(out, expected);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serialized_values_follow_their_apparent_type() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("model", b"board\0")
+        .prop("compatible", b"vendor,board\0generic\0")
+        .begin_node("node@10")
+        .prop("reg", &cells(&[0x10, 0x20]))
+        .prop("mac", &[0x02, 0x00, 0xff])
+        .prop("flag", b"")
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let expected = serde_json::json!({
+        "model": "board",
+        "compatible": ["vendor,board", "generic"],
+        "node@10": {
+            "reg": [0x10, 0x20],
+            "mac": [0x02, 0x00, 0xff],
+            "flag": true,
+        },
+    });
+    assert_eq!(serde_json::to_value(&index).unwrap(), expected);
+
+    // Properties precede child nodes within each map.
+    let json = serde_json::to_string(&index.root()).unwrap();
+    assert!(json.starts_with("{\"model\":\"board\",\"compatible\""));
+}
+
+#[test]
+fn cell_iterators_ignore_trailing_bytes() {
+    let mut value = cells(&[0x1, 0x2, 0x3, 0x4, 0x5]);
+    value.extend_from_slice(&[0xff, 0xff]);
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("array", &value)
+        .prop("empty", &[])
+        .end_node()
+        .end();
+    let dtb = blob.build();
+
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+    let array = index.root().prop("array").unwrap();
+    assert_eq!(array.iter_u32().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+    assert_eq!(array.iter_u32().len(), 5);
+    assert_eq!(array.iter_u64().collect::<Vec<_>>(), [0x1_0000_0002, 0x3_0000_0004]);
+    let single: Vec<_> = array.iter_cells(1).unwrap().collect();
+    assert_eq!(single, [1, 2, 3, 4, 5]);
+    assert!(array.iter_cells(0).is_err());
+    assert!(array.iter_cells(3).is_err());
+
+    let empty = index.root().prop("empty").unwrap();
+    assert_eq!(empty.iter_u32().next(), None);
+    assert_eq!(empty.iter_u64().next(), None);
+
+    let devtree = dtb.devtree().unwrap();
+    let root = devtree.root().unwrap().unwrap();
+    let base_array = root.prop("array").unwrap().unwrap();
+    assert!(base_array.iter_u32().eq(array.iter_u32()));
+    assert!(base_array.iter_u64().eq(array.iter_u64()));
+}
+
+#[test]
+fn large_props_are_found_and_chunked() {
+    let firmware: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"test\0")
+        .begin_node("firmware")
+        .prop("edid", &[0xab; 128])
+        .prop("payload", &firmware)
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let large: Vec<_> = index.props_larger_than(4096).collect();
+    assert_eq!(large.len(), 1);
+    let payload = &large[0];
+    assert_eq!(payload.name(), Ok("payload"));
+    assert_eq!(payload.node().name(), Ok("firmware"));
+
+    let offset = payload.value_offset();
+    assert_eq!(&dtb.bytes()[offset..offset + firmware.len()], firmware.as_slice());
+
+    let chunks: Vec<_> = payload.raw_chunks(1024).collect();
+    assert_eq!(chunks.len(), 5);
+    assert!(chunks[..4].iter().all(|chunk| chunk.len() == 1024));
+    assert_eq!(chunks[4].len(), 5000 - 4 * 1024);
+    assert_eq!(chunks.concat(), firmware);
+
+    let names: Vec<_> = index
+        .props_larger_than(64)
+        .map(|prop| prop.name().unwrap())
+        .collect();
+    assert_eq!(names, ["edid", "payload"]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn missing_end_token_follows_policy() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .prop("compatible", b"test\0")
+        .begin_node("child")
+        .end_node()
+        .end_node();
+    let dtb = blob.build();
+
+    let strict = dtb.devtree().unwrap();
+    assert_eq!(strict.missing_end_policy(), MissingEndPolicy::Strict);
+    assert_eq!(strict.validate(), Err(DevTreeError::MissingEndToken));
+    assert_eq!(
+        strict.nodes().count().map(|_| ()),
+        Err(DevTreeError::MissingEndToken)
+    );
+
+    let permissive = strict.with_missing_end_policy(MissingEndPolicy::Permissive);
+    permissive.validate().unwrap();
+    let names: Vec<_> = permissive.nodes().map(|n| n.name()).collect().unwrap();
+    assert_eq!(names, ["", "child"]);
+
+    let size = DevTreeIndex::required_buffer_size(&permissive).unwrap();
+    let mut buf = vec![0u8; size];
+    let index = DevTreeIndex::new(permissive, &mut buf).unwrap();
+    assert_eq!(index.nodes().count(), 2);
+    let index = unsafe { DevTreeIndex::new_unchecked(permissive, &mut buf) }.unwrap();
+    assert_eq!(index.nodes().count(), 2);
+    assert_eq!(index.props().count(), 1);
+
+    // Trees with their end token parse identically under either policy.
+    let dtb = simple_tree().build();
+    let permissive = dtb
+        .devtree()
+        .unwrap()
+        .with_missing_end_policy(MissingEndPolicy::Permissive);
+    permissive.validate().unwrap();
+    assert_eq!(permissive.nodes().count().unwrap(), 2);
+}
+
+#[test]
+fn aliases_resolve_in_path_lookups() {
+    let mut blob = Blob::default();
+    blob.begin_node("")
+        .begin_node("aliases")
+        .prop("serial0", b"/soc/uart@1000\0")
+        .prop("bus", b"/soc\0")
+        .prop("dangling", b"/soc/missing\0")
+        .prop("relative", b"soc\0")
+        .prop("malformed", b"/soc")
+        .end_node()
+        .begin_node("soc")
+        .begin_node("uart@1000")
+        .end_node()
+        .begin_node("uart@2000")
+        .end_node()
+        .end_node()
+        .end_node()
+        .end();
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    assert_eq!(index.resolve_alias("serial0"), Ok(Some("/soc/uart@1000")));
+    assert_eq!(devtree.resolve_alias("serial0"), Ok(Some("/soc/uart@1000")));
+    assert_eq!(index.resolve_alias("serial1"), Ok(None));
+    assert_eq!(devtree.resolve_alias("serial1"), Ok(None));
+    // Both backends report an alias whose value is not a string.
+    assert!(index.resolve_alias("malformed").is_err());
+    assert!(devtree.resolve_alias("malformed").is_err());
+    assert!(index.node_at_path("malformed").is_none());
+    assert!(devtree.node_at_path("malformed").is_err());
+
+    for (path, expected) in [
+        ("serial0", Some("uart@1000")),
+        ("bus/uart@2000", Some("uart@2000")),
+        ("bus/uart", Some("uart@1000")),
+        ("/soc/uart@2000", Some("uart@2000")),
+        ("serial1", None),
+        ("dangling", None),
+        ("relative", None),
+        ("serial0/missing", None),
+    ]
+    .iter()
+    {
+        let from_index = index.node_at_path(path).map(|node| node.name().unwrap());
+        let node = devtree.node_at_path(path).unwrap();
+        let from_base = node.as_ref().map(|node| node.name().unwrap());
+        assert_eq!(from_index, *expected, "{}", path);
+        assert_eq!(from_base, *expected, "{}", path);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn name_offsets_may_point_into_other_strings() {
+    // As with `dtc`'s string sharing, names which are suffixes of other names are stored once
+    // and referenced by offsets into the middle of the longer string.
+    let mut blob = Blob::default();
+    blob.strings.extend_from_slice(b"#address-cells\0linux,phandle\0");
+    blob.begin_node("")
+        .raw_prop(0, &cells(&[1]))
+        .raw_prop(1, &cells(&[2]))
+        .raw_prop(9, &cells(&[3]))
+        .begin_node("intc")
+        .raw_prop(15, &cells(&[7]))
+        .raw_prop(21, &cells(&[8]))
+        .end_node()
+        .end_node()
+        .end();
+    // Names added through `prop()` reuse the shared strings too.
+    assert_eq!(blob.string_offset("phandle"), 21);
+    let dtb = blob.build();
+    let devtree = dtb.devtree().unwrap();
+    devtree.validate().unwrap();
+    let mut buf = Vec::new();
+    let index = dtb.index(&mut buf);
+
+    let expected = [
+        "#address-cells",
+        "address-cells",
+        "cells",
+        "linux,phandle",
+        "phandle",
+    ];
+    let names: Vec<_> = devtree.props().map(|p| p.name()).collect().unwrap();
+    assert_eq!(names, expected);
+    let names: Vec<_> = index.props().map(|p| p.name().unwrap()).collect();
+    assert_eq!(names, expected);
+
+    let root = index.root();
+    assert_eq!(root.prop_u32("address-cells").unwrap(), Some(2));
+    assert_eq!(root.prop_u32("cells").unwrap(), Some(3));
+    let intc = index.node_at_path("/intc").unwrap();
+    assert_eq!(intc.prop_u32("phandle").unwrap(), Some(8));
+    assert_eq!(intc.to_base().prop_u32("phandle").unwrap(), Some(8));
+    assert_eq!(node_by_phandle(&index, 8).unwrap().unwrap().name(), Ok("intc"));
+
+    let base = devtree.canonical_items(PropOrder::ByName);
+    assert!(index.canonical_items(PropOrder::ByName).eq(base).unwrap());
+}
+
+#[test]
+fn validate_rejects_malformed_structure() {
+    simple_tree().build().devtree().unwrap().validate().unwrap();
+
+    // Each error reports the offset of the offending token within the structure block.
+    let misplaced = |devtree: &DevTree, offset, token| DevTreeError::MalformedToken {
+        offset: devtree.off_dt_struct() + offset,
+        token: Some(token),
+    };
+
+    let mut unterminated = Blob::default();
+    unterminated.begin_node("").begin_node("child").end_node().end();
+    let dtb = unterminated.build();
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.validate(), Err(misplaced(&devtree, 24, FdtTok::End)));
+
+    let mut two_roots = Blob::default();
+    two_roots.begin_node("").end_node().begin_node("").end_node().end();
+    let dtb = two_roots.build();
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.validate(), Err(misplaced(&devtree, 12, FdtTok::BeginNode)));
+
+    let mut unbalanced = Blob::default();
+    unbalanced.begin_node("").end_node().end_node().end();
+    let dtb = unbalanced.build();
+    let devtree = dtb.devtree().unwrap();
+    assert_eq!(devtree.validate(), Err(misplaced(&devtree, 12, FdtTok::EndNode)));
+}
+
+#[test]
+fn parse_iter_resumes_only_at_token_offsets() {
+    let dtb = simple_tree().build();
+    let devtree = dtb.devtree().unwrap();
+    let mut tokens = devtree.parse_iter();
+    assert!(matches!(tokens.next(), Ok(Some(ParsedTok::BeginNode(_)))));
+    let offset = tokens.offset();
+
+    let mut resumed = DevTreeParseIter::at_offset(&devtree, offset).unwrap();
+    match resumed.next() {
+        Ok(Some(ParsedTok::Prop(prop))) => {
+            assert_eq!(devtree.string_at(prop.name_offset), Ok("compatible"))
+        }
+        _ => panic!("expected the root node's property"),
+    }
+    assert_eq!(resumed.offset(), {
+        tokens.next().unwrap();
+        tokens.offset()
+    });
+
+    let struct_off = devtree.off_dt_struct();
+    let struct_end = struct_off + devtree.size_dt_struct();
+    for &bad in &[offset + 2, offset - 1, 0, struct_off - 4, struct_end] {
+        assert!(matches!(
+            DevTreeParseIter::at_offset(&devtree, bad),
+            Err(DevTreeError::InvalidOffset)
+        ));
+    }
+}
+
+/// Re-emit the structure block of `devtree` from the tokens returned by its
+/// [`fdt_rs::base::parse::DevTreeParseIter`].
+///
+/// Returns the emitted bytes along with the ranges of (zeroed) alignment padding within them.
+fn reemit(devtree: &DevTree) -> (Vec<u8>, Vec<core::ops::Range<usize>>) {
+    let mut blob = Blob::default();
+    let mut padding = Vec::new();
+    let mut tokens = devtree.parse_iter();
+    while let Some(tok) = tokens.next().unwrap() {
+        let unpadded_end = match tok {
+            ParsedTok::BeginNode(node) => {
+                blob.token(0x1);
+                blob.structure.extend_from_slice(node.name);
+                blob.structure.push(0);
+                let end = blob.structure.len();
+                blob.align();
+                end
+            }
+            ParsedTok::Prop(prop) => {
+                blob.raw_prop(prop.name_offset as u32, prop.prop_buf);
+                blob.structure.len() - (prop.prop_buf.len().wrapping_neg() & 3)
+            }
+            ParsedTok::EndNode => blob.end_node().structure.len(),
+            ParsedTok::Nop => blob.nop().structure.len(),
+        };
+        padding.push(unpadded_end..blob.structure.len());
+    }
+    blob.end();
+    (blob.structure, padding)
+}
+
+/// Assert that re-emitting the tokens of `dtb` reproduces its structure block.
+///
+/// The contents of alignment padding are only compared if `exact_padding` is set. Some real
+/// device trees (e.g. those edited in place by QEMU) leave stale bytes in their padding.
+fn assert_round_trips(dtb: &Dtb, exact_padding: bool) {
+    let devtree = dtb.devtree().unwrap();
+    let (emitted, padding) = reemit(&devtree);
+    let mut original = dtb.struct_block().to_vec();
+    assert_eq!(emitted.len(), original.len());
+    if !exact_padding {
+        for range in padding {
+            original[range].iter_mut().for_each(|b| *b = 0);
+        }
+    }
+    assert_eq!(emitted, original);
+}
+
+#[test]
+fn parse_tokens_round_trip_byte_exact() {
+    let real = Dtb::from_bytes(include_bytes!("riscv64-virt.dtb"));
+    assert_round_trips(&real, false);
+
+    let mut nops = Blob::default();
+    nops.nop()
+        .begin_node("")
+        .nop()
+        .prop("empty", b"")
+        .prop("odd-length", b"abcde")
+        .begin_node("child@1000")
+        .nop()
+        .end_node()
+        .end_node()
+        .nop()
+        .end();
+
+    for blob in &[
+        simple_tree(),
+        status_tree(),
+        nested_tree(12),
+        dma_tree(0x4000_0000),
+        nops,
+    ] {
+        assert_round_trips(&blob.build(), true);
+    }
+}
+
+/// A tiny device tree exercising one of the token arrangements permitted by the specification,
+/// along with its expected number of nodes and properties.
+#[cfg(feature = "std")]
+struct Vector {
+    name: &'static str,
+    blob: Blob,
+    nodes: usize,
+    props: usize,
+}
+
+/// The spec-conformance vectors. Every parser change is expected to handle all of these.
+#[cfg(feature = "std")]
+fn conformance_vectors() -> Vec<Vector> {
+    // Names are limited to 31 characters, excluding any unit address.
+    let long_node = format!("{}@{}", "n".repeat(31), "f".repeat(16));
+    let long_prop = "p".repeat(31);
+
+    let mut empty = Blob::default();
+    empty.begin_node("").end_node().end();
+
+    let mut no_props = Blob::default();
+    no_props
+        .begin_node("")
+        .begin_node("a")
+        .begin_node("b")
+        .end_node()
+        .end_node()
+        .begin_node("c")
+        .end_node()
+        .end_node()
+        .end();
+
+    let mut zero_length = Blob::default();
+    zero_length
+        .begin_node("")
+        .prop("ranges", &[])
+        .prop("dma-coherent", &[])
+        .begin_node("child@0")
+        .prop("interrupt-controller", &[])
+        .prop("reg", &[])
+        .end_node()
+        .end_node()
+        .end();
+
+    let mut long_names = Blob::default();
+    long_names
+        .begin_node("")
+        .prop(&long_prop, &cells(&[1]))
+        .begin_node(&long_node)
+        .prop(&long_prop, b"value\0")
+        .end_node()
+        .end_node()
+        .end();
+
+    let mut nop_storm = Blob::default();
+    nop_storm.nop().nop().begin_node("").nop().nop();
+    nop_storm.prop("#address-cells", &cells(&[1])).nop();
+    nop_storm.prop("#size-cells", &cells(&[1])).nop().nop();
+    nop_storm.begin_node("dev@100").nop().prop("reg", &cells(&[0x100, 0x10]));
+    nop_storm.nop().end_node().nop().end_node().nop().nop().end();
+
+    let mut siblings = Blob::default();
+    siblings
+        .begin_node("")
+        .prop("#address-cells", &cells(&[1]))
+        .prop("#size-cells", &cells(&[0]))
+        .begin_node("cpu@0")
+        .prop("reg", &cells(&[0]))
+        .end_node()
+        .begin_node("cpu@1")
+        .prop("reg", &cells(&[1]))
+        .end_node()
+        .end_node()
+        .end();
+
+    vec![
+        Vector {
+            name: "empty tree",
+            blob: empty,
+            nodes: 1,
+            props: 0,
+        },
+        Vector {
+            name: "nodes without props",
+            blob: no_props,
+            nodes: 4,
+            props: 0,
+        },
+        Vector {
+            name: "zero length props",
+            blob: zero_length,
+            nodes: 2,
+            props: 4,
+        },
+        Vector {
+            name: "deep nesting",
+            blob: nested_tree(MAX_CANONICAL_DEPTH + 1),
+            nodes: MAX_CANONICAL_DEPTH + 1,
+            props: 0,
+        },
+        Vector {
+            name: "maximum name lengths",
+            blob: long_names,
+            nodes: 2,
+            props: 2,
+        },
+        Vector {
+            name: "NOP storm",
+            blob: nop_storm,
+            nodes: 2,
+            props: 3,
+        },
+        Vector {
+            name: "same named siblings",
+            blob: siblings,
+            nodes: 3,
+            props: 4,
+        },
+    ]
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn conformance_vectors_agree_across_backends() {
+    for vector in conformance_vectors() {
+        let name = vector.name;
+        let dtb = vector.blob.build();
+        let devtree = dtb.devtree().unwrap();
+        assert_eq!(devtree.validate(), Ok(()), "{}", name);
+        assert_round_trips(&dtb, true);
+        let mut buf = Vec::new();
+        let index = dtb.index(&mut buf);
+
+        assert_eq!(devtree.nodes().count().unwrap(), vector.nodes, "{}", name);
+        assert_eq!(devtree.props().count().unwrap(), vector.props, "{}", name);
+        assert_eq!(index.nodes().count(), vector.nodes, "{}", name);
+        assert_eq!(index.props().count(), vector.props, "{}", name);
+
+        for order in &[PropOrder::Document, PropOrder::ByName] {
+            let base = devtree.canonical_items(*order);
+            assert!(index.canonical_items(*order).eq(base).unwrap(), "{}", name);
+        }
+
+        for node in index.nodes() {
+            let base = node.to_base();
+            assert_eq!(base.name(), node.name(), "{}", name);
+            let parent = base.parent().unwrap();
+            assert_eq!(
+                parent.as_ref().map(|p| p.name().unwrap()),
+                node.parent().map(|p| p.name().unwrap()),
+                "{}",
+                name
+            );
+            let children: Vec<_> = base.children().map(|c| c.name()).collect().unwrap();
+            let expected: Vec<_> = node.children().map(|c| c.name().unwrap()).collect();
+            assert_eq!(children, expected, "{}", name);
+
+            let reg: Vec<_> = node.reg().unwrap().collect().unwrap();
+            assert_eq!(base.reg().unwrap().collect::<Vec<_>>().unwrap(), reg, "{}", name);
+            for prop in node.props() {
+                let prop_name = prop.name().unwrap();
+                let base_prop = base.prop(prop_name).unwrap().unwrap();
+                assert_eq!(base_prop.propbuf(), prop.propbuf(), "{}", name);
+                assert_eq!(base_prop.name_offset(), prop.name_offset(), "{}", name);
+            }
+            assert_eq!(base.status().unwrap(), node.status().unwrap(), "{}", name);
+            assert!(interrupts(&node).unwrap().is_none(), "{}", name);
+        }
+    }
+}
+
+/// Property based tests which check the parsers against a model of randomly generated trees.
+mod model_tests {
+    use super::*;
+    use fdt_rs::base::DevTreeItem;
+    use fdt_rs::index::{DevTreeIndexItem, DevTreeIndexNode};
+    use fdt_rs::quick;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Node {
+        name: String,
+        props: Vec<(String, Vec<u8>)>,
+        children: Vec<Node>,
+    }
+
+    /// A flattened item of a tree along with its depth (the root has a depth of 1).
+    #[derive(Debug, PartialEq)]
+    enum Item {
+        Node(usize, String),
+        Prop(usize, String, Vec<u8>),
+    }
+
+    fn arb_props() -> impl Strategy<Value = Vec<(String, Vec<u8>)>> {
+        vec(("[a-z#][a-z0-9,#-]{0,15}", vec(any::<u8>(), 0..24)), 0..4)
+    }
+
+    fn arb_node() -> impl Strategy<Value = Node> {
+        let name = "[a-z][a-z0-9,._+-]{0,12}(@[0-9a-f]{1,8})?";
+        let leaf = (name, arb_props()).prop_map(|(name, props)| Node {
+            name,
+            props,
+            children: Vec::new(),
+        });
+        leaf.prop_recursive(4, 32, 4, move |inner| {
+            (name, arb_props(), vec(inner, 0..4)).prop_map(|(name, props, children)| Node {
+                name,
+                props,
+                children,
+            })
+        })
+    }
+
+    fn arb_tree() -> impl Strategy<Value = Node> {
+        (arb_props(), vec(arb_node(), 0..4)).prop_map(|(props, children)| Node {
+            name: String::new(),
+            props,
+            children,
+        })
+    }
+
+    fn emit(blob: &mut Blob, node: &Node) {
+        blob.begin_node(&node.name);
+        for (name, value) in &node.props {
+            blob.prop(name, value);
+        }
+        for child in &node.children {
+            emit(blob, child);
+        }
+        blob.end_node();
+    }
+
+    fn serialize(root: &Node) -> Dtb {
+        let mut blob = Blob::default();
+        emit(&mut blob, root);
+        blob.end();
+        blob.build()
+    }
+
+    fn flatten(node: &Node, depth: usize, items: &mut Vec<Item>) {
+        items.push(Item::Node(depth, node.name.clone()));
+        for (name, value) in &node.props {
+            items.push(Item::Prop(depth, name.clone(), value.clone()));
+        }
+        for child in &node.children {
+            flatten(child, depth + 1, items);
+        }
+    }
+
+    /// Rebuild the model of `node` from the index.
+    fn unindex(node: DevTreeIndexNode) -> Node {
+        Node {
+            name: node.name().unwrap().to_string(),
+            props: node
+                .props()
+                .map(|p| (p.name().unwrap().to_string(), p.propbuf().to_vec()))
+                .collect(),
+            children: node.children().map(unindex).collect(),
+        }
+    }
+
+    /// Exercise both backends over a (possibly malformed) device tree, discarding any errors.
+    fn walk(bytes: &[u8]) {
+        let _ = quick::find_compatible(bytes, "ns16550a");
+        let _ = quick::memory(bytes);
+
+        let dtb = Dtb::from_bytes(bytes);
+        let devtree = match dtb.devtree() {
+            Ok(devtree) => devtree,
+            Err(_) => return,
+        };
+        let mut items = devtree.items();
+        while let Ok(Some(item)) = items.next() {
+            match item {
+                DevTreeItem::Node(node) => {
+                    let _ = node.name();
+                    let _ = node.subtree_byte_range();
+                    let _ = node.status();
+                }
+                DevTreeItem::Prop(prop) => {
+                    let _ = prop.name();
+                    let _ = prop.str();
+                }
+            }
+        }
+
+        let size = match DevTreeIndex::required_buffer_size(&devtree) {
+            Ok(size) => size,
+            Err(_) => return,
+        };
+        let mut buf = vec![0u8; size];
+        let index = match DevTreeIndex::new(devtree, &mut buf) {
+            Ok(index) => index,
+            Err(_) => return,
+        };
+        for item in index.items() {
+            match item {
+                DevTreeIndexItem::Node(node) => {
+                    let _ = node.name();
+                    let _ = node.status();
+                    let _ = node.child(node.child_count().saturating_sub(1));
+                }
+                DevTreeIndexItem::Prop(prop) => {
+                    let _ = prop.name();
+                    let _ = prop.str();
+                }
+            }
+        }
+        let _ = index.find_first_compatible_node("ns16550a");
+
+        // Trees which pass validation must be safe to traverse without checks.
+        if devtree.validate().is_ok() {
+            let checked = devtree.parse_iter().count().unwrap();
+            assert_eq!(unsafe { devtree.raw_tokens_unchecked() }.count(), checked);
+            let mut buf = vec![0u8; size];
+            unsafe { DevTreeIndex::new_unchecked(devtree, &mut buf) }.unwrap();
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn backends_parse_serialized_trees(root in arb_tree()) {
+            let dtb = serialize(&root);
+            let devtree = dtb.devtree().unwrap();
+
+            let mut expected = Vec::new();
+            flatten(&root, 1, &mut expected);
+
+            let mut parsed = Vec::new();
+            let mut items = devtree.items();
+            while let Some(item) = items.next().unwrap() {
+                parsed.push(match item {
+                    DevTreeItem::Node(node) => {
+                        Item::Node(items.depth(), node.name().unwrap().to_string())
+                    }
+                    DevTreeItem::Prop(prop) => Item::Prop(
+                        items.depth(),
+                        prop.name().unwrap().to_string(),
+                        prop.propbuf().to_vec(),
+                    ),
+                });
+            }
+            prop_assert_eq!(parsed, expected);
+
+            let mut buf = Vec::new();
+            let index = dtb.index(&mut buf);
+            prop_assert_eq!(unindex(index.root()), root);
+        }
+
+        #[test]
+        fn serialized_trees_round_trip_byte_exact(root in arb_tree()) {
+            let dtb = serialize(&root);
+            let devtree = dtb.devtree().unwrap();
+            let (emitted, _) = reemit(&devtree);
+            prop_assert_eq!(emitted.as_slice(), dtb.struct_block());
+        }
+
+        #[test]
+        fn mutated_trees_never_panic(
+            root in arb_tree(),
+            mutations in vec((any::<prop::sample::Index>(), any::<u8>()), 1..8),
+            truncate in any::<prop::sample::Index>(),
+            should_truncate in any::<bool>(),
+        ) {
+            let mut bytes = serialize(&root).bytes().to_vec();
+            for (idx, byte) in mutations {
+                let i = idx.index(bytes.len());
+                bytes[i] = byte;
+            }
+            if should_truncate {
+                bytes.truncate(truncate.index(bytes.len()));
+            }
+            walk(&bytes);
+        }
+    }
+}
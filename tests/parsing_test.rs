@@ -1,11 +1,23 @@
 extern crate fdt_rs;
 
-use fdt_rs::base::DevTree;
+use fdt_rs::base::storage::ReadableBuf;
+use fdt_rs::base::{DevTree, DevTreeItem};
+use fdt_rs::bounded::{BoundedVec, Collected};
 use fdt_rs::error::{DevTreeError, Result};
-use fdt_rs::index::DevTreeIndex;
+use fdt_rs::index::buffer::{index_size, DevTreeIndexBuffer};
+use fdt_rs::index::cache::{CacheEntry, CacheStats, QueryCache};
+use fdt_rs::index::{DevTreeIndex, DevTreeIndexItem, NodeSnapshot, SecondaryIndexes, SubtreeStats};
+use fdt_rs::matcher::{Glob, IgnoreCase, MatchFn, Matcher, Prefix};
 use fdt_rs::prelude::*;
+use fdt_rs::quick;
+#[cfg(feature = "std")]
+use fdt_rs::spec::StandardProp;
+use fdt_rs::util::read_reg;
 
 use criterion::{criterion_group, criterion_main, Criterion};
+use std::cell::Cell;
+#[cfg(feature = "std")]
+use std::error::Error;
 
 /// Fallible Basic Iterator
 ///
@@ -64,8 +76,8 @@ pub struct FdtIndex<'dt> {
 fn get_fdt_index<'dt>() -> FdtIndex<'dt> {
     unsafe {
         let devtree = DevTree::new(FDT).unwrap();
-        let layout = DevTreeIndex::get_layout(&devtree).unwrap();
-        let mut vec = vec![0u8; layout.size() + layout.align()];
+        let size = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+        let mut vec = vec![0u8; size];
         let slice = core::slice::from_raw_parts_mut(vec.as_mut_ptr(), vec.len());
         FdtIndex {
             index: DevTreeIndex::new(devtree, slice).unwrap(),
@@ -83,6 +95,224 @@ fn test_readsize_advice() {
     }
 }
 
+#[test]
+fn quick_index_reports_each_stage() {
+    let mut storage = vec![0u32; FDT.len() / 4 + 4];
+    let bytes = unsafe {
+        core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, storage.len() * 4)
+    };
+    bytes[..FDT.len()].copy_from_slice(FDT);
+    let mut index_buf = vec![0u8; 64 * 1024];
+
+    // Bytes beyond totalsize are ignored.
+    let (devtree, index) = fdt_rs::quick_index(bytes, &mut index_buf).unwrap();
+    assert_eq!(devtree.totalsize(), FDT.len());
+    let names: Vec<_> = index.nodes().map(|node| node.name().unwrap()).collect();
+    assert_eq!(names, DFS_NODES);
+
+    let error = |fdt: &[u8], index_len: usize| {
+        let mut index_buf = vec![0u8; index_len];
+        fdt_rs::quick_index(fdt, &mut index_buf).err()
+    };
+    let param = |fdt: &[u8]| matches!(error(fdt, 0), Some(DevTreeError::InvalidParameter(_)));
+    assert!(param(&bytes[..8]));
+    assert!(param(&bytes[1..]));
+    assert!(param(&bytes[..FDT.len() - 4]));
+    assert_eq!(error(&bytes[4..], 0), Some(DevTreeError::InvalidMagicNumber));
+    assert_eq!(error(bytes, 64), Some(DevTreeError::NotEnoughMemory));
+}
+
+#[test]
+fn snapshots_rehydrate_to_their_node() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<NodeSnapshot>();
+
+    let idx = get_fdt_index();
+    let snapshots: Vec<_> = idx.index.nodes().map(|node| node.snapshot()).collect();
+    for (node, snapshot) in idx.index.nodes().zip(&snapshots) {
+        let name = unsafe { core::slice::from_raw_parts(snapshot.name_ptr, snapshot.name_len) };
+        assert_eq!(name, node.name().unwrap().as_bytes());
+        assert_eq!(snapshot.prop_count, node.props().count());
+        assert_eq!(idx.index.rehydrate(snapshot).unwrap().id(), node.id());
+    }
+
+    let root = &snapshots[0];
+    assert_eq!(root.next_sibling, NodeSnapshot::NO_NODE);
+    assert_eq!(root.first_child, snapshots[1].id);
+    let chosen = idx.index.node_at_path("/chosen").unwrap().snapshot();
+    assert_eq!(chosen.first_child, NodeSnapshot::NO_NODE);
+    assert_eq!(chosen.next_sibling, idx.index.node_at_path("/uart").unwrap().id());
+
+    // Snapshots which describe no node of the index are rejected.
+    let moved = NodeSnapshot {
+        offset: chosen.offset + 4,
+        ..chosen
+    };
+    assert!(idx.index.rehydrate(&moved).is_none());
+    let renamed = NodeSnapshot {
+        name_len: 0,
+        ..chosen
+    };
+    assert!(idx.index.rehydrate(&renamed).is_none());
+}
+
+#[test]
+fn relocate_copies_and_validates() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let mut storage = vec![0u32; FDT.len() / 4 + 1];
+        let dst = core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len() + 4);
+
+        let relocated = fdt.relocate_to_verified(dst).unwrap();
+        assert_eq!(relocated.totalsize(), fdt.totalsize());
+        assert_eq!(relocated.fingerprint(), fdt.fingerprint());
+        assert_ne!(relocated.buf().as_ptr(), fdt.buf().as_ptr());
+        assert_eq!(
+            relocated.nodes().count().unwrap(),
+            fdt.nodes().count().unwrap()
+        );
+    }
+}
+
+#[test]
+fn relocate_rejects_small_or_unaligned_buffers() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let mut storage = vec![0u32; FDT.len() / 4 + 1];
+        let dst = core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len() + 4);
+
+        assert_eq!(
+            fdt.relocate_to(&mut dst[..FDT.len() - 1]).unwrap_err(),
+            DevTreeError::NotEnoughMemory
+        );
+        match fdt.relocate_to(&mut dst[1..]) {
+            Err(DevTreeError::InvalidParameter(_)) => {}
+            _ => panic!("Expected an unaligned buffer to be rejected."),
+        }
+    }
+}
+
+/// Emulates storage which may only be read through a small bounce buffer.
+#[derive(Debug)]
+struct BounceReader<'a> {
+    storage: &'a [u8],
+    reads: Cell<usize>,
+}
+
+impl<'a> ReadableBuf for BounceReader<'a> {
+    fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    fn read_at(&self, offset: usize, dst: &mut [u8]) -> Result<()> {
+        let mut bounce = [0u8; 16];
+        for (i, chunk) in dst.chunks_mut(bounce.len()).enumerate() {
+            let start = offset + i * bounce.len();
+            let src = self
+                .storage
+                .get(start..start + chunk.len())
+                .ok_or(DevTreeError::InvalidOffset)?;
+            bounce[..chunk.len()].copy_from_slice(src);
+            chunk.copy_from_slice(&bounce[..chunk.len()]);
+            self.reads.set(self.reads.get() + 1);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn devtree_over_readable_buf() {
+    let src = BounceReader {
+        storage: FDT,
+        reads: Cell::new(0),
+    };
+    let readable = DevTree::from_readable(&src).unwrap();
+    let fdt = unsafe { DevTree::new(FDT) }.unwrap();
+    assert_eq!(readable.totalsize(), fdt.totalsize());
+    assert_eq!(readable.off_dt_struct(), fdt.off_dt_struct());
+    assert_eq!(readable.size_dt_strings(), fdt.size_dt_strings());
+    assert_eq!(readable.check_version(), fdt.check_version());
+    assert_eq!(readable.boot_cpuid_phys(), fdt.boot_cpuid_phys());
+
+    let mut storage = vec![0u32; FDT.len() / 4 + 1];
+    let dst = unsafe {
+        core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len() + 4)
+    };
+    // The header was read at construction, so only the device tree itself is copied.
+    src.reads.set(0);
+    let relocated = readable.relocate_to(dst).unwrap();
+    assert_eq!(src.reads.get(), (FDT.len() + 15) / 16);
+    assert_eq!(relocated.fingerprint(), fdt.fingerprint());
+
+    // Storage must hold the entire device tree.
+    let truncated = BounceReader {
+        storage: &FDT[..FDT.len() - 1],
+        reads: Cell::new(0),
+    };
+    assert_eq!(
+        DevTree::from_readable(&truncated).unwrap_err(),
+        DevTreeError::ParseError
+    );
+    let mut garbage = FDT.to_vec();
+    garbage[0] ^= 0xff;
+    let garbage = BounceReader {
+        storage: &garbage,
+        reads: Cell::new(0),
+    };
+    assert_eq!(
+        DevTree::from_readable(&garbage).unwrap_err(),
+        DevTreeError::InvalidMagicNumber
+    );
+}
+
+#[test]
+fn load_from_readable_buf() {
+    let mut storage = vec![0u32; FDT.len() / 4 + 1];
+    let dst = unsafe {
+        core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len() + 4)
+    };
+
+    let src = BounceReader {
+        storage: FDT,
+        reads: Cell::new(0),
+    };
+    let loaded = DevTree::load_from(&src, dst).unwrap();
+    let fdt = unsafe { DevTree::new(FDT) }.unwrap();
+    assert_eq!(loaded.fingerprint(), fdt.fingerprint());
+    assert_eq!(loaded.totalsize(), FDT.len());
+
+    // The source must hold the entire device tree, and the destination must fit it.
+    let truncated: &[u8] = &FDT[..FDT.len() - 1];
+    assert_eq!(
+        DevTree::load_from(truncated, dst).unwrap_err(),
+        DevTreeError::ParseError
+    );
+    assert_eq!(
+        DevTree::load_from(FDT, &mut dst[..FDT.len() - 1]).unwrap_err(),
+        DevTreeError::NotEnoughMemory
+    );
+}
+
+#[test]
+fn slice_reads_reject_overflowing_ranges() {
+    let mut dst = [0u8; 4];
+    assert_eq!(FDT.read_at(usize::MAX - 1, &mut dst), Err(DevTreeError::InvalidOffset));
+    assert_eq!(FDT.read_at(FDT.len() - 3, &mut dst), Err(DevTreeError::InvalidOffset));
+    FDT.read_at(0, &mut dst).unwrap();
+    assert_eq!(dst, FDT[..4]);
+
+    // Unaligned slices may not be parsed in place.
+    let mut storage = vec![0u32; FDT.len() / 4 + 1];
+    let unaligned = unsafe {
+        core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, FDT.len() + 4)
+    };
+    unaligned[1..=FDT.len()].copy_from_slice(FDT);
+    match DevTree::from_readable(&unaligned[1..=FDT.len()]) {
+        Err(DevTreeError::InvalidParameter(_)) => {}
+        _ => panic!("Expected an unaligned buffer to be rejected."),
+    }
+}
+
 #[test]
 fn reserved_entries_iter() {
     unsafe {
@@ -113,13 +343,13 @@ fn node_prop_iter() {
             let mut prop_iter = node.props();
             while let Some(prop) = prop_iter.next().unwrap() {
                 if prop.length() > 0 {
-                    if let Ok(i) = prop.get_str_count() {
+                    if let Ok(i) = prop.str_count() {
                         if i == 0 {
                             continue;
                         }
                         assert!(i < 64);
-                        let mut vec: &mut [Option<&str>] = &mut [None; 64];
-                        if prop.get_strlist(&mut vec).is_err() {
+                        let vec: &mut [Option<&str>] = &mut [None; 64];
+                        if prop.strlist(vec).is_err() {
                             continue;
                         }
 
@@ -135,6 +365,502 @@ fn node_prop_iter() {
     }
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn node_props_are_scoped_to_node() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let node = fdt.compatible_nodes("riscv,clint0").next().unwrap().unwrap();
+        let names: Vec<_> = node.props().map(|p| p.name()).collect().unwrap();
+        assert_eq!(names, &["interrupts-extended", "reg", "compatible"]);
+    }
+}
+
+#[test]
+fn node_items_in_document_order() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let mut nodes = fdt.nodes();
+        let cpus = loop {
+            let node = nodes.next().unwrap().unwrap();
+            if node.name().unwrap() == "cpus" {
+                break node;
+            }
+        };
+
+        let mut items = cpus.items();
+        let mut names = Vec::new();
+        while let Some(item) = items.next().unwrap() {
+            names.push(match item {
+                DevTreeItem::Node(n) => n.name().unwrap(),
+                DevTreeItem::Prop(p) => p.name().unwrap(),
+            });
+        }
+        assert_eq!(
+            names,
+            &["#address-cells", "#size-cells", "timebase-frequency", "cpu-map", "cpu@0"]
+        );
+    }
+}
+
+#[test]
+fn typed_prop_getters_match_on_both_backends() {
+    let idx = get_fdt_index();
+    let cpus = idx.index.node_at_path("/cpus").unwrap();
+    let base = cpus.to_base();
+    let freq = cpus.prop_u32("timebase-frequency").unwrap();
+    assert!(freq.is_some());
+    assert_eq!(base.prop_u32("timebase-frequency").unwrap(), freq);
+    assert_eq!(cpus.prop_u32("missing").unwrap(), None);
+    assert_eq!(base.prop_u32("missing").unwrap(), None);
+
+    let memory = idx.index.node_at_path("/memory").unwrap();
+    let base = memory.to_base();
+    assert_eq!(memory.prop_u64_cells("reg", 0).unwrap(), Some(0x8000_0000));
+    assert_eq!(base.prop_u64_cells("reg", 1).unwrap(), Some(0x800_0000));
+    assert!(memory.prop_u64_cells("reg", 2).is_err());
+    assert!(base.prop_u64_cells("reg", 2).is_err());
+    // An index whose offset overflows must not wrap around to the start of the property.
+    let overflowing = usize::MAX / 8 + 1;
+    let err = Err(DevTreeError::InvalidOffset);
+    assert_eq!(memory.prop_u64_cells("reg", overflowing), err);
+    assert_eq!(base.prop_u64_cells("reg", overflowing), err);
+
+    let root = idx.index.root();
+    assert_eq!(root.prop_str("compatible").unwrap(), Some("riscv-virtio"));
+    assert_eq!(root.to_base().prop_str("compatible").unwrap(), Some("riscv-virtio"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn reg_decodes_with_parent_cells_on_both_backends() {
+    let idx = get_fdt_index();
+    let mut with_reg = 0;
+    for node in idx.index.nodes() {
+        let entries: Vec<_> = node.reg().unwrap().collect().unwrap();
+        let base = node.to_base();
+        let base_entries: Vec<_> = base.reg().unwrap().collect().unwrap();
+        assert_eq!(entries, base_entries);
+        if node.prop("reg").is_some() {
+            assert!(!entries.is_empty());
+            with_reg += 1;
+        }
+    }
+    assert!(with_reg > 0);
+
+    let memory = idx.index.node_at_path("/memory").unwrap();
+    let reg = memory.reg().unwrap();
+    assert_eq!((reg.address_cells(), reg.size_cells()), (2, 2));
+    assert_eq!(reg.collect::<Vec<_>>().unwrap(), vec![(0x8000_0000, 0x800_0000)]);
+    let cpu = idx.index.node_at_path("/cpus/cpu@0").unwrap();
+    assert_eq!(cpu.reg().unwrap().collect::<Vec<_>>().unwrap(), vec![(0, 0)]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn errors_convert_to_boxed_std_errors() {
+    fn first_str(value: &'static [u8]) -> std::result::Result<&'static str, Box<dyn Error>> {
+        Ok(core::str::from_utf8(value).map_err(DevTreeError::from)?)
+    }
+    assert_eq!(first_str(b"okay").unwrap(), "okay");
+    let err = first_str(b"\xff").unwrap_err();
+    assert!(err.to_string().starts_with("Failed to parse device tree string: "));
+    assert!(err.source().is_some());
+
+    let err: Box<dyn Error> = Box::new(DevTreeError::InvalidOffset);
+    assert_eq!(err.to_string(), "Invalid offset provided.");
+    assert!(err.source().is_none());
+}
+
+fn assert_words<T>(max_words: usize) {
+    let size = core::mem::size_of::<T>();
+    assert!(
+        size <= max_words * core::mem::size_of::<usize>(),
+        "{} is {} bytes",
+        core::any::type_name::<T>(),
+        size
+    );
+}
+
+// Iterators are cloned freely (e.g. to resume searches) so they must stay small. See the
+// "Iterator Costs" sections of the base and index modules.
+#[test]
+fn iterators_stay_small() {
+    use fdt_rs::base::iters::*;
+    use fdt_rs::base::{DevTreeNode, DevTreeProp};
+    use fdt_rs::canonical::CanonicalPath;
+    use fdt_rs::index::iters::*;
+    use fdt_rs::index::{DevTreeIndexNode, DevTreeIndexProp};
+    use fdt_rs::view::{PropCellIter, PropStrIter, PropU32Iter, PropViews};
+
+    assert_words::<DevTreeReserveEntryIter>(8);
+    assert_words::<DevTreeIter>(8);
+    assert_words::<DevTreeNodeIter>(8);
+    assert_words::<DevTreePropIter>(8);
+    assert_words::<DevTreeNodePropIter>(8);
+    assert_words::<DevTreeNodeStandardPropIter>(8);
+    assert_words::<DevTreeNodeItemIter>(8);
+    assert_words::<DevTreeNodeChildIter>(8);
+    assert_words::<DevTreeNodeSubtreeIter>(8);
+    assert_words::<DevTreeNodeDescendantIter>(8);
+    assert_words::<DevTreePathMatchIter>(8);
+    assert_words::<DevTreeLimitIter>(8);
+    assert_words::<DevTreeCompatibleNodeIter>(8);
+    assert_words::<DevTreeNode>(8);
+    assert_words::<DevTreeProp>(8);
+
+    assert_words::<DevTreeIndexIter>(8);
+    assert_words::<DevTreeIndexNodeIter>(8);
+    assert_words::<DevTreeIndexPropIter>(8);
+    assert_words::<DevTreeIndexNodePropIter>(8);
+    assert_words::<DevTreeIndexNodeStandardPropIter>(8);
+    assert_words::<DevTreeIndexNodeSiblingIter>(8);
+    assert_words::<DevTreeIndexNodeDescendantIter>(8);
+    assert_words::<DevTreeIndexNodeItemIter>(8);
+    assert_words::<DevTreeIndexLimitIter>(8);
+    assert_words::<DevTreeIndexPathMatchIter>(8);
+    assert_words::<DevTreeIndexPropValueIter>(8);
+    assert_words::<DevTreeIndexLargePropIter>(8);
+    assert_words::<DevTreeIndexCompatibleNodeIter>(8);
+    assert_words::<DevTreeIndexNode>(2);
+    assert_words::<DevTreeIndexProp>(3);
+
+    assert_words::<PropU32Iter>(8);
+    assert_words::<PropCellIter>(8);
+    assert_words::<PropStrIter>(8);
+    assert_words::<PropViews<DevTreeIndexProp>>(8);
+
+    // Canonical iterators store a path inline, and otherwise must stay as small as the others.
+    let canonical_words =
+        core::mem::size_of::<CanonicalPath>() / core::mem::size_of::<usize>() + 16;
+    assert_words::<DevTreeCanonicalIter>(canonical_words);
+    assert_words::<DevTreeIndexCanonicalIter>(canonical_words);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn compatible_nodes_any_matches_each_node_once() {
+    let idx = get_fdt_index();
+    let compatibles = ["virtio,mmio", "ns16550a", "riscv", "missing"];
+    let expected: Vec<_> = idx
+        .index
+        .nodes()
+        .filter(|node| match node.prop("compatible") {
+            Some(prop) => compatibles.iter().any(|c| prop.strlist_contains(c)),
+            None => false,
+        })
+        .map(|node| node.subtree_byte_range())
+        .collect();
+    assert_eq!(expected.len(), 8 + 1 + 1);
+
+    let found: Vec<_> = idx
+        .index
+        .compatible_nodes_any(&compatibles)
+        .map(|node| node.subtree_byte_range())
+        .collect();
+    assert_eq!(found, expected);
+
+    let base: Vec<_> = idx
+        .index
+        .fdt()
+        .compatible_nodes_any(&compatibles)
+        .map(|node| Ok(node.subtree_byte_range().unwrap()))
+        .collect()
+        .unwrap();
+    assert_eq!(base, expected);
+
+    assert_eq!(idx.index.compatible_nodes_any(&[]).count(), 0);
+}
+
+#[test]
+fn compatible_groups_hold_every_node_of_each_string() {
+    let idx = get_fdt_index();
+    // Each distinct compatible string, in the order it first appears.
+    let mut expected: Vec<&str> = Vec::new();
+    for node in idx.index.nodes() {
+        if let Some(prop) = node.prop("compatible") {
+            for s in prop.iter_str() {
+                let s = s.unwrap();
+                if !expected.contains(&s) {
+                    expected.push(s);
+                }
+            }
+        }
+    }
+
+    let groups: Vec<_> = idx.index.group_by_compatible().collect();
+    let strings: Vec<_> = groups.iter().map(|(s, _)| *s).collect();
+    assert_eq!(strings, expected);
+    for (s, nodes) in groups {
+        let names: Vec<_> = nodes.map(|node| node.name().unwrap()).collect();
+        let all: Vec<_> = idx.index.compatible_nodes(s).map(|node| node.name().unwrap()).collect();
+        assert!(!names.is_empty());
+        assert_eq!(names, all);
+    }
+    let (_, mmio) = idx.index.group_by_compatible().find(|(s, _)| *s == "virtio,mmio").unwrap();
+    assert_eq!(mmio.count(), 8);
+
+    // With a compatible index, the same groups are returned in sorted order.
+    let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    let indexes = SecondaryIndexes {
+        compatible: true,
+        ..SecondaryIndexes::default()
+    };
+    let mut vec = vec![0u8; DevTreeIndex::required_buffer_size_with(&devtree, indexes).unwrap()];
+    let sorted = DevTreeIndex::new_with_secondary_indexes(devtree, &mut vec, indexes).unwrap();
+    let mut strings = expected;
+    strings.sort_unstable();
+    let groups: Vec<_> = sorted.group_by_compatible().collect();
+    assert_eq!(groups.iter().map(|(s, _)| *s).collect::<Vec<_>>(), strings);
+    for (s, nodes) in groups {
+        let ranges: Vec<_> = nodes.map(|node| node.subtree_byte_range()).collect();
+        let all: Vec<_> = idx.index.compatible_nodes(s).map(|n| n.subtree_byte_range()).collect();
+        assert_eq!(ranges, all);
+    }
+}
+
+#[test]
+fn recorded_counts_match_the_index_contents() {
+    let idx = get_fdt_index();
+    assert_eq!(idx.index.node_count(), idx.index.nodes().count());
+    assert_eq!(idx.index.prop_count(), idx.index.props().count());
+    for node in idx.index.nodes() {
+        assert_eq!(node.prop_count(), node.props().count());
+        assert_eq!(node.child_count(), node.children().count());
+    }
+}
+
+#[test]
+fn name_index_finds_the_first_node_of_each_name() {
+    let idx = get_fdt_index();
+    let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    let size = DevTreeIndex::required_buffer_size_with_name_index(&devtree).unwrap();
+    let mut vec = vec![0u8; size + 1];
+    // The name index is built after the index, at an unaligned start.
+    let sorted = DevTreeIndex::new_with_name_index(devtree, &mut vec[1..]).unwrap();
+    assert!(sorted.has_name_index());
+    assert!(!idx.index.has_name_index());
+
+    for node in idx.index.nodes() {
+        let name = node.name().unwrap();
+        let expected = idx.index.nodes().find(|n| n.name() == Ok(name)).unwrap();
+        let linear = idx.index.find_node_by_name(name).unwrap();
+        let found = sorted.find_node_by_name(name).unwrap();
+        assert_eq!(linear.subtree_byte_range(), expected.subtree_byte_range());
+        assert_eq!(found.subtree_byte_range(), expected.subtree_byte_range());
+    }
+    assert!(sorted.find_node_by_name("uart").is_none());
+    assert!(sorted.find_node_by_name("zzz").is_none());
+
+    let required = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+    assert_eq!(
+        DevTreeIndex::new_with_name_index(devtree, &mut vec[..required]).unwrap_err(),
+        DevTreeError::NotEnoughMemory
+    );
+}
+
+#[test]
+fn compatible_index_lists_the_nodes_of_each_string() {
+    let idx = get_fdt_index();
+    let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    let indexes = SecondaryIndexes {
+        names: true,
+        compatible: true,
+    };
+    let size = DevTreeIndex::required_buffer_size_with(&devtree, indexes).unwrap();
+    let mut vec = vec![0u8; size + 1];
+    let sorted = DevTreeIndex::new_with_secondary_indexes(devtree, &mut vec[1..], indexes).unwrap();
+    assert!(sorted.has_compatible_index() && sorted.has_name_index());
+    assert!(!idx.index.has_compatible_index());
+
+    for (compatible, nodes) in idx.index.group_by_compatible() {
+        let expected: Vec<_> = nodes.map(|node| node.subtree_byte_range()).collect();
+        for index in &[&idx.index, &sorted] {
+            let found = index.nodes_by_compatible(compatible);
+            assert_eq!(found.map(|node| node.subtree_byte_range()).collect::<Vec<_>>(), expected);
+            let first = index.find_node_by_compatible(compatible).unwrap();
+            assert_eq!(first.subtree_byte_range(), expected[0]);
+        }
+    }
+    assert_eq!(sorted.nodes_by_compatible("virtio,mmio").count(), 8);
+    assert!(sorted.find_node_by_compatible("ns16550").is_none());
+    assert!(sorted.find_node_by_compatible("zzz").is_none());
+
+    let names = SecondaryIndexes {
+        names: true,
+        ..SecondaryIndexes::default()
+    };
+    let required = DevTreeIndex::required_buffer_size_with(&devtree, names).unwrap();
+    assert_eq!(
+        DevTreeIndex::new_with_secondary_indexes(devtree, &mut vec[..required], indexes)
+            .unwrap_err(),
+        DevTreeError::NotEnoughMemory
+    );
+}
+
+#[test]
+fn subtree_stats_count_the_subtree_contents() {
+    let idx = get_fdt_index();
+    for node in idx.index.nodes() {
+        let mut expected = SubtreeStats::default();
+        for other in core::iter::once(node).chain(node.descendants()) {
+            for prop in other.props() {
+                expected.props += 1;
+                expected.value_bytes += prop.length();
+            }
+        }
+        expected.nodes = node.descendants().count();
+        assert_eq!(node.subtree_stats(), expected);
+    }
+
+    let root = idx.index.root().subtree_stats();
+    assert_eq!(root.nodes, idx.index.nodes().count() - 1);
+    assert_eq!(root.props, idx.index.props().count());
+}
+
+#[test]
+fn first_matching_prefers_earlier_compatibles() {
+    let idx = get_fdt_index();
+    let fdt = idx.index.fdt();
+    let first_virtio = fdt.compatible_nodes("virtio,mmio").next().unwrap().unwrap();
+    let first_uart = fdt.compatible_nodes("ns16550a").next().unwrap().unwrap();
+
+    let (node, index) = fdt
+        .first_matching(&["missing", "virtio,mmio", "ns16550a"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(index, 1);
+    assert_eq!(node.subtree_byte_range(), first_virtio.subtree_byte_range());
+
+    let (node, index) = fdt
+        .first_matching(&["ns16550a", "virtio,mmio"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(index, 0);
+    assert_eq!(node.subtree_byte_range(), first_uart.subtree_byte_range());
+
+    assert!(fdt.first_matching(&["missing"]).unwrap().is_none());
+    assert!(fdt.first_matching(&[]).unwrap().is_none());
+}
+
+// Common operations must not require importing the prelude.
+mod without_prelude {
+    use super::get_fdt_index;
+
+    #[test]
+    fn common_operations_are_inherent() {
+        let idx = get_fdt_index();
+        let uart = idx.index.find_first_compatible_node("ns16550a").unwrap().unwrap();
+        let prop = uart.prop("compatible").unwrap();
+        assert_eq!(prop.name().unwrap(), "compatible");
+        assert!(prop.strlist_contains("ns16550a"));
+        assert_eq!(prop.str().unwrap(), "ns16550a");
+        assert_eq!(prop.length(), "ns16550a".len() + 1);
+        assert_eq!(prop.node().name().unwrap(), "uart@10000000");
+
+        let reg = uart.prop("reg").unwrap();
+        assert_eq!(reg.u64(0).unwrap(), 0x1000_0000);
+        assert_eq!(reg.u32(12).unwrap(), 0x100);
+        assert_eq!(reg.raw().len(), 16);
+
+        let fdt = idx.index.fdt();
+        let base = fdt.find_first_compatible_node("ns16550a").unwrap().unwrap();
+        let base_reg = base.prop("reg").unwrap().unwrap();
+        assert_eq!(base_reg.name().unwrap(), "reg");
+        assert_eq!(base_reg.u64(0).unwrap(), 0x1000_0000);
+        assert_eq!(base_reg.node().name().unwrap(), "uart@10000000");
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn descendants_stop_at_the_end_of_the_subtree() {
+    let idx = get_fdt_index();
+    for node in idx.index.nodes() {
+        let range = node.subtree_byte_range();
+        let expected: Vec<_> = idx
+            .index
+            .nodes()
+            .filter(|other| {
+                let start = other.subtree_byte_range().start;
+                start > range.start && start < range.end
+            })
+            .map(|other| other.subtree_byte_range())
+            .collect();
+        let descendants: Vec<_> = node
+            .descendants()
+            .map(|other| other.subtree_byte_range())
+            .collect();
+        assert_eq!(descendants, expected);
+
+        let base: Vec<_> = node
+            .to_base()
+            .descendants()
+            .map(|other| Ok(other.subtree_byte_range().unwrap()))
+            .collect()
+            .unwrap();
+        assert_eq!(base, expected);
+    }
+
+    let root = idx.index.root();
+    assert_eq!(root.descendants().count(), idx.index.nodes().count() - 1);
+    let cpus = idx.index.node_at_path("/cpus").unwrap();
+    let names: Vec<_> = cpus.descendants().map(|node| node.name().unwrap()).collect();
+    assert!(names.contains(&"cpu@0"));
+    assert!(!names.contains(&"cpus"));
+    assert!(!names.iter().any(|name| name.starts_with("memory")));
+    let uart = idx.index.node_at_path("/uart").unwrap();
+    assert_eq!(uart.descendants().count(), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn base_node_navigation_matches_index() {
+    let idx = get_fdt_index();
+    let fdt = idx.index.fdt();
+    let mut nodes = fdt.nodes();
+    let mut count = 0;
+    while let Some(node) = nodes.next().unwrap() {
+        let name = node.name().unwrap();
+        let indexed = idx.index.nodes().find(|n| n.name() == Ok(name)).unwrap();
+
+        let children: Vec<_> = node.children().map(|n| n.name()).collect().unwrap();
+        let expected: Vec<_> = indexed.children().map(|n| n.name().unwrap()).collect();
+        assert_eq!(children, expected);
+
+        let parent = node.parent().unwrap().map(|n| n.name().unwrap());
+        assert_eq!(parent, indexed.parent().map(|n| n.name().unwrap()));
+
+        let mut subtree = node.subtree();
+        let mut subtree_nodes = 0;
+        while let Some(item) = subtree.next().unwrap() {
+            if let DevTreeItem::Node(_) = item {
+                assert!(subtree.depth() > nodes.0.depth());
+                subtree_nodes += 1;
+            }
+        }
+        let expected = idx.index.nodes().skip_while(|n| n.name() != Ok(name)).skip(1);
+        let expected = expected
+            .take_while(|n| {
+                let mut parent = n.parent();
+                while let Some(p) = parent {
+                    if p.name() == Ok(name) {
+                        return true;
+                    }
+                    parent = p.parent();
+                }
+                false
+            })
+            .count();
+        assert_eq!(subtree_nodes, expected);
+        count += 1;
+    }
+    assert_eq!(count, DFS_NODES.len());
+
+    let root = fdt.root().unwrap().unwrap();
+    assert_eq!(root.subtree().count().unwrap(), fdt.items().count().unwrap() - 1);
+}
+
 #[test]
 fn next_compatible_finds_initial_node() {
     unsafe {
@@ -161,6 +887,30 @@ fn next_compatible_finds_final_node() {
     }
 }
 
+#[test]
+fn compatible_nodes_with_matcher() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let node = fdt
+            .compatible_nodes_matching(&IgnoreCase("RISCV,CLINT0"))
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(node.name().unwrap(), "clint@2000000");
+        assert_eq!(
+            fdt.compatible_nodes_matching(&Prefix("virtio,"))
+                .count()
+                .unwrap(),
+            8
+        );
+        assert!(fdt
+            .compatible_nodes_matching(&Prefix("virtio,mmio,"))
+            .next()
+            .unwrap()
+            .is_none());
+    }
+}
+
 #[test]
 fn find_all_compatible() {
     unsafe {
@@ -184,6 +934,248 @@ fn find_all_compatible() {
     }
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn compatible_search_resumes_after_a_position() {
+    let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+    let compat = "virtio,mmio";
+    let all: Vec<_> = devtree
+        .compatible_nodes(compat)
+        .map(|n| n.subtree_byte_range())
+        .collect()
+        .unwrap();
+    assert_eq!(all.len(), 8);
+
+    let mut nodes = devtree.compatible_nodes(compat);
+    nodes.next().unwrap();
+    let third = nodes.nth(1).unwrap().unwrap();
+    let rest: Vec<_> = devtree
+        .compatible_nodes_from(&third, compat)
+        .map(|n| n.subtree_byte_range())
+        .collect()
+        .unwrap();
+    assert_eq!(rest, &all[3..]);
+
+    let offset = third.subtree_byte_range().unwrap().start;
+    let mut from_offset = devtree.compatible_nodes_from_offset(offset, compat).unwrap();
+    let resumed = from_offset.next().unwrap().unwrap();
+    assert_eq!(resumed.subtree_byte_range().unwrap(), all[3]);
+
+    from_offset.rewind();
+    assert_eq!(from_offset.count().unwrap(), all.len());
+
+    assert_eq!(
+        devtree.compatible_nodes_from_offset(offset + 4, compat).err(),
+        Some(DevTreeError::InvalidOffset)
+    );
+}
+
+#[test]
+fn name_offsets_identify_names_on_both_backends() {
+    let idx = get_fdt_index();
+    let fdt = idx.index.fdt();
+    let mut reg_offset = None;
+    let mut base_props = fdt.props();
+    for prop in idx.index.props() {
+        let base = base_props.next().unwrap().unwrap();
+        assert_eq!(prop.name_offset(), base.name_offset());
+        assert_eq!(fdt.string_at(prop.name_offset()), prop.name());
+        if prop.name() == Ok("reg") {
+            assert_eq!(*reg_offset.get_or_insert(prop.name_offset()), prop.name_offset());
+        } else {
+            assert_ne!(Some(prop.name_offset()), reg_offset);
+        }
+    }
+    assert!(reg_offset.is_some());
+    assert!(base_props.next().unwrap().is_none());
+}
+
+#[test]
+fn write_tree_limits_depth_and_values() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let mut out = String::new();
+        fdt.write_tree(&mut out, 0, 4).unwrap();
+
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some("/"));
+        assert_eq!(lines.next(), Some("  #address-cells = <0x2>"));
+        assert_eq!(lines.next(), Some("  #size-cells = <0x2>"));
+        assert_eq!(lines.next(), Some("  compatible = \"risc\"..."));
+        assert_eq!(lines.next(), Some("  model = \"risc\"..."));
+        assert_eq!(lines.next(), Some("  ..."));
+        assert_eq!(lines.next(), None);
+    }
+}
+
+/// Returns a short label for each property, matching exhaustively over the standard set.
+#[cfg(feature = "std")]
+fn standard_label<P>(prop: &StandardProp<P>) -> &'static str {
+    match prop {
+        StandardProp::Reg(_) => "reg",
+        StandardProp::Compatible(_) => "compatible",
+        StandardProp::Status(_) => "status",
+        StandardProp::AddressCells(_) => "address-cells",
+        StandardProp::SizeCells(_) => "size-cells",
+        StandardProp::Interrupts(_) => "interrupts",
+        StandardProp::Ranges(_) => "ranges",
+        StandardProp::Other(_) => "other",
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn standard_props_classify_both_backends() {
+    let idx = get_fdt_index();
+    let soc = idx.index.node_at_path("/soc").unwrap();
+    let labels: Vec<_> = soc.standard_props().map(|p| standard_label(&p)).collect();
+    let names: Vec<_> = soc.props().map(|p| p.name().unwrap()).collect();
+    assert_eq!(labels.len(), names.len());
+    for (label, name) in labels.iter().zip(&names) {
+        match *name {
+            "#address-cells" | "#size-cells" => assert_eq!(*label, &name[1..]),
+            "reg" | "compatible" | "status" | "interrupts" | "ranges" => assert_eq!(label, name),
+            _ => assert_eq!(*label, "other"),
+        }
+    }
+    assert!(labels.contains(&"ranges"));
+
+    let base = soc.to_base();
+    let base_labels: Vec<_> = base
+        .standard_props()
+        .map(|p| Ok(standard_label(&p)))
+        .collect()
+        .unwrap();
+    assert_eq!(base_labels, labels);
+
+    let uart = idx.index.compatible_nodes("ns16550a").next().unwrap();
+    for prop in uart.standard_props() {
+        if let StandardProp::Interrupts(p) = prop {
+            assert_eq!(p.name().unwrap(), "interrupts");
+            assert_eq!(p.u32(0).unwrap(), 0xa);
+        }
+    }
+}
+
+#[test]
+fn bounded_collectors_count_truncated_items() {
+    let idx = get_fdt_index();
+    let fdt = idx.index.fdt();
+
+    let all: Collected<_, 16> = idx.index.collect_nodes("virtio,mmio");
+    assert!(all.is_complete());
+    assert_eq!(all.stored(), 8);
+
+    let base: Collected<_, 3> = fdt.collect_nodes("virtio,mmio").unwrap();
+    assert_eq!((base.stored(), base.found()), (3, 8));
+    for (node, expected) in base.items().iter().zip(all.items()) {
+        assert_eq!(node.name().unwrap(), expected.name().unwrap());
+    }
+
+    let none: Collected<_, 0> = idx.index.collect_nodes("virtio,mmio");
+    assert_eq!((none.stored(), none.found()), (0, 8));
+
+    let test = idx.index.compatible_nodes("sifive,test0").next().unwrap();
+    let compatible = test.prop("compatible").unwrap();
+    let strs: Collected<_, 2> = compatible.iter_str().collect::<Result<_>>().unwrap();
+    assert_eq!(strs.items().as_slice(), &["sifive,test1", "sifive,test0"]);
+    assert_eq!(strs.found(), 3);
+}
+
+#[test]
+fn bounded_vec_drops_stored_items() {
+    let item = std::rc::Rc::new(());
+    let mut vec = BoundedVec::<_, 2>::new();
+    assert!(vec.push(item.clone()).is_ok());
+    assert!(vec.push(item.clone()).is_ok());
+    assert!(vec.is_full());
+    assert!(vec.push(item.clone()).is_err());
+    assert_eq!(std::rc::Rc::strong_count(&item), 3);
+
+    let clone = vec.clone();
+    assert_eq!(std::rc::Rc::strong_count(&item), 5);
+    drop(vec);
+    drop(clone);
+    assert_eq!(std::rc::Rc::strong_count(&item), 1);
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn search_stats_are_recorded() {
+    use fdt_rs::profiling::last_search_stats;
+
+    let idx = get_fdt_index();
+    idx.index.node_at_path("/cpus/cpu@0").unwrap();
+    let stats = last_search_stats();
+    assert_eq!(stats.tokens_scanned, 0);
+    assert!(stats.nodes_visited >= 2);
+
+    let fdt = idx.index.fdt();
+    let mut nodes = fdt.compatible_nodes("virtio,mmio");
+    nodes.next().unwrap().unwrap();
+    let first = last_search_stats();
+    nodes.next().unwrap().unwrap();
+    let second = last_search_stats();
+    assert!(first.tokens_scanned > second.tokens_scanned);
+    assert!(second.nodes_visited >= 1);
+}
+
+#[cfg(feature = "coverage")]
+#[test]
+fn accesses_are_recorded_by_id() {
+    use fdt_rs::coverage::{bitmap_words, is_recorded, record_into, stop_recording};
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicU32;
+
+    const CLEAR: AtomicU32 = AtomicU32::new(0);
+    static WORDS: [AtomicU32; 256] = [CLEAR; 256];
+    static BITMAP: &[AtomicU32] = &WORDS;
+
+    let idx = get_fdt_index();
+    let mut ids = HashSet::new();
+    for node in idx.index.nodes() {
+        assert!(ids.insert(node.id()));
+        for prop in node.props() {
+            assert!(ids.insert(prop.id()));
+        }
+    }
+    assert!(ids.iter().all(|&id| id < idx.index.id_limit()));
+    assert!(bitmap_words(&idx.index) <= BITMAP.len());
+
+    record_into(&BITMAP);
+    let cpu = idx.index.node_at_path("/cpus/cpu@0").unwrap();
+    let isa = cpu.prop("riscv,isa").unwrap();
+    isa.str().unwrap();
+    stop_recording();
+
+    assert!(is_recorded(BITMAP, cpu.id()));
+    assert!(is_recorded(BITMAP, isa.id()));
+    assert!(!is_recorded(BITMAP, BITMAP.len() * 32));
+}
+
+#[test]
+fn quick_queries_match_index() {
+    let idx = get_fdt_index();
+    let uart = idx.index.compatible_nodes("ns16550a").next().unwrap();
+    assert_eq!(
+        quick::find_compatible(FDT, "ns16550a"),
+        Some((0x1000_0000, uart.subtree_byte_range().start))
+    );
+    // Entries other than the first of a stringlist also match.
+    let test = idx.index.compatible_nodes("sifive,test0").next().unwrap();
+    assert_eq!(
+        quick::find_compatible(FDT, "sifive,test0"),
+        Some((0x10_0000, test.subtree_byte_range().start))
+    );
+    // The soc bus is compatible, but has no reg.
+    assert_eq!(quick::find_compatible(FDT, "simple-bus"), None);
+    assert_eq!(quick::find_compatible(FDT, "no-such-device"), None);
+
+    assert_eq!(quick::memory(FDT), Some((0x8000_0000, 0x800_0000)));
+    assert_eq!(quick::memory(&FDT[..FDT.len() / 2]), None);
+    assert_eq!(quick::memory(&[0; 64]), None);
+}
+
 pub mod index_tests {
     use super::*;
 
@@ -193,17 +1185,87 @@ pub mod index_tests {
         let _ = get_fdt_index();
     }
 
-    // Test that our index get_layout returns a usable layout size.
+    // Test that our index required_buffer_size returns a usable buffer size.
     #[test]
     fn create_sized_index() {
         unsafe {
             let devtree = DevTree::new(FDT).unwrap();
-            let layout = DevTreeIndex::get_layout(&devtree).unwrap();
-            let mut vec = vec![0u8; layout.size() + layout.align()];
+            let size = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+            let mut vec = vec![0u8; size];
             DevTreeIndex::new(devtree, vec.as_mut_slice()).unwrap();
         }
     }
 
+    // Test that alignment padding is accounted for exactly, whatever the buffer's alignment.
+    #[test]
+    fn index_alignment_padding_is_reported() {
+        let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+        let layout = DevTreeIndex::get_layout(&devtree).unwrap();
+        let size = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+        assert_eq!(size, layout.size() + layout.align() - 1);
+
+        let mut vec = vec![0u8; size + layout.align()];
+        for start in 0..layout.align() {
+            let buf = &mut vec[start..start + size];
+            let padding = buf.as_ptr().align_offset(layout.align());
+            let index = DevTreeIndex::new(devtree, buf).unwrap();
+            assert_eq!(index.alignment_padding(), padding);
+
+            // The layout's size after the padding is exactly sufficient.
+            let exact = layout.size() + padding;
+            DevTreeIndex::new(devtree, &mut vec[start..start + exact]).unwrap();
+            DevTreeIndex::new(devtree, &mut vec[start..start + exact - 1]).unwrap_err();
+        }
+    }
+
+    // Test that running out of memory leaves a usable index of the nodes processed so far.
+    #[test]
+    fn partial_index_covers_processed_nodes() {
+        let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+        let size = DevTreeIndex::required_buffer_size(&devtree).unwrap();
+        let full = get_fdt_index();
+
+        let mut vec = vec![0u8; size];
+        let partial = DevTreeIndex::new_partial(devtree, &mut vec[..0]).unwrap_err();
+        assert_eq!(partial.error, DevTreeError::NotEnoughMemory);
+        assert!(partial.index.is_none());
+
+        let mut last_count = 0;
+        for len in (size / 8..size - DevTreeIndex::get_layout(&devtree).unwrap().align())
+            .step_by(64)
+        {
+            let partial = DevTreeIndex::new_partial(devtree, &mut vec[..len]).unwrap_err();
+            assert_eq!(partial.error, DevTreeError::NotEnoughMemory);
+            let index = partial.index.unwrap();
+
+            // The partial index holds a prefix of the full index.
+            let mut count = 0;
+            for (item, expected) in index.items().zip(full.index.items()) {
+                match (item, expected) {
+                    (DevTreeIndexItem::Node(node), DevTreeIndexItem::Node(expected)) => {
+                        assert_eq!(node.name(), expected.name());
+                        let (range, expected) =
+                            (node.subtree_byte_range(), expected.subtree_byte_range());
+                        assert_eq!(range.start, expected.start);
+                        assert!(range.end <= expected.end);
+                    }
+                    (DevTreeIndexItem::Prop(prop), DevTreeIndexItem::Prop(expected)) => {
+                        assert_eq!(prop.name(), expected.name());
+                    }
+                    _ => panic!("partial index diverged from the full index"),
+                }
+                count += 1;
+            }
+            assert_eq!(count, index.items().count());
+            assert_eq!(count, index.node_count() + index.prop_count());
+            assert!(count < full.index.items().count());
+            assert!(count >= last_count);
+            last_count = count;
+        }
+
+        DevTreeIndex::new_partial(devtree, &mut vec).unwrap();
+    }
+
     // Test that an invalid buffer size results in NotEnoughMemory on index allocation.
     #[test]
     fn expect_create_index_layout_fails_with_invalid_layout() {
@@ -234,6 +1296,306 @@ pub mod index_tests {
         test_prop_iteration(&get_fdt_index());
     }
 
+    #[test]
+    fn write_tree_matches_base() {
+        let idx = get_fdt_index();
+        let mut index_out = String::new();
+        let mut base_out = String::new();
+        idx.index.write_tree(&mut index_out, 8, 64).unwrap();
+        idx.index.fdt().write_tree(&mut base_out, 8, 64).unwrap();
+        assert_eq!(index_out, base_out);
+        assert!(index_out.contains("\n  chosen\n"));
+        assert!(index_out.contains("\n    cpu@0\n"));
+    }
+
+    #[test]
+    fn compatible_nodes_with_matcher() {
+        let idx = get_fdt_index();
+        let node = idx
+            .index
+            .compatible_nodes_matching(&IgnoreCase("RISCV,CLINT0"))
+            .next()
+            .unwrap();
+        assert_eq!(node.name().unwrap(), "clint@2000000");
+        let matcher = MatchFn(|s: &str| s.ends_with(",mmio"));
+        assert_eq!(idx.index.compatible_nodes_matching(&matcher).count(), 8);
+    }
+
+    #[test]
+    fn find_prop_values_containing_pattern() {
+        let idx = get_fdt_index();
+        let mut count = 0;
+        for found in idx.index.find_prop_value_containing(b"virtio,mmio") {
+            assert_eq!(found.prop.name().unwrap(), "compatible");
+            assert!(found.prop.node().name().unwrap().starts_with("virtio_mmio@"));
+            assert_eq!(found.offset, 0);
+            count += 1;
+        }
+        assert_eq!(count, 8);
+
+        // The memory node's reg value: a base of 0x80000000 followed by its size.
+        let pattern = [0, 0, 0, 0, 0x80, 0, 0, 0];
+        let found = idx.index.find_prop_value_containing(&pattern).next().unwrap();
+        assert_eq!(found.prop.node().name().unwrap(), "memory@80000000");
+        assert_eq!(found.prop.name().unwrap(), "reg");
+
+        assert!(idx.index.find_prop_value_containing(b"not present").next().is_none());
+    }
+
+    #[test]
+    fn glob_matcher() {
+        let cases = [
+            ("", "", true),
+            ("", "a", false),
+            ("*", "", true),
+            ("*", "anything", true),
+            ("a*b*c", "aXXbYYc", true),
+            ("a*b*c", "aXXbYY", false),
+            ("*@*000", "uart@10000000", true),
+            ("cpu@?", "cpu@1", true),
+            ("cpu@?", "cpu@10", false),
+            ("[a-c]x", "bx", true),
+            ("[a-c]x", "dx", false),
+            ("[!a-c]x", "dx", true),
+            ("[^a-c]x", "ax", false),
+            ("[]]", "]", true),
+            ("[a-]", "-", true),
+            ("[abc", "[abc", true),
+            ("*[0-9]", "virtio_mmio@10008000", true),
+            ("**a", "bbba", true),
+            ("?", "\u{e9}", true),
+        ];
+        for (pattern, candidate, expected) in cases.iter() {
+            assert_eq!(
+                Glob(pattern).matches(candidate),
+                *expected,
+                "{:?} against {:?}",
+                pattern,
+                candidate
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn nodes_matching_path_agrees_between_backends() {
+        let idx = get_fdt_index();
+        let fdt = idx.index.fdt();
+        let patterns = [
+            ("/", 1),
+            ("/*", 18),
+            ("/virtio_mmio@1000[1-4]000", 4),
+            ("/soc/*", 3),
+            ("/soc/pci", 1),
+            ("/cpus/cpu-map/*/core?", 1),
+            ("/*/*/*/*/*", 0),
+            ("/memory", 1),
+            ("/cpus/cpu@0/interrupt-controller", 1),
+            ("soc", 0),
+        ];
+        for (pattern, expected) in patterns.iter() {
+            let indexed: Vec<_> = idx
+                .index
+                .nodes_matching_path(pattern)
+                .map(|node| node.name().unwrap())
+                .collect();
+            let streamed: Vec<_> = fdt
+                .nodes_matching_path(pattern)
+                .map(|node| node.name())
+                .collect()
+                .unwrap();
+            assert_eq!(indexed.len(), *expected, "{}", pattern);
+            assert_eq!(indexed, streamed, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn node_at_path() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/soc/pci@30000000").unwrap();
+        assert_eq!(node.name().unwrap(), "pci@30000000");
+        let node = idx.index.node_at_path("/memory").unwrap();
+        assert_eq!(node.name().unwrap(), "memory@80000000");
+        assert_eq!(idx.index.node_at_path("/").unwrap().name().unwrap(), "");
+        assert!(idx.index.node_at_path("/soc/missing").is_none());
+        assert!(idx.index.node_at_path("soc").is_none());
+    }
+
+    #[test]
+    fn query_cache_evicts_least_recently_used() {
+        let idx = get_fdt_index();
+        let mut storage: [Option<CacheEntry>; 2] = [None; 2];
+        let mut cache = QueryCache::new(&idx.index, &mut storage);
+
+        assert!(cache.node_at_path("/chosen").is_some());
+        assert!(cache.first_compatible("riscv,clint0").is_some());
+        // Refresh /chosen so the compatible search becomes least recently used.
+        assert!(cache.node_at_path("/chosen").is_some());
+        assert!(cache.node_at_path("/missing").is_none());
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 3 });
+
+        assert!(cache.node_at_path("/chosen").is_some());
+        assert!(cache.node_at_path("/missing").is_none());
+        assert!(cache.first_compatible("riscv,clint0").is_some());
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 4 });
+    }
+
+    #[test]
+    fn const_index_size_matches_layout() {
+        const SIZE: usize = index_size(FDT);
+        let devtree = unsafe { DevTree::new(FDT) }.unwrap();
+        let layout = DevTreeIndex::get_layout(&devtree).unwrap();
+        assert_eq!(SIZE, layout.size());
+
+        // An exactly sized buffer is sufficient.
+        let mut buf = Box::new(DevTreeIndexBuffer::<SIZE>::new());
+        let index = DevTreeIndex::new(devtree, buf.as_mut_slice()).unwrap();
+        assert_eq!(index.nodes().count(), DFS_NODES.len());
+    }
+
+    #[test]
+    fn subtree_byte_ranges_match_base() {
+        let idx = get_fdt_index();
+        let mut base_nodes = idx.index.fdt().nodes();
+        for node in idx.index.nodes() {
+            let base_node = base_nodes.next().unwrap().unwrap();
+            assert_eq!(
+                node.subtree_byte_range(),
+                base_node.subtree_byte_range().unwrap()
+            );
+        }
+
+        // Children lie within their parent's range.
+        let root = idx.index.root().subtree_byte_range();
+        assert_eq!(root.start, idx.index.fdt().off_dt_struct());
+        for node in idx.index.nodes().skip(1) {
+            let range = node.subtree_byte_range();
+            assert!(root.start < range.start && range.end < root.end);
+        }
+    }
+
+    #[test]
+    fn unchecked_index_matches_checked() {
+        let idx = get_fdt_index();
+        let fdt = *idx.index.fdt();
+        fdt.validate().unwrap();
+
+        let raw_count = unsafe { fdt.raw_tokens_unchecked() }.count();
+        assert_eq!(raw_count, fdt.parse_iter().count().unwrap());
+
+        let size = DevTreeIndex::required_buffer_size(&fdt).unwrap();
+        let mut buf = vec![0u8; size];
+        let index = unsafe { DevTreeIndex::new_unchecked(fdt, &mut buf) }.unwrap();
+        for (node, expected) in index.nodes().zip(idx.index.nodes()) {
+            assert_eq!(node.name(), expected.name());
+            assert_eq!(node.subtree_byte_range(), expected.subtree_byte_range());
+            assert_eq!(node.props().count(), expected.props().count());
+        }
+        assert_eq!(index.nodes().count(), DFS_NODES.len());
+    }
+
+    #[test]
+    fn indexed_child_access() {
+        let idx = get_fdt_index();
+        for node in idx.index.nodes() {
+            assert_eq!(node.child_count(), node.children().count());
+        }
+
+        let cpus = idx.index.node_at_path("/cpus").unwrap();
+        assert_eq!(cpus.child_count(), 2);
+        assert_eq!(cpus.child(0).unwrap().name().unwrap(), "cpu-map");
+        assert_eq!(cpus.child(1).unwrap().name().unwrap(), "cpu@0");
+        assert!(cpus.child(2).is_none());
+        assert_eq!(cpus.child(1).unwrap().child_count(), 1);
+    }
+
+    #[test]
+    fn handles_expose_their_tree() {
+        let idx = get_fdt_index();
+        let node = idx.index.compatible_nodes("ns16550a").next().unwrap();
+        assert_eq!(node.fdt().totalsize(), FDT.len());
+        let prop = node.props().next().unwrap();
+        assert_eq!(prop.tree().buf().as_ptr(), idx.index.fdt().buf().as_ptr());
+
+        let fdt = idx.index.fdt();
+        let node = fdt.compatible_nodes("ns16550a").next().unwrap().unwrap();
+        assert_eq!(node.tree().totalsize(), FDT.len());
+        let mut props = node.props();
+        let prop = props.next().unwrap().unwrap();
+        assert_eq!(prop.tree().off_dt_strings(), fdt.off_dt_strings());
+    }
+
+    /// Generic over both backends' properties.
+    fn first_reg<'dt, P: PropReader<'dt>>(prop: &P) -> (u64, u64) {
+        read_reg(prop, 2, 2).next().unwrap().unwrap()
+    }
+
+    #[test]
+    fn prop_helpers_are_generic_over_backends() {
+        let idx = get_fdt_index();
+        let uart = idx.index.compatible_nodes("ns16550a").next().unwrap();
+        let reg = uart.props().find(|p| p.name() == Ok("reg")).unwrap();
+
+        let fdt = idx.index.fdt();
+        let base_uart = fdt.compatible_nodes("ns16550a").next().unwrap().unwrap();
+        let base_reg = base_uart
+            .props()
+            .find(|p| Ok(p.name()? == "reg"))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(first_reg(&reg), (0x1000_0000, 0x100));
+        assert_eq!(first_reg(&reg), first_reg(&base_reg));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn node_items_match_base() {
+        let idx = get_fdt_index();
+        let mut base_nodes = idx.index.fdt().nodes();
+        for node in idx.index.nodes() {
+            let base_node = base_nodes.next().unwrap().unwrap();
+            let names: Vec<_> = node
+                .items()
+                .map(|item| match item {
+                    DevTreeIndexItem::Node(n) => n.name().unwrap(),
+                    DevTreeIndexItem::Prop(p) => p.name().unwrap(),
+                })
+                .collect();
+            let base_names: Vec<_> = base_node
+                .items()
+                .map(|item| match item {
+                    DevTreeItem::Node(n) => n.name(),
+                    DevTreeItem::Prop(p) => p.name(),
+                })
+                .collect()
+                .unwrap();
+            assert_eq!(names, base_names);
+        }
+    }
+
+    #[test]
+    fn convert_between_base_and_index_nodes() {
+        let idx = get_fdt_index();
+        let mut base_nodes = idx.index.fdt().nodes();
+        for node in idx.index.nodes() {
+            let base_node = base_nodes.next().unwrap().unwrap();
+            let upgraded = idx.index.node_from_base(&base_node).unwrap();
+            assert_eq!(upgraded.subtree_byte_range(), node.subtree_byte_range());
+
+            let base = node.to_base();
+            assert_eq!(base.name(), node.name());
+            assert_eq!(base.subtree_byte_range().unwrap(), node.subtree_byte_range());
+            assert_eq!(base.props().count().unwrap(), node.props().count());
+        }
+
+        let fdt = idx.index.fdt();
+        let uart = fdt.compatible_nodes("ns16550a").next().unwrap().unwrap();
+        let node = idx.index.node_from_base(&uart).unwrap();
+        assert_eq!(node.name().unwrap(), "uart@10000000");
+        assert_eq!(node.parent().unwrap().name().unwrap(), "");
+    }
+
     pub fn test_prop_iteration<'dt>(idx: &FdtIndex<'dt>) {
         let iter = idx.index.props();
         assert_eq!(iter.count(), 105);
@@ -279,6 +1641,15 @@ fn benchmark(c: &mut Criterion) {
 
     group.bench_function("Raw DFS", |b| b.iter(|| test_fdt_dfs(&idx)));
 
+    group.bench_function("Index Build", |b| {
+        let fdt = *idx.index.fdt();
+        let size = DevTreeIndex::required_buffer_size(&fdt).unwrap();
+        let mut buf = vec![0u8; size];
+        b.iter(|| {
+            DevTreeIndex::new(fdt, &mut buf).unwrap();
+        })
+    });
+
     group.bench_function("Index DFS", |b| {
         b.iter(|| index_tests::test_index_dfs(&idx))
     });